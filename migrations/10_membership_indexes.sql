@@ -0,0 +1,12 @@
+-- Per-user lookups (history_for_user, the `last` CTE's GROUP BY user_id + MAX(id),
+-- latest-row fetches) can be answered from the index alone once `id` is included:
+-- SQLite reports "USING COVERING INDEX idx_memberships_guild_user" instead of
+-- a lookup per row. Replaces the (guild_id, user_id) index from 0_init.
+DROP INDEX IF EXISTS idx_memberships_guild_user;
+CREATE INDEX IF NOT EXISTS idx_memberships_guild_user
+  ON memberships (guild_id, user_id, id);
+
+-- Exit/open-stint queries filter on `left_at IS [NOT] NULL` within a guild
+-- ("SEARCH memberships USING COVERING INDEX idx_memberships_guild_left (guild_id=? AND left_at=?)").
+CREATE INDEX IF NOT EXISTS idx_memberships_guild_left
+  ON memberships (guild_id, left_at);
@@ -0,0 +1,8 @@
+-- daily current-member count per guild (one row per UTC day; re-recording a day overwrites it)
+CREATE TABLE IF NOT EXISTS member_count_snapshots (
+  guild_id      TEXT NOT NULL,
+  day           TEXT NOT NULL,      -- YYYY-MM-DD (UTC)
+  member_count  INTEGER NOT NULL,
+  taken_at      TEXT NOT NULL,      -- RFC2822 string
+  PRIMARY KEY (guild_id, day)
+);
@@ -0,0 +1,2 @@
+-- seconds after a ban during which a removal counts as that ban (NULL = default 15)
+ALTER TABLE guild_settings ADD COLUMN ban_detection_window_secs INTEGER;
@@ -0,0 +1,2 @@
+-- stints synthesized for leaves whose join we never saw (bot offline); joined_at = left_at
+ALTER TABLE memberships ADD COLUMN inferred BOOLEAN NOT NULL DEFAULT 0;
@@ -0,0 +1,3 @@
+-- daily recap posted to the mod log; hour is in the guild's timezone (NULL = default 9)
+ALTER TABLE guild_settings ADD COLUMN digest_enabled BOOLEAN NOT NULL DEFAULT 0;
+ALTER TABLE guild_settings ADD COLUMN digest_hour INTEGER;
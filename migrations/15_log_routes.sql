@@ -0,0 +1,7 @@
+-- extra destinations per log type; the *_log_channel_id columns stay the primary route
+CREATE TABLE IF NOT EXISTS log_routes (
+  guild_id   TEXT NOT NULL,
+  log_kind   TEXT NOT NULL,   -- 'join' | 'leave' | 'mod'
+  channel_id TEXT NOT NULL,
+  PRIMARY KEY (guild_id, log_kind, channel_id)
+);
@@ -0,0 +1,2 @@
+-- opt-in: log deleted/edited message content to the mod log
+ALTER TABLE guild_settings ADD COLUMN message_log_enabled BOOLEAN NOT NULL DEFAULT 0;
@@ -0,0 +1,16 @@
+-- invite analytics: one row per invite code seen (from fetches and InviteCreate)
+CREATE TABLE IF NOT EXISTS invites (
+  guild_id      TEXT NOT NULL,
+  code          TEXT NOT NULL,
+  inviter_id    TEXT,                        -- NULL for vanity/widget invites
+  created_at    TEXT NOT NULL,               -- RFC2822 string
+  expires_at    TEXT,                        -- RFC2822 string; NULL = never expires
+  uses          INTEGER NOT NULL DEFAULT 0,  -- Discord's use count at last sight
+  joins         INTEGER NOT NULL DEFAULT 0,  -- joins we attributed to this invite
+  last_used_at  TEXT,                        -- RFC2822 string of the last attributed join
+  deleted_at    TEXT,                        -- RFC2822 string; set on InviteDelete
+  PRIMARY KEY (guild_id, code)
+);
+
+CREATE INDEX IF NOT EXISTS idx_invites_guild_inviter
+  ON invites (guild_id, inviter_id);
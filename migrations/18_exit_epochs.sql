@@ -0,0 +1,6 @@
+-- left_at as unix seconds so exit queries can range-filter in SQL (RFC2822 doesn't sort).
+-- Existing rows are backfilled from left_at at startup (`backfill_left_at_epochs`).
+ALTER TABLE memberships ADD COLUMN left_at_epoch INTEGER;
+
+CREATE INDEX IF NOT EXISTS idx_memberships_guild_left_epoch
+  ON memberships (guild_id, left_at_epoch);
@@ -0,0 +1,6 @@
+-- joined_at as unix seconds, alongside left_at_epoch, so windowed stats filter in SQL.
+-- Backfilled from joined_at at startup (`backfill_epochs`); NULL only if unparseable.
+ALTER TABLE memberships ADD COLUMN joined_at_epoch INTEGER;
+
+CREATE INDEX IF NOT EXISTS idx_memberships_guild_joined_epoch
+  ON memberships (guild_id, joined_at_epoch);
@@ -0,0 +1,6 @@
+-- roles whose grants/removals are logged; no rows = log every role change
+CREATE TABLE IF NOT EXISTS watched_roles (
+  guild_id TEXT NOT NULL,
+  role_id  TEXT NOT NULL,
+  PRIMARY KEY (guild_id, role_id)
+);
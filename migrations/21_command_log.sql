@@ -0,0 +1,12 @@
+-- who ran which command, for /admin command-log; args are redacted/truncated before storage
+CREATE TABLE IF NOT EXISTS command_log (
+  id         INTEGER PRIMARY KEY AUTOINCREMENT,
+  guild_id   TEXT,                  -- NULL for commands run in DMs
+  user_id    TEXT NOT NULL,
+  command    TEXT NOT NULL,         -- qualified name, e.g. "stats exits"
+  args       TEXT NOT NULL,
+  invoked_at INTEGER NOT NULL       -- unix seconds
+);
+
+CREATE INDEX IF NOT EXISTS idx_command_log_guild
+  ON command_log (guild_id, id);
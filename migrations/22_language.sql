@@ -0,0 +1,2 @@
+-- Language code for bot messages (see src/strings.rs; NULL = English)
+ALTER TABLE guild_settings ADD COLUMN lang TEXT;
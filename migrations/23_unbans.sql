@@ -0,0 +1,2 @@
+-- When a banned stint's ban was lifted (unix seconds; NULL = still banned or not a ban)
+ALTER TABLE memberships ADD COLUMN unbanned_at_epoch INTEGER;
@@ -0,0 +1,2 @@
+-- joins (or leaves) per minute that trigger a raid alert in the mod log (NULL = default 10)
+ALTER TABLE guild_settings ADD COLUMN raid_threshold INTEGER;
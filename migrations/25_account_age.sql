@@ -0,0 +1,4 @@
+-- accounts younger than this many days are flagged on join (NULL = off)
+ALTER TABLE guild_settings ADD COLUMN min_account_age_days INTEGER;
+-- what happens to them: 'log' (default), 'dm' or 'kick'; see AccountAgeAction
+ALTER TABLE guild_settings ADD COLUMN account_age_action TEXT;
@@ -0,0 +1,5 @@
+-- per-log webhook URLs; when set, log embeds go through the webhook and the log's
+-- channels are only used as a fallback (NULL = post to the channels as before)
+ALTER TABLE guild_settings ADD COLUMN join_log_webhook_url TEXT;
+ALTER TABLE guild_settings ADD COLUMN leave_log_webhook_url TEXT;
+ALTER TABLE guild_settings ADD COLUMN mod_log_webhook_url TEXT;
@@ -0,0 +1,9 @@
+-- members who asked not to be tracked; their joins/leaves/renames are not stored
+CREATE TABLE IF NOT EXISTS tracking_optout (
+  guild_id      TEXT NOT NULL,
+  user_id       TEXT NOT NULL,
+  opted_out_at  INTEGER NOT NULL,  -- unix seconds
+  PRIMARY KEY (guild_id, user_id)
+);
+-- servers that must keep full records can ignore opt-outs (0 = opt-outs honoured)
+ALTER TABLE guild_settings ADD COLUMN tracking_required INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,2 @@
+-- let the settings panel offer voice/stage channel chats as log targets (0 = text, announcement, forum only)
+ALTER TABLE guild_settings ADD COLUMN extended_log_channels INTEGER NOT NULL DEFAULT 0;
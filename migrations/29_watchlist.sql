@@ -0,0 +1,9 @@
+-- users moderators want to hear about when they (re)join; keyed by user id so entries outlive stints
+CREATE TABLE IF NOT EXISTS watchlist (
+  guild_id   TEXT NOT NULL,
+  user_id    TEXT NOT NULL,
+  reason     TEXT,
+  added_by   TEXT NOT NULL,
+  added_at   INTEGER NOT NULL,  -- unix seconds
+  PRIMARY KEY (guild_id, user_id)
+);
@@ -0,0 +1,6 @@
+-- invite attribution per stint (best effort; NULL when unknown)
+ALTER TABLE memberships ADD COLUMN invite_code TEXT;   -- invite code used to join
+ALTER TABLE memberships ADD COLUMN inviter_id  TEXT;   -- user who created that invite
+
+CREATE INDEX IF NOT EXISTS idx_memberships_guild_inviter
+  ON memberships (guild_id, inviter_id);
@@ -0,0 +1,2 @@
+-- days of history to keep; older closed stints, notes and name changes are pruned daily (NULL = keep forever)
+ALTER TABLE guild_settings ADD COLUMN retention_days INTEGER;
@@ -0,0 +1,18 @@
+-- self-assign role menus posted by /role-menu; their collectors are re-attached on startup
+CREATE TABLE IF NOT EXISTS role_menus (
+  message_id TEXT PRIMARY KEY,
+  guild_id   TEXT NOT NULL,
+  channel_id TEXT NOT NULL,
+  title      TEXT NOT NULL,
+  created_by TEXT NOT NULL,
+  created_at INTEGER NOT NULL  -- unix seconds
+);
+
+CREATE INDEX IF NOT EXISTS idx_role_menus_guild ON role_menus (guild_id);
+
+-- roles offered by each menu, in menu order (rowid)
+CREATE TABLE IF NOT EXISTS role_menu_roles (
+  message_id TEXT NOT NULL,
+  role_id    TEXT NOT NULL,
+  PRIMARY KEY (message_id, role_id)
+);
@@ -0,0 +1,150 @@
+-- Every stored point in time becomes unix seconds (INTEGER), as memberships.*_epoch
+-- already are. Older builds wrote RFC 2822 text ("Tue, 1 Jul 2003 10:52:37 +0000",
+-- day without padding); newer ones RFC 3339, which SQLite's date functions read as is.
+
+-- Each distinct stored string once, parsed step by step.
+CREATE TEMP TABLE stamp_parse (
+  raw    TEXT PRIMARY KEY,
+  rest   TEXT,      -- RFC 2822 without the weekday: "1 Jul 2003 10:52:37 +0000"
+  day    INTEGER,
+  tail   TEXT,      -- rest without the day:         "Jul 2003 10:52:37 +0000"
+  epoch  INTEGER    -- NULL = unparseable
+);
+
+INSERT OR IGNORE INTO stamp_parse (raw)
+  SELECT joined_at FROM memberships WHERE joined_at_epoch IS NULL
+  UNION SELECT left_at FROM memberships WHERE left_at IS NOT NULL AND left_at_epoch IS NULL
+  UNION SELECT changed_at FROM name_changes
+  UNION SELECT created_at FROM member_notes
+  UNION SELECT taken_at FROM member_count_snapshots
+  UNION SELECT created_at FROM invites
+  UNION SELECT expires_at FROM invites WHERE expires_at IS NOT NULL
+  UNION SELECT last_used_at FROM invites WHERE last_used_at IS NOT NULL
+  UNION SELECT deleted_at FROM invites WHERE deleted_at IS NOT NULL;
+
+UPDATE stamp_parse SET epoch = CAST(strftime('%s', raw) AS INTEGER)
+ WHERE strftime('%s', raw) IS NOT NULL;
+
+UPDATE stamp_parse SET rest = ltrim(substr(raw, instr(raw, ',') + 1))
+ WHERE epoch IS NULL AND instr(raw, ',') > 0;
+
+UPDATE stamp_parse
+   SET day  = CAST(substr(rest, 1, instr(rest, ' ') - 1) AS INTEGER),
+       tail = substr(rest, instr(rest, ' ') + 1)
+ WHERE rest IS NOT NULL;
+
+-- Local time minus the "+hhmm"/"-hhmm" offset ("GMT"/"UT" count as +0000).
+UPDATE stamp_parse
+   SET epoch =
+         CAST(strftime('%s', printf('%s-%02d-%02d %s',
+           substr(tail, 5, 4),
+           (instr('JanFebMarAprMayJunJulAugSepOctNovDec', substr(tail, 1, 3)) + 2) / 3,
+           day,
+           substr(tail, 10, 8))) AS INTEGER)
+         - (CASE substr(tail, 19, 1) WHEN '-' THEN -1 ELSE 1 END)
+           * (CAST(substr(tail, 20, 2) AS INTEGER) * 3600
+              + CAST(substr(tail, 22, 2) AS INTEGER) * 60)
+ WHERE tail IS NOT NULL
+   AND instr('JanFebMarAprMayJunJulAugSepOctNovDec', substr(tail, 1, 3)) > 0;
+
+-- memberships: readers need joined_at_epoch set, and left_at_epoch set exactly when
+-- left_at is. An unparseable join falls back to the leave (or now), an unparseable
+-- leave to the join. The text columns are rewritten as RFC 3339.
+UPDATE memberships
+   SET left_at_epoch = (SELECT epoch FROM stamp_parse WHERE raw = memberships.left_at)
+ WHERE left_at IS NOT NULL AND left_at_epoch IS NULL;
+
+UPDATE memberships
+   SET joined_at_epoch = COALESCE(
+         (SELECT epoch FROM stamp_parse WHERE raw = memberships.joined_at),
+         left_at_epoch,
+         CAST(strftime('%s', 'now') AS INTEGER))
+ WHERE joined_at_epoch IS NULL;
+
+UPDATE memberships SET left_at_epoch = joined_at_epoch
+ WHERE left_at IS NOT NULL AND left_at_epoch IS NULL;
+
+UPDATE memberships
+   SET joined_at = strftime('%Y-%m-%dT%H:%M:%SZ', joined_at_epoch, 'unixepoch'),
+       left_at   = strftime('%Y-%m-%dT%H:%M:%SZ', left_at_epoch, 'unixepoch');
+
+-- The other tables are rebuilt with INTEGER columns; an unparseable required value
+-- becomes the migration time, an unparseable optional one NULL.
+CREATE TABLE name_changes_new (
+  id          INTEGER PRIMARY KEY AUTOINCREMENT,
+  guild_id    TEXT NOT NULL,
+  user_id     TEXT NOT NULL,
+  kind        TEXT NOT NULL,    -- 'account' (username) or 'server' (nickname)
+  old_value   TEXT,             -- NULL when previously unset
+  new_value   TEXT,             -- NULL when cleared
+  changed_at  INTEGER NOT NULL  -- unix seconds
+);
+INSERT INTO name_changes_new (id, guild_id, user_id, kind, old_value, new_value, changed_at)
+  SELECT n.id, n.guild_id, n.user_id, n.kind, n.old_value, n.new_value,
+         COALESCE(p.epoch, CAST(strftime('%s', 'now') AS INTEGER))
+  FROM name_changes n LEFT JOIN stamp_parse p ON p.raw = n.changed_at;
+DROP TABLE name_changes;
+ALTER TABLE name_changes_new RENAME TO name_changes;
+CREATE INDEX IF NOT EXISTS idx_name_changes_guild_user
+  ON name_changes (guild_id, user_id, id);
+
+CREATE TABLE member_notes_new (
+  id          INTEGER PRIMARY KEY AUTOINCREMENT,
+  guild_id    TEXT NOT NULL,
+  user_id     TEXT NOT NULL,
+  author_id   TEXT NOT NULL,
+  body        TEXT NOT NULL,
+  created_at  INTEGER NOT NULL       -- unix seconds
+);
+INSERT INTO member_notes_new (id, guild_id, user_id, author_id, body, created_at)
+  SELECT n.id, n.guild_id, n.user_id, n.author_id, n.body,
+         COALESCE(p.epoch, CAST(strftime('%s', 'now') AS INTEGER))
+  FROM member_notes n LEFT JOIN stamp_parse p ON p.raw = n.created_at;
+DROP TABLE member_notes;
+ALTER TABLE member_notes_new RENAME TO member_notes;
+CREATE INDEX IF NOT EXISTS idx_member_notes_guild_user
+  ON member_notes (guild_id, user_id, id);
+
+CREATE TABLE member_count_snapshots_new (
+  guild_id      TEXT NOT NULL,
+  day           TEXT NOT NULL,      -- YYYY-MM-DD (UTC)
+  member_count  INTEGER NOT NULL,
+  taken_at      INTEGER NOT NULL,   -- unix seconds
+  PRIMARY KEY (guild_id, day)
+);
+INSERT INTO member_count_snapshots_new (guild_id, day, member_count, taken_at)
+  SELECT s.guild_id, s.day, s.member_count,
+         COALESCE(p.epoch, CAST(strftime('%s', s.day) AS INTEGER))
+  FROM member_count_snapshots s LEFT JOIN stamp_parse p ON p.raw = s.taken_at;
+DROP TABLE member_count_snapshots;
+ALTER TABLE member_count_snapshots_new RENAME TO member_count_snapshots;
+
+CREATE TABLE invites_new (
+  guild_id      TEXT NOT NULL,
+  code          TEXT NOT NULL,
+  inviter_id    TEXT,                        -- NULL for vanity/widget invites
+  created_at    INTEGER NOT NULL,            -- unix seconds
+  expires_at    INTEGER,                     -- unix seconds; NULL = never expires
+  uses          INTEGER NOT NULL DEFAULT 0,  -- Discord's use count at last sight
+  joins         INTEGER NOT NULL DEFAULT 0,  -- joins we attributed to this invite
+  last_used_at  INTEGER,                     -- unix seconds of the last attributed join
+  deleted_at    INTEGER,                     -- unix seconds; set on InviteDelete
+  PRIMARY KEY (guild_id, code)
+);
+INSERT INTO invites_new
+  (guild_id, code, inviter_id, created_at, expires_at, uses, joins, last_used_at, deleted_at)
+  SELECT i.guild_id, i.code, i.inviter_id,
+         COALESCE(
+           (SELECT epoch FROM stamp_parse WHERE raw = i.created_at),
+           CAST(strftime('%s', 'now') AS INTEGER)),
+         (SELECT epoch FROM stamp_parse WHERE raw = i.expires_at),
+         i.uses, i.joins,
+         (SELECT epoch FROM stamp_parse WHERE raw = i.last_used_at),
+         (SELECT epoch FROM stamp_parse WHERE raw = i.deleted_at)
+  FROM invites i;
+DROP TABLE invites;
+ALTER TABLE invites_new RENAME TO invites;
+CREATE INDEX IF NOT EXISTS idx_invites_guild_inviter
+  ON invites (guild_id, inviter_id);
+
+DROP TABLE stamp_parse;
@@ -0,0 +1,13 @@
+-- name_changes: audit trail of account username / server nickname changes
+CREATE TABLE IF NOT EXISTS name_changes (
+  id          INTEGER PRIMARY KEY AUTOINCREMENT,
+  guild_id    TEXT NOT NULL,
+  user_id     TEXT NOT NULL,
+  kind        TEXT NOT NULL,    -- 'account' (username) or 'server' (nickname)
+  old_value   TEXT,             -- NULL when previously unset
+  new_value   TEXT,             -- NULL when cleared
+  changed_at  TEXT NOT NULL     -- RFC2822 string
+);
+
+CREATE INDEX IF NOT EXISTS idx_name_changes_guild_user
+  ON name_changes (guild_id, user_id, id);
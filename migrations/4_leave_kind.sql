@@ -0,0 +1,5 @@
+-- leave classification per stint: 0 = left (or still open), 1 = kicked, 2 = banned.
+-- `banned` is kept in sync (banned = leave_kind = 2) for older readers.
+ALTER TABLE memberships ADD COLUMN leave_kind INTEGER NOT NULL DEFAULT 0;
+
+UPDATE memberships SET leave_kind = 2 WHERE banned = 1;
@@ -0,0 +1,2 @@
+-- ban reason from the audit log (NULL when unavailable or not a ban)
+ALTER TABLE memberships ADD COLUMN ban_reason TEXT;
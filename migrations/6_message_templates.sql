@@ -0,0 +1,3 @@
+-- optional custom text for join/leave log embeds (NULL = built-in text)
+ALTER TABLE guild_settings ADD COLUMN join_message_template TEXT;
+ALTER TABLE guild_settings ADD COLUMN leave_message_template TEXT;
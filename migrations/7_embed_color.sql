@@ -0,0 +1,2 @@
+-- per-guild embed colour as 0xRRGGBB (NULL = Discord default)
+ALTER TABLE guild_settings ADD COLUMN embed_color INTEGER;
@@ -0,0 +1,12 @@
+-- moderator notes about a user; keyed by guild+user so they survive rejoins
+CREATE TABLE IF NOT EXISTS member_notes (
+  id          INTEGER PRIMARY KEY AUTOINCREMENT,
+  guild_id    TEXT NOT NULL,
+  user_id     TEXT NOT NULL,
+  author_id   TEXT NOT NULL,
+  body        TEXT NOT NULL,
+  created_at  TEXT NOT NULL          -- RFC2822 string
+);
+
+CREATE INDEX IF NOT EXISTS idx_member_notes_guild_user
+  ON member_notes (guild_id, user_id, id);
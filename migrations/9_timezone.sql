@@ -0,0 +1,2 @@
+-- IANA zone name used for calendar bucketing in stats (NULL = UTC)
+ALTER TABLE guild_settings ADD COLUMN timezone TEXT;
@@ -1,22 +1,126 @@
+use std::sync::{Arc, OnceLock};
+use std::time::Instant;
+
 use anyhow::{Context as AnyhowContext, Result};
 use poise::Framework;
 use serenity::all::{CacheHttp, ClientBuilder, GatewayIntents, GuildId};
+use serenity::cache::Settings as CacheSettings;
+use tokio_util::sync::CancellationToken;
 use tracing::info;
 use tracing_subscriber::EnvFilter;
 
-use crate::commands::{member, settings, stats, userinfo};
+use crate::commands::{admin, member, role_menu, settings, stats, userinfo};
 use crate::events::event_handler;
+use crate::spans::Traced;
 use crate::state::AppState;
 
+/// Messages kept per channel so deletes/edits can be logged with their old content.
+const MESSAGE_CACHE_PER_CHANNEL: usize = 200;
+
+/// Where slash commands get registered at startup (`REGISTER_SCOPE`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RegisterScope {
+    Global,
+    Guild,
+    Both,
+}
+
+impl RegisterScope {
+    /// `REGISTER_SCOPE=global|guild|both`; defaults to `guild` when a test guild is set
+    /// (so commands don't show up twice there) and `global` otherwise.
+    fn from_env(test_guild: Option<GuildId>) -> Result<Self> {
+        let scope = match std::env::var("REGISTER_SCOPE") {
+            Ok(raw) => match raw.trim().to_ascii_lowercase().as_str() {
+                "global" => Self::Global,
+                "guild" => Self::Guild,
+                "both" => Self::Both,
+                _ => anyhow::bail!("REGISTER_SCOPE must be global, guild or both (got {raw:?})"),
+            },
+            Err(_) if test_guild.is_some() => Self::Guild,
+            Err(_) => Self::Global,
+        };
+        if scope != Self::Global && test_guild.is_none() {
+            anyhow::bail!("REGISTER_SCOPE={scope:?} needs TEST_GUILD_ID to be set");
+        }
+        Ok(scope)
+    }
+
+    fn global(self) -> bool {
+        matches!(self, Self::Global | Self::Both)
+    }
+
+    fn guild(self) -> bool {
+        matches!(self, Self::Guild | Self::Both)
+    }
+}
+
+/// Log line layout (`LOG_FORMAT`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LogFormat {
+    /// tracing-subscriber's default multi-field lines.
+    Pretty,
+    /// Shorter single lines.
+    Compact,
+    /// One JSON object per line, span fields included, for log aggregators.
+    Json,
+}
+
+impl LogFormat {
+    /// `LOG_FORMAT=pretty|compact|json`; defaults to `pretty`. Logging isn't up yet
+    /// when this runs, so an unknown value is handed back for the caller to report.
+    fn from_env() -> (Self, Option<String>) {
+        let Ok(raw) = std::env::var("LOG_FORMAT") else {
+            return (Self::Pretty, None);
+        };
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "pretty" | "" => (Self::Pretty, None),
+            "compact" => (Self::Compact, None),
+            "json" => (Self::Json, None),
+            _ => (Self::Pretty, Some(raw)),
+        }
+    }
+}
+
+/// Install the global subscriber in the configured format (`RUST_LOG` filters it).
+fn init_logging() {
+    let (format, unknown) = LogFormat::from_env();
+    let builder = tracing_subscriber::fmt().with_env_filter(EnvFilter::from_default_env());
+    match format {
+        LogFormat::Pretty => builder.init(),
+        LogFormat::Compact => builder.compact().init(),
+        LogFormat::Json => builder
+            .json()
+            .with_current_span(true)
+            .with_span_list(true)
+            .init(),
+    }
+    if let Some(raw) = unknown {
+        tracing::warn!("LOG_FORMAT must be pretty, compact or json (got {raw:?}); using pretty");
+    }
+}
+
+/// `1`/`true`/`yes`/`on` (any case) enable a flag; anything else, or unset, doesn't.
+fn env_flag(name: &str) -> bool {
+    std::env::var(name).is_ok_and(|v| {
+        matches!(v.trim().to_ascii_lowercase().as_str(), "1" | "true" | "yes" | "on")
+    })
+}
+
 pub async fn run() -> Result<()> {
     dotenvy::dotenv().ok();
-    tracing_subscriber::fmt()
-        .with_env_filter(EnvFilter::from_default_env())
-        .init();
+    init_logging();
 
     let token = std::env::var("DISCORD_TOKEN").context("Set DISCORD_TOKEN in env")?;
     let db_url = std::env::var("DATABASE_URL").unwrap_or_else(|_| "sqlite://bot.db".into());
-    let test_guild = std::env::var("TEST_GUILD_ID").ok();
+    let test_guild = std::env::var("TEST_GUILD_ID")
+        .ok()
+        .map(|raw| raw.parse::<u64>().map(GuildId::new))
+        .transpose()
+        .context("TEST_GUILD_ID must be a valid u64")?;
+    let register_scope = RegisterScope::from_env(test_guild)?;
+    // Exit non-zero on a failed registration so a supervisor restarts us, instead of
+    // running with no usable commands.
+    let strict_registration = env_flag("STRICT_REGISTRATION");
 
     let token_tail = token
         .chars()
@@ -28,54 +132,100 @@ pub async fn run() -> Result<()> {
         .collect::<String>();
     info!("Starting bot with DB: {db_url}");
     info!("Discord token: ...{token_tail} (len={})", token.len());
+    info!("Registering commands: {register_scope:?} (strict: {strict_registration})");
+
+    // MESSAGE_CONTENT is privileged: enable it in the developer portal for message logging.
+    let intents = GatewayIntents::GUILD_MEMBERS
+        | GatewayIntents::GUILD_MESSAGES
+        | GatewayIntents::MESSAGE_CONTENT
+        | GatewayIntents::non_privileged();
+    let mut cache_settings = CacheSettings::default();
+    cache_settings.max_messages = MESSAGE_CACHE_PER_CHANNEL;
 
-    let intents = GatewayIntents::GUILD_MEMBERS | GatewayIntents::non_privileged();
+    // Connect (and migrate) before going online; the framework hands out clones.
+    let shutdown = CancellationToken::new();
+    let state = AppState::new(&db_url, shutdown.clone()).await?;
+    let framework_state = state.clone();
+    let registration_error: Arc<OnceLock<String>> = Arc::new(OnceLock::new());
+    let setup_registration_error = registration_error.clone();
+    let setup_shutdown = shutdown.clone();
 
     let framework = Framework::builder()
         .options(poise::FrameworkOptions {
             commands: vec![
                 userinfo::userinfo(),
+                userinfo::userinfo_context(),
                 settings::settings(),
                 member::member(),
                 stats::stats(),
+                role_menu::role_menu(),
+                admin::admin(),
             ],
             event_handler: |ctx, event, framework, data| {
                 Box::pin(event_handler(ctx, event, framework, data))
             },
+            // Audit trail, plus command latency for /metrics (only successful runs
+            // reach post_command).
+            pre_command: |ctx| {
+                Box::pin(async move {
+                    crate::spans::record_command(&ctx.command().qualified_name);
+                    ctx.set_invocation_data(Instant::now()).await;
+                    crate::command_log::record(ctx).await;
+                })
+            },
+            post_command: |ctx| {
+                Box::pin(async move {
+                    let started = ctx.invocation_data::<Instant>().await.map(|t| *t);
+                    if let Some(started) = started {
+                        ctx.data()
+                            .metrics
+                            .observe_command(&ctx.command().qualified_name, started.elapsed());
+                    }
+                })
+            },
             ..Default::default()
         })
         .setup(move |ctx, _ready, framework| {
             Box::pin(async move {
-                // only register commands if no test guild is set
-
-                match poise::builtins::register_globally(ctx, &framework.options().commands).await {
-                    Ok(_) => info!("Registered application commands globally"),
-                    Err(e) => {
-                        eprintln!("Failed to register application commands globally: {e:#}")
+                let mut failures = Vec::new();
+                if register_scope.global() {
+                    match poise::builtins::register_globally(ctx, &framework.options().commands).await {
+                        Ok(_) => info!("Registered application commands globally"),
+                        Err(e) => {
+                            tracing::error!("Failed to register application commands globally: {e:#}");
+                            failures.push(format!("global: {e}"));
+                        }
                     }
                 }
 
                 // Register commands in a specific guild for faster iteration during development
-                if let Some(gid) = test_guild.as_ref() {
-                    let gid = gid
-                        .parse::<u64>()
-                        .context("TEST_GUILD_ID must be a valid u64")?;
+                if register_scope.guild()
+                    && let Some(gid) = test_guild
+                {
                     match poise::builtins::register_in_guild(
                         ctx,
                         &framework.options().commands,
-                        GuildId::new(gid),
+                        gid,
                     )
                     .await
                     {
                         Ok(_) => info!("Registered application commands in test guild {gid}"),
                         Err(e) => {
-                            eprintln!(
+                            tracing::error!(
                                 "Failed to register application commands in test guild {gid}: {e:#}"
-                            )
+                            );
+                            failures.push(format!("guild {gid}: {e}"));
                         }
                     }
                 }
 
+                if strict_registration && !failures.is_empty() {
+                    let msg = failures.join("; ");
+                    let _ = setup_registration_error.set(msg.clone());
+                    setup_shutdown.cancel();
+                    anyhow::bail!("Command registration failed: {msg}");
+                }
+
                 match ctx.http().get_global_commands().await {
                     Ok(cmds) => {
                         info!("Currently registered global commands:");
@@ -83,25 +233,116 @@ pub async fn run() -> Result<()> {
                             info!(" - {} (ID {})", cmd.name, cmd.id);
                         }
                     }
-                    Err(e) => eprintln!("Failed to fetch global commands: {e:#}"),
+                    Err(e) => tracing::error!("Failed to fetch global commands: {e:#}"),
                 }
 
-                AppState::new(&db_url).await
+                Ok(framework_state)
             })
         })
         .build();
 
     let mut client = ClientBuilder::new(token, intents)
-        .framework(framework)
+        .cache_settings(cache_settings)
+        .framework(Traced(framework))
         .await
         .context("Building serenity client failed")?;
 
+    // Optional liveness/readiness probe for orchestrators.
+    #[cfg(feature = "health")]
+    {
+        let probe = crate::health::HealthProbe {
+            db: state.db.clone(),
+            cache: client.cache.clone(),
+            shards: client.shard_manager.clone(),
+        };
+        crate::http::spawn_from_env(
+            "HEALTH_BIND",
+            "Health",
+            move |req| probe.clone().respond(req),
+            shutdown.clone(),
+        )?;
+    }
+    #[cfg(not(feature = "health"))]
+    if std::env::var("HEALTH_BIND").is_ok() {
+        tracing::warn!("HEALTH_BIND is set but this build lacks the `health` feature; ignoring");
+    }
+
+    // Optional Prometheus scrape endpoint.
+    #[cfg(feature = "metrics")]
+    {
+        let metrics_state = state.clone();
+        crate::http::spawn_from_env(
+            "METRICS_BIND",
+            "Metrics",
+            move |req| crate::metrics::respond(metrics_state.clone(), req),
+            shutdown.clone(),
+        )?;
+    }
+    #[cfg(not(feature = "metrics"))]
+    if std::env::var("METRICS_BIND").is_ok() {
+        tracing::warn!("METRICS_BIND is set but this build lacks the `metrics` feature; ignoring");
+    }
+
+    // Ctrl-C / SIGTERM (or a fatal setup error cancelling the token): stop background
+    // loops, then close the gateway so `client.start()` returns normally instead of
+    // the process being killed.
+    let shard_manager = client.shard_manager.clone();
+    let signal_token = shutdown.clone();
+    tokio::spawn(async move {
+        tokio::select! {
+            _ = wait_for_signal() => info!("Shutdown signal received, stopping…"),
+            _ = signal_token.cancelled() => info!("Shutdown requested, stopping…"),
+        }
+        signal_token.cancel();
+        shard_manager.shutdown_all().await;
+    });
+
     info!("Connecting to Discord gateway…");
-    if let Err(e) = client.start().await {
+    let started = client.start().await;
+
+    // Stop background loops (also when the client died on its own) and let
+    // in-flight writes finish before the pool goes away.
+    shutdown.cancel();
+    state.db.pool.close().await;
+
+    if let Err(e) = started {
         // Network/auth/config error -> fail non-zero
         return Err(anyhow::anyhow!("Discord client error: {e:#}"));
     }
+    if let Some(msg) = registration_error.get() {
+        return Err(anyhow::anyhow!("Command registration failed: {msg}"));
+    }
 
-    info!("Discord client disconnected gracefully.");
+    info!("Shut down cleanly.");
     Ok(())
 }
+
+/// Resolves on Ctrl-C, or SIGTERM on Unix (what `docker stop` sends).
+async fn wait_for_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::warn!("Could not listen for Ctrl-C: {e}");
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut sig) => {
+                sig.recv().await;
+            }
+            Err(e) => {
+                tracing::warn!("Could not listen for SIGTERM: {e}");
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+}
@@ -0,0 +1,64 @@
+use serenity::all::audit_log::{Action, AuditLogEntry, MemberAction};
+use serenity::all::{GuildId, Timestamp, UserId};
+use serenity::http::Http;
+
+/// Find the newest audit-log entry of `action` targeting `user_id` within `window_secs`.
+///
+/// Best effort: returns `None` when the bot lacks View Audit Log or the request fails.
+pub async fn recent_entry_for(
+    http: &Http,
+    guild_id: GuildId,
+    action: MemberAction,
+    user_id: UserId,
+    window_secs: i64,
+) -> Option<AuditLogEntry> {
+    let logs = match guild_id
+        .audit_logs(http, Some(Action::Member(action)), None, None, Some(10))
+        .await
+    {
+        Ok(l) => l,
+        Err(e) => {
+            tracing::debug!(%guild_id, error = format!("{e:#}"), "Could not read audit log");
+            return None;
+        }
+    };
+
+    let now = Timestamp::now().unix_timestamp();
+    logs.entries.into_iter().find(|e| {
+        e.target_id.is_some_and(|t| t.get() == user_id.get())
+            && now - e.id.created_at().unix_timestamp() <= window_secs
+    })
+}
+
+/// How old a `MemberKick` entry may be and still explain a member removal.
+///
+/// Unlike the ban window this isn't a guild setting: it doesn't bridge two gateway
+/// events, it only has to cover the audit-log write racing `GuildMemberRemove` (usually
+/// well under a second) plus a slow audit-log read. Longer risks labelling a later,
+/// voluntary leave as an old kick.
+pub const KICK_WINDOW_SECS: i64 = 15;
+
+/// Was `user_id` kicked in the last `window_secs`?
+pub async fn was_recently_kicked(
+    http: &Http,
+    guild_id: GuildId,
+    user_id: UserId,
+    window_secs: i64,
+) -> bool {
+    recent_entry_for(http, guild_id, MemberAction::Kick, user_id, window_secs)
+        .await
+        .is_some()
+}
+
+/// Reason attached to a fresh `MemberBanAdd` entry for `user_id`, if any.
+pub async fn recent_ban_reason(
+    http: &Http,
+    guild_id: GuildId,
+    user_id: UserId,
+    window_secs: i64,
+) -> Option<String> {
+    recent_entry_for(http, guild_id, MemberAction::BanAdd, user_id, window_secs)
+        .await
+        .and_then(|e| e.reason)
+        .filter(|r| !r.trim().is_empty())
+}
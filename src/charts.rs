@@ -0,0 +1,335 @@
+//! PNG chart rendering for stats (behind the `charts` feature).
+//!
+//! Built without font support to keep the dependency light, so line charts carry
+//! no text; callers put dates/ranges in the accompanying embed instead. The
+//! dashboard draws its few labels with a small built-in bitmap font.
+
+use anyhow::{Result, anyhow};
+use chrono::NaiveDate;
+use plotters::coord::Shift;
+use plotters::prelude::*;
+use unicode_normalization::UnicodeNormalization;
+use unicode_normalization::char::is_combining_mark;
+
+const WIDTH: u32 = 900;
+const HEIGHT: u32 = 360;
+
+/// Line chart of net member delta per bucket (oldest → newest), with a zero baseline.
+pub fn render_delta_chart(data: &[(NaiveDate, i64)]) -> Result<Vec<u8>> {
+    render_line_chart(data, true)
+}
+
+/// Line chart of absolute values (e.g. member counts); the y-axis hugs the data.
+pub fn render_count_chart(data: &[(NaiveDate, i64)]) -> Result<Vec<u8>> {
+    render_line_chart(data, false)
+}
+
+fn render_line_chart(data: &[(NaiveDate, i64)], zero_baseline: bool) -> Result<Vec<u8>> {
+    let mut rgb = vec![0u8; (WIDTH * HEIGHT * 3) as usize];
+    {
+        let root = BitMapBackend::with_buffer(&mut rgb, (WIDTH, HEIGHT)).into_drawing_area();
+        root.fill(&WHITE).map_err(|e| anyhow!("chart: {e}"))?;
+
+        let last_x = data.len().saturating_sub(1).max(1) as i64;
+        let mut lo = data.iter().map(|(_, v)| *v).min().unwrap_or(0);
+        let mut hi = data.iter().map(|(_, v)| *v).max().unwrap_or(0);
+        if zero_baseline {
+            lo = lo.min(0);
+            hi = hi.max(0);
+        }
+        // Pad so flat series and the extremes aren't drawn on the border.
+        let pad = ((hi - lo) / 10).max(1);
+
+        let mut chart = ChartBuilder::on(&root)
+            .margin(20)
+            .build_cartesian_2d(0..last_x, (lo - pad)..(hi + pad))
+            .map_err(|e| anyhow!("chart: {e}"))?;
+
+        if zero_baseline {
+            chart
+                .draw_series(LineSeries::new([(0, 0), (last_x, 0)], BLACK.mix(0.4)))
+                .map_err(|e| anyhow!("chart: {e}"))?;
+        }
+        chart
+            .draw_series(LineSeries::new(
+                data.iter().enumerate().map(|(i, (_, v))| (i as i64, *v)),
+                BLUE.stroke_width(2),
+            ))
+            .map_err(|e| anyhow!("chart: {e}"))?;
+        chart
+            .draw_series(
+                data.iter()
+                    .enumerate()
+                    .map(|(i, (_, v))| Circle::new((i as i64, *v), 3, BLUE.filled())),
+            )
+            .map_err(|e| anyhow!("chart: {e}"))?;
+
+        root.present().map_err(|e| anyhow!("chart: {e}"))?;
+    }
+
+    encode_png(&rgb, WIDTH, HEIGHT)
+}
+
+fn encode_png(rgb: &[u8], width: u32, height: u32) -> Result<Vec<u8>> {
+    let mut png_bytes = Vec::new();
+    let mut encoder = png::Encoder::new(&mut png_bytes, width, height);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.write_header()?.write_image_data(rgb)?;
+    Ok(png_bytes)
+}
+
+const DASH_WIDTH: u32 = 900;
+const DASH_HEIGHT: u32 = 480;
+const DASH_PAD: i32 = 20;
+const HEADER_HEIGHT: i32 = 90;
+const TILE_TOP: i32 = HEADER_HEIGHT + DASH_PAD;
+const TILE_HEIGHT: i32 = 140;
+const SPARK_TOP: i32 = TILE_TOP + TILE_HEIGHT + DASH_PAD;
+/// Text scale bounds (one font pixel = this many image pixels); below the minimum
+/// the bitmap font stops being legible.
+const MIN_TEXT_SCALE: i32 = 2;
+const MAX_TEXT_SCALE: i32 = 6;
+
+const HEADER_BG: RGBColor = RGBColor(47, 49, 54);
+const PANEL_BG: RGBColor = RGBColor(240, 241, 243);
+const LABEL_FG: RGBColor = RGBColor(96, 100, 108);
+
+/// One headline number on the dashboard.
+#[derive(Debug, Clone)]
+pub struct DashboardTile {
+    pub label: String,
+    pub value: String,
+}
+
+/// Everything `render_dashboard` draws; labels arrive already translated.
+#[derive(Debug, Clone)]
+pub struct Dashboard {
+    /// Header line, usually the server name.
+    pub title: String,
+    /// Up to three tiles, left to right.
+    pub tiles: Vec<DashboardTile>,
+    pub spark_label: String,
+    /// Net member delta per day, oldest first.
+    pub spark: Vec<i64>,
+}
+
+/// Single PNG summarising a server: header, a fixed row of tiles and a delta sparkline.
+/// Text is scaled down to fit its box, so long server names never overflow.
+pub fn render_dashboard(dash: &Dashboard) -> Result<Vec<u8>> {
+    let mut rgb = vec![0u8; (DASH_WIDTH * DASH_HEIGHT * 3) as usize];
+    {
+        let root = BitMapBackend::with_buffer(&mut rgb, (DASH_WIDTH, DASH_HEIGHT))
+            .into_drawing_area();
+        root.fill(&WHITE).map_err(|e| anyhow!("chart: {e}"))?;
+        let width = DASH_WIDTH as i32;
+
+        fill_rect(&root, (0, 0), (width, HEADER_HEIGHT), HEADER_BG)?;
+        let title_box = (width - 2 * DASH_PAD, HEADER_HEIGHT);
+        draw_text_fit(&root, &dash.title, (DASH_PAD, 0), title_box, WHITE)?;
+
+        let tiles = dash.tiles.iter().take(3);
+        let tile_w = (width - 4 * DASH_PAD) / 3;
+        for (i, tile) in tiles.enumerate() {
+            let x = DASH_PAD + i as i32 * (tile_w + DASH_PAD);
+            fill_rect(&root, (x, TILE_TOP), (x + tile_w, TILE_TOP + TILE_HEIGHT), PANEL_BG)?;
+            let inner = tile_w - DASH_PAD;
+            let label_x = x + DASH_PAD / 2;
+            draw_text_fit(&root, &tile.label, (label_x, TILE_TOP + 8), (inner, 30), LABEL_FG)?;
+            let value_box = (inner, TILE_HEIGHT - 48);
+            draw_text_fit(&root, &tile.value, (label_x, TILE_TOP + 40), value_box, BLACK)?;
+        }
+
+        let spark_bottom = DASH_HEIGHT as i32 - DASH_PAD;
+        fill_rect(&root, (DASH_PAD, SPARK_TOP), (width - DASH_PAD, spark_bottom), PANEL_BG)?;
+        let (label_at, label_box) = ((DASH_PAD * 3 / 2, SPARK_TOP + 8), (width - 3 * DASH_PAD, 30));
+        draw_text_fit(&root, &dash.spark_label, label_at, label_box, LABEL_FG)?;
+        if dash.spark.len() > 1 {
+            let area = root.clone().shrink(
+                (DASH_PAD as u32 * 2, (SPARK_TOP + 44) as u32),
+                (
+                    DASH_WIDTH - 4 * DASH_PAD as u32,
+                    (spark_bottom - SPARK_TOP - 44 - DASH_PAD / 2) as u32,
+                ),
+            );
+            draw_sparkline(&area, &dash.spark)?;
+        }
+
+        root.present().map_err(|e| anyhow!("chart: {e}"))?;
+    }
+    encode_png(&rgb, DASH_WIDTH, DASH_HEIGHT)
+}
+
+fn draw_sparkline<DB: DrawingBackend>(area: &DrawingArea<DB, Shift>, data: &[i64]) -> Result<()> {
+    let last_x = data.len().saturating_sub(1).max(1) as i64;
+    let lo = data.iter().copied().min().unwrap_or(0).min(0);
+    let hi = data.iter().copied().max().unwrap_or(0).max(0);
+    let pad = ((hi - lo) / 10).max(1);
+
+    let mut chart = ChartBuilder::on(area)
+        .build_cartesian_2d(0..last_x, (lo - pad)..(hi + pad))
+        .map_err(|e| anyhow!("chart: {e:?}"))?;
+    chart
+        .draw_series(LineSeries::new([(0, 0), (last_x, 0)], BLACK.mix(0.4)))
+        .map_err(|e| anyhow!("chart: {e:?}"))?;
+    chart
+        .draw_series(LineSeries::new(
+            data.iter().enumerate().map(|(i, v)| (i as i64, *v)),
+            BLUE.stroke_width(3),
+        ))
+        .map_err(|e| anyhow!("chart: {e:?}"))?;
+    Ok(())
+}
+
+fn fill_rect<DB: DrawingBackend>(
+    area: &DrawingArea<DB, Shift>,
+    from: (i32, i32),
+    to: (i32, i32),
+    colour: RGBColor,
+) -> Result<()> {
+    area.draw(&Rectangle::new([from, to], colour.filled()))
+        .map_err(|e| anyhow!("chart: {e:?}"))
+}
+
+/// Draw `text` left-aligned and vertically centred in the box at `origin`, using the
+/// largest scale (up to `MAX_TEXT_SCALE`) that fits. Text too long even at
+/// `MIN_TEXT_SCALE` is truncated with "...".
+fn draw_text_fit<DB: DrawingBackend>(
+    area: &DrawingArea<DB, Shift>,
+    text: &str,
+    origin: (i32, i32),
+    size: (i32, i32),
+    colour: RGBColor,
+) -> Result<()> {
+    let mut glyphs = text_glyphs(text);
+    let n = glyphs.len() as i32;
+    if n == 0 {
+        return Ok(());
+    }
+    // A glyph is GLYPH_W wide plus one column of spacing (none after the last).
+    let advance = GLYPH_W + 1;
+    let by_width = size.0 / (n * advance - 1);
+    let by_height = size.1 / GLYPH_H;
+    let scale = by_width.min(by_height).clamp(MIN_TEXT_SCALE, MAX_TEXT_SCALE);
+    let fits = ((size.0 + scale) / (advance * scale)).max(3) as usize;
+    if glyphs.len() > fits {
+        let dot = glyph('.').expect("font has '.'");
+        glyphs.truncate(fits - 3);
+        glyphs.extend([dot; 3]);
+    }
+
+    let y0 = origin.1 + (size.1 - GLYPH_H * scale) / 2;
+    for (i, rows) in glyphs.iter().enumerate() {
+        let x0 = origin.0 + i as i32 * advance * scale;
+        for (row, bits) in rows.iter().enumerate() {
+            for col in 0..GLYPH_W {
+                if bits & (1 << (GLYPH_W - 1 - col)) == 0 {
+                    continue;
+                }
+                let x = x0 + col * scale;
+                let y = y0 + row as i32 * scale;
+                fill_rect(area, (x, y), (x + scale, y + scale), colour)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+const GLYPH_W: i32 = 5;
+const GLYPH_H: i32 = 7;
+
+/// Bitmaps for `text`: accents are stripped and letters uppercased; anything the
+/// font lacks is drawn as `?`.
+fn text_glyphs(text: &str) -> Vec<&'static [u8; 7]> {
+    let fallback = glyph('?').expect("font has '?'");
+    text.trim()
+        .nfd()
+        .filter(|c| !is_combining_mark(*c))
+        .flat_map(char::to_uppercase)
+        .map(|c| glyph(c).unwrap_or(fallback))
+        .collect()
+}
+
+fn glyph(c: char) -> Option<&'static [u8; 7]> {
+    FONT.iter().find(|(g, _)| *g == c).map(|(_, rows)| rows)
+}
+
+/// 5x7 pixel font, one byte per row with the low five bits left to right.
+const FONT: [(char, [u8; 7]); 52] = [
+    ('0', [0b01110, 0b10001, 0b10011, 0b10101, 0b11001, 0b10001, 0b01110]),
+    ('1', [0b00100, 0b01100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110]),
+    ('2', [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b01000, 0b11111]),
+    ('3', [0b11110, 0b00001, 0b00001, 0b01110, 0b00001, 0b00001, 0b11110]),
+    ('4', [0b00010, 0b00110, 0b01010, 0b10010, 0b11111, 0b00010, 0b00010]),
+    ('5', [0b11111, 0b10000, 0b11110, 0b00001, 0b00001, 0b10001, 0b01110]),
+    ('6', [0b00110, 0b01000, 0b10000, 0b11110, 0b10001, 0b10001, 0b01110]),
+    ('7', [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b01000, 0b01000]),
+    ('8', [0b01110, 0b10001, 0b10001, 0b01110, 0b10001, 0b10001, 0b01110]),
+    ('9', [0b01110, 0b10001, 0b10001, 0b01111, 0b00001, 0b00010, 0b01100]),
+    ('A', [0b01110, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001]),
+    ('B', [0b11110, 0b10001, 0b10001, 0b11110, 0b10001, 0b10001, 0b11110]),
+    ('C', [0b01110, 0b10001, 0b10000, 0b10000, 0b10000, 0b10001, 0b01110]),
+    ('D', [0b11110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b11110]),
+    ('E', [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b11111]),
+    ('F', [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b10000]),
+    ('G', [0b01110, 0b10001, 0b10000, 0b10111, 0b10001, 0b10001, 0b01111]),
+    ('H', [0b10001, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001]),
+    ('I', [0b01110, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110]),
+    ('J', [0b00111, 0b00010, 0b00010, 0b00010, 0b00010, 0b10010, 0b01100]),
+    ('K', [0b10001, 0b10010, 0b10100, 0b11000, 0b10100, 0b10010, 0b10001]),
+    ('L', [0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b11111]),
+    ('M', [0b10001, 0b11011, 0b10101, 0b10101, 0b10001, 0b10001, 0b10001]),
+    ('N', [0b10001, 0b10001, 0b11001, 0b10101, 0b10011, 0b10001, 0b10001]),
+    ('O', [0b01110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110]),
+    ('P', [0b11110, 0b10001, 0b10001, 0b11110, 0b10000, 0b10000, 0b10000]),
+    ('Q', [0b01110, 0b10001, 0b10001, 0b10001, 0b10101, 0b10010, 0b01101]),
+    ('R', [0b11110, 0b10001, 0b10001, 0b11110, 0b10100, 0b10010, 0b10001]),
+    ('S', [0b01111, 0b10000, 0b10000, 0b01110, 0b00001, 0b00001, 0b11110]),
+    ('T', [0b11111, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100]),
+    ('U', [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110]),
+    ('V', [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01010, 0b00100]),
+    ('W', [0b10001, 0b10001, 0b10001, 0b10101, 0b10101, 0b10101, 0b01010]),
+    ('X', [0b10001, 0b10001, 0b01010, 0b00100, 0b01010, 0b10001, 0b10001]),
+    ('Y', [0b10001, 0b10001, 0b01010, 0b00100, 0b00100, 0b00100, 0b00100]),
+    ('Z', [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b11111]),
+    (' ', [0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000]),
+    ('?', [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b00000, 0b00100]),
+    ('!', [0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00000, 0b00100]),
+    ('.', [0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b01100, 0b01100]),
+    (',', [0b00000, 0b00000, 0b00000, 0b00000, 0b01100, 0b00100, 0b01000]),
+    (':', [0b00000, 0b01100, 0b01100, 0b00000, 0b01100, 0b01100, 0b00000]),
+    ('-', [0b00000, 0b00000, 0b00000, 0b11111, 0b00000, 0b00000, 0b00000]),
+    ('+', [0b00000, 0b00100, 0b00100, 0b11111, 0b00100, 0b00100, 0b00000]),
+    ('%', [0b11000, 0b11001, 0b00010, 0b00100, 0b01000, 0b10011, 0b00011]),
+    ('/', [0b00000, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b00000]),
+    ('(', [0b00010, 0b00100, 0b01000, 0b01000, 0b01000, 0b00100, 0b00010]),
+    (')', [0b01000, 0b00100, 0b00010, 0b00010, 0b00010, 0b00100, 0b01000]),
+    ('#', [0b01010, 0b01010, 0b11111, 0b01010, 0b11111, 0b01010, 0b01010]),
+    ('&', [0b01100, 0b10010, 0b10100, 0b01000, 0b10101, 0b10010, 0b01101]),
+    ('\'', [0b00100, 0b00100, 0b01000, 0b00000, 0b00000, 0b00000, 0b00000]),
+    ('_', [0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b11111]),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delta_chart_is_a_decodable_png() {
+        let day = |d| NaiveDate::from_ymd_opt(2024, 1, d).unwrap();
+        let data = [(day(1), 3), (day(2), -2), (day(3), 0), (day(4), 5)];
+        let bytes = render_delta_chart(&data).unwrap();
+        assert!(!bytes.is_empty());
+
+        let mut reader = png::Decoder::new(bytes.as_slice()).read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        let frame = reader.next_frame(&mut pixels).unwrap();
+        assert_eq!((frame.width, frame.height), (WIDTH, HEIGHT));
+    }
+
+    #[test]
+    fn delta_chart_renders_an_empty_series() {
+        let bytes = render_delta_chart(&[]).unwrap();
+        assert!(png::Decoder::new(bytes.as_slice()).read_info().is_ok());
+    }
+}
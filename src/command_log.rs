@@ -0,0 +1,71 @@
+//! Audit trail of command invocations (`pre_command` hook → `command_log` table).
+
+use poise::serenity_prelude as serenity;
+use serenity::all::{ResolvedOption, ResolvedValue};
+
+use crate::repos::CommandLogRepo;
+use crate::state::Ctx;
+
+/// Options whose values are never stored (free text that may hold private details).
+const REDACTED_ARGS: &[&str] = &["text"];
+/// Longest single value kept; longer ones are cut with an ellipsis.
+const MAX_ARG_VALUE_CHARS: usize = 100;
+/// Cap on the whole stored argument string.
+const MAX_ARGS_CHARS: usize = 500;
+
+/// Record who ran which command where. Failures are logged, never surfaced to the user.
+pub async fn record(ctx: Ctx<'_>) {
+    let args = match ctx {
+        poise::Context::Application(app) => describe_args(app.args),
+        poise::Context::Prefix(prefix) => clip(prefix.args, MAX_ARGS_CHARS),
+    };
+    let res = CommandLogRepo::new(&ctx.data().db)
+        .record(
+            ctx.guild_id(),
+            ctx.author().id,
+            &ctx.command().qualified_name,
+            &args,
+        )
+        .await;
+    if let Err(e) = res {
+        tracing::warn!("Failed to record command invocation: {e}");
+    }
+}
+
+/// `name=value` pairs with sensitive options redacted and every value capped.
+fn describe_args(args: &[ResolvedOption<'_>]) -> String {
+    let parts: Vec<String> = args
+        .iter()
+        .map(|opt| {
+            let value = if REDACTED_ARGS.contains(&opt.name) {
+                "[redacted]".to_string()
+            } else {
+                describe_value(&opt.value)
+            };
+            format!("{}={}", opt.name, clip(&value, MAX_ARG_VALUE_CHARS))
+        })
+        .collect();
+    clip(&parts.join(" "), MAX_ARGS_CHARS)
+}
+
+fn describe_value(value: &ResolvedValue<'_>) -> String {
+    match value {
+        ResolvedValue::Boolean(b) => b.to_string(),
+        ResolvedValue::Integer(i) => i.to_string(),
+        ResolvedValue::Number(n) => n.to_string(),
+        ResolvedValue::String(s) => format!("{s:?}"),
+        ResolvedValue::User(u, _) => format!("<@{}>", u.id.get()),
+        ResolvedValue::Channel(c) => format!("<#{}>", c.id.get()),
+        ResolvedValue::Role(r) => format!("<@&{}>", r.id.get()),
+        // Only the name: the CDN URL is a bearer link to the file.
+        ResolvedValue::Attachment(a) => format!("file:{}", a.filename),
+        ResolvedValue::SubCommand(opts) | ResolvedValue::SubCommandGroup(opts) => {
+            describe_args(opts)
+        }
+        _ => "?".to_string(),
+    }
+}
+
+fn clip(text: &str, max: usize) -> String {
+    crate::commands::clip(text.to_string(), max)
+}
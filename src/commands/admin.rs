@@ -0,0 +1,348 @@
+use std::time::Instant;
+
+use anyhow::Result;
+use poise::serenity_prelude as serenity;
+
+use crate::commands::{
+    MAX_EMBED_FIELD_CHARS, clip, format_duration, guild_lang, owner_or_manage_guild,
+    send_chunked_embeds, send_paginated_embeds,
+};
+use crate::members::fetch_all_members;
+use crate::repos::{CommandLogRepo, MembershipsRepo, OptOutRepo, RepoError};
+use crate::state::Ctx;
+use crate::strings::t;
+
+/// `/admin` parent command: operator diagnostics and the command audit log.
+///
+/// Access is checked per subcommand: most are owner-only, `command-log` and `reconcile`
+/// are also open to members with Manage Server.
+#[poise::command(
+    slash_command,
+    ephemeral,
+    subcommands(
+        "admin_stats_runtime",
+        "admin_reload_fts",
+        "admin_stats_db",
+        "admin_command_log",
+        "admin_reconcile"
+    ),
+    rename = "admin"
+)]
+pub async fn admin(_: Ctx<'_>) -> Result<()> {
+    Ok(())
+}
+
+/// Default and maximum number of entries shown by `/admin command-log`.
+const COMMAND_LOG_DEFAULT: i64 = 100;
+const COMMAND_LOG_MAX: i64 = 500;
+
+/// Recent command invocations in this server (who ran what, and when).
+#[poise::command(
+    slash_command,
+    guild_only,
+    ephemeral,
+    check = "owner_or_manage_guild",
+    rename = "command-log"
+)]
+pub async fn admin_command_log(
+    ctx: Ctx<'_>,
+    #[description = "How many entries to show (default 100)"]
+    #[min = 1]
+    #[max = 500]
+    limit: Option<i64>,
+) -> Result<()> {
+    let guild_id = ctx.guild_id().expect("guild_only");
+    let lang = guild_lang(ctx).await;
+    let limit = limit
+        .unwrap_or(COMMAND_LOG_DEFAULT)
+        .clamp(1, COMMAND_LOG_MAX);
+    let entries = CommandLogRepo::new(&ctx.data().db)
+        .recent(guild_id, limit)
+        .await?;
+
+    if entries.is_empty() {
+        ctx.say(t("admin.command_log.none", lang, &[])).await?;
+        return Ok(());
+    }
+
+    let lines = entries
+        .iter()
+        .map(|e| {
+            let args = if e.args.is_empty() {
+                String::new()
+            } else {
+                format!(" {}", e.args)
+            };
+            format!(
+                "`#{}` {} — <@{}> — `/{}`{}",
+                e.id,
+                e.invoked_at.discord('f'),
+                e.user_id,
+                e.command,
+                args
+            )
+        })
+        .collect();
+
+    send_paginated_embeds(
+        ctx,
+        lines,
+        |first| {
+            serenity::CreateEmbed::new()
+                .title(t("admin.command_log.title", lang, &[]))
+                .description(first)
+        },
+        |index, cont| {
+            let title = t("admin.command_log.title", lang, &[]);
+            serenity::CreateEmbed::new()
+                .title(t("stats.cont", lang, &[("title", &title), ("n", &index)]))
+                .description(cont)
+        },
+    )
+    .await
+}
+
+/// Resync tracked members with the live member list (fixes events missed while offline).
+///
+/// Closes stints of users who are gone and opens stints for members who have none.
+#[poise::command(
+    slash_command,
+    guild_only,
+    ephemeral,
+    check = "owner_or_manage_guild",
+    rename = "reconcile"
+)]
+pub async fn admin_reconcile(ctx: Ctx<'_>) -> Result<()> {
+    let guild_id = ctx.guild_id().expect("guild_only");
+    let lang = guild_lang(ctx).await;
+    ctx.defer_ephemeral().await?;
+
+    let mut members = fetch_all_members(ctx.http(), guild_id).await?;
+    // Opted-out members are never tracked, same as `/settings backfill`.
+    if !ctx.data().settings_repo().get(&guild_id).await?.tracking_required {
+        let opted_out = OptOutRepo::new(&ctx.data().db)
+            .opted_out_users(guild_id)
+            .await?;
+        members.retain(|m| !opted_out.contains(&m.user.id));
+    }
+
+    let repo = MembershipsRepo::new(&ctx.data().db);
+    let tracked = repo.open_stint_user_ids(guild_id).await?.len();
+    let counts = repo.reconcile_open_stints(guild_id, &members).await?;
+    if counts.total() == 0 {
+        ctx.say(t("admin.reconcile.in_sync", lang, &[("live", &members.len())]))
+            .await?;
+        return Ok(());
+    }
+    ctx.data().forget_member_count(guild_id);
+
+    let mut msg = t(
+        "admin.reconcile.fixed",
+        lang,
+        &[
+            ("tracked", &tracked),
+            ("live", &members.len()),
+            ("total", &counts.total()),
+            ("closed", &counts.closed),
+            ("opened", &counts.opened),
+        ],
+    );
+    if counts.opened > 0 {
+        match repo.rebuild_usernames_fts_for_guild(guild_id).await {
+            Ok(_) => {}
+            Err(RepoError::FtsUnavailable) => {
+                msg.push('\n');
+                msg.push_str(&t("admin.reconcile.no_fts", lang, &[]));
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+    ctx.say(msg).await?;
+    Ok(())
+}
+
+/// Live event/throughput counters since start (or since the last reset).
+#[poise::command(slash_command, owners_only, ephemeral, rename = "stats-runtime")]
+pub async fn admin_stats_runtime(
+    ctx: Ctx<'_>,
+    #[description = "Reset the counters after reading them"] reset: Option<bool>,
+) -> Result<()> {
+    let lang = guild_lang(ctx).await;
+    let counters = &ctx.data().counters;
+    let s = if reset.unwrap_or(false) {
+        counters.snapshot_and_reset()
+    } else {
+        counters.snapshot()
+    };
+
+    let label = |key: &str| t(key, lang, &[]);
+    let embed = serenity::CreateEmbed::new()
+        .title(label("admin.runtime.title"))
+        .field(
+            label("admin.runtime.uptime"),
+            format_duration(chrono::Duration::seconds(s.uptime_secs as i64)),
+            true,
+        )
+        .field(label("admin.runtime.joins"), s.joins.to_string(), true)
+        .field(label("admin.runtime.leaves"), s.leaves.to_string(), true)
+        .field(label("admin.runtime.bans"), s.bans.to_string(), true)
+        .field(label("admin.runtime.kicks"), s.kicks.to_string(), true)
+        .field(label("admin.runtime.log_posts"), s.log_posts.to_string(), true)
+        .field(label("admin.runtime.db_errors"), s.db_errors.to_string(), true)
+        .field(label("admin.runtime.guilds_left"), s.guilds_left.to_string(), true)
+        .footer(serenity::CreateEmbedFooter::new(if reset.unwrap_or(false) {
+            label("admin.runtime.reset")
+        } else {
+            label("admin.runtime.since_start")
+        }));
+
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+    Ok(())
+}
+
+/// Guilds listed individually in `/admin stats-db`; the rest are summed.
+const STATS_DB_TOP_GUILDS: usize = 10;
+
+/// Database row counts, size on disk and journal mode.
+#[poise::command(slash_command, owners_only, ephemeral, rename = "stats-db")]
+pub async fn admin_stats_db(ctx: Ctx<'_>) -> Result<()> {
+    let lang = guild_lang(ctx).await;
+    let label = |key: &str| t(key, lang, &[]);
+    let d = ctx.data().db.diagnostics().await?;
+
+    let size = match d.file_size {
+        Some(bytes) => format!("{:.2} MiB", bytes as f64 / (1024.0 * 1024.0)),
+        None => label("admin.db.not_a_file"),
+    };
+    let fts = match d.fts_rows {
+        Some(n) => n.to_string(),
+        None => label("admin.db.fts_unavailable"),
+    };
+
+    let mut guild_lines: Vec<String> = d
+        .rows_per_guild
+        .iter()
+        .take(STATS_DB_TOP_GUILDS)
+        .map(|(gid, n)| {
+            let name = gid
+                .parse::<u64>()
+                .ok()
+                .and_then(|id| {
+                    let guild = ctx.cache().guild(serenity::GuildId::new(id))?;
+                    Some(guild.name.clone())
+                })
+                .unwrap_or_else(|| label("admin.db.not_cached"));
+            format!("`{gid}` {name} — {n}")
+        })
+        .collect();
+    let rest = d.rows_per_guild.len().saturating_sub(STATS_DB_TOP_GUILDS);
+    if rest > 0 {
+        let rest_rows: i64 = d
+            .rows_per_guild
+            .iter()
+            .skip(STATS_DB_TOP_GUILDS)
+            .map(|(_, n)| n)
+            .sum();
+        guild_lines.push(t("admin.db.more", lang, &[("count", &rest), ("rows", &rest_rows)]));
+    }
+    let guilds = if guild_lines.is_empty() {
+        label("admin.db.no_rows")
+    } else {
+        guild_lines.join("\n")
+    };
+
+    let embed = serenity::CreateEmbed::new()
+        .title(label("admin.db.title"))
+        .field(label("admin.db.membership_rows"), d.membership_rows.to_string(), true)
+        .field(label("admin.db.fts_rows"), fts, true)
+        .field(label("admin.db.servers"), d.rows_per_guild.len().to_string(), true)
+        .field(label("admin.db.size"), size, true)
+        .field(label("admin.db.journal_mode"), d.journal_mode, true)
+        .field(label("admin.db.per_server"), clip(guilds, MAX_EMBED_FIELD_CHARS), false);
+
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+    Ok(())
+}
+
+/// Rebuild the username search index here (or everywhere), with row counts and timings.
+#[poise::command(slash_command, owners_only, ephemeral, rename = "reload-fts")]
+pub async fn admin_reload_fts(
+    ctx: Ctx<'_>,
+    #[description = "Rebuild every server the bot is in, not just this one"] all_guilds: Option<
+        bool,
+    >,
+) -> Result<()> {
+    let lang = guild_lang(ctx).await;
+    let guilds = if all_guilds.unwrap_or(false) {
+        ctx.cache().guilds()
+    } else {
+        match ctx.guild_id() {
+            Some(gid) => vec![gid],
+            None => {
+                ctx.say(t("admin.reload_fts.need_guild", lang, &[])).await?;
+                return Ok(());
+            }
+        }
+    };
+    ctx.defer_ephemeral().await?;
+
+    let repo = MembershipsRepo::new(&ctx.data().db);
+    let started = Instant::now();
+    let mut total_rows = 0u64;
+    let mut failures = 0usize;
+    let mut lines = Vec::with_capacity(guilds.len());
+    for gid in &guilds {
+        let guild_started = Instant::now();
+        match repo.rebuild_usernames_fts_for_guild(*gid).await {
+            Ok(rows) => {
+                total_rows += rows;
+                let took = format!("{:.1?}", guild_started.elapsed());
+                lines.push(t(
+                    "admin.reload_fts.guild",
+                    lang,
+                    &[("guild", &gid), ("rows", &rows), ("took", &took)],
+                ));
+            }
+            Err(RepoError::FtsUnavailable) => {
+                ctx.say(t("admin.reload_fts.no_fts", lang, &[])).await?;
+                return Ok(());
+            }
+            Err(e) => {
+                failures += 1;
+                tracing::warn!("FTS rebuild failed for guild {gid}: {e}");
+                lines.push(format!("• `{gid}` — ❌ {e}"));
+            }
+        }
+    }
+
+    let took = format!("{:.1?}", started.elapsed());
+    let mut header = t(
+        "admin.reload_fts.header",
+        lang,
+        &[("rows", &total_rows), ("servers", &guilds.len()), ("took", &took)],
+    );
+    if failures > 0 {
+        header.push(' ');
+        header.push_str(&t("admin.reload_fts.failed", lang, &[("count", &failures)]));
+    }
+    lines.insert(0, header);
+
+    send_chunked_embeds(
+        ctx,
+        lines,
+        false,
+        |desc| {
+            serenity::CreateEmbed::new()
+                .title(t("admin.reload_fts.title", lang, &[]))
+                .description(desc)
+        },
+        |idx, desc| {
+            let title = t("admin.reload_fts.title", lang, &[]);
+            serenity::CreateEmbed::new()
+                .title(t("stats.cont", lang, &[("title", &title), ("n", &idx)]))
+                .description(desc)
+        },
+    )
+    .await?;
+    Ok(())
+}
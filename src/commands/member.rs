@@ -1,45 +1,72 @@
 use anyhow::Result;
 use poise::serenity_prelude as serenity;
 
-use crate::commands::send_chunked_embeds;
-use crate::repos::MembershipsRepo;
-use crate::state::Ctx;
+use crate::commands::{
+    ban_reason_suffix, format_duration, guild_colour, guild_lang, owner_or_manage_guild,
+    send_chunked_embeds, send_paginated_embeds, with_colour,
+};
+use crate::flows::confirm::confirm;
+use crate::repos::{
+    LeaveKind, MemberNotesRepo, MembershipRow, MembershipStore, MembershipsRepo, OptOutRepo,
+    RepoError, UnixTime, UserSummary, WatchlistRepo,
+};
+use crate::state::{AUTOCOMPLETE_MIN_INTERVAL, Ctx};
+use crate::strings::t;
 
 /// Autocomplete by nickname/account username; returns `AutocompleteChoice<label, value=user_id>`
 pub async fn ac_member(ctx: Ctx<'_>, partial: &str) -> Vec<serenity::AutocompleteChoice> {
     let Some(gid) = ctx.guild_id() else {
         return Vec::new();
     };
+    let state = ctx.data();
+    let uid = ctx.author().id;
 
-    let repo = MembershipsRepo::new(&ctx.data().db);
-    // Limit 25: Discord max visible suggestions
-    let Ok(rows) = repo.search_user_summaries_prefix(gid, partial, 25).await else {
+    let search = || async {
+        // Limit 25: Discord max visible suggestions
+        let rows = state
+            .memberships()
+            .search_user_summaries_prefix(gid, partial, 25)
+            .await?;
+        // value = user_id (string). Keeps execution side simple/reliable even for ex-members.
+        Ok(rows.into_iter().map(|r| (r.label(), r.user_id)).collect())
+    };
+    // Fast typists: reuse the last answer instead of querying on every keystroke.
+    let Ok(choices) = state
+        .autocomplete_with(gid, uid, AUTOCOMPLETE_MIN_INTERVAL, search)
+        .await
+    else {
         return Vec::new();
     };
 
-    rows.into_iter()
-        .map(|r| {
-            let label = match (r.server_username.as_deref(), r.account_username.as_deref()) {
-                (Some(nick), Some(acc)) if !nick.is_empty() => format!("{nick} (aka {acc})"),
-                (_, Some(acc)) => acc.to_string(),
-                (Some(nick), None) => nick.to_string(),
-                _ => format!("User {}", r.user_id),
-            };
-            // value = user_id (string). Keeps execution side simple/reliable even for ex-members.
-            serenity::AutocompleteChoice::new(label, r.user_id)
-        })
+    choices
+        .into_iter()
+        .map(|(label, value)| serenity::AutocompleteChoice::new(label, value))
         .collect()
 }
 
 /// Parent command: `/member`
 ///
-/// Right now it only exposes `/member history`, but you can add more later
-/// (e.g. `/member search`, `/member summary`, etc.).
+/// Exposes `/member history`, `/member status`, `/member timeline`, `/member search`,
+/// `/member recent` and the `/member note …` and `/member watch …` groups;
+/// add more later (e.g. `/member summary`).
 #[poise::command(
     slash_command,
     guild_only,
     ephemeral,
-    subcommands("member_history"),
+    subcommands(
+        "member_history",
+        "member_status",
+        "member_timeline",
+        "member_search",
+        "member_recent",
+        "member_note",
+        "member_watch",
+        "member_export",
+        "member_export_user",
+        "member_purge",
+        "member_merge",
+        "member_optout"
+    ),
     rename = "member"
 )]
 pub async fn member(_: Ctx<'_>) -> Result<()> {
@@ -57,53 +84,64 @@ pub async fn member_history(
     #[autocomplete = "ac_member"]
     user_id: String,
 ) -> Result<()> {
+    let lang = guild_lang(ctx).await;
     let Some(guild_id) = ctx.guild_id() else {
-        ctx.say("This command can only be used in a guild.").await?;
+        ctx.say(t("common.guild_only", lang, &[])).await?;
         return Ok(());
     };
 
-    let repo = MembershipsRepo::new(&ctx.data().db);
+    let repo = ctx.data().memberships();
 
     let uid = match user_id.parse::<u64>() {
         Ok(raw) => serenity::all::UserId::new(raw),
         Err(_) => {
-            ctx.say("Couldn't parse that user id. Please pick from the autocomplete list.")
-                .await?;
+            ctx.say(t("member.bad_user_id", lang, &[])).await?;
             return Ok(());
         }
     };
 
     let rows = repo.history_for_user(guild_id, uid).await?;
 
-    // Helper for timestamps
-    let ts = |rfc2822: &str| -> String {
-        if let Ok(dt) = chrono::DateTime::parse_from_rfc2822(rfc2822) {
-            format!("<t:{}:f>", dt.timestamp())
-        } else {
-            rfc2822.to_string()
-        }
-    };
+    let ts = |at: UnixTime| at.discord('f');
 
     let mut lines: Vec<String> = Vec::with_capacity(rows.len() * 2);
     for r in &rows {
-        lines.push(format!("joined — {}", ts(&r.joined_at)));
-        if let Some(left_at) = r.left_at.as_deref() {
-            let action = if r.banned { "banned" } else { "left" };
-            lines.push(format!("{action} — {}", ts(left_at)));
+        if r.inferred {
+            // Only the leave was seen (bot offline at join time).
+            lines.push(t("userinfo.joined_untracked", lang, &[]));
+        } else {
+            lines.push(t("userinfo.joined", lang, &[("when", &ts(r.joined_at))]));
+        }
+        if let Some(left_at) = r.left_at {
+            let action = t(&format!("leave_kind.{}", r.leave_kind.verb()), lang, &[]);
+            let reason = if r.leave_kind == LeaveKind::Banned {
+                ban_reason_suffix(r.ban_reason.as_deref(), lang)
+            } else {
+                String::new()
+            };
+            lines.push(t(
+                "userinfo.exit",
+                lang,
+                &[("action", &action), ("when", &ts(left_at)), ("reason", &reason)],
+            ));
+        }
+        if let Some(unbanned_at) = r.unbanned_at {
+            lines.push(t("userinfo.unbanned", lang, &[("when", &ts(unbanned_at))]));
         }
     }
 
-    let title = format!("History for user {}", uid);
+    let title = t("userinfo.title_id", lang, &[("id", &uid)]);
     if lines.is_empty() {
         let embed = serenity::CreateEmbed::new()
             .title(title)
-            .description("No membership history found for this user.");
+            .description(t("member.no_history", lang, &[]));
 
+        let embed = with_colour(embed, guild_colour(ctx).await);
         ctx.send(poise::CreateReply::default().embed(embed)).await?;
         return Ok(());
     }
 
-    send_chunked_embeds(
+    send_paginated_embeds(
         ctx,
         lines,
         |first_desc| {
@@ -112,8 +150,9 @@ pub async fn member_history(
                 .description(first_desc)
         },
         |index, cont_desc| {
+            let title = t("member.history_title", lang, &[]);
             serenity::CreateEmbed::new()
-                .title(format!("History (cont. #{})", index))
+                .title(t("stats.cont", lang, &[("title", &title), ("n", &index)]))
                 .description(cont_desc)
         },
     )
@@ -121,3 +160,929 @@ pub async fn member_history(
 
     Ok(())
 }
+
+/// Quick yes/no for moderation: in the server now, last seen, stint count, banned.
+///
+/// Usage: `/member status user:<type to search or paste an id>`
+#[poise::command(slash_command, guild_only, ephemeral, rename = "status")]
+pub async fn member_status(
+    ctx: Ctx<'_>,
+    #[description = "Pick a user by name, or paste an id or mention"]
+    #[autocomplete = "ac_member"]
+    user_id: String,
+) -> Result<()> {
+    let lang = guild_lang(ctx).await;
+    let Some(guild_id) = ctx.guild_id() else {
+        ctx.say(t("common.guild_only", lang, &[])).await?;
+        return Ok(());
+    };
+    let Some(uid) = parse_user_id(&user_id) else {
+        ctx.say(t("member.bad_user_id", lang, &[])).await?;
+        return Ok(());
+    };
+
+    let rows = ctx.data().memberships()
+        .history_for_user(guild_id, uid)
+        .await?;
+    let (Some(first), Some(last)) = (rows.first(), rows.last()) else {
+        let embed = serenity::CreateEmbed::new()
+            .title(t("member.status.title_id", lang, &[("id", &uid)]))
+            .description(t("member.status.never_seen", lang, &[("user", &format!("<@{uid}>"))]));
+        let embed = with_colour(embed, guild_colour(ctx).await);
+        ctx.send(poise::CreateReply::default().embed(embed)).await?;
+        return Ok(());
+    };
+
+    let name = last
+        .server_username
+        .as_deref()
+        .or(last.account_username.as_deref())
+        .map(str::to_string)
+        .unwrap_or_else(|| t("member.status.unknown_name", lang, &[]));
+    let present = match last.left_at {
+        None => t("member.status.present", lang, &[]),
+        Some(_) => {
+            let kind = t(&format!("leave_kind.{}", last.leave_kind.verb()), lang, &[]);
+            t("member.status.gone", lang, &[("kind", &kind)])
+        }
+    };
+    let last_seen = match last.left_at {
+        None => t("member.status.now", lang, &[]),
+        Some(left_at) => format!("{} ({})", left_at.discord('f'), left_at.discord('R')),
+    };
+    let first_seen = if first.inferred {
+        t("member.untracked", lang, &[])
+    } else {
+        first.joined_at.discord('D')
+    };
+    let banned = if last.leave_kind == LeaveKind::Banned && last.unbanned_at.is_none() {
+        let reason = ban_reason_suffix(last.ban_reason.as_deref(), lang);
+        t("member.status.banned", lang, &[("reason", &reason)])
+    } else {
+        t("member.status.not_banned", lang, &[])
+    };
+
+    let label = |key: &str| t(key, lang, &[]);
+    let embed = serenity::CreateEmbed::new()
+        .title(t("member.status.title", lang, &[("name", &name)]))
+        .description(format!("<@{uid}> · `{uid}`"))
+        .field(label("member.status.in_server"), present, true)
+        .field(label("member.status.last_seen"), last_seen, true)
+        .field(label("member.status.stints"), rows.len().to_string(), true)
+        .field(label("member.status.first_seen"), first_seen, true)
+        .field(label("member.status.banned_field"), banned, true);
+    let embed = with_colour(embed, guild_colour(ctx).await);
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+    Ok(())
+}
+
+/// Most stints drawn by `/member timeline` (newest kept) so the block fits one embed.
+const TIMELINE_MAX_STINTS: usize = 20;
+/// Width of the longest bar; shorter stints scale down from it.
+const TIMELINE_BAR_WIDTH: usize = 24;
+
+/// Draw a user's stints as bars scaled by how long each lasted.
+///
+/// Usage: `/member timeline user:<type to search>`
+#[poise::command(slash_command, guild_only, ephemeral, rename = "timeline")]
+pub async fn member_timeline(
+    ctx: Ctx<'_>,
+    #[description = "Pick a user by name"]
+    #[autocomplete = "ac_member"]
+    user_id: String,
+) -> Result<()> {
+    let lang = guild_lang(ctx).await;
+    let Some(guild_id) = ctx.guild_id() else {
+        ctx.say(t("common.guild_only", lang, &[])).await?;
+        return Ok(());
+    };
+
+    let uid = match user_id.parse::<u64>() {
+        Ok(raw) => serenity::all::UserId::new(raw),
+        Err(_) => {
+            ctx.say(t("member.bad_user_id", lang, &[])).await?;
+            return Ok(());
+        }
+    };
+
+    let rows = ctx.data().memberships()
+        .history_for_user(guild_id, uid)
+        .await?;
+    let tz = ctx.data().settings_repo().get_cached(&guild_id).await?.tz();
+
+    let total = rows.len();
+    let shown = &rows[total.saturating_sub(TIMELINE_MAX_STINTS)..];
+    let description = if shown.is_empty() {
+        t("member.no_history", lang, &[])
+    } else {
+        let mut desc = format!(
+            "```text\n{}\n```",
+            render_timeline(shown, chrono::Utc::now(), tz, lang).join("\n")
+        );
+        if total > shown.len() {
+            desc.push('\n');
+            desc.push_str(&t(
+                "member.timeline.truncated",
+                lang,
+                &[("shown", &shown.len()), ("total", &total)],
+            ));
+        }
+        desc
+    };
+
+    let embed = serenity::CreateEmbed::new()
+        .title(t("member.timeline.title", lang, &[("id", &uid)]))
+        .description(description)
+        .footer(serenity::CreateEmbedFooter::new(t(
+            "member.timeline.footer",
+            lang,
+            &[("tz", &tz.name())],
+        )));
+    let embed = with_colour(embed, guild_colour(ctx).await);
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+    Ok(())
+}
+
+/// One line per stint: `join date |bar| duration end`, bars scaled to the longest stint.
+///
+/// Open stints run to `now`; inferred ones (join never seen) have no length and get `?`.
+fn render_timeline(
+    rows: &[MembershipRow],
+    now: chrono::DateTime<chrono::Utc>,
+    tz: chrono_tz::Tz,
+    lang: &str,
+) -> Vec<String> {
+    let spans: Vec<Option<chrono::Duration>> = rows
+        .iter()
+        .map(|r| {
+            if r.inferred {
+                return None;
+            }
+            let joined = r.joined_at.to_datetime();
+            let ended = r.left_at.map_or(now, UnixTime::to_datetime);
+            Some(ended - joined)
+        })
+        .collect();
+    let longest = spans
+        .iter()
+        .flatten()
+        .map(|d| d.num_seconds())
+        .max()
+        .unwrap_or(0)
+        .max(1);
+
+    rows.iter()
+        .zip(&spans)
+        .map(|(r, span)| {
+            let date = if r.inferred {
+                "????-??-??".to_string()
+            } else {
+                let joined = r.joined_at.to_datetime().with_timezone(&tz);
+                joined.format("%Y-%m-%d").to_string()
+            };
+            let (bar, length) = match span {
+                Some(d) => {
+                    let filled = (d.num_seconds().max(0) as f64 / longest as f64
+                        * TIMELINE_BAR_WIDTH as f64)
+                        .round()
+                        .max(1.0) as usize;
+                    ("█".repeat(filled.min(TIMELINE_BAR_WIDTH)), format_duration(*d))
+                }
+                None => ("?".to_string(), "?".to_string()),
+            };
+            let end = match r.left_at {
+                Some(_) => t(&format!("leave_kind.{}", r.leave_kind.verb()), lang, &[]),
+                None => "●".to_string(),
+            };
+            format!("{date} |{bar:<TIMELINE_BAR_WIDTH$}| {length:>7} {end}")
+        })
+        .collect()
+}
+
+/// Most recently active members (by latest membership row), paged backward.
+///
+/// Usage: `/member recent`
+#[poise::command(slash_command, guild_only, ephemeral, rename = "recent")]
+pub async fn member_recent(ctx: Ctx<'_>) -> Result<()> {
+    let lang = guild_lang(ctx).await;
+    let Some(guild_id) = ctx.guild_id() else {
+        ctx.say(t("common.guild_only", lang, &[])).await?;
+        return Ok(());
+    };
+
+    crate::flows::recent_members::browse_recent_members(ctx, guild_id).await
+}
+
+/// Results per page for `/member search`.
+const SEARCH_PAGE_SIZE: usize = 10;
+/// Upper bound on rows fetched for one search (keeps the paginator bounded).
+const SEARCH_MAX_RESULTS: i64 = 250;
+
+/// Full name search over last-known account/server names, paged ten at a time.
+///
+/// Usage: `/member search query:<text> [include_left:true]`
+#[poise::command(slash_command, guild_only, ephemeral, rename = "search")]
+pub async fn member_search(
+    ctx: Ctx<'_>,
+    #[description = "Part of a username or nickname"] query: String,
+    #[description = "Only show members who have since left"] include_left: Option<bool>,
+) -> Result<()> {
+    let lang = guild_lang(ctx).await;
+    let Some(guild_id) = ctx.guild_id() else {
+        ctx.say(t("common.guild_only", lang, &[])).await?;
+        return Ok(());
+    };
+
+    let query = query.trim();
+    if query.is_empty() {
+        ctx.say(t("member.search.empty_query", lang, &[])).await?;
+        return Ok(());
+    }
+
+    let only_left = include_left.unwrap_or(false);
+    let repo = ctx.data().memberships();
+    let rows: Vec<UserSummary> = repo
+        .search_user_summaries(guild_id, &format!("%{query}%"), SEARCH_MAX_RESULTS)
+        .await?
+        .into_iter()
+        .filter(|r| !only_left || !r.is_present())
+        .collect();
+
+    let title_key = if only_left {
+        "member.search.title_left"
+    } else {
+        "member.search.title"
+    };
+    let title = t(title_key, lang, &[("query", &query)]);
+    let colour = guild_colour(ctx).await;
+
+    if rows.is_empty() {
+        let embed = serenity::CreateEmbed::new()
+            .title(title)
+            .description(t("member.search.no_matches", lang, &[]));
+        ctx.send(poise::CreateReply::default().embed(with_colour(embed, colour)))
+            .await?;
+        return Ok(());
+    }
+
+    let pages = rows
+        .chunks(SEARCH_PAGE_SIZE)
+        .map(|chunk| {
+            let lines: Vec<String> = chunk
+                .iter()
+                .map(|r| {
+                    let status = if r.is_present() {
+                        t("member.search.present", lang, &[])
+                    } else {
+                        t("member.search.left", lang, &[])
+                    };
+                    format!("• {} — <@{}> · {status}", r.label(), r.user_id)
+                })
+                .collect();
+            with_colour(
+                serenity::CreateEmbed::new()
+                    .title(title.clone())
+                    .description(lines.join("\n")),
+                colour,
+            )
+        })
+        .collect();
+
+    crate::flows::paginator::paginate(ctx, pages).await
+}
+
+/// Notes are shown in an embed field (1024 chars), so keep them well under that.
+const MAX_NOTE_CHARS: usize = 900;
+
+/// Parse the autocomplete value (a user id string); a pasted `<@id>` mention also works.
+fn parse_user_id(raw: &str) -> Option<serenity::all::UserId> {
+    let raw = raw.trim();
+    let raw = raw
+        .strip_prefix("<@")
+        .and_then(|r| r.strip_suffix('>'))
+        .map(|r| r.trim_start_matches('!'))
+        .unwrap_or(raw);
+    raw.parse::<u64>().ok().filter(|&id| id != 0).map(serenity::all::UserId::new)
+}
+
+/// `/member note …`: moderator notes that persist across rejoins.
+#[poise::command(
+    slash_command,
+    guild_only,
+    ephemeral,
+    subcommands("member_note_add", "member_note_list", "member_note_remove"),
+    rename = "note"
+)]
+pub async fn member_note(_: Ctx<'_>) -> Result<()> {
+    Ok(())
+}
+
+/// Attach a note to a user.
+#[poise::command(slash_command, guild_only, ephemeral, rename = "add")]
+pub async fn member_note_add(
+    ctx: Ctx<'_>,
+    #[description = "Pick a user by name"]
+    #[autocomplete = "ac_member"]
+    user_id: String,
+    #[description = "The note"] text: String,
+) -> Result<()> {
+    let lang = guild_lang(ctx).await;
+    let Some(guild_id) = ctx.guild_id() else {
+        ctx.say(t("common.guild_only", lang, &[])).await?;
+        return Ok(());
+    };
+    let Some(uid) = parse_user_id(&user_id) else {
+        ctx.say(t("member.bad_user_id", lang, &[])).await?;
+        return Ok(());
+    };
+
+    let text = text.trim();
+    if text.is_empty() {
+        ctx.say(t("member.note.empty", lang, &[])).await?;
+        return Ok(());
+    }
+    if text.chars().count() > MAX_NOTE_CHARS {
+        ctx.say(t("member.note.too_long", lang, &[("max", &MAX_NOTE_CHARS)]))
+            .await?;
+        return Ok(());
+    }
+
+    let repo = MemberNotesRepo::new(&ctx.data().db);
+    let id = repo.add_note(guild_id, uid, ctx.author().id, text).await?;
+
+    let user = format!("<@{}>", uid.get());
+    ctx.say(t("member.note.added", lang, &[("id", &id), ("user", &user)]))
+        .await?;
+    Ok(())
+}
+
+/// List all notes for a user.
+#[poise::command(slash_command, guild_only, ephemeral, rename = "list")]
+pub async fn member_note_list(
+    ctx: Ctx<'_>,
+    #[description = "Pick a user by name"]
+    #[autocomplete = "ac_member"]
+    user_id: String,
+) -> Result<()> {
+    let lang = guild_lang(ctx).await;
+    let Some(guild_id) = ctx.guild_id() else {
+        ctx.say(t("common.guild_only", lang, &[])).await?;
+        return Ok(());
+    };
+    let Some(uid) = parse_user_id(&user_id) else {
+        ctx.say(t("member.bad_user_id", lang, &[])).await?;
+        return Ok(());
+    };
+
+    let repo = MemberNotesRepo::new(&ctx.data().db);
+    let notes = repo.list_notes(guild_id, uid).await?;
+    if notes.is_empty() {
+        let user = format!("<@{}>", uid.get());
+        ctx.say(t("member.note.none", lang, &[("user", &user)])).await?;
+        return Ok(());
+    }
+
+    let lines: Vec<String> = notes
+        .iter()
+        .map(|n| {
+            format!(
+                "`#{}` — <@{}> — {}\n{}",
+                n.id,
+                n.author_id,
+                n.created_at.discord('f'),
+                n.body
+            )
+        })
+        .collect();
+
+    let title = t("member.note.title_id", lang, &[("id", &uid)]);
+    send_chunked_embeds(
+        ctx,
+        lines,
+        false,
+        |desc| {
+            serenity::CreateEmbed::new()
+                .title(title)
+                .description(desc)
+        },
+        |idx, desc| {
+            serenity::CreateEmbed::new()
+                .title(t(
+                    "stats.cont",
+                    lang,
+                    &[("title", &t("member.note.title", lang, &[])), ("n", &idx)],
+                ))
+                .description(desc)
+        },
+    )
+    .await?;
+    Ok(())
+}
+
+/// Remove a note by its id (see `/member note list`).
+#[poise::command(slash_command, guild_only, ephemeral, rename = "remove")]
+pub async fn member_note_remove(
+    ctx: Ctx<'_>,
+    #[description = "Pick a user by name"]
+    #[autocomplete = "ac_member"]
+    user_id: String,
+    #[description = "Note id, e.g. 12 for #12"] note_id: i64,
+) -> Result<()> {
+    let lang = guild_lang(ctx).await;
+    let Some(guild_id) = ctx.guild_id() else {
+        ctx.say(t("common.guild_only", lang, &[])).await?;
+        return Ok(());
+    };
+    let Some(uid) = parse_user_id(&user_id) else {
+        ctx.say(t("member.bad_user_id", lang, &[])).await?;
+        return Ok(());
+    };
+
+    let repo = MemberNotesRepo::new(&ctx.data().db);
+    match repo.delete_note(guild_id, uid, note_id).await {
+        Ok(()) => {
+            ctx.say(t("member.note.removed", lang, &[("id", &note_id)])).await?;
+        }
+        Err(RepoError::NotFound) => {
+            let user = format!("<@{}>", uid.get());
+            ctx.say(t("member.note.not_found", lang, &[("id", &note_id), ("user", &user)]))
+                .await?;
+        }
+        Err(e) => return Err(e.into()),
+    }
+    Ok(())
+}
+
+/// `/member watch …`: users to alert the mod log about whenever they join.
+#[poise::command(
+    slash_command,
+    guild_only,
+    ephemeral,
+    subcommands("member_watch_add", "member_watch_remove", "member_watch_list"),
+    rename = "watch"
+)]
+pub async fn member_watch(_: Ctx<'_>) -> Result<()> {
+    Ok(())
+}
+
+/// Alert the mod log whenever this user joins (works for users who never joined too).
+#[poise::command(slash_command, guild_only, ephemeral, rename = "add")]
+pub async fn member_watch_add(
+    ctx: Ctx<'_>,
+    #[description = "Pick a user by name, or paste an id or mention"]
+    #[autocomplete = "ac_member"]
+    user_id: String,
+    #[description = "Why they're watched (shown in the alert)"] reason: Option<String>,
+) -> Result<()> {
+    let lang = guild_lang(ctx).await;
+    let Some(guild_id) = ctx.guild_id() else {
+        ctx.say(t("common.guild_only", lang, &[])).await?;
+        return Ok(());
+    };
+    let Some(uid) = parse_user_id(&user_id) else {
+        ctx.say(t("member.bad_user_id", lang, &[])).await?;
+        return Ok(());
+    };
+
+    let reason = reason.as_deref().map(str::trim).filter(|r| !r.is_empty());
+    if reason.is_some_and(|r| r.chars().count() > MAX_NOTE_CHARS) {
+        ctx.say(t("member.watch.reason_too_long", lang, &[("max", &MAX_NOTE_CHARS)]))
+            .await?;
+        return Ok(());
+    }
+
+    let repo = WatchlistRepo::new(&ctx.data().db);
+    let added = repo.add(guild_id, uid, reason, ctx.author().id).await?;
+    let user = format!("<@{}>", uid.get());
+    let key = if added {
+        "member.watch.added"
+    } else {
+        "member.watch.updated"
+    };
+    let msg = t(key, lang, &[("user", &user)]);
+    ctx.say(msg).await?;
+    Ok(())
+}
+
+/// Take a user off the watchlist.
+#[poise::command(slash_command, guild_only, ephemeral, rename = "remove")]
+pub async fn member_watch_remove(
+    ctx: Ctx<'_>,
+    #[description = "Pick a user by name, or paste an id or mention"]
+    #[autocomplete = "ac_member"]
+    user_id: String,
+) -> Result<()> {
+    let lang = guild_lang(ctx).await;
+    let Some(guild_id) = ctx.guild_id() else {
+        ctx.say(t("common.guild_only", lang, &[])).await?;
+        return Ok(());
+    };
+    let Some(uid) = parse_user_id(&user_id) else {
+        ctx.say(t("member.bad_user_id", lang, &[])).await?;
+        return Ok(());
+    };
+
+    let repo = WatchlistRepo::new(&ctx.data().db);
+    let user = format!("<@{}>", uid.get());
+    let key = if repo.remove(guild_id, uid).await? {
+        "member.watch.removed"
+    } else {
+        "member.watch.not_watched"
+    };
+    let msg = t(key, lang, &[("user", &user)]);
+    ctx.say(msg).await?;
+    Ok(())
+}
+
+/// Everyone on the watchlist, most recently added first.
+#[poise::command(slash_command, guild_only, ephemeral, rename = "list")]
+pub async fn member_watch_list(ctx: Ctx<'_>) -> Result<()> {
+    let lang = guild_lang(ctx).await;
+    let Some(guild_id) = ctx.guild_id() else {
+        ctx.say(t("common.guild_only", lang, &[])).await?;
+        return Ok(());
+    };
+
+    let entries = WatchlistRepo::new(&ctx.data().db).list(guild_id).await?;
+    if entries.is_empty() {
+        ctx.say(t("member.watch.empty", lang, &[])).await?;
+        return Ok(());
+    }
+
+    let lines: Vec<String> = entries
+        .iter()
+        .map(|w| {
+            let reason = w
+                .reason
+                .clone()
+                .unwrap_or_else(|| t("member.watch.no_reason", lang, &[]));
+            format!(
+                "<@{}> (`{}`) — by <@{}> {}\n{reason}",
+                w.user_id,
+                w.user_id,
+                w.added_by,
+                w.added_at.discord('R')
+            )
+        })
+        .collect();
+
+    let title = t("member.watch.title", lang, &[("count", &entries.len())]);
+    send_chunked_embeds(
+        ctx,
+        lines,
+        false,
+        |desc| {
+            serenity::CreateEmbed::new()
+                .title(title)
+                .description(desc)
+        },
+        |idx, desc| {
+            serenity::CreateEmbed::new()
+                .title(t(
+                    "stats.cont",
+                    lang,
+                    &[("title", &t("member.watch.title_plain", lang, &[])), ("n", &idx)],
+                ))
+                .description(desc)
+        },
+    )
+    .await?;
+    Ok(())
+}
+
+/// Stay under Discord's 8 MB upload cap (with headroom for multipart overhead).
+const MAX_EXPORT_BYTES: usize = 7_500_000;
+
+/// Export every membership row for this server as CSV attachment(s).
+#[poise::command(
+    slash_command,
+    guild_only,
+    ephemeral,
+    required_permissions = "MANAGE_GUILD",
+    rename = "export"
+)]
+pub async fn member_export(ctx: Ctx<'_>) -> Result<()> {
+    let lang = guild_lang(ctx).await;
+    let Some(guild_id) = ctx.guild_id() else {
+        ctx.say(t("common.guild_only", lang, &[])).await?;
+        return Ok(());
+    };
+    ctx.defer_ephemeral().await?;
+
+    let repo = ctx.data().memberships();
+    let rows = repo.all_rows_for_guild(guild_id).await?;
+    if rows.is_empty() {
+        ctx.say(t("member.export.empty", lang, &[])).await?;
+        return Ok(());
+    }
+
+    const HEADER: [&str; 9] = [
+        "guild_id",
+        "user_id",
+        "joined_at",
+        "left_at",
+        "banned",
+        "leave_kind",
+        "account_username",
+        "server_username",
+        "inferred",
+    ];
+
+    // Encode row by row so each file can be cut before it crosses the cap.
+    let mut files: Vec<Vec<u8>> = Vec::new();
+    let mut current = csv_line(&HEADER)?;
+    let mut rows_in_current = 0usize;
+    for r in &rows {
+        let joined_at = r.joined_at.to_rfc3339();
+        let left_at = r.left_at.map(UnixTime::to_rfc3339).unwrap_or_default();
+        let line = csv_line(&[
+            r.guild_id.as_str(),
+            r.user_id.as_str(),
+            joined_at.as_str(),
+            left_at.as_str(),
+            if r.banned { "1" } else { "0" },
+            r.leave_kind.verb(),
+            r.account_username.as_deref().unwrap_or(""),
+            r.server_username.as_deref().unwrap_or(""),
+            if r.inferred { "1" } else { "0" },
+        ])?;
+        if rows_in_current > 0 && current.len() + line.len() > MAX_EXPORT_BYTES {
+            files.push(std::mem::replace(&mut current, csv_line(&HEADER)?));
+            rows_in_current = 0;
+        }
+        current.extend_from_slice(&line);
+        rows_in_current += 1;
+    }
+    files.push(current);
+
+    let parts = files.len();
+    for (i, bytes) in files.into_iter().enumerate() {
+        let name = if parts == 1 {
+            format!("memberships-{}.csv", guild_id.get())
+        } else {
+            format!("memberships-{}-part{}.csv", guild_id.get(), i + 1)
+        };
+        let mut reply = poise::CreateReply::default()
+            .attachment(serenity::CreateAttachment::bytes(bytes, name));
+        if i == 0 {
+            let content = if parts > 1 {
+                t("member.export.done_parts", lang, &[("rows", &rows.len()), ("parts", &parts)])
+            } else {
+                t("member.export.done", lang, &[("rows", &rows.len())])
+            };
+            reply = reply.content(content);
+        }
+        ctx.send(reply).await?;
+    }
+    Ok(())
+}
+
+/// One CSV record (quoted as needed, newline-terminated).
+fn csv_line(fields: &[&str]) -> Result<Vec<u8>> {
+    let mut w = csv::Writer::from_writer(Vec::new());
+    w.write_record(fields)?;
+    Ok(w.into_inner()?)
+}
+
+/// Whether the invoking member has Manage Guild in this channel (as resolved by Discord).
+fn author_can_manage_guild(ctx: Ctx<'_>) -> bool {
+    match ctx {
+        poise::Context::Application(actx) => actx
+            .interaction
+            .member
+            .as_ref()
+            .and_then(|m| m.permissions)
+            .is_some_and(|p| p.manage_guild()),
+        poise::Context::Prefix(_) => false,
+    }
+}
+
+/// Download everything stored about one user as JSON.
+///
+/// Anyone may export their own record; exporting others needs Manage Server.
+#[poise::command(slash_command, guild_only, ephemeral, rename = "export-user")]
+pub async fn member_export_user(
+    ctx: Ctx<'_>,
+    #[description = "Pick a user by name"]
+    #[autocomplete = "ac_member"]
+    user_id: String,
+) -> Result<()> {
+    let lang = guild_lang(ctx).await;
+    let Some(guild_id) = ctx.guild_id() else {
+        ctx.say(t("common.guild_only", lang, &[])).await?;
+        return Ok(());
+    };
+    let Some(uid) = parse_user_id(&user_id) else {
+        ctx.say(t("member.bad_user_id", lang, &[])).await?;
+        return Ok(());
+    };
+    if uid != ctx.author().id && !author_can_manage_guild(ctx) {
+        ctx.say(t("member.export_user.not_allowed", lang, &[])).await?;
+        return Ok(());
+    }
+
+    let repo = ctx.data().memberships();
+    let record = repo.full_user_record(guild_id, uid).await?;
+    let json = serde_json::to_vec_pretty(&record)?;
+
+    let name = format!("user-{}-{}.json", guild_id.get(), uid.get());
+    ctx.send(
+        poise::CreateReply::default()
+            .content(t(
+                "member.export_user.done",
+                lang,
+                &[
+                    ("user", &format!("<@{}>", uid.get())),
+                    ("stays", &record.memberships.len()),
+                    ("names", &record.name_changes.len()),
+                    ("notes", &record.notes.len()),
+                ],
+            ))
+            .attachment(serenity::CreateAttachment::bytes(json, name)),
+    )
+    .await?;
+    Ok(())
+}
+
+/// Permanently delete everything stored about a user in this server (asks first).
+#[poise::command(
+    slash_command,
+    guild_only,
+    ephemeral,
+    required_permissions = "MANAGE_GUILD",
+    rename = "purge"
+)]
+pub async fn member_purge(
+    ctx: Ctx<'_>,
+    #[description = "Pick a user by name"]
+    #[autocomplete = "ac_member"]
+    user_id: String,
+) -> Result<()> {
+    let lang = guild_lang(ctx).await;
+    let Some(guild_id) = ctx.guild_id() else {
+        ctx.say(t("common.guild_only", lang, &[])).await?;
+        return Ok(());
+    };
+    let Some(uid) = parse_user_id(&user_id) else {
+        ctx.say(t("member.bad_user_id", lang, &[])).await?;
+        return Ok(());
+    };
+
+    let confirmed = confirm(
+        ctx,
+        t("member.purge.confirm", lang, &[("user", &format!("<@{}>", uid.get()))]),
+    )
+    .await?;
+    if !confirmed {
+        return Ok(());
+    }
+
+    let db = &ctx.data().db;
+    let repo = MembershipsRepo::new(db);
+    let mut tx = db.transaction().await?;
+    let removed = repo.purge_user(&mut tx, guild_id, uid).await?;
+    tx.commit().await?;
+
+    ctx.say(t(
+        "member.purge.done",
+        lang,
+        &[
+            ("user", &format!("<@{}>", uid.get())),
+            ("stays", &removed.memberships),
+            ("fts", &removed.fts),
+            ("notes", &removed.notes),
+            ("names", &removed.name_changes),
+        ],
+    ))
+    .await?;
+    Ok(())
+}
+
+/// Delete your history in this server and stop tracking you (joins/leaves still hit the logs).
+///
+/// Opting out permanently deletes your recorded stays, name history and moderator notes
+/// here. From then on your joins, leaves and name changes are not stored, though they are
+/// still posted to the server's log channels as they happen. Servers that must keep full
+/// records can turn opt-outs off (`/settings tracking-required`).
+#[poise::command(slash_command, guild_only, ephemeral, rename = "optout")]
+pub async fn member_optout(
+    ctx: Ctx<'_>,
+    #[description = "Opt back in: your future joins, leaves and renames are stored again"]
+    undo: Option<bool>,
+) -> Result<()> {
+    let lang = guild_lang(ctx).await;
+    let Some(guild_id) = ctx.guild_id() else {
+        ctx.say(t("common.guild_only", lang, &[])).await?;
+        return Ok(());
+    };
+    let uid = ctx.author().id;
+    let db = &ctx.data().db;
+    let optouts = OptOutRepo::new(db);
+
+    if undo.unwrap_or(false) {
+        let key = if optouts.opt_in(guild_id, uid).await? {
+            "member.optout.opted_in"
+        } else {
+            "member.optout.not_opted_out"
+        };
+        ctx.say(t(key, lang, &[])).await?;
+        return Ok(());
+    }
+
+    if ctx.data().settings_repo().get_cached(&guild_id).await?.tracking_required {
+        ctx.say(t("member.optout.required", lang, &[])).await?;
+        return Ok(());
+    }
+    if optouts.is_opted_out(guild_id, uid).await? {
+        ctx.say(t("member.optout.already", lang, &[])).await?;
+        return Ok(());
+    }
+
+    let confirmed = confirm(ctx, t("member.optout.confirm", lang, &[])).await?;
+    if !confirmed {
+        return Ok(());
+    }
+
+    let repo = MembershipsRepo::new(db);
+    let mut tx = db.transaction().await?;
+    let removed = repo.purge_user(&mut tx, guild_id, uid).await?;
+    optouts.opt_out(&mut tx, guild_id, uid).await?;
+    tx.commit().await?;
+    ctx.data().forget_member_count(guild_id);
+
+    ctx.say(t(
+        "member.optout.done",
+        lang,
+        &[
+            ("stays", &removed.memberships),
+            ("notes", &removed.notes),
+            ("names", &removed.name_changes),
+        ],
+    ))
+    .await?;
+    Ok(())
+}
+
+/// Move all records of one user id onto another (e.g. after a bad import; asks first).
+#[poise::command(
+    slash_command,
+    guild_only,
+    ephemeral,
+    check = "owner_or_manage_guild",
+    rename = "merge"
+)]
+pub async fn member_merge(
+    ctx: Ctx<'_>,
+    #[description = "User id whose records are moved (and then gone)"]
+    #[autocomplete = "ac_member"]
+    from: String,
+    #[description = "User id that receives the records"]
+    #[autocomplete = "ac_member"]
+    into: String,
+) -> Result<()> {
+    let lang = guild_lang(ctx).await;
+    let Some(guild_id) = ctx.guild_id() else {
+        ctx.say(t("common.guild_only", lang, &[])).await?;
+        return Ok(());
+    };
+    let (Some(from), Some(into)) = (parse_user_id(&from), parse_user_id(&into)) else {
+        ctx.say(t("member.merge.bad_ids", lang, &[])).await?;
+        return Ok(());
+    };
+    if from == into {
+        ctx.say(t("member.merge.same_user", lang, &[])).await?;
+        return Ok(());
+    }
+
+    let confirmed = confirm(
+        ctx,
+        t(
+            "member.merge.confirm",
+            lang,
+            &[("from", &from.get()), ("into", &format!("<@{}>", into.get()))],
+        ),
+    )
+    .await?;
+    if !confirmed {
+        return Ok(());
+    }
+
+    let moved = MembershipsRepo::new(&ctx.data().db)
+        .merge_users(guild_id, from, into)
+        .await?;
+    ctx.data().forget_member_count(guild_id);
+
+    ctx.say(t(
+        "member.merge.done",
+        lang,
+        &[
+            ("from", &from.get()),
+            ("into", &format!("<@{}>", into.get())),
+            ("stays", &moved.memberships),
+            ("notes", &moved.notes),
+            ("names", &moved.name_changes),
+        ],
+    ))
+    .await?;
+    Ok(())
+}
@@ -1,13 +1,59 @@
 use anyhow::Result;
+use poise::serenity_prelude as serenity;
+use serenity::all::{Colour, CreateEmbed};
 
 use crate::state::Ctx;
 
+pub mod admin;
 pub mod member;
+pub mod role_menu;
 pub mod settings;
 pub mod stats;
 pub mod userinfo;
 
 pub const MAX_EMBED_DESCRIPTION_CHARS: usize = 4096;
+pub const MAX_EMBED_FIELD_CHARS: usize = 1024;
+
+/// The invoking guild's configured embed colour (best effort; `None` = default).
+pub async fn guild_colour(ctx: Ctx<'_>) -> Option<Colour> {
+    let gid = ctx.guild_id()?;
+    ctx.data()
+        .settings_repo()
+        .get_cached(&gid)
+        .await
+        .ok()?
+        .embed_colour()
+}
+
+/// The invoking guild's message language (English outside guilds or on lookup errors).
+pub async fn guild_lang(ctx: Ctx<'_>) -> &'static str {
+    let Some(gid) = ctx.guild_id() else {
+        return crate::strings::DEFAULT_LANG;
+    };
+    match ctx.data().settings_repo().get_cached(&gid).await {
+        Ok(settings) => settings.lang(),
+        Err(_) => crate::strings::DEFAULT_LANG,
+    }
+}
+
+/// Command check: bot owners anywhere, or members with Manage Server in the current guild.
+pub async fn owner_or_manage_guild(ctx: Ctx<'_>) -> Result<bool> {
+    if ctx.framework().options().owners.contains(&ctx.author().id) {
+        return Ok(true);
+    }
+    let Some(member) = ctx.author_member().await else {
+        return Ok(false);
+    };
+    Ok(member.permissions.is_some_and(|p| p.manage_guild()))
+}
+
+/// Apply `colour` to `embed` if one is configured.
+pub fn with_colour(embed: CreateEmbed, colour: Option<Colour>) -> CreateEmbed {
+    match colour {
+        Some(c) => embed.colour(c),
+        None => embed,
+    }
+}
 
 /// Split lines into description chunks, each <= max_chars (counted in Unicode scalar values).
 pub fn chunk_lines(lines: &[String], max_chars: usize) -> Vec<String> {
@@ -46,19 +92,55 @@ pub fn chunk_lines(lines: &[String], max_chars: usize) -> Vec<String> {
     chunks
 }
 
+/// Cap `text` at `max` chars, marking the cut with an ellipsis.
+pub fn clip(text: String, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text;
+    }
+    let mut capped: String = text.chars().take(max - 1).collect();
+    capped.push('…');
+    capped
+}
+
+/// Suffix for banned history/exit lines: ` — reason: …` or ` — (no reason recorded)`.
+pub fn ban_reason_suffix(reason: Option<&str>, lang: &str) -> String {
+    match reason {
+        Some(r) => crate::strings::t("common.ban_reason", lang, &[("reason", &r)]),
+        None => crate::strings::t("common.no_ban_reason", lang, &[]),
+    }
+}
+
+/// Compact human-readable duration using the two largest units, e.g. `3d 4h`, `2h 5m`, `45s`.
+pub fn format_duration(d: chrono::Duration) -> String {
+    let secs = d.num_seconds().max(0);
+    let (days, hours, mins) = (secs / 86_400, (secs % 86_400) / 3_600, (secs % 3_600) / 60);
+
+    if days > 0 {
+        format!("{days}d {hours}h")
+    } else if hours > 0 {
+        format!("{hours}h {mins}m")
+    } else if mins > 0 {
+        format!("{mins}m")
+    } else {
+        format!("{secs}s")
+    }
+}
+
 /// Generic helper:
 /// - `lines` → will be joined into descriptions (split into chunks).
 /// - `build_first` → called for the first chunk; lets you add thumbnail/fields/etc.
 /// - `build_cont`  → called for each continuation chunk with `(index, chunk)`.
+/// - `ephemeral` → send every chunk ephemerally; `false` keeps the command's default.
 pub async fn send_chunked_embeds<BF, BC>(
     ctx: Ctx<'_>,
     lines: Vec<String>,
+    ephemeral: bool,
     build_first: BF,
     build_cont: BC,
 ) -> Result<()>
 where
-    BF: FnOnce(String) -> serenity::all::CreateEmbed,
-    BC: Fn(usize, String) -> serenity::all::CreateEmbed,
+    BF: FnOnce(String) -> CreateEmbed,
+    BC: Fn(usize, String) -> CreateEmbed,
 {
     use poise::CreateReply;
 
@@ -68,18 +150,54 @@ where
         return Ok(());
     }
 
+    let colour = guild_colour(ctx).await;
+    // Only force the flag on: poise treats an explicit `false` as overriding
+    // commands declared `ephemeral`.
+    let reply = |embed: CreateEmbed| {
+        let reply = CreateReply::default().embed(embed);
+        if ephemeral { reply.ephemeral(true) } else { reply }
+    };
+
     // First embed
     let first_desc = chunks[0].clone();
-    let first_embed = build_first(first_desc);
-    ctx.send(CreateReply::default().embed(first_embed)).await?;
+    let first_embed = with_colour(build_first(first_desc), colour);
+    ctx.send(reply(first_embed)).await?;
 
     // Continuations
     if chunks.len() > 1 {
         for (idx, chunk) in chunks.into_iter().enumerate().skip(1) {
-            let embed = build_cont(idx, chunk);
-            ctx.send(CreateReply::default().embed(embed)).await?;
+            let embed = with_colour(build_cont(idx, chunk), colour);
+            ctx.send(reply(embed)).await?;
         }
     }
 
     Ok(())
 }
+
+/// Like `send_chunked_embeds`, but shows the chunks as one paginated message
+/// (◀/▶ buttons) instead of sending each as its own reply.
+pub async fn send_paginated_embeds<BF, BC>(
+    ctx: Ctx<'_>,
+    lines: Vec<String>,
+    build_first: BF,
+    build_cont: BC,
+) -> Result<()>
+where
+    BF: FnOnce(String) -> CreateEmbed,
+    BC: Fn(usize, String) -> CreateEmbed,
+{
+    let mut chunks = chunk_lines(&lines, MAX_EMBED_DESCRIPTION_CHARS).into_iter();
+    let Some(first) = chunks.next() else {
+        return Ok(());
+    };
+
+    let colour = guild_colour(ctx).await;
+    let mut pages = vec![with_colour(build_first(first), colour)];
+    pages.extend(
+        chunks
+            .enumerate()
+            .map(|(i, chunk)| with_colour(build_cont(i + 1, chunk), colour)),
+    );
+
+    crate::flows::paginator::paginate(ctx, pages).await
+}
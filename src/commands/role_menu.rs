@@ -0,0 +1,198 @@
+use anyhow::Result;
+use poise::serenity_prelude as serenity;
+use serenity::all::{MessageId, Role, RoleId};
+
+use crate::commands::{guild_colour, guild_lang, send_chunked_embeds};
+use crate::flows::post_detached;
+use crate::flows::role_menu;
+use crate::repos::{RoleMenu, RoleMenusRepo};
+use crate::state::Ctx;
+
+/// `/role-menu` parent: messages where members pick roles for themselves.
+#[poise::command(
+    slash_command,
+    guild_only,
+    ephemeral,
+    default_member_permissions = "MANAGE_ROLES",
+    subcommands("role_menu_create", "role_menu_list", "role_menu_remove"),
+    rename = "role-menu"
+)]
+pub async fn role_menu(_: Ctx<'_>) -> Result<()> {
+    Ok(())
+}
+
+/// Post a menu in this channel that lets members add or remove up to five roles.
+#[poise::command(slash_command, guild_only, ephemeral, rename = "create")]
+pub async fn role_menu_create(
+    ctx: Ctx<'_>,
+    #[description = "Heading shown on the menu"]
+    #[max_length = 100]
+    title: String,
+    #[description = "Role to offer"] role1: Role,
+    #[description = "Another role to offer"] role2: Option<Role>,
+    #[description = "Another role to offer"] role3: Option<Role>,
+    #[description = "Another role to offer"] role4: Option<Role>,
+    #[description = "Another role to offer"] role5: Option<Role>,
+) -> Result<()> {
+    let guild_id = ctx.guild_id().expect("guild_only");
+
+    let mut roles: Vec<Role> = Vec::new();
+    for role in [Some(role1), role2, role3, role4, role5].into_iter().flatten() {
+        if !roles.iter().any(|r| r.id == role.id) {
+            roles.push(role);
+        }
+    }
+    let problems: Vec<String> = roles
+        .iter()
+        .filter_map(|r| unassignable_reason(ctx, r).map(|why| format!("• <@&{}>: {why}", r.id)))
+        .collect();
+    if !problems.is_empty() {
+        ctx.say(format!("❌ I can't hand out these roles:\n{}", problems.join("\n")))
+            .await?;
+        return Ok(());
+    }
+
+    let role_ids: Vec<RoleId> = roles.iter().map(|r| r.id).collect();
+    let colour = guild_colour(ctx).await;
+    let lang = guild_lang(ctx).await;
+    let view = role_menu::render(guild_id, &title, &role_ids, ctx.cache(), colour, lang);
+    let msg = post_detached(ctx, view).await?;
+
+    let menu = RoleMenu {
+        guild_id,
+        channel_id: msg.channel_id,
+        message_id: msg.id,
+        title,
+        roles: role_ids,
+    };
+    if let Err(e) = RoleMenusRepo::new(&ctx.data().db)
+        .create(&menu, ctx.author().id)
+        .await
+    {
+        // Unsaved, the menu would stop working at the next restart; don't leave it up.
+        if let Err(del) = msg.delete(ctx).await {
+            tracing::warn!(%guild_id, error = %del, "Failed to delete unsaved role menu");
+        }
+        return Err(e.into());
+    }
+    tokio::spawn(role_menu::attach(
+        ctx.serenity_context().clone(),
+        ctx.data().clone(),
+        menu,
+    ));
+    Ok(())
+}
+
+/// Why the bot couldn't hand out `role`, checked against the cache. `None` when it can,
+/// or when the cache can't tell (a failing pick then reports Discord's error).
+fn unassignable_reason(ctx: Ctx<'_>, role: &Role) -> Option<&'static str> {
+    if role.id.get() == role.guild_id.get() {
+        return Some("@everyone can't be self-assigned");
+    }
+    if role.managed {
+        return Some("it's managed by an integration");
+    }
+    let guild = ctx.guild()?;
+    let bot = guild.members.get(&ctx.cache().current_user().id)?;
+    let channel = guild.channels.get(&ctx.channel_id())?;
+    if !guild.user_permissions_in(channel, bot).manage_roles() {
+        return Some("I need the **Manage Roles** permission");
+    }
+    let top = guild.member_highest_role(bot).map_or(0, |r| r.position);
+    if role.position >= top {
+        return Some("it isn't below my highest role");
+    }
+    None
+}
+
+/// Role menus in this server, oldest first.
+#[poise::command(slash_command, guild_only, ephemeral, rename = "list")]
+pub async fn role_menu_list(ctx: Ctx<'_>) -> Result<()> {
+    let guild_id = ctx.guild_id().expect("guild_only");
+    let menus = RoleMenusRepo::new(&ctx.data().db).list(guild_id).await?;
+    if menus.is_empty() {
+        ctx.say("No role menus here yet. Post one with `/role-menu create`.")
+            .await?;
+        return Ok(());
+    }
+
+    let lines: Vec<String> = menus
+        .iter()
+        .map(|m| {
+            let roles = m
+                .roles
+                .iter()
+                .map(|r| format!("<@&{r}>"))
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!(
+                "**{}** — {} (`{}`)\n{roles}",
+                m.title,
+                m.message_id.link(m.channel_id, Some(guild_id)),
+                m.message_id
+            )
+        })
+        .collect();
+
+    let title = format!("Role menus ({})", menus.len());
+    send_chunked_embeds(
+        ctx,
+        lines,
+        true,
+        |desc| {
+            serenity::CreateEmbed::new()
+                .title(title)
+                .description(desc)
+        },
+        |idx, desc| {
+            serenity::CreateEmbed::new()
+                .title(format!("Role menus (cont. #{idx})"))
+                .description(desc)
+        },
+    )
+    .await
+}
+
+/// Take down a role menu and delete its message.
+#[poise::command(slash_command, guild_only, ephemeral, rename = "remove")]
+pub async fn role_menu_remove(
+    ctx: Ctx<'_>,
+    #[description = "Message ID or link of the menu"] message: String,
+) -> Result<()> {
+    let guild_id = ctx.guild_id().expect("guild_only");
+    // A message link ends in the message id.
+    let Some(message_id) = message
+        .trim()
+        .rsplit('/')
+        .next()
+        .and_then(|id| id.parse::<u64>().ok())
+        .filter(|&id| id != 0)
+        .map(MessageId::new)
+    else {
+        ctx.say("That isn't a message ID or link.").await?;
+        return Ok(());
+    };
+
+    let repo = RoleMenusRepo::new(&ctx.data().db);
+    let Some(menu) = repo
+        .list(guild_id)
+        .await?
+        .into_iter()
+        .find(|m| m.message_id == message_id)
+    else {
+        ctx.say("There's no role menu with that message here.").await?;
+        return Ok(());
+    };
+    repo.remove(guild_id, message_id).await?;
+    ctx.data().untrack_role_menu(message_id);
+
+    let msg = match menu.channel_id.delete_message(ctx.http(), message_id).await {
+        Ok(()) => "🗑️ Removed the role menu and deleted its message.".to_string(),
+        Err(e) => format!(
+            "🗑️ Removed the role menu (it no longer responds), but couldn't delete its \
+             message: {e}"
+        ),
+    };
+    ctx.say(msg).await?;
+    Ok(())
+}
@@ -1,8 +1,22 @@
 use anyhow::Result;
 use poise::serenity_prelude as serenity;
+use serde::{Deserialize, Serialize};
 
-use crate::repos::{GuildSettings, GuildSettingsRepo};
+use crate::commands::guild_lang;
+use crate::events::{
+    LogDestination, is_not_postable, is_unknown_channel, parse_webhook_url, post_embed,
+};
+use crate::flows::confirm::confirm;
+use crate::flows::settings_panel::open_settings_panel;
+use crate::members::fetch_all_members;
+use crate::repos::{
+    ACCOUNT_AGE_RANGE, AccountAgeAction, BAN_WINDOW_RANGE, DEFAULT_BAN_WINDOW_SECS,
+    DEFAULT_RAID_THRESHOLD, GuildSettings, GuildSettingsRepo, LogKind, MAX_LOG_CHANNELS, MAX_WATCHED_ROLES,
+    MembershipsRepo, OptOutRepo, RAID_THRESHOLD_RANGE, RETENTION_RANGE, RepoError,
+};
+use crate::retry;
 use crate::state::Ctx;
+use crate::strings::{LANGUAGES, t};
 
 /// `/settings` parent command, like in your other bot.
 /// All real work happens in the subcommands.
@@ -15,7 +29,26 @@ use crate::state::Ctx;
         "settings_join_log",
         "settings_leave_log",
         "settings_mod_log",
-        "settings_show"
+        "settings_log_webhook",
+        "settings_show",
+        "settings_test",
+        "settings_clear",
+        "settings_panel",
+        "settings_color",
+        "settings_timezone",
+        "settings_language",
+        "settings_ban_window",
+        "settings_raid_threshold",
+        "settings_account_age",
+        "settings_digest",
+        "settings_message_log",
+        "settings_tracking_required",
+        "settings_log_channel_types",
+        "settings_retention",
+        "settings_watched_roles",
+        "settings_export",
+        "settings_import",
+        "settings_backfill"
     )
 )]
 pub async fn settings(_: Ctx<'_>) -> Result<()> {
@@ -42,29 +75,25 @@ pub async fn settings_join_log(
     #[description = "Clear the join log channel instead of setting it"]
     clear: Option<bool>,
 ) -> Result<()> {
+    let lang = guild_lang(ctx).await;
     let gid = match ctx.guild_id() {
         Some(g) => g,
         None => {
-            ctx.say("Use this command in a server channel.").await?;
+            ctx.say(t("common.guild_only", lang, &[])).await?;
             return Ok(());
         }
     };
 
-    let db = &ctx.data().db;
-    let repo = GuildSettingsRepo::new(db);
+    let repo = ctx.data().settings_repo();
     repo.ensure_row(&gid).await?;
 
     if clear.unwrap_or(false) {
-        repo
-            .set_column(&gid, "join_log_channel_id", None)
-            .await?;
-        ctx.say("✅ Cleared **join log** channel.").await?;
+        repo.set_join_log(&gid, None).await?;
+        ctx.say(t("settings.join_log.cleared", lang, &[])).await?;
     } else {
         let ch = channel.unwrap_or_else(|| ctx.channel_id());
-        repo
-            .set_column(&gid, "join_log_channel_id", Some(ch))
-            .await?;
-        ctx.say(format!("✅ **Join log** channel set to <#{}>.", ch.get()))
+        repo.set_join_log(&gid, Some(ch)).await?;
+        ctx.say(t("settings.join_log.set", lang, &[("channel", &format!("<#{}>", ch.get()))]))
             .await?;
     }
 
@@ -85,29 +114,25 @@ pub async fn settings_leave_log(
     #[description = "Clear the leave log channel instead of setting it"]
     clear: Option<bool>,
 ) -> Result<()> {
+    let lang = guild_lang(ctx).await;
     let gid = match ctx.guild_id() {
         Some(g) => g,
         None => {
-            ctx.say("Use this command in a server channel.").await?;
+            ctx.say(t("common.guild_only", lang, &[])).await?;
             return Ok(());
         }
     };
 
-    let db = &ctx.data().db;
-    let repo = GuildSettingsRepo::new(db);
+    let repo = ctx.data().settings_repo();
     repo.ensure_row(&gid).await?;
 
     if clear.unwrap_or(false) {
-        repo
-            .set_column(&gid, "leave_log_channel_id", None)
-            .await?;
-        ctx.say("✅ Cleared **leave log** channel.").await?;
+        repo.set_leave_log(&gid, None).await?;
+        ctx.say(t("settings.leave_log.cleared", lang, &[])).await?;
     } else {
         let ch = channel.unwrap_or_else(|| ctx.channel_id());
-        repo
-            .set_column(&gid, "leave_log_channel_id", Some(ch))
-            .await?;
-        ctx.say(format!("✅ **Leave log** channel set to <#{}>.", ch.get()))
+        repo.set_leave_log(&gid, Some(ch)).await?;
+        ctx.say(t("settings.leave_log.set", lang, &[("channel", &format!("<#{}>", ch.get()))]))
             .await?;
     }
 
@@ -128,38 +153,81 @@ pub async fn settings_mod_log(
     #[description = "Clear the moderation log channel instead of setting it"]
     clear: Option<bool>,
 ) -> Result<()> {
+    let lang = guild_lang(ctx).await;
     let gid = match ctx.guild_id() {
         Some(g) => g,
         None => {
-            ctx.say("Use this command in a server channel.").await?;
+            ctx.say(t("common.guild_only", lang, &[])).await?;
             return Ok(());
         }
     };
 
-    let db = &ctx.data().db;
-    let repo = GuildSettingsRepo::new(db);
+    let repo = ctx.data().settings_repo();
     repo.ensure_row(&gid).await?;
 
     if clear.unwrap_or(false) {
-        repo
-            .set_column(&gid, "mod_log_channel_id", None)
-            .await?;
-        ctx.say("✅ Cleared **moderation log** channel.").await?;
+        repo.set_mod_log(&gid, None).await?;
+        ctx.say(t("settings.mod_log.cleared", lang, &[])).await?;
     } else {
         let ch = channel.unwrap_or_else(|| ctx.channel_id());
-        repo
-            .set_column(&gid, "mod_log_channel_id", Some(ch))
+        repo.set_mod_log(&gid, Some(ch)).await?;
+        ctx.say(t("settings.mod_log.set", lang, &[("channel", &format!("<#{}>", ch.get()))]))
             .await?;
-        ctx.say(format!(
-            "✅ **Moderation log** channel set to <#{}>.",
-            ch.get()
-        ))
-        .await?;
     }
 
     Ok(())
 }
 
+/// Post a log through a webhook instead of (or, if it fails, before) its channels.
+#[poise::command(
+    slash_command,
+    guild_only,
+    ephemeral,
+    rename = "log-webhook"
+)]
+pub async fn settings_log_webhook(
+    ctx: Ctx<'_>,
+    #[description = "Which log"] log: LogKind,
+    #[description = "Webhook URL (channel settings → Integrations → Webhooks)"]
+    url: Option<String>,
+    #[description = "Stop using a webhook for this log"] clear: Option<bool>,
+) -> Result<()> {
+    let lang = guild_lang(ctx).await;
+    let gid = match ctx.guild_id() {
+        Some(g) => g,
+        None => {
+            ctx.say(t("common.guild_only", lang, &[])).await?;
+            return Ok(());
+        }
+    };
+
+    let repo = ctx.data().settings_repo();
+    repo.ensure_row(&gid).await?;
+    let label = t(&format!("settings.log_label.{}", log.as_str()), lang, &[]);
+
+    if clear.unwrap_or(false) {
+        repo.set_text_column(&gid, log.webhook_column(), None).await?;
+        ctx.say(t("settings.log_webhook.cleared", lang, &[("log", &label)]))
+            .await?;
+        return Ok(());
+    }
+
+    let Some(url) = url else {
+        ctx.say(t("settings.log_webhook.missing", lang, &[])).await?;
+        return Ok(());
+    };
+    if parse_webhook_url(&url).is_none() {
+        ctx.say(t("settings.log_webhook.invalid", lang, &[])).await?;
+        return Ok(());
+    }
+
+    repo.set_text_column(&gid, log.webhook_column(), Some(url.trim()))
+        .await?;
+    ctx.say(t("settings.log_webhook.set", lang, &[("log", &label)]))
+        .await?;
+    Ok(())
+}
+
 /// Show the current log channel configuration for this server.
 #[poise::command(
     slash_command,
@@ -168,35 +236,1309 @@ pub async fn settings_mod_log(
     rename = "show"
 )]
 pub async fn settings_show(ctx: Ctx<'_>) -> Result<()> {
+    let lang = guild_lang(ctx).await;
     let gid = match ctx.guild_id() {
         Some(g) => g,
         None => {
-            ctx.say("Use this command in a server channel.").await?;
+            ctx.say(t("common.guild_only", lang, &[])).await?;
             return Ok(());
         }
     };
 
-    let db = &ctx.data().db;
-    let repo = GuildSettingsRepo::new(db);
+    let repo = ctx.data().settings_repo();
 
     let current: GuildSettings = repo.get(&gid).await?;
 
-    let fmt = |ch: Option<serenity::ChannelId>| {
-        ch.map(|c| format!("<#{}>", c.get()))
-            .unwrap_or_else(|| "— not set —".to_string())
+    let fmt = |kind: LogKind| {
+        let targets = current.targets(kind);
+        let channels = targets
+            .iter()
+            .map(|c| format!("<#{}>", c.get()))
+            .collect::<Vec<_>>()
+            .join(", ");
+        match (current.webhook(kind).is_some(), targets.is_empty()) {
+            (false, true) => t("settings.show.not_set", lang, &[]),
+            (false, false) => channels,
+            (true, true) => t("settings.show.webhook", lang, &[]),
+            (true, false) => {
+                t("settings.show.webhook_fallback", lang, &[("channels", &channels)])
+            }
+        }
     };
 
-    let join = fmt(current.join_log);
-    let leave = fmt(current.leave_log);
-    let modu = fmt(current.mod_log);
+    let join = fmt(LogKind::Join);
+    let leave = fmt(LogKind::Leave);
+    let modu = fmt(LogKind::Mod);
+    let template = |tpl: Option<&str>| {
+        let key = if tpl.is_some() { "settings.show.custom" } else { "settings.show.default" };
+        t(key, lang, &[])
+    };
+    let join_msg = template(current.join_template());
+    let leave_msg = template(current.leave_template());
+    let colour = current
+        .embed_colour()
+        .map(|c| format!("`#{:06X}`", c.0))
+        .unwrap_or_else(|| t("settings.show.default", lang, &[]));
+    let tz = current.tz().name();
+    let ban_window = current.ban_window_secs();
+    let raid_threshold = current.raid_threshold();
+    let account_age = match current.min_account_age() {
+        Some(age) => t(
+            "settings.show.account_age_on",
+            lang,
+            &[
+                ("days", &age.num_days()),
+                ("action", &current.account_age_action().as_str()),
+            ],
+        ),
+        None => t("settings.show.off", lang, &[]),
+    };
+    let digest = if current.digest_enabled {
+        t(
+            "settings.show.digest_on",
+            lang,
+            &[("hour", &format!("{:02}", current.digest_hour())), ("tz", &tz)],
+        )
+    } else {
+        t("settings.show.off", lang, &[])
+    };
+    let message_log = if current.message_log_enabled {
+        t("settings.show.on", lang, &[])
+    } else {
+        t("settings.show.off", lang, &[])
+    };
+    let opt_outs = if current.tracking_required {
+        t("settings.show.opt_outs_ignored", lang, &[])
+    } else {
+        t("settings.show.opt_outs_honoured", lang, &[])
+    };
+    let retention = match current.retention_days {
+        Some(days) => t("settings.show.retention_on", lang, &[("days", &days)]),
+        None => t("settings.show.off", lang, &[]),
+    };
+    let channel_types = if current.extended_log_channels {
+        t("settings.show.channel_types_extended", lang, &[])
+    } else {
+        t("settings.show.channel_types_default", lang, &[])
+    };
+    let language = LANGUAGES
+        .iter()
+        .find(|(code, _)| *code == current.lang())
+        .map_or(current.lang(), |(_, name)| *name);
+    let watched_roles = if current.watched_roles.is_empty() {
+        t("settings.show.all_roles", lang, &[])
+    } else {
+        current
+            .watched_roles
+            .iter()
+            .map(|r| format!("<@&{}>", r.get()))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
 
-    let msg = format!(
-        "**Current log settings for this server**\n\
-         • **Join log:** {join}\n\
-         • **Leave log:** {leave}\n\
-         • **Moderation log:** {modu}"
+    let msg = t(
+        "settings.show.body",
+        lang,
+        &[
+            ("join", &join),
+            ("leave", &leave),
+            ("mod", &modu),
+            ("join_msg", &join_msg),
+            ("leave_msg", &leave_msg),
+            ("colour", &colour),
+            ("tz", &tz),
+            ("ban_window", &ban_window),
+            ("raid_threshold", &raid_threshold),
+            ("account_age", &account_age),
+            ("digest", &digest),
+            ("message_log", &message_log),
+            ("opt_outs", &opt_outs),
+            ("channel_types", &channel_types),
+            ("retention", &retention),
+            ("watched_roles", &watched_roles),
+            ("language", &language),
+        ],
     );
 
     ctx.say(msg).await?;
     Ok(())
 }
+
+/// Post a sample message to every configured log channel and report which ones worked.
+#[poise::command(
+    slash_command,
+    guild_only,
+    ephemeral,
+    rename = "test"
+)]
+pub async fn settings_test(ctx: Ctx<'_>) -> Result<()> {
+    let lang = guild_lang(ctx).await;
+    let gid = match ctx.guild_id() {
+        Some(g) => g,
+        None => {
+            ctx.say(t("common.guild_only", lang, &[])).await?;
+            return Ok(());
+        }
+    };
+
+    // Failing channels are retried with backoff, which can exceed the 3s reply window.
+    ctx.defer().await?;
+
+    let current = ctx.data().settings_repo().get(&gid).await?;
+    let mut lines = vec![t("settings.test.header", lang, &[])];
+    for kind in LogKind::ALL {
+        let log = t(&format!("settings.log_label.{}", kind.as_str()), lang, &[]);
+        let targets = current.log_targets(kind);
+        if targets.is_empty() {
+            lines.push(t("settings.test.not_set", lang, &[("log", &log)]));
+            continue;
+        }
+
+        let outcomes = post_embed(
+            ctx.data(),
+            gid,
+            ctx.http(),
+            &targets,
+            current.embed_colour(),
+            &t("settings.test.embed_title", lang, &[("log", &log)]),
+            |e| e.description(t("settings.test.embed_body", lang, &[])),
+        )
+        .await;
+        for (dest, outcome) in outcomes {
+            let channel = match dest {
+                LogDestination::Webhook => t("settings.test.webhook", lang, &[]),
+                LogDestination::Channel(ch) => format!("<#{}>", ch.get()),
+            };
+            let line = match outcome {
+                Ok(()) => t("settings.test.ok", lang, &[("log", &log), ("channel", &channel)]),
+                Err(e) => {
+                    let error = describe_post_error(&e, dest, lang);
+                    t(
+                        "settings.test.failed",
+                        lang,
+                        &[("log", &log), ("channel", &channel), ("error", &error)],
+                    )
+                }
+            };
+            lines.push(line);
+        }
+    }
+
+    ctx.say(lines.join("\n")).await?;
+    Ok(())
+}
+
+/// Short, admin-facing reason a test post failed.
+fn describe_post_error(err: &serenity::Error, dest: LogDestination, lang: &str) -> String {
+    if matches!(dest, LogDestination::Webhook) {
+        return match retry::status_of(err) {
+            Some(serenity::StatusCode::NOT_FOUND) => t("settings.test.webhook_deleted", lang, &[]),
+            _ => err.to_string(),
+        };
+    }
+    if is_unknown_channel(err) {
+        return t("settings.test.deleted", lang, &[]);
+    }
+    if is_not_postable(err) {
+        return t("settings.test.not_postable", lang, &[]);
+    }
+    match retry::status_of(err) {
+        Some(serenity::StatusCode::FORBIDDEN) => t("settings.test.missing_perms", lang, &[]),
+        _ => err.to_string(),
+    }
+}
+
+/// Clear **all** log channels (asks for confirmation first).
+#[poise::command(
+    slash_command,
+    guild_only,
+    ephemeral,
+    rename = "clear"
+)]
+pub async fn settings_clear(ctx: Ctx<'_>) -> Result<()> {
+    let lang = guild_lang(ctx).await;
+    let gid = match ctx.guild_id() {
+        Some(g) => g,
+        None => {
+            ctx.say(t("common.guild_only", lang, &[])).await?;
+            return Ok(());
+        }
+    };
+
+    let confirmed = confirm(ctx, t("settings.clear.confirm", lang, &[])).await?;
+    if clear_logs_if_confirmed(&ctx.data().settings_repo(), gid, confirmed).await? {
+        ctx.say(t("settings.clear.done", lang, &[])).await?;
+    }
+    Ok(())
+}
+
+/// Unset every log channel, mirror and webhook, but only after an explicit Yes.
+/// Returns whether anything was cleared.
+async fn clear_logs_if_confirmed(
+    repo: &GuildSettingsRepo<'_>,
+    gid: serenity::GuildId,
+    confirmed: bool,
+) -> Result<bool> {
+    if !confirmed {
+        return Ok(false);
+    }
+    repo.set_columns(
+        &gid,
+        &[
+            ("join_log_channel_id", None),
+            ("leave_log_channel_id", None),
+            ("mod_log_channel_id", None),
+        ],
+        &LogKind::ALL.map(|kind| (kind.webhook_column(), None)),
+        &[
+            (LogKind::Join, &[]),
+            (LogKind::Leave, &[]),
+            (LogKind::Mod, &[]),
+        ],
+    )
+    .await?;
+    Ok(true)
+}
+
+/// Open the interactive settings panel (channels + join/leave message templates).
+///
+/// With `shared`, the panel is posted (and pinned until it closes) in the channel so other
+/// admins can help set it up.
+#[poise::command(
+    slash_command,
+    guild_only,
+    ephemeral,
+    rename = "panel"
+)]
+pub async fn settings_panel(
+    ctx: Ctx<'_>,
+    #[description = "Post the panel in this channel so other admins can use it too"]
+    shared: Option<bool>,
+) -> Result<()> {
+    let lang = guild_lang(ctx).await;
+    let gid = match ctx.guild_id() {
+        Some(g) => g,
+        None => {
+            ctx.say(t("common.guild_only", lang, &[])).await?;
+            return Ok(());
+        }
+    };
+
+    open_settings_panel(ctx, gid, shared.unwrap_or(false)).await
+}
+
+/// Parse `#RRGGBB`, `RRGGBB` or `0xRRGGBB` into a 24-bit colour value.
+fn parse_hex_colour(input: &str) -> Option<u32> {
+    let hex = input.trim();
+    let hex = hex
+        .strip_prefix('#')
+        .or_else(|| hex.strip_prefix("0x"))
+        .or_else(|| hex.strip_prefix("0X"))
+        .unwrap_or(hex);
+    if hex.is_empty() || hex.len() > 8 {
+        return None;
+    }
+    u32::from_str_radix(hex, 16)
+        .ok()
+        .filter(|v| *v <= 0xFF_FF_FF)
+}
+
+/// Set or clear the colour used for this server's embeds.
+#[poise::command(
+    slash_command,
+    guild_only,
+    ephemeral,
+    rename = "color"
+)]
+pub async fn settings_color(
+    ctx: Ctx<'_>,
+    #[description = "Hex colour, e.g. #5865F2"]
+    hex: Option<String>,
+    #[description = "Reset to Discord's default colour"]
+    clear: Option<bool>,
+) -> Result<()> {
+    let lang = guild_lang(ctx).await;
+    let gid = match ctx.guild_id() {
+        Some(g) => g,
+        None => {
+            ctx.say(t("common.guild_only", lang, &[])).await?;
+            return Ok(());
+        }
+    };
+
+    let repo = ctx.data().settings_repo();
+    repo.ensure_row(&gid).await?;
+
+    if clear.unwrap_or(false) {
+        repo.set_embed_color(&gid, None).await?;
+        ctx.say(t("settings.color.reset", lang, &[])).await?;
+        return Ok(());
+    }
+
+    let Some(hex) = hex else {
+        ctx.say(t("settings.color.missing", lang, &[])).await?;
+        return Ok(());
+    };
+    let Some(value) = parse_hex_colour(&hex) else {
+        ctx.say(t("settings.color.invalid", lang, &[("input", &hex)]))
+            .await?;
+        return Ok(());
+    };
+
+    repo.set_embed_color(&gid, Some(value)).await?;
+    ctx.say(t("settings.color.set", lang, &[("colour", &format!("#{value:06X}"))]))
+        .await?;
+    Ok(())
+}
+
+/// Autocomplete IANA zone names (case-insensitive substring match).
+async fn ac_timezone(_ctx: Ctx<'_>, partial: &str) -> Vec<String> {
+    let needle = partial.to_lowercase();
+    chrono_tz::TZ_VARIANTS
+        .iter()
+        .map(|tz| tz.name())
+        .filter(|name| name.to_lowercase().contains(&needle))
+        .take(25)
+        .map(str::to_string)
+        .collect()
+}
+
+/// Set or clear the timezone used to group stats by calendar day.
+#[poise::command(
+    slash_command,
+    guild_only,
+    ephemeral,
+    rename = "timezone"
+)]
+pub async fn settings_timezone(
+    ctx: Ctx<'_>,
+    #[description = "IANA zone, e.g. Europe/Berlin"]
+    #[autocomplete = "ac_timezone"]
+    zone: Option<String>,
+    #[description = "Reset to UTC"]
+    clear: Option<bool>,
+) -> Result<()> {
+    let lang = guild_lang(ctx).await;
+    let gid = match ctx.guild_id() {
+        Some(g) => g,
+        None => {
+            ctx.say(t("common.guild_only", lang, &[])).await?;
+            return Ok(());
+        }
+    };
+
+    let repo = ctx.data().settings_repo();
+    repo.ensure_row(&gid).await?;
+
+    if clear.unwrap_or(false) {
+        repo.set_text_column(&gid, "timezone", None).await?;
+        ctx.say(t("settings.timezone.reset", lang, &[])).await?;
+        return Ok(());
+    }
+
+    let Some(zone) = zone else {
+        ctx.say(t("settings.timezone.missing", lang, &[])).await?;
+        return Ok(());
+    };
+    let Ok(tz) = zone.trim().parse::<chrono_tz::Tz>() else {
+        ctx.say(t("settings.timezone.invalid", lang, &[("input", &zone)]))
+            .await?;
+        return Ok(());
+    };
+
+    repo.set_text_column(&gid, "timezone", Some(tz.name()))
+        .await?;
+    ctx.say(t("settings.timezone.set", lang, &[("zone", &tz.name())]))
+        .await?;
+    Ok(())
+}
+
+/// Autocomplete supported languages by code or native name.
+async fn ac_language(_ctx: Ctx<'_>, partial: &str) -> Vec<serenity::AutocompleteChoice> {
+    let needle = partial.to_lowercase();
+    LANGUAGES
+        .iter()
+        .filter(|(code, name)| code.contains(&needle) || name.to_lowercase().contains(&needle))
+        .map(|(code, name)| serenity::AutocompleteChoice::new(*name, *code))
+        .collect()
+}
+
+/// Set or reset the language the bot uses for messages in this server.
+#[poise::command(
+    slash_command,
+    guild_only,
+    ephemeral,
+    rename = "language"
+)]
+pub async fn settings_language(
+    ctx: Ctx<'_>,
+    #[description = "Language code, e.g. de"]
+    #[autocomplete = "ac_language"]
+    language: Option<String>,
+    #[description = "Reset to English"]
+    clear: Option<bool>,
+) -> Result<()> {
+    let lang = guild_lang(ctx).await;
+    let gid = match ctx.guild_id() {
+        Some(g) => g,
+        None => {
+            ctx.say(t("common.guild_only", lang, &[])).await?;
+            return Ok(());
+        }
+    };
+
+    let repo = ctx.data().settings_repo();
+    repo.ensure_row(&gid).await?;
+
+    if clear.unwrap_or(false) {
+        repo.set_text_column(&gid, "lang", None).await?;
+        ctx.say(t("settings.language.set", crate::strings::DEFAULT_LANG, &[]))
+            .await?;
+        return Ok(());
+    }
+
+    let Some(language) = language else {
+        ctx.say(t("settings.language.missing", lang, &[])).await?;
+        return Ok(());
+    };
+    let Some(code) = crate::strings::supported(&language) else {
+        let known = LANGUAGES
+            .iter()
+            .map(|(code, name)| format!("`{code}` ({name})"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        ctx.say(t(
+            "settings.language.invalid",
+            lang,
+            &[("input", &language), ("known", &known)],
+        ))
+        .await?;
+        return Ok(());
+    };
+
+    repo.set_text_column(&gid, "lang", Some(code)).await?;
+    // Confirm in the language just picked.
+    ctx.say(t("settings.language.set", code, &[])).await?;
+    Ok(())
+}
+
+/// Set or reset how long after a ban a member removal still counts as that ban.
+#[poise::command(
+    slash_command,
+    guild_only,
+    ephemeral,
+    rename = "ban-window"
+)]
+pub async fn settings_ban_window(
+    ctx: Ctx<'_>,
+    #[description = "Seconds (1–120)"]
+    seconds: Option<i64>,
+    #[description = "Reset to the default (15s)"]
+    clear: Option<bool>,
+) -> Result<()> {
+    let lang = guild_lang(ctx).await;
+    let gid = match ctx.guild_id() {
+        Some(g) => g,
+        None => {
+            ctx.say(t("common.guild_only", lang, &[])).await?;
+            return Ok(());
+        }
+    };
+
+    let repo = ctx.data().settings_repo();
+    repo.ensure_row(&gid).await?;
+
+    if clear.unwrap_or(false) {
+        repo.set_ban_window(&gid, None).await?;
+        ctx.say(t(
+            "settings.ban_window.reset",
+            lang,
+            &[("seconds", &DEFAULT_BAN_WINDOW_SECS)],
+        ))
+        .await?;
+        return Ok(());
+    }
+
+    let Some(seconds) = seconds else {
+        ctx.say(t(
+            "settings.ban_window.missing",
+            lang,
+            &[("min", BAN_WINDOW_RANGE.start()), ("max", BAN_WINDOW_RANGE.end())],
+        ))
+        .await?;
+        return Ok(());
+    };
+    if !BAN_WINDOW_RANGE.contains(&seconds) {
+        ctx.say(t(
+            "settings.ban_window.invalid",
+            lang,
+            &[
+                ("input", &seconds),
+                ("min", BAN_WINDOW_RANGE.start()),
+                ("max", BAN_WINDOW_RANGE.end()),
+            ],
+        ))
+        .await?;
+        return Ok(());
+    }
+
+    repo.set_ban_window(&gid, Some(seconds)).await?;
+    ctx.say(t("settings.ban_window.set", lang, &[("seconds", &seconds)]))
+        .await?;
+    Ok(())
+}
+
+/// Set or reset how many joins (or leaves) in one minute raise a raid alert.
+#[poise::command(
+    slash_command,
+    guild_only,
+    ephemeral,
+    rename = "raid-threshold"
+)]
+pub async fn settings_raid_threshold(
+    ctx: Ctx<'_>,
+    #[description = "Joins or leaves per minute (3–500)"]
+    per_minute: Option<i64>,
+    #[description = "Reset to the default (10 per minute)"]
+    clear: Option<bool>,
+) -> Result<()> {
+    let lang = guild_lang(ctx).await;
+    let gid = match ctx.guild_id() {
+        Some(g) => g,
+        None => {
+            ctx.say(t("common.guild_only", lang, &[])).await?;
+            return Ok(());
+        }
+    };
+
+    let repo = ctx.data().settings_repo();
+    repo.ensure_row(&gid).await?;
+
+    if clear.unwrap_or(false) {
+        repo.set_raid_threshold(&gid, None).await?;
+        ctx.say(t(
+            "settings.raid_threshold.reset",
+            lang,
+            &[("count", &DEFAULT_RAID_THRESHOLD)],
+        ))
+        .await?;
+        return Ok(());
+    }
+
+    let Some(per_minute) = per_minute else {
+        ctx.say(t(
+            "settings.raid_threshold.missing",
+            lang,
+            &[("min", RAID_THRESHOLD_RANGE.start()), ("max", RAID_THRESHOLD_RANGE.end())],
+        ))
+        .await?;
+        return Ok(());
+    };
+    if !RAID_THRESHOLD_RANGE.contains(&per_minute) {
+        ctx.say(t(
+            "settings.raid_threshold.invalid",
+            lang,
+            &[
+                ("input", &per_minute),
+                ("min", RAID_THRESHOLD_RANGE.start()),
+                ("max", RAID_THRESHOLD_RANGE.end()),
+            ],
+        ))
+        .await?;
+        return Ok(());
+    }
+
+    repo.set_raid_threshold(&gid, Some(per_minute)).await?;
+    ctx.say(t("settings.raid_threshold.set", lang, &[("count", &per_minute)]))
+        .await?;
+    Ok(())
+}
+
+/// Flag (and optionally DM or kick) joining accounts younger than a minimum age.
+#[poise::command(
+    slash_command,
+    guild_only,
+    ephemeral,
+    rename = "account-age"
+)]
+pub async fn settings_account_age(
+    ctx: Ctx<'_>,
+    #[description = "Minimum account age in days (1–365)"]
+    min_days: Option<i64>,
+    #[description = "What to do with younger accounts (default: log)"]
+    action: Option<AccountAgeAction>,
+    #[description = "Turn the check off"]
+    clear: Option<bool>,
+) -> Result<()> {
+    let lang = guild_lang(ctx).await;
+    let gid = match ctx.guild_id() {
+        Some(g) => g,
+        None => {
+            ctx.say(t("common.guild_only", lang, &[])).await?;
+            return Ok(());
+        }
+    };
+
+    let repo = ctx.data().settings_repo();
+    repo.ensure_row(&gid).await?;
+
+    if clear.unwrap_or(false) {
+        repo.set_account_age(&gid, None, AccountAgeAction::default()).await?;
+        ctx.say(t("settings.account_age.off", lang, &[])).await?;
+        return Ok(());
+    }
+
+    let Some(min_days) = min_days else {
+        ctx.say(t(
+            "settings.account_age.missing",
+            lang,
+            &[("min", ACCOUNT_AGE_RANGE.start()), ("max", ACCOUNT_AGE_RANGE.end())],
+        ))
+        .await?;
+        return Ok(());
+    };
+    if !ACCOUNT_AGE_RANGE.contains(&min_days) {
+        ctx.say(t(
+            "settings.account_age.invalid",
+            lang,
+            &[
+                ("input", &min_days),
+                ("min", ACCOUNT_AGE_RANGE.start()),
+                ("max", ACCOUNT_AGE_RANGE.end()),
+            ],
+        ))
+        .await?;
+        return Ok(());
+    }
+
+    let action = action.unwrap_or_default();
+    repo.set_account_age(&gid, Some(min_days), action).await?;
+    let key = match action {
+        AccountAgeAction::Log => "settings.account_age.set_log",
+        AccountAgeAction::Dm => "settings.account_age.set_dm",
+        AccountAgeAction::Kick => "settings.account_age.set_kick",
+    };
+    ctx.say(t(key, lang, &[("days", &min_days)])).await?;
+    Ok(())
+}
+
+/// Turn the daily digest (posted to the moderation log) on or off.
+#[poise::command(
+    slash_command,
+    guild_only,
+    ephemeral,
+    rename = "digest"
+)]
+pub async fn settings_digest(
+    ctx: Ctx<'_>,
+    #[description = "Post a daily recap to the moderation log"]
+    enabled: bool,
+    #[description = "Hour of day in the server timezone (0–23, default 9)"]
+    #[min = 0]
+    #[max = 23]
+    hour: Option<u32>,
+) -> Result<()> {
+    let lang = guild_lang(ctx).await;
+    let gid = match ctx.guild_id() {
+        Some(g) => g,
+        None => {
+            ctx.say(t("common.guild_only", lang, &[])).await?;
+            return Ok(());
+        }
+    };
+
+    let repo = ctx.data().settings_repo();
+    repo.ensure_row(&gid).await?;
+    repo.set_digest(&gid, enabled, hour).await?;
+
+    if !enabled {
+        ctx.say(t("settings.digest.off", lang, &[])).await?;
+        return Ok(());
+    }
+
+    let current = repo.get(&gid).await?;
+    let mut msg = t(
+        "settings.digest.on",
+        lang,
+        &[
+            ("hour", &format!("{:02}", current.digest_hour())),
+            ("tz", &current.tz().name()),
+        ],
+    );
+    if current.mod_log.is_none() {
+        msg.push('\n');
+        msg.push_str(&t("settings.no_mod_log_warning", lang, &[]));
+    }
+    ctx.say(msg).await?;
+    Ok(())
+}
+
+/// Log deleted and edited messages (with their content) to the moderation log.
+#[poise::command(
+    slash_command,
+    guild_only,
+    ephemeral,
+    rename = "message-log"
+)]
+pub async fn settings_message_log(
+    ctx: Ctx<'_>,
+    #[description = "Post deleted/edited message content to the moderation log"]
+    enabled: bool,
+) -> Result<()> {
+    let lang = guild_lang(ctx).await;
+    let gid = match ctx.guild_id() {
+        Some(g) => g,
+        None => {
+            ctx.say(t("common.guild_only", lang, &[])).await?;
+            return Ok(());
+        }
+    };
+
+    let repo = ctx.data().settings_repo();
+    repo.ensure_row(&gid).await?;
+    repo.set_message_log(&gid, enabled).await?;
+
+    if !enabled {
+        ctx.say(t("settings.message_log.off", lang, &[])).await?;
+        return Ok(());
+    }
+
+    let current = repo.get(&gid).await?;
+    let mut msg = t("settings.message_log.on", lang, &[]);
+    if current.mod_log.is_none() {
+        msg.push('\n');
+        msg.push_str(&t("settings.no_mod_log_warning", lang, &[]));
+    }
+    ctx.say(msg).await?;
+    Ok(())
+}
+
+/// Keep full records of every member, ignoring `/member optout` (for servers that must).
+#[poise::command(
+    slash_command,
+    guild_only,
+    ephemeral,
+    rename = "tracking-required"
+)]
+pub async fn settings_tracking_required(
+    ctx: Ctx<'_>,
+    #[description = "Track everyone, even members who opted out"] enabled: bool,
+) -> Result<()> {
+    let lang = guild_lang(ctx).await;
+    let gid = match ctx.guild_id() {
+        Some(g) => g,
+        None => {
+            ctx.say(t("common.guild_only", lang, &[])).await?;
+            return Ok(());
+        }
+    };
+
+    let repo = ctx.data().settings_repo();
+    repo.ensure_row(&gid).await?;
+    repo.set_tracking_required(&gid, enabled).await?;
+
+    let key = if enabled {
+        "settings.tracking_required.on"
+    } else {
+        "settings.tracking_required.off"
+    };
+    ctx.say(t(key, lang, &[])).await?;
+    Ok(())
+}
+
+/// Let the settings panel offer voice/stage channel chats as log channels.
+///
+/// Off by default: those chats can be unavailable, so run `/settings test` after picking one.
+#[poise::command(
+    slash_command,
+    guild_only,
+    ephemeral,
+    rename = "log-channel-types"
+)]
+pub async fn settings_log_channel_types(
+    ctx: Ctx<'_>,
+    #[description = "Also offer voice and stage channel chats"] extended: bool,
+) -> Result<()> {
+    let lang = guild_lang(ctx).await;
+    let gid = match ctx.guild_id() {
+        Some(g) => g,
+        None => {
+            ctx.say(t("common.guild_only", lang, &[])).await?;
+            return Ok(());
+        }
+    };
+
+    let repo = ctx.data().settings_repo();
+    repo.ensure_row(&gid).await?;
+    repo.set_extended_log_channels(&gid, extended).await?;
+
+    let key = if extended {
+        "settings.log_channel_types.extended"
+    } else {
+        "settings.log_channel_types.default"
+    };
+    ctx.say(t(key, lang, &[])).await?;
+    Ok(())
+}
+
+/// Automatically delete history older than a number of days (off by default).
+///
+/// Runs once a day: closed stints that ended before the cutoff (each member's latest
+/// stint is always kept), plus moderator notes and name changes written before it.
+#[poise::command(
+    slash_command,
+    guild_only,
+    ephemeral,
+    rename = "retention"
+)]
+pub async fn settings_retention(
+    ctx: Ctx<'_>,
+    #[description = "Days of history to keep (30–3650)"]
+    days: Option<i64>,
+    #[description = "Keep history forever (turn retention off)"]
+    clear: Option<bool>,
+) -> Result<()> {
+    let lang = guild_lang(ctx).await;
+    let gid = match ctx.guild_id() {
+        Some(g) => g,
+        None => {
+            ctx.say(t("common.guild_only", lang, &[])).await?;
+            return Ok(());
+        }
+    };
+
+    let repo = ctx.data().settings_repo();
+    repo.ensure_row(&gid).await?;
+
+    if clear.unwrap_or(false) {
+        repo.set_retention_days(&gid, None).await?;
+        ctx.say(t("settings.retention.off", lang, &[])).await?;
+        return Ok(());
+    }
+
+    let Some(days) = days else {
+        ctx.say(t(
+            "settings.retention.missing",
+            lang,
+            &[("min", RETENTION_RANGE.start()), ("max", RETENTION_RANGE.end())],
+        ))
+        .await?;
+        return Ok(());
+    };
+    if !RETENTION_RANGE.contains(&days) {
+        ctx.say(t(
+            "settings.retention.invalid",
+            lang,
+            &[
+                ("input", &days),
+                ("min", RETENTION_RANGE.start()),
+                ("max", RETENTION_RANGE.end()),
+            ],
+        ))
+        .await?;
+        return Ok(());
+    }
+
+    repo.set_retention_days(&gid, Some(days)).await?;
+    ctx.say(t("settings.retention.set", lang, &[("days", &days)])).await?;
+    Ok(())
+}
+
+/// Limit role-change logging to specific roles (e.g. admin roles).
+///
+/// With no roles watched, every role grant/removal is posted to the moderation log.
+#[poise::command(
+    slash_command,
+    guild_only,
+    ephemeral,
+    rename = "watched-roles"
+)]
+pub async fn settings_watched_roles(
+    ctx: Ctx<'_>,
+    #[description = "Start logging changes to this role"]
+    add: Option<serenity::RoleId>,
+    #[description = "Stop logging changes to this role"]
+    remove: Option<serenity::RoleId>,
+    #[description = "Clear the list (log every role change again)"]
+    clear: Option<bool>,
+) -> Result<()> {
+    let lang = guild_lang(ctx).await;
+    let gid = match ctx.guild_id() {
+        Some(g) => g,
+        None => {
+            ctx.say(t("common.guild_only", lang, &[])).await?;
+            return Ok(());
+        }
+    };
+
+    let repo = ctx.data().settings_repo();
+    repo.ensure_row(&gid).await?;
+    let mut roles = repo.get(&gid).await?.watched_roles;
+
+    if clear.unwrap_or(false) {
+        roles.clear();
+    }
+    if let Some(role) = remove {
+        roles.retain(|r| *r != role);
+    }
+    if let Some(role) = add
+        && !roles.contains(&role)
+    {
+        if roles.len() >= MAX_WATCHED_ROLES {
+            ctx.say(t(
+                "settings.watched_roles.too_many",
+                lang,
+                &[("max", &MAX_WATCHED_ROLES)],
+            ))
+            .await?;
+            return Ok(());
+        }
+        roles.push(role);
+    }
+
+    if add.is_some() || remove.is_some() || clear.unwrap_or(false) {
+        repo.set_watched_roles(&gid, &roles).await?;
+    }
+
+    let msg = if roles.is_empty() {
+        t("settings.watched_roles.all", lang, &[])
+    } else {
+        let list = roles
+            .iter()
+            .map(|r| format!("<@&{}>", r.get()))
+            .collect::<Vec<_>>()
+            .join(", ");
+        t("settings.watched_roles.some", lang, &[("roles", &list)])
+    };
+    ctx.say(msg).await?;
+    Ok(())
+}
+
+/// Bump when the export layout changes incompatibly.
+const SETTINGS_EXPORT_VERSION: u32 = 1;
+
+/// Imports are a few hundred bytes; anything huge is not ours.
+const MAX_IMPORT_BYTES: u32 = 64 * 1024;
+
+/// File format for `/settings export` / `/settings import`.
+#[derive(Serialize, Deserialize)]
+struct SettingsExport {
+    version: u32,
+    settings: GuildSettings,
+}
+
+/// Download this server's settings as JSON (to copy them to another server).
+#[poise::command(
+    slash_command,
+    guild_only,
+    ephemeral,
+    rename = "export"
+)]
+pub async fn settings_export(ctx: Ctx<'_>) -> Result<()> {
+    let lang = guild_lang(ctx).await;
+    let gid = match ctx.guild_id() {
+        Some(g) => g,
+        None => {
+            ctx.say(t("common.guild_only", lang, &[])).await?;
+            return Ok(());
+        }
+    };
+
+    let settings = ctx.data().settings_repo().get(&gid).await?;
+    let json = serde_json::to_vec_pretty(&SettingsExport {
+        version: SETTINGS_EXPORT_VERSION,
+        settings,
+    })?;
+
+    ctx.send(
+        poise::CreateReply::default()
+            .content(t("settings.export.done", lang, &[]))
+            .attachment(serenity::CreateAttachment::bytes(
+                json,
+                format!("settings-{}.json", gid.get()),
+            )),
+    )
+    .await?;
+    Ok(())
+}
+
+/// Replace this server's settings with a file from `/settings export`.
+///
+/// Values that don't fit this server (unknown channels, bad zones, out-of-range numbers)
+/// are dropped with a warning; everything else is written in one statement.
+#[poise::command(
+    slash_command,
+    guild_only,
+    ephemeral,
+    rename = "import"
+)]
+pub async fn settings_import(
+    ctx: Ctx<'_>,
+    #[description = "JSON file produced by /settings export"]
+    file: serenity::Attachment,
+) -> Result<()> {
+    let lang = guild_lang(ctx).await;
+    let gid = match ctx.guild_id() {
+        Some(g) => g,
+        None => {
+            ctx.say(t("common.guild_only", lang, &[])).await?;
+            return Ok(());
+        }
+    };
+    if file.size > MAX_IMPORT_BYTES {
+        ctx.say(t("settings.import.too_large", lang, &[])).await?;
+        return Ok(());
+    }
+    ctx.defer_ephemeral().await?;
+
+    let bytes = file.download().await?;
+    let export: SettingsExport = match serde_json::from_slice(&bytes) {
+        Ok(e) => e,
+        Err(e) => {
+            ctx.say(t("settings.import.unreadable", lang, &[("error", &e)]))
+                .await?;
+            return Ok(());
+        }
+    };
+    if export.version != SETTINGS_EXPORT_VERSION {
+        ctx.say(t(
+            "settings.import.bad_version",
+            lang,
+            &[
+                ("version", &export.version),
+                ("expected", &SETTINGS_EXPORT_VERSION),
+            ],
+        ))
+        .await?;
+        return Ok(());
+    }
+
+    let mut settings = export.settings;
+    let mut warnings: Vec<String> = Vec::new();
+
+    let channels = gid.channels(ctx.http()).await?;
+    let label = |kind: LogKind| t(&format!("settings.log_label.{}", kind.as_str()), lang, &[]);
+    for (kind, slot) in [
+        (LogKind::Join, &mut settings.join_log),
+        (LogKind::Leave, &mut settings.leave_log),
+        (LogKind::Mod, &mut settings.mod_log),
+    ] {
+        if let Some(ch) = *slot
+            && !channels.contains_key(&ch)
+        {
+            warnings.push(t(
+                "settings.import.channel_unset",
+                lang,
+                &[("log", &label(kind)), ("channel", &ch)],
+            ));
+            *slot = None;
+        }
+    }
+    for (kind, extras) in [
+        (LogKind::Join, &mut settings.extra_join_logs),
+        (LogKind::Leave, &mut settings.extra_leave_logs),
+        (LogKind::Mod, &mut settings.extra_mod_logs),
+    ] {
+        extras.retain(|ch| {
+            let exists = channels.contains_key(ch);
+            if !exists {
+                warnings.push(t(
+                    "settings.import.channel_dropped",
+                    lang,
+                    &[("log", &label(kind)), ("channel", ch)],
+                ));
+            }
+            exists
+        });
+        extras.truncate(MAX_LOG_CHANNELS - 1);
+    }
+    if !settings.watched_roles.is_empty() {
+        let roles = gid.roles(ctx.http()).await?;
+        settings.watched_roles.retain(|role| {
+            let exists = roles.contains_key(role);
+            if !exists {
+                warnings.push(t("settings.import.role_dropped", lang, &[("role", role)]));
+            }
+            exists
+        });
+        settings.watched_roles.truncate(MAX_WATCHED_ROLES);
+    }
+    for (kind, slot) in [
+        (LogKind::Join, &mut settings.join_log_webhook_url),
+        (LogKind::Leave, &mut settings.leave_log_webhook_url),
+        (LogKind::Mod, &mut settings.mod_log_webhook_url),
+    ] {
+        if slot.as_deref().is_some_and(|u| parse_webhook_url(u).is_none()) {
+            warnings.push(t("settings.import.bad_webhook", lang, &[("log", &label(kind))]));
+            *slot = None;
+        }
+    }
+    if let Some(zone) = settings.timezone.take() {
+        if zone.parse::<chrono_tz::Tz>().is_ok() {
+            settings.timezone = Some(zone);
+        } else {
+            warnings.push(t("settings.import.bad_timezone", lang, &[("zone", &zone)]));
+        }
+    }
+    if let Some(code) = settings.lang.take() {
+        match crate::strings::supported(&code) {
+            Some(code) => settings.lang = Some(code.to_string()),
+            None => warnings.push(t("settings.import.bad_language", lang, &[("lang", &code)])),
+        }
+    }
+    if settings
+        .embed_color
+        .is_some_and(|c| !(0..=0xFF_FF_FF).contains(&c))
+    {
+        warnings.push(t("settings.import.bad_colour", lang, &[]));
+        settings.embed_color = None;
+    }
+    if settings
+        .ban_detection_window_secs
+        .is_some_and(|w| !BAN_WINDOW_RANGE.contains(&w))
+    {
+        warnings.push(t(
+            "settings.import.bad_ban_window",
+            lang,
+            &[("seconds", &DEFAULT_BAN_WINDOW_SECS)],
+        ));
+        settings.ban_detection_window_secs = None;
+    }
+    if settings
+        .raid_threshold
+        .is_some_and(|n| !RAID_THRESHOLD_RANGE.contains(&n))
+    {
+        warnings.push(t(
+            "settings.import.bad_raid_threshold",
+            lang,
+            &[("count", &DEFAULT_RAID_THRESHOLD)],
+        ));
+        settings.raid_threshold = None;
+    }
+    if settings
+        .min_account_age_days
+        .is_some_and(|d| !ACCOUNT_AGE_RANGE.contains(&d))
+    {
+        warnings.push(t("settings.import.bad_account_age", lang, &[]));
+        settings.min_account_age_days = None;
+    }
+    if let Some(action) = settings.account_age_action.take() {
+        match AccountAgeAction::parse(&action) {
+            Some(a) => settings.account_age_action = Some(a.as_str().to_string()),
+            None => warnings.push(t(
+                "settings.import.bad_account_age_action",
+                lang,
+                &[("action", &action)],
+            )),
+        }
+    }
+    if settings
+        .retention_days
+        .is_some_and(|d| !RETENTION_RANGE.contains(&d))
+    {
+        warnings.push(t("settings.import.bad_retention", lang, &[]));
+        settings.retention_days = None;
+    }
+    if settings.digest_hour.is_some_and(|h| !(0..24).contains(&h)) {
+        warnings.push(t("settings.import.bad_digest_hour", lang, &[]));
+        settings.digest_hour = None;
+    }
+
+    ctx.data().settings_repo().upsert(&gid, &settings).await?;
+
+    let mut msg = t("settings.import.done", lang, &[]);
+    if !warnings.is_empty() {
+        msg.push_str("\n\n");
+        msg.push_str(&t("settings.import.warnings", lang, &[]));
+        msg.push('\n');
+        msg.push_str(
+            &warnings
+                .iter()
+                .map(|w| format!("• {w}"))
+                .collect::<Vec<_>>()
+                .join("\n"),
+        );
+    }
+    ctx.say(msg).await?;
+    Ok(())
+}
+
+/// Start tracking everyone already in the server (one-off, after inviting the bot).
+#[poise::command(
+    slash_command,
+    guild_only,
+    ephemeral,
+    rename = "backfill"
+)]
+pub async fn settings_backfill(ctx: Ctx<'_>) -> Result<()> {
+    let lang = guild_lang(ctx).await;
+    let gid = match ctx.guild_id() {
+        Some(g) => g,
+        None => {
+            ctx.say(t("common.guild_only", lang, &[])).await?;
+            return Ok(());
+        }
+    };
+    ctx.defer_ephemeral().await?;
+
+    let mut members = fetch_all_members(ctx.http(), gid).await?;
+    if !ctx.data().settings_repo().get(&gid).await?.tracking_required {
+        let opted_out = OptOutRepo::new(&ctx.data().db).opted_out_users(gid).await?;
+        members.retain(|m| !opted_out.contains(&m.user.id));
+    }
+
+    let repo = MembershipsRepo::new(&ctx.data().db);
+    let added = repo.bulk_record_joins(gid, &members).await?;
+    ctx.data().forget_member_count(gid);
+    // Name search still works without FTS (LIKE fallback), so that's only a note.
+    let mut msg = t(
+        "settings.backfill.done",
+        lang,
+        &[
+            ("added", &added),
+            ("total", &members.len()),
+            ("tracked", &(members.len() as u64 - added)),
+        ],
+    );
+    if added > 0 {
+        match repo.rebuild_usernames_fts_for_guild(gid).await {
+            Ok(_) => {}
+            Err(RepoError::FtsUnavailable) => {
+                msg.push('\n');
+                msg.push_str(&t("settings.backfill.no_fts", lang, &[]));
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    ctx.say(msg).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{GUILD, test_db};
+
+    #[tokio::test]
+    async fn clear_only_runs_after_yes() {
+        let db = test_db().await;
+        let repo = GuildSettingsRepo::new(&db);
+        repo.ensure_row(&GUILD).await.unwrap();
+        let channel = serenity::ChannelId::new(7);
+        repo.set_join_log(&GUILD, Some(channel)).await.unwrap();
+        repo.set_mod_log(&GUILD, Some(channel)).await.unwrap();
+
+        // No and a timeout both leave `Confirm::confirmed` false (see `flows::confirm`).
+        assert!(!clear_logs_if_confirmed(&repo, GUILD, false).await.unwrap());
+        let kept = repo.get(&GUILD).await.unwrap();
+        assert_eq!(kept.targets(LogKind::Join), [channel]);
+        assert_eq!(kept.targets(LogKind::Mod), [channel]);
+
+        assert!(clear_logs_if_confirmed(&repo, GUILD, true).await.unwrap());
+        let cleared = repo.get(&GUILD).await.unwrap();
+        for kind in LogKind::ALL {
+            assert!(cleared.log_targets(kind).is_empty(), "{} still set", kind.as_str());
+        }
+    }
+}
@@ -1,24 +1,39 @@
 use anyhow::Result;
 use poise::serenity_prelude as serenity;
 
-use crate::commands::send_chunked_embeds;
-use crate::repos::MembershipsRepo;
+use crate::commands::{
+    ban_reason_suffix, format_duration, guild_colour, guild_lang, send_chunked_embeds,
+    with_colour,
+};
+use crate::repos::{
+    ChurnStint, InviteRow, InvitesRepo, LeaveKind, MembershipStore, RejoinTimes, TimeWindow,
+    UnixTime,
+};
 use crate::state::Ctx;
+use crate::strings::t;
 
 /// Helper: choose a nice label from names or fall back to user id mention.
 fn format_member_label(
     user_id: &str,
     account_username: &Option<String>,
     server_username: &Option<String>,
+    lang: &str,
 ) -> String {
     match (server_username.as_deref(), account_username.as_deref()) {
-        (Some(nick), Some(acc)) if !nick.is_empty() => format!("{nick} (aka {acc})"),
+        (Some(nick), Some(acc)) if !nick.is_empty() => {
+            t("stats.member_aka", lang, &[("nick", &nick), ("account", &acc)])
+        }
         (_, Some(acc)) => acc.to_string(),
         (Some(nick), None) => nick.to_string(),
         _ => format!("<@{user_id}>"),
     }
 }
 
+/// Reply builder honouring the subcommand's `ephemeral` toggle.
+fn reply(ephemeral: bool) -> poise::CreateReply {
+    poise::CreateReply::default().ephemeral(ephemeral)
+}
+
 /// `/stats` parent command. All real work happens in subcommands.
 #[poise::command(
     slash_command,
@@ -26,8 +41,20 @@ fn format_member_label(
     subcommands(
         "stats_current",
         "stats_rejoiners",
+        "stats_churn",
+        "stats_leaderboard_longevity",
+        "stats_first_joins",
         "stats_exits",
-        "stats_member_balance"
+        "stats_ban_rate",
+        "stats_member_balance",
+        "stats_compare",
+        "stats_invites",
+        "stats_boosters",
+        "stats_retention",
+        "stats_duration",
+        "stats_activity_heatmap",
+        "stats_growth",
+        "stats_export_image"
     ),
     rename = "stats"
 )]
@@ -41,46 +68,351 @@ pub async fn stats_rejoiners(
     ctx: Ctx<'_>,
     #[description = "Minimum joins (default 2)"] min_joins: Option<i64>,
     #[description = "Max users to show (default 15)"] limit: Option<i64>,
+    #[description = "Only show the result to you"] ephemeral: Option<bool>,
 ) -> Result<()> {
     let gid = ctx
         .guild_id()
         .expect("guild_only command should always have a guild_id");
+    let lang = guild_lang(ctx).await;
+    let ephemeral = ephemeral.unwrap_or(false);
 
     let min_rejoins = min_joins.unwrap_or(2).max(2);
     let limit = limit.unwrap_or(15).clamp(1, 100);
 
-    let repo = MembershipsRepo::new(&ctx.data().db);
+    let repo = ctx.data().memberships();
     let rows = repo.rejoiners(gid, min_rejoins, limit).await?;
 
     if rows.is_empty() {
-        ctx.say(format!("No users with ≥{} rejoins.", min_rejoins))
-            .await?;
+        ctx.send(
+            reply(ephemeral).content(t("stats.rejoins.none", lang, &[("min", &min_rejoins)])),
+        )
+        .await?;
         return Ok(());
     }
 
     let mut lines = Vec::with_capacity(rows.len());
     for r in rows {
-        let label = format_member_label(&r.user_id, &r.account_username, &r.server_username);
-        lines.push(format!(
-            "• {label} — {} rejoins ({} exits)",
-            r.rejoin_count, r.times_left
+        let label =
+            format_member_label(&r.user_id, &r.account_username, &r.server_username, lang);
+        lines.push(t(
+            "stats.rejoins.line",
+            lang,
+            &[
+                ("member", &label),
+                ("rejoins", &r.rejoin_count),
+                ("exits", &r.times_left),
+            ],
         ));
     }
 
-    let base_title = format!("Rejoiners (≥{} rejoins)", min_rejoins);
+    let base_title = t("stats.rejoins.title", lang, &[("min", &min_rejoins)]);
+    let base_title_cont = base_title.clone();
+
+    send_chunked_embeds(
+        ctx,
+        lines,
+        ephemeral,
+        move |desc| {
+            serenity::CreateEmbed::new()
+                .title(base_title.clone())
+                .description(desc)
+        },
+        move |idx, desc| {
+            serenity::CreateEmbed::new()
+                .title(t("stats.cont", lang, &[("title", &base_title_cont), ("n", &idx)]))
+                .description(desc)
+        },
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Most stints read for `/stats churn` (repeat members with the most stints first).
+const CHURN_SCAN_CAP: i64 = 20_000;
+/// Stays shown per member in the churn pattern (most recent kept).
+const CHURN_PATTERN_STAYS: usize = 4;
+
+/// One repeat member's leave → rejoin behaviour.
+struct Churner<'a> {
+    stints: &'a [ChurnStint],
+    /// Time between each leave and the following rejoin (when both are known).
+    gaps: Vec<chrono::Duration>,
+}
+
+impl Churner<'_> {
+    fn average_gap(&self) -> Option<chrono::Duration> {
+        let n = i32::try_from(self.gaps.len()).ok().filter(|&n| n > 0)?;
+        Some(self.gaps.iter().copied().sum::<chrono::Duration>() / n)
+    }
+}
+
+/// Split the grouped rows into members and measure their leave → rejoin gaps.
+/// Rejoins after an inferred stint (join time unknown) still count: the gap runs
+/// from the known leave to the next real join.
+fn churners(rows: &[ChurnStint]) -> Vec<Churner<'_>> {
+    rows.chunk_by(|a, b| a.user_id == b.user_id)
+        .map(|stints| {
+            let gaps = stints
+                .windows(2)
+                .filter(|w| !w[1].inferred)
+                .filter_map(|w| {
+                    let left = w[0].left_at?;
+                    Some(w[1].joined_at.to_datetime() - left.to_datetime())
+                })
+                .filter(|gap| *gap >= chrono::Duration::zero())
+                .collect();
+            Churner { stints, gaps }
+        })
+        .filter(|c| !c.gaps.is_empty())
+        .collect()
+}
+
+/// "Mar 03–Mar 05 · Mar 05–now" for the member's most recent stays, in `tz`.
+fn churn_pattern(stints: &[ChurnStint], tz: chrono_tz::Tz, lang: &str) -> String {
+    let day = |t: UnixTime| t.to_datetime().with_timezone(&tz).format("%b %d").to_string();
+    let shown = &stints[stints.len().saturating_sub(CHURN_PATTERN_STAYS)..];
+    let mut parts: Vec<String> = shown
+        .iter()
+        .map(|s| {
+            let start = if s.inferred { "?".to_string() } else { day(s.joined_at) };
+            let end = s.left_at.map_or_else(|| t("stats.churn.now", lang, &[]), day);
+            format!("{start}–{end}")
+        })
+        .collect();
+    if stints.len() > shown.len() {
+        parts.insert(0, "…".to_string());
+    }
+    parts.join(" · ")
+}
+
+/// Revolving-door members: who keeps leaving and coming back, and how fast.
+#[poise::command(slash_command, guild_only, rename = "churn")]
+pub async fn stats_churn(
+    ctx: Ctx<'_>,
+    #[description = "Minimum stays per member (default 3)"] min_stays: Option<i64>,
+    #[description = "Flag average leave → rejoin gaps under this many hours (default 24)"]
+    short_gap_hours: Option<i64>,
+    #[description = "Max members to show (default 15)"] limit: Option<i64>,
+    #[description = "Only show the result to you"] ephemeral: Option<bool>,
+) -> Result<()> {
+    let gid = ctx
+        .guild_id()
+        .expect("guild_only command should always have a guild_id");
+    let lang = guild_lang(ctx).await;
+    let ephemeral = ephemeral.unwrap_or(false);
+
+    let min_stays = min_stays.unwrap_or(3).clamp(2, 100);
+    let short_gap = chrono::Duration::hours(short_gap_hours.unwrap_or(24).clamp(1, 24 * 90));
+    let limit = usize::try_from(limit.unwrap_or(15).clamp(1, 100)).unwrap_or(15);
+    let tz = ctx.data().settings_repo().get_cached(&gid).await?.tz();
+
+    let repo = ctx.data().memberships();
+    let rows = repo.churn_stats(gid, min_stays, CHURN_SCAN_CAP).await?;
+
+    // Most leave → rejoin cycles first; among equals, the quickest returners.
+    let mut members = churners(&rows);
+    members.sort_by_key(|c| (std::cmp::Reverse(c.gaps.len()), c.average_gap()));
+    members.truncate(limit);
+
+    if members.is_empty() {
+        ctx.send(reply(ephemeral).content(t("stats.churn.none", lang, &[("min", &min_stays)])))
+            .await?;
+        return Ok(());
+    }
+
+    let flag = t("stats.churn.short_gap", lang, &[]);
+    let lines: Vec<String> = members
+        .iter()
+        .enumerate()
+        .map(|(idx, c)| {
+            let last = &c.stints[c.stints.len() - 1];
+            let label = format_member_label(
+                &last.user_id,
+                &last.account_username,
+                &last.server_username,
+                lang,
+            );
+            let avg = c.average_gap().unwrap_or_default();
+            t(
+                "stats.churn.line",
+                lang,
+                &[
+                    ("rank", &(idx + 1)),
+                    ("member", &label),
+                    ("cycles", &c.gaps.len()),
+                    ("gap", &format_duration(avg)),
+                    ("flag", &if avg < short_gap { flag.as_str() } else { "" }),
+                    ("pattern", &churn_pattern(c.stints, tz, lang)),
+                ],
+            )
+        })
+        .collect();
+
+    let base_title = t("stats.churn.title", lang, &[("min", &min_stays)]);
+    let base_title_cont = base_title.clone();
+    let footer = t(
+        "stats.churn.footer",
+        lang,
+        &[("hours", &short_gap.num_hours()), ("tz", &tz.name())],
+    );
+
+    send_chunked_embeds(
+        ctx,
+        lines,
+        ephemeral,
+        move |desc| {
+            serenity::CreateEmbed::new()
+                .title(base_title.clone())
+                .description(desc)
+                .footer(serenity::CreateEmbedFooter::new(footer.clone()))
+        },
+        move |idx, desc| {
+            serenity::CreateEmbed::new()
+                .title(t("stats.cont", lang, &[("title", &base_title_cont), ("n", &idx)]))
+                .description(desc)
+        },
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Longest-tenured members by total time in the server, across all their stays.
+#[poise::command(slash_command, guild_only, rename = "leaderboard-longevity")]
+pub async fn stats_leaderboard_longevity(
+    ctx: Ctx<'_>,
+    #[description = "Max members to show (default 15)"] limit: Option<i64>,
+    #[description = "Also rank people who have since left"] include_former: Option<bool>,
+    #[description = "Only show the result to you"] ephemeral: Option<bool>,
+) -> Result<()> {
+    let gid = ctx
+        .guild_id()
+        .expect("guild_only command should always have a guild_id");
+    let lang = guild_lang(ctx).await;
+    let ephemeral = ephemeral.unwrap_or(false);
+
+    let limit = limit.unwrap_or(15).clamp(1, 100);
+    let include_former = include_former.unwrap_or(false);
+
+    let repo = ctx.data().memberships();
+    let rows = repo.tenure_by_user(gid, include_former, limit).await?;
+
+    if rows.is_empty() {
+        ctx.send(reply(ephemeral).content(t("stats.longevity.none", lang, &[]))).await?;
+        return Ok(());
+    }
+
+    let former = t("stats.longevity.former", lang, &[]);
+    let lines: Vec<String> = rows
+        .iter()
+        .enumerate()
+        .map(|(idx, r)| {
+            let label =
+                format_member_label(&r.user_id, &r.account_username, &r.server_username, lang);
+            t(
+                "stats.longevity.line",
+                lang,
+                &[
+                    ("rank", &(idx + 1)),
+                    ("member", &label),
+                    ("duration", &format_duration(r.total())),
+                    ("former", &if r.present { "" } else { former.as_str() }),
+                ],
+            )
+        })
+        .collect();
+
+    let base_title = if include_former {
+        t("stats.longevity.title_all", lang, &[])
+    } else {
+        t("stats.longevity.title", lang, &[])
+    };
+    let base_title_cont = base_title.clone();
+
+    send_chunked_embeds(
+        ctx,
+        lines,
+        ephemeral,
+        move |desc| {
+            serenity::CreateEmbed::new()
+                .title(base_title.clone())
+                .description(desc)
+        },
+        move |idx, desc| {
+            serenity::CreateEmbed::new()
+                .title(t("stats.cont", lang, &[("title", &base_title_cont), ("n", &idx)]))
+                .description(desc)
+        },
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// The earliest members ever recorded here, oldest join first.
+///
+/// Only joins seen by the bot (or imported by backfill) count, so servers older
+/// than the bot's arrival may be missing their true founders.
+#[poise::command(slash_command, guild_only, rename = "first-joins")]
+pub async fn stats_first_joins(
+    ctx: Ctx<'_>,
+    #[description = "Max members to show (default 15)"] limit: Option<i64>,
+    #[description = "Only show the result to you"] ephemeral: Option<bool>,
+) -> Result<()> {
+    let gid = ctx
+        .guild_id()
+        .expect("guild_only command should always have a guild_id");
+    let lang = guild_lang(ctx).await;
+    let ephemeral = ephemeral.unwrap_or(false);
+
+    let limit = limit.unwrap_or(15).clamp(1, 100);
+
+    let repo = ctx.data().memberships();
+    let rows = repo.earliest_joiners(gid, limit).await?;
+
+    if rows.is_empty() {
+        ctx.send(reply(ephemeral).content(t("stats.first_joins.none", lang, &[]))).await?;
+        return Ok(());
+    }
+
+    let former = t("stats.longevity.former", lang, &[]);
+    let lines: Vec<String> = rows
+        .iter()
+        .enumerate()
+        .map(|(idx, r)| {
+            let label =
+                format_member_label(&r.user_id, &r.account_username, &r.server_username, lang);
+            t(
+                "stats.first_joins.line",
+                lang,
+                &[
+                    ("rank", &(idx + 1)),
+                    ("member", &label),
+                    ("date", &r.joined_at.discord('D')),
+                    ("former", &if r.present { "" } else { former.as_str() }),
+                ],
+            )
+        })
+        .collect();
+
+    let base_title = t("stats.first_joins.title", lang, &[]);
     let base_title_cont = base_title.clone();
+    let footer = t("stats.first_joins.footer", lang, &[]);
 
     send_chunked_embeds(
         ctx,
         lines,
+        ephemeral,
         move |desc| {
             serenity::CreateEmbed::new()
                 .title(base_title.clone())
                 .description(desc)
+                .footer(serenity::CreateEmbedFooter::new(footer.clone()))
         },
         move |idx, desc| {
             serenity::CreateEmbed::new()
-                .title(format!("{base_title_cont} — cont. #{idx}"))
+                .title(t("stats.cont", lang, &[("title", &base_title_cont), ("n", &idx)]))
                 .description(desc)
         },
     )
@@ -89,86 +421,352 @@ pub async fn stats_rejoiners(
     Ok(())
 }
 
-/// Recent exits with left vs banned split.
+/// Top inviters by attributed joins, or the details of one invite code.
+#[poise::command(slash_command, guild_only, rename = "invites")]
+pub async fn stats_invites(
+    ctx: Ctx<'_>,
+    #[description = "Max inviters to show (default 15)"] limit: Option<i64>,
+    #[description = "Only count joins from the last N days (default: all time)"] days: Option<i64>,
+    #[description = "Show details for one invite code instead"] code: Option<String>,
+    #[description = "Only show the result to you"] ephemeral: Option<bool>,
+) -> Result<()> {
+    use chrono::{Duration, Utc};
+    use std::collections::HashMap;
+
+    let gid = ctx
+        .guild_id()
+        .expect("guild_only command should always have a guild_id");
+    let lang = guild_lang(ctx).await;
+    let ephemeral = ephemeral.unwrap_or(false);
+
+    let invites = InvitesRepo::new(&ctx.data().db);
+    if let Some(code) = code {
+        // Accept pasted links as well as bare codes.
+        let code = code.trim().rsplit('/').next().unwrap_or_default().to_string();
+        return match invites.invite_detail(gid, &code).await? {
+            Some(row) => {
+                let embed = invite_detail_embed(&row, lang);
+                let embed = with_colour(embed, guild_colour(ctx).await);
+                ctx.send(reply(ephemeral).embed(embed)).await?;
+                Ok(())
+            }
+            None => {
+                let msg = t("stats.invites.unknown_code", lang, &[("code", &code)]);
+                ctx.send(reply(ephemeral).content(msg)).await?;
+                Ok(())
+            }
+        };
+    }
+    // Lifetime totals per inviter (includes uses from before the bot tracked joins).
+    let totals: HashMap<String, (i64, i64)> = invites
+        .top_inviters(gid, 1_000)
+        .await?
+        .into_iter()
+        .map(|t| (t.inviter_id, (t.invites, t.uses)))
+        .collect();
+
+    let limit = limit.unwrap_or(15).clamp(1, 100);
+    let days = days.map(|d| d.clamp(1, 3650));
+
+    let repo = ctx.data().memberships();
+    let rows = repo.invite_joins_raw(gid, 100_000).await?;
+
+    let cutoff = days.map(|d| Utc::now() - Duration::days(d));
+
+    // inviter_id -> (joins, account_username, server_username)
+    let mut by_inviter: HashMap<String, (i64, Option<String>, Option<String>)> = HashMap::new();
+    let mut unattributed = 0i64;
+
+    for r in rows {
+        if let Some(cutoff) = cutoff
+            && r.joined_at.to_datetime() < cutoff
+        {
+            continue;
+        }
+        match r.inviter_id {
+            Some(inviter) => {
+                let e = by_inviter.entry(inviter).or_insert_with(|| {
+                    (0, r.inviter_account_username, r.inviter_server_username)
+                });
+                e.0 += 1;
+            }
+            None => unattributed += 1,
+        }
+    }
+
+    let window = match days {
+        Some(d) => t("stats.window.last_days", lang, &[("days", &d)]),
+        None => t("stats.window.all_time", lang, &[]),
+    };
+
+    if by_inviter.is_empty() {
+        ctx.send(reply(ephemeral).content(t(
+            "stats.invites.none",
+            lang,
+            &[("window", &window), ("unattributed", &unattributed)],
+        )))
+        .await?;
+        return Ok(());
+    }
+
+    let mut ranked: Vec<_> = by_inviter.into_iter().collect();
+    ranked.sort_by(|(a_id, a), (b_id, b)| b.0.cmp(&a.0).then_with(|| a_id.cmp(b_id)));
+
+    let mut lines = Vec::with_capacity(ranked.len().min(limit as usize) + 2);
+    lines.push(t(
+        "stats.invites.unattributed",
+        lang,
+        &[("count", &unattributed)],
+    ));
+    lines.push("".into());
+
+    for (rank, (inviter_id, (joins, account, server))) in
+        ranked.into_iter().take(limit as usize).enumerate()
+    {
+        let label = format_member_label(&inviter_id, &account, &server, lang);
+        let lifetime = match totals.get(&inviter_id) {
+            Some((codes, uses)) => t(
+                "stats.invites.lifetime",
+                lang,
+                &[("uses", uses), ("codes", codes)],
+            ),
+            None => String::new(),
+        };
+        lines.push(t(
+            "stats.invites.line",
+            lang,
+            &[
+                ("rank", &(rank + 1)),
+                ("member", &label),
+                ("joins", &joins),
+                ("lifetime", &lifetime),
+            ],
+        ));
+    }
+
+    let base_title = t("stats.invites.title", lang, &[("window", &window)]);
+    let base_title_cont = base_title.clone();
+
+    send_chunked_embeds(
+        ctx,
+        lines,
+        ephemeral,
+        move |desc| {
+            serenity::CreateEmbed::new()
+                .title(base_title.clone())
+                .description(desc)
+        },
+        move |idx, desc| {
+            serenity::CreateEmbed::new()
+                .title(t("stats.cont", lang, &[("title", &base_title_cont), ("n", &idx)]))
+                .description(desc)
+        },
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Embed for `/stats invites code:…`.
+fn invite_detail_embed(row: &InviteRow, lang: &str) -> serenity::CreateEmbed {
+    let ts = |t: UnixTime| t.discord('f');
+
+    let creator = match row.inviter_id.as_deref() {
+        Some(id) => format!("<@{id}>"),
+        None => t("stats.invite.unknown", lang, &[]),
+    };
+    let expires = match (row.deleted_at, row.expires_at) {
+        (Some(deleted), _) => t("stats.invite.deleted", lang, &[("when", &ts(deleted))]),
+        (None, Some(expires)) => ts(expires),
+        (None, None) => t("stats.invite.never", lang, &[]),
+    };
+    let last_used = row
+        .last_used_at
+        .map(ts)
+        .unwrap_or_else(|| "—".to_string());
+
+    serenity::CreateEmbed::new()
+        .title(t("stats.invite.title", lang, &[("code", &row.code)]))
+        .field(t("stats.invite.created_by", lang, &[]), creator, true)
+        .field(t("stats.invite.created", lang, &[]), ts(row.created_at), true)
+        .field(t("stats.invite.expires", lang, &[]), expires, true)
+        .field(t("stats.invite.uses", lang, &[]), row.uses.to_string(), true)
+        .field(t("stats.invite.joins", lang, &[]), row.joins.to_string(), true)
+        .field(t("stats.invite.last_join", lang, &[]), last_used, true)
+}
+
+/// Current server boosters, longest-boosting first.
+#[poise::command(slash_command, guild_only, rename = "boosters")]
+pub async fn stats_boosters(
+    ctx: Ctx<'_>,
+    #[description = "Only show the result to you"] ephemeral: Option<bool>,
+) -> Result<()> {
+    use chrono::{Duration, Utc};
+
+    let gid = ctx
+        .guild_id()
+        .expect("guild_only command should always have a guild_id");
+    let lang = guild_lang(ctx).await;
+    let ephemeral = ephemeral.unwrap_or(false);
+
+    if ephemeral {
+        ctx.defer_ephemeral().await?;
+    } else {
+        ctx.defer().await?;
+    }
+
+    let members = crate::members::fetch_all_members(ctx.http(), gid).await?;
+
+    let mut boosters: Vec<_> = members
+        .into_iter()
+        .filter_map(|m| m.premium_since.map(|since| (since, m)))
+        .collect();
+
+    if boosters.is_empty() {
+        ctx.send(reply(ephemeral).content(t("stats.boosters.none", lang, &[]))).await?;
+        return Ok(());
+    }
+
+    // Oldest boost first
+    boosters.sort_by_key(|(since, _)| *since);
+
+    let now = Utc::now().timestamp();
+    let mut lines = Vec::with_capacity(boosters.len());
+    for (idx, (since, m)) in boosters.iter().enumerate() {
+        let since = since.unix_timestamp();
+        lines.push(t(
+            "stats.boosters.line",
+            lang,
+            &[
+                ("rank", &(idx + 1)),
+                ("member", &format!("<@{}>", m.user.id.get())),
+                ("since", &format!("<t:{since}:D>")),
+                ("duration", &format_duration(Duration::seconds(now - since))),
+            ],
+        ));
+    }
+
+    let base_title = t("stats.boosters.title", lang, &[("count", &boosters.len())]);
+    let base_title_cont = base_title.clone();
+
+    send_chunked_embeds(
+        ctx,
+        lines,
+        ephemeral,
+        move |desc| {
+            serenity::CreateEmbed::new()
+                .title(base_title.clone())
+                .description(desc)
+        },
+        move |idx, desc| {
+            serenity::CreateEmbed::new()
+                .title(t("stats.cont", lang, &[("title", &base_title_cont), ("n", &idx)]))
+                .description(desc)
+        },
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Rows fetched per `all_exits` call in `/stats exits`.
+const EXITS_PAGE_SIZE: i64 = 500;
+
+/// Recent exits with left vs kicked vs banned split.
 #[poise::command(slash_command, guild_only, rename = "exits")]
 pub async fn stats_exits(
     ctx: Ctx<'_>,
     #[description = "Look back this many days (default 30)"] days: Option<i64>,
     #[description = "Max rows shown (default 20)"] show: Option<i64>,
+    #[description = "Only show the result to you"] ephemeral: Option<bool>,
 ) -> Result<()> {
-    use chrono::{DateTime, Duration, Utc};
+    use chrono::{Duration, Utc};
 
     let gid = ctx
         .guild_id()
         .expect("guild_only command should always have a guild_id");
+    let lang = guild_lang(ctx).await;
+    let ephemeral = ephemeral.unwrap_or(false);
 
     let days = days.unwrap_or(30).clamp(1, 365);
     let show = show.unwrap_or(20).clamp(1, 100);
 
-    // Pull a safety window: get up to 2k exits and filter in Rust by timestamp
-    let repo = MembershipsRepo::new(&ctx.data().db);
-    let rows = repo.all_exits(gid, 2000).await?;
-
-    let now = Utc::now();
-    let cutoff = now - Duration::days(days);
+    // The window is applied in SQL; page through it so big servers aren't capped.
+    let repo = ctx.data().memberships();
+    let window = TimeWindow::since(Utc::now() - Duration::days(days));
 
     let mut filtered = Vec::new();
     let mut left_count = 0usize;
+    let mut kicked_count = 0usize;
     let mut banned_count = 0usize;
 
-    for r in rows {
-        // Parse RFC2822
-        if let Ok(dt) = DateTime::parse_from_rfc2822(&r.left_at) {
-            let dt_utc = dt.with_timezone(&Utc);
-            if dt_utc >= cutoff {
-                if r.banned {
-                    banned_count += 1;
-                } else {
-                    left_count += 1;
-                }
-                filtered.push((dt_utc, r));
+    let mut cursor = None;
+    loop {
+        let page = repo.all_exits(gid, window, cursor, EXITS_PAGE_SIZE).await?;
+        for r in page.rows {
+            match r.leave_kind {
+                LeaveKind::Left => left_count += 1,
+                LeaveKind::Kicked => kicked_count += 1,
+                LeaveKind::Banned => banned_count += 1,
             }
+            filtered.push(r);
+        }
+        match page.next_cursor {
+            Some(next) => cursor = Some(next),
+            None => break,
         }
     }
 
     if filtered.is_empty() {
-        ctx.say(format!("No exits in the last {} days.", days))
-            .await?;
+        ctx.send(
+            reply(ephemeral).content(t("stats.exits.none", lang, &[("days", &days)])),
+        )
+        .await?;
         return Ok(());
     }
 
     // Sort newest first
-    filtered.sort_by_key(|(t, _)| *t);
-    filtered.reverse();
+    filtered.sort_by_key(|r| std::cmp::Reverse(r.left_at));
 
-    let total = left_count + banned_count;
+    let total = left_count + kicked_count + banned_count;
     let mut lines = Vec::new();
-    lines.push(format!(
-        "**Total:** {} (left: {}, banned: {})",
-        total, left_count, banned_count
+    lines.push(t(
+        "stats.exits.total",
+        lang,
+        &[
+            ("total", &total),
+            ("left", &left_count),
+            ("kicked", &kicked_count),
+            ("banned", &banned_count),
+        ],
     ));
     lines.push("".into());
 
-    for (_, r) in filtered.iter().take(show as usize) {
-        let label = format_member_label(&r.user_id, &r.account_username, &r.server_username);
+    for r in filtered.iter().take(show as usize) {
+        let label =
+            format_member_label(&r.user_id, &r.account_username, &r.server_username, lang);
 
-        // Discord timestamp token
-        let ts = if let Ok(dt) = chrono::DateTime::parse_from_rfc2822(&r.left_at) {
-            format!("<t:{}:R>", dt.timestamp())
-        } else {
-            r.left_at.clone()
-        };
+        let ts = r.left_at.discord('R');
 
-        let kind = if r.banned { "**banned**" } else { "left" };
+        let kind = match r.leave_kind {
+            LeaveKind::Left => t("stats.exits.left", lang, &[]),
+            LeaveKind::Kicked => t("stats.exits.kicked", lang, &[]),
+            LeaveKind::Banned => t(
+                "stats.exits.banned",
+                lang,
+                &[("reason", &ban_reason_suffix(r.ban_reason.as_deref(), lang))],
+            ),
+        };
         lines.push(format!("• {label} — {kind} — {ts}"));
     }
 
-    let base_title = format!("Exits in last {} days", days);
+    let base_title = t("stats.exits.title", lang, &[("days", &days)]);
     let base_title_cont = base_title.clone();
 
     send_chunked_embeds(
         ctx,
         lines,
+        ephemeral,
         move |desc| {
             serenity::CreateEmbed::new()
                 .title(base_title.clone())
@@ -176,7 +774,129 @@ pub async fn stats_exits(
         },
         move |idx, desc| {
             serenity::CreateEmbed::new()
-                .title(format!("{base_title_cont} — cont. #{idx}"))
+                .title(t("stats.cont", lang, &[("title", &base_title_cont), ("n", &idx)]))
+                .description(desc)
+        },
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Banned exits per day/week/month and their share of all exits, oldest bucket first.
+#[poise::command(slash_command, guild_only, rename = "ban-rate")]
+pub async fn stats_ban_rate(
+    ctx: Ctx<'_>,
+    #[description = "Look back this many days (default 90)"] days: Option<i64>,
+    #[description = "Bucket rows by day, week or month (default week)"] granularity: Option<
+        Granularity,
+    >,
+    #[description = "Only show the result to you"] ephemeral: Option<bool>,
+) -> Result<()> {
+    use chrono::{Duration, NaiveDate, Utc};
+    use std::collections::BTreeMap;
+
+    let gid = ctx
+        .guild_id()
+        .expect("guild_only command should always have a guild_id");
+    let lang = guild_lang(ctx).await;
+    let ephemeral = ephemeral.unwrap_or(false);
+
+    let days = days.unwrap_or(90).clamp(1, 365);
+    let granularity = granularity.unwrap_or(Granularity::Week);
+    let tz = ctx.data().settings_repo().get_cached(&gid).await?.tz();
+
+    let repo = ctx.data().memberships();
+    let window = TimeWindow::since(Utc::now() - Duration::days(days));
+
+    // bucket start -> (banned, all exits)
+    let mut buckets: BTreeMap<NaiveDate, (i64, i64)> = BTreeMap::new();
+    let mut cursor = None;
+    loop {
+        let page = repo.all_exits(gid, window, cursor, EXITS_PAGE_SIZE).await?;
+        for r in page.rows {
+            let day = r.left_at.to_datetime().with_timezone(&tz).date_naive();
+            let e = buckets.entry(granularity.bucket_start(day)).or_default();
+            e.1 += 1;
+            if r.leave_kind == LeaveKind::Banned {
+                e.0 += 1;
+            }
+        }
+        match page.next_cursor {
+            Some(next) => cursor = Some(next),
+            None => break,
+        }
+    }
+
+    if buckets.is_empty() {
+        ctx.send(
+            reply(ephemeral).content(t("stats.exits.none", lang, &[("days", &days)])),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let ratio = |banned: i64, total: i64| {
+        if total == 0 {
+            "—".to_string()
+        } else {
+            format!("{}%", banned * 100 / total)
+        }
+    };
+
+    let (banned_total, exits_total) = buckets
+        .values()
+        .fold((0, 0), |(b, t), (bb, tt)| (b + bb, t + tt));
+
+    let mut lines = Vec::new();
+    lines.push(t(
+        "stats.ban_rate.window",
+        lang,
+        &[
+            ("days", &days),
+            ("banned", &banned_total),
+            ("total", &exits_total),
+            ("ratio", &ratio(banned_total, exits_total)),
+        ],
+    ));
+    lines.push(String::new());
+
+    for (start, (banned, total)) in &buckets {
+        lines.push(t(
+            "stats.ban_rate.line",
+            lang,
+            &[
+                ("bucket", &granularity.bucket_label(*start, lang)),
+                ("banned", banned),
+                ("total", total),
+                ("ratio", &ratio(*banned, *total)),
+            ],
+        ));
+    }
+
+    let base_title = t(
+        "stats.ban_rate.title",
+        lang,
+        &[
+            ("days", &days),
+            ("granularity", &granularity.noun(lang)),
+            ("tz", &tz.name()),
+        ],
+    );
+    let base_title_cont = base_title.clone();
+
+    send_chunked_embeds(
+        ctx,
+        lines,
+        ephemeral,
+        move |desc| {
+            serenity::CreateEmbed::new()
+                .title(base_title.clone())
+                .description(desc)
+        },
+        move |idx, desc| {
+            serenity::CreateEmbed::new()
+                .title(t("stats.cont", lang, &[("title", &base_title_cont), ("n", &idx)]))
                 .description(desc)
         },
     )
@@ -187,131 +907,250 @@ pub async fn stats_exits(
 
 /// Snapshot counts: current members, lifetime uniques, exits, bans, server stays.
 #[poise::command(slash_command, guild_only, rename = "current")]
-pub async fn stats_current(ctx: Ctx<'_>) -> Result<()> {
+pub async fn stats_current(
+    ctx: Ctx<'_>,
+    #[description = "Only show the result to you"] ephemeral: Option<bool>,
+) -> Result<()> {
     let gid = ctx
         .guild_id()
         .expect("guild_only command should always have a guild_id");
+    let lang = guild_lang(ctx).await;
+    let ephemeral = ephemeral.unwrap_or(false);
 
-    let repo = MembershipsRepo::new(&ctx.data().db);
+    let repo = ctx.data().memberships();
     let s = repo.stats_current(gid).await?;
 
+    let label = |key: &str| t(key, lang, &[]);
     let embed = serenity::CreateEmbed::new()
-        .title("Current stats")
+        .title(label("stats.current.title"))
         .field(
-            "Current members",
+            label("stats.current.members"),
             format!("**{}**", s.current_members),
             true,
         )
-        .field("Unique users ever", format!("{}", s.unique_ever), true)
-        .field("Total rejoins", format!("{}", s.total_rejoins), true)
-        .field("Total exits", format!("{}", s.total_exits), true)
-        .field("Banned (of exits)", format!("{}", s.total_banned), true)
+        .field(label("stats.current.unique_ever"), format!("{}", s.unique_ever), true)
+        .field(label("stats.current.stays"), format!("{}", s.total_stints), true)
+        .field(label("stats.current.rejoins"), format!("{}", s.total_rejoins), true)
+        .field(label("stats.current.exits"), format!("{}", s.total_exits), true)
+        .field(label("stats.current.banned"), format!("{}", s.total_banned), true)
+        .field(label("stats.current.kicked"), format!("{}", s.total_kicked), true)
         .field(
-            "Left (of exits)",
-            format!("{}", s.total_exits.saturating_sub(s.total_banned)),
+            label("stats.current.left"),
+            format!(
+                "{}",
+                s.total_exits
+                    .saturating_sub(s.total_banned)
+                    .saturating_sub(s.total_kicked)
+            ),
             true,
         );
 
-    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+    let embed = with_colour(embed, guild_colour(ctx).await);
+    ctx.send(reply(ephemeral).embed(embed)).await?;
     Ok(())
 }
 
-/// Daily net member delta (joins - leaves) with totals and unique users.
+/// Bucket size for the delta and ban-rate commands' rows.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum Granularity {
+    #[default]
+    #[name = "day"]
+    Day,
+    #[name = "week"]
+    Week,
+    #[name = "month"]
+    Month,
+}
+
+impl Granularity {
+    /// First calendar day of the bucket containing `d` (weeks start on Monday).
+    fn bucket_start(self, d: chrono::NaiveDate) -> chrono::NaiveDate {
+        use chrono::Datelike;
+        match self {
+            Granularity::Day => d,
+            Granularity::Week => {
+                d - chrono::Duration::days(d.weekday().num_days_from_monday() as i64)
+            }
+            Granularity::Month => d.with_day(1).unwrap_or(d),
+        }
+    }
+
+    fn bucket_label(self, start: chrono::NaiveDate, lang: &str) -> String {
+        match self {
+            Granularity::Day => start.format("%Y-%m-%d").to_string(),
+            Granularity::Week => t(
+                "stats.granularity.week_label",
+                lang,
+                &[("date", &start.format("%Y-%m-%d"))],
+            ),
+            Granularity::Month => start.format("%Y-%m").to_string(),
+        }
+    }
+
+    fn noun(self, lang: &str) -> String {
+        let key = match self {
+            Granularity::Day => "stats.granularity.daily",
+            Granularity::Week => "stats.granularity.weekly",
+            Granularity::Month => "stats.granularity.monthly",
+        };
+        t(key, lang, &[])
+    }
+}
+
+/// Join/leave counts inside a window, leaves split by how they ended.
+/// Shared by `/stats delta`, `/stats compare` and the daily digest.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct WindowTally {
+    pub joins: i64,
+    pub left: i64,
+    pub kicked: i64,
+    pub banned: i64,
+}
+
+impl WindowTally {
+    pub fn leaves(&self) -> i64 {
+        self.left + self.kicked + self.banned
+    }
+
+    pub fn net(&self) -> i64 {
+        self.joins - self.leaves()
+    }
+}
+
+/// Tally joins (skipping inferred stints) and leaves at or after `cutoff`.
+pub fn tally_window(raw: &[RejoinTimes], cutoff: chrono::DateTime<chrono::Utc>) -> WindowTally {
+    tally_range(raw, cutoff, None)
+}
+
+/// Like `tally_window`, but only counting events before `until` (when given).
+pub fn tally_range(
+    raw: &[RejoinTimes],
+    from: chrono::DateTime<chrono::Utc>,
+    until: Option<chrono::DateTime<chrono::Utc>>,
+) -> WindowTally {
+    let from = UnixTime::from(from);
+    let until = until.map(UnixTime::from);
+    let in_range = |t: UnixTime| t >= from && until.is_none_or(|u| t < u);
+
+    let mut tally = WindowTally::default();
+    for item in raw {
+        if !item.inferred && in_range(item.joined_at) {
+            tally.joins += 1;
+        }
+        if item.left_at.is_some_and(in_range) {
+            match item.leave_kind {
+                LeaveKind::Left => tally.left += 1,
+                LeaveKind::Kicked => tally.kicked += 1,
+                LeaveKind::Banned => tally.banned += 1,
+            }
+        }
+    }
+    tally
+}
+
+/// Net member delta (joins - leaves) per day/week/month with totals and unique users.
 #[poise::command(slash_command, guild_only, rename = "delta")]
 pub async fn stats_member_balance(
     ctx: Ctx<'_>,
     #[description = "Days to look back (default 30)"] days: Option<i64>,
     #[description = "Max rows to scan (default 2000)"] cap: Option<i64>,
+    #[description = "Bucket rows by day, week or month (default day)"] granularity: Option<
+        Granularity,
+    >,
+    #[description = "Also attach a PNG chart of the net delta"] chart: Option<bool>,
+    #[description = "Only show the result to you"] ephemeral: Option<bool>,
 ) -> Result<()> {
-    use chrono::{DateTime, Duration, NaiveDate, Utc};
+    use chrono::{Duration, NaiveDate, Utc};
     use std::collections::{BTreeMap, BTreeSet};
 
     let gid = ctx
         .guild_id()
         .expect("guild_only command should always have a guild_id");
+    let lang = guild_lang(ctx).await;
+    let ephemeral = ephemeral.unwrap_or(false);
+
+    // Calendar buckets follow the guild's zone; the window cutoff is absolute.
+    let tz = ctx.data().settings_repo().get_cached(&gid).await?.tz();
 
     let days = days.unwrap_or(30).clamp(1, 365);
     let cap = cap.unwrap_or(2000).clamp(100, 100_000);
-
-    let repo = MembershipsRepo::new(&ctx.data().db);
-    let raw = repo.recent_rejoins_raw(gid, cap).await?;
+    let granularity = granularity.unwrap_or_default();
 
     let cutoff = Utc::now() - Duration::days(days);
 
-    // Per-day tallies
+    let repo = ctx.data().memberships();
+    let raw = repo.activity_since(gid, cutoff.into(), cap).await?;
+
+    // Per-bucket tallies
+    #[derive(Default)]
     struct Tallies {
         total: i64,
         uniq: BTreeSet<String>,
     }
-    impl Default for Tallies {
-        fn default() -> Self {
-            Self {
-                total: 0,
-                uniq: BTreeSet::new(),
-            }
-        }
-    }
 
     let mut joins: BTreeMap<NaiveDate, Tallies> = BTreeMap::new();
     let mut leaves: BTreeMap<NaiveDate, Tallies> = BTreeMap::new();
 
-    for item in raw {
-        // joins
-        if let Ok(jdt) = DateTime::parse_from_rfc2822(&item.joined_at) {
-            let jutc = jdt.with_timezone(&Utc);
-            if jutc >= cutoff {
-                let d = jutc.date_naive();
-                let e = joins.entry(d).or_default();
-                e.total += 1;
-                e.uniq.insert(item.user_id.clone());
-            }
+    for item in &raw {
+        // joins (inferred stints have no real join time)
+        let jutc = item.joined_at.to_datetime();
+        if !item.inferred && jutc >= cutoff {
+            let d = granularity.bucket_start(jutc.with_timezone(&tz).date_naive());
+            let e = joins.entry(d).or_default();
+            e.total += 1;
+            e.uniq.insert(item.user_id.clone());
         }
         // leaves
-        if let Some(left) = &item.left_at {
-            if let Ok(ldt) = DateTime::parse_from_rfc2822(left) {
-                let lutc = ldt.with_timezone(&Utc);
-                if lutc >= cutoff {
-                    let d = lutc.date_naive();
-                    let e = leaves.entry(d).or_default();
-                    e.total += 1;
-                    e.uniq.insert(item.user_id.clone());
-                }
-            }
+        if let Some(lutc) = item.left_at.map(UnixTime::to_datetime)
+            && lutc >= cutoff
+        {
+            let d = granularity.bucket_start(lutc.with_timezone(&tz).date_naive());
+            let e = leaves.entry(d).or_default();
+            e.total += 1;
+            e.uniq.insert(item.user_id.clone());
         }
     }
 
-    // union of all days present
-    let all_days: BTreeSet<_> = joins.keys().chain(leaves.keys()).copied().collect();
-    if all_days.is_empty() {
-        ctx.say(format!("No join/leave activity in the last {} days.", days))
-            .await?;
+    // union of all buckets present
+    let all_buckets: BTreeSet<_> = joins.keys().chain(leaves.keys()).copied().collect();
+    if all_buckets.is_empty() {
+        ctx.send(
+            reply(ephemeral).content(t("stats.delta.none", lang, &[("days", &days)])),
+        )
+        .await?;
         return Ok(());
     }
 
-    // header totals (window-wide)
-    let (mut j_total, mut j_uniq_all) = (0i64, BTreeSet::<String>::new());
-    let (mut l_total, mut l_uniq_all) = (0i64, BTreeSet::<String>::new());
-
-    for (_d, t) in &joins {
-        j_total += t.total;
+    // header totals (window-wide, independent of granularity)
+    let window = tally_window(&raw, cutoff);
+    let (j_total, l_total, net_total) = (window.joins, window.leaves(), window.net());
+    let mut j_uniq_all = BTreeSet::<String>::new();
+    let mut l_uniq_all = BTreeSet::<String>::new();
+    for t in joins.values() {
         j_uniq_all.extend(t.uniq.iter().cloned());
     }
-    for (_d, t) in &leaves {
-        l_total += t.total;
+    for t in leaves.values() {
         l_uniq_all.extend(t.uniq.iter().cloned());
     }
 
-    let net_total = j_total - l_total;
-
-    // lines per day (chronological)
+    // lines per bucket (chronological)
     let mut lines = Vec::new();
-    lines.push(format!(
-        "**Window totals ({} days):**  net {:+}  |  joins: {} ({} unique)  |  leaves: {} ({} unique)",
-        days, net_total, j_total, j_uniq_all.len(), l_total, l_uniq_all.len()
+    lines.push(t(
+        "stats.delta.window",
+        lang,
+        &[
+            ("days", &days),
+            ("net", &format!("{net_total:+}")),
+            ("joins", &j_total),
+            ("joins_unique", &j_uniq_all.len()),
+            ("leaves", &l_total),
+            ("leaves_unique", &l_uniq_all.len()),
+        ],
     ));
     lines.push("".into());
 
-    for d in all_days {
+    let mut series = Vec::with_capacity(all_buckets.len());
+    for d in all_buckets {
         let j = joins.get(&d);
         let l = leaves.get(&d);
 
@@ -320,6 +1159,7 @@ pub async fn stats_member_balance(
         let lt = l.map(|x| x.total).unwrap_or(0);
         let lu = l.map(|x| x.uniq.len()).unwrap_or(0);
         let net = jt - lt;
+        series.push((d, net));
 
         let sign = if net > 0 {
             "+"
@@ -328,22 +1168,274 @@ pub async fn stats_member_balance(
         } else {
             " "
         };
-        lines.push(format!(
-            "{d}  {sign}{:>2}  (joins: {} / {} unique,  leaves: {} / {} unique)",
-            net.abs(),
-            jt,
-            ju,
-            lt,
-            lu
+        lines.push(t(
+            "stats.delta.line",
+            lang,
+            &[
+                ("bucket", &granularity.bucket_label(d, lang)),
+                ("net", &format!("{sign}{:>2}", net.abs())),
+                ("joins", &jt),
+                ("joins_unique", &ju),
+                ("leaves", &lt),
+                ("leaves_unique", &lu),
+            ],
+        ));
+    }
+
+    let base_title = t(
+        "stats.delta.title",
+        lang,
+        &[
+            ("days", &days),
+            ("granularity", &granularity.noun(lang)),
+            ("tz", &tz.name()),
+        ],
+    );
+    let base_title_cont = base_title.clone();
+
+    send_chunked_embeds(
+        ctx,
+        lines,
+        ephemeral,
+        move |desc| {
+            serenity::CreateEmbed::new()
+                .title(base_title.clone())
+                .description(desc)
+        },
+        move |idx, desc| {
+            serenity::CreateEmbed::new()
+                .title(t("stats.cont", lang, &[("title", &base_title_cont), ("n", &idx)]))
+                .description(desc)
+        },
+    )
+    .await?;
+
+    if chart.unwrap_or(false) {
+        send_delta_chart(ctx, &series, granularity, lang, ephemeral).await?;
+    }
+
+    Ok(())
+}
+
+/// Joins, leaves and net for the last N days against the N days before.
+#[poise::command(slash_command, guild_only, rename = "compare")]
+pub async fn stats_compare(
+    ctx: Ctx<'_>,
+    #[description = "Length of each window in days (default 7)"] days: Option<i64>,
+    #[description = "Only show the result to you"] ephemeral: Option<bool>,
+) -> Result<()> {
+    use chrono::{Duration, Utc};
+
+    let gid = ctx
+        .guild_id()
+        .expect("guild_only command should always have a guild_id");
+    let lang = guild_lang(ctx).await;
+    let ephemeral = ephemeral.unwrap_or(false);
+
+    let days = days.unwrap_or(7).clamp(1, 180);
+    let now = Utc::now();
+    let split = now - Duration::days(days);
+    let start = split - Duration::days(days);
+
+    let repo = ctx.data().memberships();
+    let raw = repo.activity_since(gid, start.into(), 100_000).await?;
+    let current = tally_range(&raw, split, None);
+    let previous = tally_range(&raw, start, Some(split));
+
+    let mut embed = serenity::CreateEmbed::new().title(t(
+        "stats.compare.title",
+        lang,
+        &[("days", &days)],
+    ));
+    for (key, cur, prev) in [
+        ("stats.compare.joins", current.joins, previous.joins),
+        ("stats.compare.leaves", current.leaves(), previous.leaves()),
+        ("stats.compare.net", current.net(), previous.net()),
+    ] {
+        let value = t(
+            "stats.compare.value",
+            lang,
+            &[
+                ("current", &cur),
+                ("previous", &prev),
+                ("change", &format!("{:+}", cur - prev)),
+                ("pct", &percent_change(prev, cur, lang)),
+            ],
+        );
+        embed = embed.field(t(key, lang, &[]), value, false);
+    }
+
+    let embed = with_colour(embed, guild_colour(ctx).await);
+    ctx.send(reply(ephemeral).embed(embed)).await?;
+    Ok(())
+}
+
+/// Relative change from `prev` to `cur`. A zero baseline has no percentage:
+/// "n/a" when nothing changed, otherwise ±∞.
+fn percent_change(prev: i64, cur: i64, lang: &str) -> String {
+    if prev == 0 {
+        return match cur.signum() {
+            0 => t("stats.compare.na", lang, &[]),
+            1 => "+∞".to_string(),
+            _ => "−∞".to_string(),
+        };
+    }
+    // Relative to |prev| so a net that went from −10 to −5 reads as an improvement.
+    let pct = (cur - prev) as f64 / prev.abs() as f64 * 100.0;
+    format!("{pct:+.1}%")
+}
+
+/// Follow-up reply with the delta series as a PNG line chart.
+#[cfg(feature = "charts")]
+async fn send_delta_chart(
+    ctx: Ctx<'_>,
+    series: &[(chrono::NaiveDate, i64)],
+    granularity: Granularity,
+    lang: &str,
+    ephemeral: bool,
+) -> Result<()> {
+    let (Some((first, _)), Some((last, _))) = (series.first(), series.last()) else {
+        return Ok(());
+    };
+    let png = crate::charts::render_delta_chart(series)?;
+    ctx.send(
+        reply(ephemeral)
+            .content(t(
+                "stats.delta.chart_caption",
+                lang,
+                &[
+                    ("from", &granularity.bucket_label(*first, lang)),
+                    ("to", &granularity.bucket_label(*last, lang)),
+                ],
+            ))
+            .attachment(serenity::CreateAttachment::bytes(png, "member-delta.png")),
+    )
+    .await?;
+    Ok(())
+}
+
+#[cfg(not(feature = "charts"))]
+async fn send_delta_chart(
+    ctx: Ctx<'_>,
+    _series: &[(chrono::NaiveDate, i64)],
+    _granularity: Granularity,
+    lang: &str,
+    ephemeral: bool,
+) -> Result<()> {
+    ctx.send(reply(ephemeral).content(t("stats.charts_unavailable", lang, &[]))).await?;
+    Ok(())
+}
+
+/// Survival checkpoints for `/stats retention` (days after first join).
+const RETENTION_HORIZONS: [i64; 3] = [1, 7, 30];
+
+/// Weekly join cohorts and the share still present 1/7/30 days after their first join.
+#[poise::command(slash_command, guild_only, rename = "retention")]
+pub async fn stats_retention(
+    ctx: Ctx<'_>,
+    #[description = "How many recent weekly cohorts to show (default 12)"]
+    #[min = 1]
+    #[max = 52]
+    weeks: Option<i64>,
+    #[description = "Only show the result to you"] ephemeral: Option<bool>,
+) -> Result<()> {
+    use chrono::{Duration, NaiveDate, Utc};
+    use std::collections::BTreeMap;
+
+    let gid = ctx
+        .guild_id()
+        .expect("guild_only command should always have a guild_id");
+    let lang = guild_lang(ctx).await;
+    let ephemeral = ephemeral.unwrap_or(false);
+
+    let weeks = weeks.unwrap_or(12).clamp(1, 52);
+    let tz = ctx.data().settings_repo().get_cached(&gid).await?.tz();
+
+    let repo = ctx.data().memberships();
+    let stints = repo.retention_cohorts(gid).await?;
+
+    let now = Utc::now();
+    let oldest = now - Duration::weeks(weeks);
+
+    // cohort week -> (members, per-horizon (eligible, surviving))
+    #[derive(Default)]
+    struct Cohort {
+        members: usize,
+        horizons: [(usize, usize); RETENTION_HORIZONS.len()],
+    }
+    let mut cohorts: BTreeMap<NaiveDate, Cohort> = BTreeMap::new();
+
+    for s in stints {
+        let joined = s.joined_at.to_datetime();
+        if joined < oldest {
+            continue;
+        }
+        // Still-present members have no left_at and count as surviving.
+        let left = s.left_at.map(UnixTime::to_datetime);
+
+        let week = Granularity::Week.bucket_start(joined.with_timezone(&tz).date_naive());
+        let cohort = cohorts.entry(week).or_default();
+        cohort.members += 1;
+
+        for (i, days) in RETENTION_HORIZONS.iter().enumerate() {
+            let checkpoint = joined + Duration::days(*days);
+            if checkpoint > now {
+                continue; // too recent to judge at this horizon
+            }
+            cohort.horizons[i].0 += 1;
+            if left.is_none_or(|l| l >= checkpoint) {
+                cohort.horizons[i].1 += 1;
+            }
+        }
+    }
+
+    if cohorts.is_empty() {
+        ctx.send(
+            reply(ephemeral).content(t("stats.retention.none", lang, &[("weeks", &weeks)])),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let mut lines = Vec::with_capacity(cohorts.len() + 2);
+    let horizons = RETENTION_HORIZONS
+        .iter()
+        .map(|d| format!("{d}d"))
+        .collect::<Vec<_>>()
+        .join(" / ");
+    lines.push(t("stats.retention.intro", lang, &[("horizons", &horizons)]));
+    lines.push(String::new());
+
+    for (week, c) in cohorts.iter().rev() {
+        let pcts: Vec<String> = RETENTION_HORIZONS
+            .iter()
+            .zip(c.horizons.iter())
+            .map(|(days, (eligible, surviving))| {
+                if *eligible == 0 {
+                    format!("{days}d: —")
+                } else {
+                    format!("{days}d: {}%", surviving * 100 / eligible)
+                }
+            })
+            .collect();
+        lines.push(t(
+            "stats.retention.line",
+            lang,
+            &[
+                ("week", &Granularity::Week.bucket_label(*week, lang)),
+                ("joined", &c.members),
+                ("shares", &pcts.join(", ")),
+            ],
         ));
     }
 
-    let base_title = format!("Member balance (last {} days)", days);
+    let base_title = t("stats.retention.title", lang, &[("tz", &tz.name())]);
     let base_title_cont = base_title.clone();
 
     send_chunked_embeds(
         ctx,
         lines,
+        ephemeral,
         move |desc| {
             serenity::CreateEmbed::new()
                 .title(base_title.clone())
@@ -351,7 +1443,7 @@ pub async fn stats_member_balance(
         },
         move |idx, desc| {
             serenity::CreateEmbed::new()
-                .title(format!("{base_title_cont} — cont. #{idx}"))
+                .title(t("stats.cont", lang, &[("title", &base_title_cont), ("n", &idx)]))
                 .description(desc)
         },
     )
@@ -359,3 +1451,300 @@ pub async fn stats_member_balance(
 
     Ok(())
 }
+
+/// Mean, median and p90 length of completed stays (join → leave).
+#[poise::command(slash_command, guild_only, rename = "duration")]
+pub async fn stats_duration(
+    ctx: Ctx<'_>,
+    #[description = "Only show the result to you"] ephemeral: Option<bool>,
+) -> Result<()> {
+    let gid = ctx
+        .guild_id()
+        .expect("guild_only command should always have a guild_id");
+    let lang = guild_lang(ctx).await;
+    let ephemeral = ephemeral.unwrap_or(false);
+
+    let repo = ctx.data().memberships();
+    let mut durations = repo.completed_stint_durations(gid).await?;
+    let open = repo.open_stint_count(gid).await?;
+
+    if durations.is_empty() {
+        ctx.send(reply(ephemeral).content(t("stats.duration.none", lang, &[]))).await?;
+        return Ok(());
+    }
+
+    durations.sort();
+    let n = durations.len();
+    let total_secs: i64 = durations.iter().map(|d| d.num_seconds()).sum();
+    let mean = chrono::Duration::seconds(total_secs / n as i64);
+    let median = if n % 2 == 0 {
+        (durations[n / 2 - 1] + durations[n / 2]) / 2
+    } else {
+        durations[n / 2]
+    };
+    // nearest-rank percentile
+    let p90 = durations[(n * 90).div_ceil(100).saturating_sub(1)];
+
+    let embed = serenity::CreateEmbed::new()
+        .title(t("stats.duration.title", lang, &[]))
+        .field(t("stats.duration.mean", lang, &[]), format_duration(mean), true)
+        .field(t("stats.duration.median", lang, &[]), format_duration(median), true)
+        .field("p90", format_duration(p90), true)
+        .field(t("stats.duration.completed", lang, &[]), n.to_string(), true)
+        .footer(serenity::CreateEmbedFooter::new(t(
+            "stats.duration.open_excluded",
+            lang,
+            &[("open", &open)],
+        )));
+
+    let embed = with_colour(embed, guild_colour(ctx).await);
+    ctx.send(reply(ephemeral).embed(embed)).await?;
+    Ok(())
+}
+
+/// Heatmap shades from low to high (empty slots render as `·`).
+const HEATMAP_SHADES: [char; 4] = ['░', '▒', '▓', '█'];
+
+/// When people join: day-of-week × hour grid in the guild's timezone.
+#[poise::command(slash_command, guild_only, rename = "activity-heatmap")]
+pub async fn stats_activity_heatmap(
+    ctx: Ctx<'_>,
+    #[description = "Days to look back (default 90)"] days: Option<i64>,
+    #[description = "Only show the result to you"] ephemeral: Option<bool>,
+) -> Result<()> {
+    use chrono::{Datelike, Duration, Timelike, Utc};
+
+    let gid = ctx
+        .guild_id()
+        .expect("guild_only command should always have a guild_id");
+    let lang = guild_lang(ctx).await;
+    let ephemeral = ephemeral.unwrap_or(false);
+
+    let days = days.unwrap_or(90).clamp(1, 365);
+    let tz = ctx.data().settings_repo().get_cached(&gid).await?.tz();
+
+    let repo = ctx.data().memberships();
+    let cutoff = Utc::now() - Duration::days(days);
+    let joins = repo.recent_joins_since(gid, cutoff.into(), 20_000).await?;
+
+    let mut grid = [[0u32; 24]; 7];
+    let mut total = 0u32;
+    for joined in &joins {
+        let local = joined.to_datetime().with_timezone(&tz);
+        grid[local.weekday().num_days_from_monday() as usize][local.hour() as usize] += 1;
+        total += 1;
+    }
+
+    if total == 0 {
+        ctx.send(
+            reply(ephemeral).content(t("stats.heatmap.none", lang, &[("days", &days)])),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let max = grid.iter().flatten().copied().max().unwrap_or(1).max(1);
+    let shade = |n: u32| -> char {
+        if n == 0 {
+            '·'
+        } else {
+            // 1..=max mapped onto the 4 shades
+            let idx = ((n * HEATMAP_SHADES.len() as u32).div_ceil(max)) as usize - 1;
+            HEATMAP_SHADES[idx.min(HEATMAP_SHADES.len() - 1)]
+        }
+    };
+
+    let mut grid_text = String::from("    0     6     12    18   \n");
+    let weekdays = t("stats.heatmap.weekdays", lang, &[]);
+    for (day, label) in weekdays.split(',').take(7).enumerate() {
+        // Fixed-width row labels keep the grid aligned in the code block.
+        grid_text.extend(label.trim().chars().chain(std::iter::repeat(' ')).take(3));
+        grid_text.push(' ');
+        grid_text.extend(grid[day].iter().map(|n| shade(*n)));
+        grid_text.push('\n');
+    }
+
+    let embed = serenity::CreateEmbed::new()
+        .title(t(
+            "stats.heatmap.title",
+            lang,
+            &[("days", &days), ("tz", &tz.name())],
+        ))
+        .description(format!("```\n{grid_text}```"))
+        .footer(serenity::CreateEmbedFooter::new(t(
+            "stats.heatmap.footer",
+            lang,
+            &[
+                ("total", &total),
+                ("max", &max),
+                ("shade", &HEATMAP_SHADES[HEATMAP_SHADES.len() - 1]),
+            ],
+        )));
+
+    let embed = with_colour(embed, guild_colour(ctx).await);
+    ctx.send(reply(ephemeral).embed(embed)).await?;
+    Ok(())
+}
+
+/// Member count over time, from the daily snapshots.
+#[poise::command(slash_command, guild_only, rename = "growth")]
+pub async fn stats_growth(
+    ctx: Ctx<'_>,
+    #[description = "Days to look back (default 90)"] days: Option<i64>,
+    #[description = "Only show the result to you"] ephemeral: Option<bool>,
+) -> Result<()> {
+    use chrono::{Duration, Utc};
+
+    let gid = ctx
+        .guild_id()
+        .expect("guild_only command should always have a guild_id");
+    let lang = guild_lang(ctx).await;
+    let ephemeral = ephemeral.unwrap_or(false);
+
+    let days = days.unwrap_or(90).clamp(1, 3650);
+    let to = Utc::now().date_naive();
+    let from = to - Duration::days(days);
+
+    let repo = ctx.data().memberships();
+    let snapshots = repo.snapshots_in_range(gid, from, to).await?;
+    if snapshots.is_empty() {
+        ctx.send(reply(ephemeral).content(t("stats.growth.none", lang, &[]))).await?;
+        return Ok(());
+    }
+
+    let mut lines = Vec::with_capacity(snapshots.len() + 2);
+    let (first, last) = (snapshots[0].1, snapshots[snapshots.len() - 1].1);
+    lines.push(t(
+        "stats.growth.summary",
+        lang,
+        &[
+            ("first", &first),
+            ("last", &last),
+            ("change", &format!("{:+}", last - first)),
+            ("snapshots", &snapshots.len()),
+        ],
+    ));
+    lines.push(String::new());
+    // newest first, each with the change since the previous snapshot
+    let mut rows: Vec<String> = Vec::with_capacity(snapshots.len());
+    let mut prev: Option<i64> = None;
+    for (day, count) in &snapshots {
+        let change = prev.map(|p| format!(" ({:+})", count - p)).unwrap_or_default();
+        rows.push(format!("{} — {}{change}", day.format("%Y-%m-%d"), count));
+        prev = Some(*count);
+    }
+    lines.extend(rows.into_iter().rev());
+
+    let base_title = t("stats.growth.title", lang, &[("days", &days)]);
+    let base_title_cont = base_title.clone();
+
+    send_chunked_embeds(
+        ctx,
+        lines,
+        ephemeral,
+        move |desc| {
+            serenity::CreateEmbed::new()
+                .title(base_title.clone())
+                .description(desc)
+        },
+        move |idx, desc| {
+            serenity::CreateEmbed::new()
+                .title(t("stats.cont", lang, &[("title", &base_title_cont), ("n", &idx)]))
+                .description(desc)
+        },
+    )
+    .await?;
+
+    #[cfg(feature = "charts")]
+    if snapshots.len() > 1 {
+        let png = crate::charts::render_count_chart(&snapshots)?;
+        ctx.send(
+            reply(ephemeral)
+                .attachment(serenity::CreateAttachment::bytes(png, "member-growth.png")),
+        )
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Days covered by the `/stats export-image` sparkline.
+#[cfg(feature = "charts")]
+const DASHBOARD_DAYS: i64 = 30;
+/// Max stints read for the dashboard's daily deltas.
+#[cfg(feature = "charts")]
+const DASHBOARD_SCAN_CAP: i64 = 100_000;
+
+/// One shareable PNG: member count, 30-day net change and exit/ban ratios.
+#[poise::command(slash_command, guild_only, rename = "export-image")]
+pub async fn stats_export_image(
+    ctx: Ctx<'_>,
+    #[description = "Only show the result to you"] ephemeral: Option<bool>,
+) -> Result<()> {
+    let lang = guild_lang(ctx).await;
+    let ephemeral = ephemeral.unwrap_or(false);
+    send_dashboard(ctx, lang, ephemeral).await
+}
+
+#[cfg(feature = "charts")]
+async fn send_dashboard(ctx: Ctx<'_>, lang: &str, ephemeral: bool) -> Result<()> {
+    use crate::charts::{Dashboard, DashboardTile, render_dashboard};
+    use chrono::{Duration, Utc};
+
+    let gid = ctx
+        .guild_id()
+        .expect("guild_only command should always have a guild_id");
+    let title = ctx
+        .guild()
+        .map(|g| g.name.clone())
+        .unwrap_or_else(|| gid.to_string());
+
+    let repo = ctx.data().memberships();
+    let s = repo.stats_current(gid).await?;
+    let start = Utc::now() - Duration::days(DASHBOARD_DAYS);
+    let raw = repo.activity_since(gid, start.into(), DASHBOARD_SCAN_CAP).await?;
+    let spark: Vec<i64> = (0..DASHBOARD_DAYS)
+        .map(|i| {
+            let from = start + Duration::days(i);
+            tally_range(&raw, from, Some(from + Duration::days(1))).net()
+        })
+        .collect();
+    let net = tally_window(&raw, start).net();
+
+    let ratio = |part: i64, whole: i64| match whole {
+        0 => t("stats.compare.na", lang, &[]),
+        _ => format!("{:.1}%", part as f64 / whole as f64 * 100.0),
+    };
+    let tile = |key: &str, value: String| DashboardTile {
+        label: t(key, lang, &[]),
+        value,
+    };
+    let dash = Dashboard {
+        title,
+        tiles: vec![
+            tile("stats.export_image.members", s.current_members.to_string()),
+            tile("stats.export_image.exit_ratio", ratio(s.total_exits, s.total_stints)),
+            tile("stats.export_image.ban_ratio", ratio(s.total_banned, s.total_exits)),
+        ],
+        spark_label: t(
+            "stats.export_image.delta",
+            lang,
+            &[("days", &DASHBOARD_DAYS), ("net", &format!("{net:+}"))],
+        ),
+        spark,
+    };
+    let png = render_dashboard(&dash)?;
+    ctx.send(
+        reply(ephemeral)
+            .content(t("stats.export_image.caption", lang, &[]))
+            .attachment(serenity::CreateAttachment::bytes(png, "server-dashboard.png")),
+    )
+    .await?;
+    Ok(())
+}
+
+#[cfg(not(feature = "charts"))]
+async fn send_dashboard(ctx: Ctx<'_>, lang: &str, ephemeral: bool) -> Result<()> {
+    ctx.send(reply(ephemeral).content(t("stats.charts_unavailable", lang, &[]))).await?;
+    Ok(())
+}
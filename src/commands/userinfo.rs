@@ -1,53 +1,127 @@
 use anyhow::Result;
 use poise::serenity_prelude as serenity;
 
-use crate::commands::send_chunked_embeds;
-use crate::repos::MembershipsRepo;
+use crate::commands::{
+    ban_reason_suffix, format_duration, guild_colour, guild_lang, send_chunked_embeds,
+    with_colour,
+};
+use crate::repos::{LeaveKind, MemberNotesRepo, MembershipStore, NameKind, UnixTime};
 use crate::state::Ctx;
+use crate::strings::t;
 
-/// Slash + context menu for user info / history.
+/// Slash command for user info / history.
 ///
-/// - Slash: `/userinfo user:<pick member>`
-/// - Context menu: right click user → "User information"
-#[poise::command(
-    slash_command,
-    context_menu_command = "User information",
-    guild_only,
-    ephemeral
-)]
+/// - `/userinfo user:<pick member>`
+/// - `/userinfo user_id:<snowflake>` for people who left and can't be picked anymore
+#[poise::command(slash_command, guild_only, ephemeral)]
 pub async fn userinfo(
     ctx: Ctx<'_>,
-    #[description = "User to look up"] user: serenity::User,
+    #[description = "User to look up"] user: Option<serenity::User>,
+    #[description = "Raw user ID (for members who already left)"] user_id: Option<String>,
 ) -> Result<()> {
+    match (user, user_id) {
+        (Some(user), None) => show_userinfo(ctx, user.id, Some(&user)).await,
+        (None, Some(raw)) => {
+            let Some(uid) = parse_snowflake(&raw) else {
+                let lang = guild_lang(ctx).await;
+                ctx.say(t("userinfo.invalid_id", lang, &[("input", &raw)])).await?;
+                return Ok(());
+            };
+            // Still resolvable for most ex-members; fall back to the bare ID if not.
+            let fetched = uid.to_user(ctx).await.ok();
+            show_userinfo(ctx, uid, fetched.as_ref()).await
+        }
+        _ => {
+            let lang = guild_lang(ctx).await;
+            ctx.say(t("userinfo.pick_one", lang, &[])).await?;
+            Ok(())
+        }
+    }
+}
+
+/// Context menu: right click user → "User information".
+#[poise::command(context_menu_command = "User information", guild_only, ephemeral)]
+pub async fn userinfo_context(ctx: Ctx<'_>, user: serenity::User) -> Result<()> {
+    show_userinfo(ctx, user.id, Some(&user)).await
+}
+
+/// Accounts younger than this when they joined get flagged (common for raid alts).
+const NEW_ACCOUNT_DAYS: i64 = 7;
+
+/// "Created …" line for a user, from the snowflake timestamp, with a ⚠️ when the
+/// account was under `NEW_ACCOUNT_DAYS` old at `joined_at`.
+fn account_age_warning(
+    user_id: serenity::UserId,
+    joined_at: Option<chrono::DateTime<chrono::Utc>>,
+    lang: &str,
+) -> String {
+    let created = UnixTime(user_id.created_at().unix_timestamp());
+    let mut line = t(
+        "userinfo.created",
+        lang,
+        &[("relative", &created.discord('R')), ("date", &created.discord('D'))],
+    );
+
+    if let Some(joined) = joined_at {
+        let age_at_join = joined.timestamp() - created.0;
+        if age_at_join < NEW_ACCOUNT_DAYS * 24 * 60 * 60 {
+            let age = format_duration(chrono::Duration::seconds(age_at_join.max(0)));
+            line.push('\n');
+            line.push_str(&t("userinfo.young_at_join", lang, &[("age", &age)]));
+        }
+    }
+    line
+}
+
+/// Accept a bare snowflake or a pasted mention (`<@123>` / `<@!123>`).
+fn parse_snowflake(raw: &str) -> Option<serenity::UserId> {
+    let trimmed = raw
+        .trim()
+        .trim_start_matches("<@")
+        .trim_start_matches('!')
+        .trim_end_matches('>');
+    trimmed
+        .parse::<u64>()
+        .ok()
+        .filter(|id| *id != 0)
+        .map(serenity::UserId::new)
+}
+
+/// Render history/status/notes for `user_id`; `user` adds the tag and avatar when known.
+async fn show_userinfo(
+    ctx: Ctx<'_>,
+    user_id: serenity::UserId,
+    user: Option<&serenity::User>,
+) -> Result<()> {
+    let lang = guild_lang(ctx).await;
     let guild_id = match ctx.guild_id() {
         Some(gid) => gid,
         None => {
-            ctx.say("This command can only be used in a guild.").await?;
+            ctx.say(t("common.guild_only", lang, &[])).await?;
             return Ok(());
         }
     };
 
-    let mrepo = MembershipsRepo::new(&ctx.data().db);
-    let rows = mrepo.history_for_user(guild_id, user.id).await?;
+    let mrepo = ctx.data().memberships();
+    let rows = mrepo.history_for_user(guild_id, user_id).await?;
 
-    // Helper to format timestamps as Discord timestamps
-    let ts = |rfc2822: &str| -> String {
-        if let Ok(dt) = chrono::DateTime::parse_from_rfc2822(rfc2822) {
-            format!("<t:{}:f>", dt.timestamp())
-        } else {
-            rfc2822.to_string()
-        }
-    };
+    let ts = |at: UnixTime| at.discord('f');
 
-    let title = format!("History for {}", user.tag());
-    let thumb_url = user.face();
+    let title = match user {
+        Some(u) => t("userinfo.title", lang, &[("user", &u.tag())]),
+        None => t("userinfo.title_id", lang, &[("id", &user_id)]),
+    };
+    let thumb_url = user.map(|u| u.face());
 
     if rows.is_empty() {
-        let embed = serenity::CreateEmbed::new()
+        let mut embed = serenity::CreateEmbed::new()
             .title(title)
-            .thumbnail(thumb_url)
-            .description("No server stays recorded for this user.");
+            .description(t("userinfo.no_stays", lang, &[]));
+        if let Some(url) = thumb_url {
+            embed = embed.thumbnail(url);
+        }
 
+        let embed = with_colour(embed, guild_colour(ctx).await);
         ctx.send(poise::CreateReply::default().embed(embed)).await?;
         return Ok(());
     }
@@ -55,10 +129,49 @@ pub async fn userinfo(
     // Build history lines for all stays
     let mut lines: Vec<String> = Vec::with_capacity(rows.len() * 2);
     for r in &rows {
-        lines.push(format!("joined — {}", ts(&r.joined_at)));
-        if let Some(left_at) = r.left_at.as_deref() {
-            let action = if r.banned { "banned" } else { "left" };
-            lines.push(format!("{action} — {}", ts(left_at)));
+        if r.inferred {
+            // Only the leave was seen (bot offline at join time).
+            lines.push(t("userinfo.joined_untracked", lang, &[]));
+        } else {
+            lines.push(t("userinfo.joined", lang, &[("when", &ts(r.joined_at))]));
+        }
+        if let Some(left_at) = r.left_at {
+            let action = t(&format!("leave_kind.{}", r.leave_kind.verb()), lang, &[]);
+            let reason = if r.leave_kind == LeaveKind::Banned {
+                ban_reason_suffix(r.ban_reason.as_deref(), lang)
+            } else {
+                String::new()
+            };
+            lines.push(t(
+                "userinfo.exit",
+                lang,
+                &[("action", &action), ("when", &ts(left_at)), ("reason", &reason)],
+            ));
+        }
+        if let Some(unbanned_at) = r.unbanned_at {
+            lines.push(t("userinfo.unbanned", lang, &[("when", &ts(unbanned_at))]));
+        }
+    }
+
+    // Previous names (omitted entirely when nothing was recorded)
+    let names = mrepo.name_history_for_user(guild_id, user_id).await?;
+    if !names.is_empty() {
+        lines.push(String::new());
+        lines.push(t("userinfo.previous_names", lang, &[]));
+        let none = t("userinfo.no_name", lang, &[]);
+        for n in &names {
+            let what = if n.kind == NameKind::Account.as_str() {
+                "userinfo.name_change_username"
+            } else {
+                "userinfo.name_change_nickname"
+            };
+            let old = n.old_value.as_deref().unwrap_or(&none);
+            let new = n.new_value.as_deref().unwrap_or(&none);
+            lines.push(t(
+                what,
+                lang,
+                &[("old", &old), ("new", &new), ("when", &ts(n.changed_at))],
+            ));
         }
     }
 
@@ -67,16 +180,37 @@ pub async fn userinfo(
     let currently_in_guild = last.left_at.is_none();
 
     let status_line = if currently_in_guild {
-        format!(
-            "Currently **in** the server (last joined: {}).",
-            ts(&last.joined_at)
-        )
-    } else if let Some(left) = last.left_at.as_deref() {
-        format!("Last seen in server: {}.", ts(left))
+        t("userinfo.status_in", lang, &[("when", &ts(last.joined_at))])
+    } else if let Some(left) = last.left_at {
+        t("userinfo.status_gone", lang, &[("when", &ts(left))])
     } else {
-        "Status unknown.".to_string()
+        t("userinfo.status_unknown", lang, &[])
     };
 
+    // Account age relative to the latest join we actually saw
+    let last_join = rows
+        .iter()
+        .rev()
+        .find(|r| !r.inferred)
+        .map(|r| r.joined_at.to_datetime());
+    let account_age = account_age_warning(user_id, last_join, lang);
+
+    // Moderator notes: count + the latest one
+    let notes = MemberNotesRepo::new(&ctx.data().db)
+        .list_notes(guild_id, user_id)
+        .await?;
+    let notes_field = notes.last().map(|n| {
+        let author = format!("<@{}>", n.author_id);
+        (
+            t("userinfo.notes", lang, &[("count", &notes.len())]),
+            t(
+                "userinfo.latest_note",
+                lang,
+                &[("author", &author), ("when", &ts(n.created_at)), ("body", &n.body)],
+            ),
+        )
+    });
+
     let base_title = title.clone();
     let base_title_cont = base_title.clone();
     let thumb_url_first = thumb_url.clone();
@@ -87,17 +221,25 @@ pub async fn userinfo(
     send_chunked_embeds(
         ctx,
         lines,
+        false,
         move |desc| {
-            serenity::CreateEmbed::new()
+            let mut embed = serenity::CreateEmbed::new()
                 .title(base_title.clone())
-                .thumbnail(thumb_url_first.clone())
-                .field("Server stays", stay_count_first.clone(), true)
-                .field("Current status", status_line_first.clone(), false)
-                .description(desc)
+                .field(t("userinfo.stays", lang, &[]), stay_count_first.clone(), true)
+                .field(t("userinfo.status", lang, &[]), status_line_first.clone(), false)
+                .field(t("userinfo.account_age", lang, &[]), account_age, false)
+                .description(desc);
+            if let Some(url) = thumb_url_first {
+                embed = embed.thumbnail(url);
+            }
+            match notes_field {
+                Some((name, value)) => embed.field(name, value, false),
+                None => embed,
+            }
         },
         move |idx, desc| {
             serenity::CreateEmbed::new()
-                .title(format!("{base_title_cont} — cont. #{idx}"))
+                .title(t("stats.cont", lang, &[("title", &base_title_cont), ("n", &idx)]))
                 .description(desc)
         },
     )
@@ -1,24 +1,270 @@
+use std::str::FromStr;
+use std::time::Duration;
+
 use anyhow::Result;
-use sqlx::migrate::MigrateDatabase;
-use sqlx::{Pool, Sqlite, sqlite::SqlitePoolOptions};
+use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions};
+use sqlx::{Pool, Sqlite, Transaction};
 
 #[derive(Clone)]
 pub struct Db {
     pub pool: Pool<Sqlite>,
 }
 
+/// Pool/pragma settings for `Db::connect_with`.
+#[derive(Debug, Clone)]
+pub struct DbConfig {
+    pub max_connections: u32,
+    /// How long a connection waits on a locked database before failing.
+    pub busy_timeout: Duration,
+    /// WAL lets readers run alongside the single writer.
+    pub wal: bool,
+    pub foreign_keys: bool,
+}
+
+impl Default for DbConfig {
+    fn default() -> Self {
+        Self {
+            max_connections: 5,
+            busy_timeout: Duration::from_secs(5),
+            wal: true,
+            foreign_keys: true,
+        }
+    }
+}
+
+impl DbConfig {
+    /// Defaults, with `DB_MAX_CONNECTIONS` overriding the pool size.
+    pub fn from_env() -> Self {
+        let mut cfg = Self::default();
+        if let Ok(raw) = std::env::var("DB_MAX_CONNECTIONS") {
+            match raw.parse::<u32>() {
+                Ok(n) if n > 0 => cfg.max_connections = n,
+                _ => tracing::warn!("Ignoring invalid DB_MAX_CONNECTIONS={raw:?}"),
+            }
+        }
+        cfg
+    }
+}
+
 impl Db {
     pub async fn connect(db_url: &str) -> Result<Self> {
-        if !Sqlite::database_exists(db_url).await.unwrap_or(false) {
-            Sqlite::create_database(db_url).await?;
-        }
+        Self::connect_with(db_url, DbConfig::from_env()).await
+    }
+
+    pub async fn connect_with(db_url: &str, cfg: DbConfig) -> Result<Self> {
+        let journal_mode = if cfg.wal {
+            SqliteJournalMode::Wal
+        } else {
+            SqliteJournalMode::Delete
+        };
+        let options = SqliteConnectOptions::from_str(db_url)?
+            .create_if_missing(true)
+            .busy_timeout(cfg.busy_timeout)
+            .journal_mode(journal_mode)
+            .foreign_keys(cfg.foreign_keys);
+
         let pool = SqlitePoolOptions::new()
-            .max_connections(5)
-            .connect(db_url)
+            .max_connections(cfg.max_connections)
+            .connect_with(options)
             .await?;
 
+        // SQLite silently keeps the old mode when WAL isn't possible (e.g. in-memory DBs).
+        if cfg.wal {
+            let mode: String = sqlx::query_scalar("PRAGMA journal_mode")
+                .fetch_one(&pool)
+                .await?;
+            if !mode.eq_ignore_ascii_case("wal") {
+                tracing::warn!("Requested WAL journal mode but SQLite is using {mode:?}");
+            }
+        }
+
         // run migrations in your /migrations folder
         sqlx::migrate!().run(&pool).await?;
         Ok(Self { pool })
     }
+
+    /// Start a transaction for multi-step writes; dropped without `commit` = rolled back.
+    pub async fn transaction(&self) -> sqlx::Result<Transaction<'static, Sqlite>> {
+        self.pool.begin().await
+    }
+
+    /// Row counts, size and journal mode for `/admin stats-db`.
+    pub async fn diagnostics(&self) -> Result<DbDiagnostics> {
+        let membership_rows: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM memberships")
+            .fetch_one(&self.pool)
+            .await?;
+        let rows_per_guild: Vec<(String, i64)> = sqlx::query_as(
+            "SELECT guild_id, COUNT(*) AS n FROM memberships GROUP BY guild_id ORDER BY n DESC",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        // Fails when this SQLite build has no FTS5 (the table was never created).
+        let fts_rows: Option<i64> = sqlx::query_scalar("SELECT COUNT(*) FROM usernames_fts")
+            .fetch_one(&self.pool)
+            .await
+            .ok();
+        let journal_mode: String = sqlx::query_scalar("PRAGMA journal_mode")
+            .fetch_one(&self.pool)
+            .await?;
+
+        // `file` is empty for in-memory and temporary databases.
+        let (_, _, file): (i64, String, String) = sqlx::query_as("PRAGMA database_list")
+            .fetch_one(&self.pool)
+            .await?;
+        let file_size = (!file.is_empty())
+            .then(|| std::fs::metadata(&file).ok().map(|m| m.len()))
+            .flatten();
+
+        Ok(DbDiagnostics {
+            membership_rows,
+            rows_per_guild,
+            fts_rows,
+            file_size,
+            journal_mode,
+        })
+    }
+}
+
+/// Snapshot from `Db::diagnostics`.
+#[derive(Debug, Clone)]
+pub struct DbDiagnostics {
+    pub membership_rows: i64,
+    /// (guild_id, membership rows), largest first.
+    pub rows_per_guild: Vec<(String, i64)>,
+    /// `None` when the FTS table is unavailable.
+    pub fts_rows: Option<i64>,
+    /// Main database file size in bytes; `None` for in-memory or unreadable databases.
+    pub file_size: Option<u64>,
+    pub journal_mode: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use poise::serenity_prelude::UserId;
+
+    use super::Db;
+    use crate::repos::{MembershipsRepo, TimeWindow};
+    use crate::test_support::{GUILD, test_db};
+
+    /// `EXPLAIN QUERY PLAN` details for `sql`, one step per line.
+    async fn query_plan(db: &Db, sql: &str) -> String {
+        let rows: Vec<(i64, i64, i64, String)> =
+            sqlx::query_as(&format!("EXPLAIN QUERY PLAN {sql}"))
+                .fetch_all(&db.pool)
+                .await
+                .unwrap();
+        rows.into_iter().map(|(.., detail)| detail).collect::<Vec<_>>().join("\n")
+    }
+
+    #[tokio::test]
+    async fn transaction_dropped_after_a_failed_write_keeps_nothing() {
+        let db = test_db().await;
+        let mut tx = db.transaction().await.unwrap();
+        sqlx::query(
+            "INSERT INTO memberships (guild_id, user_id, joined_at) VALUES ('1', '10', 'x')",
+        )
+        .execute(&mut *tx)
+        .await
+        .unwrap();
+        // user_id is NOT NULL.
+        let second = sqlx::query(
+            "INSERT INTO memberships (guild_id, user_id, joined_at) VALUES ('1', NULL, 'x')",
+        )
+        .execute(&mut *tx)
+        .await;
+        assert!(second.is_err());
+        drop(tx);
+
+        let rows: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM memberships")
+            .fetch_one(&db.pool)
+            .await
+            .unwrap();
+        assert_eq!(rows, 0);
+    }
+
+    #[tokio::test]
+    async fn membership_lookups_use_their_indexes() {
+        let db = test_db().await;
+        for (sql, index) in [
+            // Latest stint per user (the `last` CTE) and a user's own stints.
+            (
+                "SELECT user_id, MAX(id) FROM memberships WHERE guild_id = ? GROUP BY user_id",
+                "COVERING INDEX idx_memberships_guild_user ",
+            ),
+            (
+                "SELECT id FROM memberships WHERE guild_id = ? AND user_id = ? \
+                 ORDER BY id DESC LIMIT 1",
+                "COVERING INDEX idx_memberships_guild_user ",
+            ),
+            // Open stints (`open_stint_user_ids`, `current_member_count`).
+            (
+                "SELECT COUNT(DISTINCT user_id) FROM memberships \
+                 WHERE guild_id = ? AND left_at IS NULL",
+                "INDEX idx_memberships_guild_left ",
+            ),
+            // Exit windows (`all_exits`, `prune_before`).
+            (
+                "SELECT id FROM memberships \
+                 WHERE guild_id = ? AND left_at_epoch IS NOT NULL AND left_at_epoch < ?",
+                "INDEX idx_memberships_guild_left_epoch ",
+            ),
+        ] {
+            let plan = query_plan(&db, sql).await;
+            assert!(plan.contains(index), "{sql}\n→ {plan}");
+        }
+    }
+
+    /// 50k rows across 10k users; the hot membership reads must stay interactive.
+    /// Timing-dependent, so run on demand: `cargo test -- --ignored`.
+    #[tokio::test]
+    #[ignore]
+    async fn membership_lookups_stay_fast_at_50k_rows() {
+        const LIMIT: Duration = Duration::from_millis(250);
+        let db = test_db().await;
+        // Five stints per user; all but the last one closed.
+        sqlx::query(
+            r#"
+            WITH RECURSIVE n(i) AS (SELECT 0 UNION ALL SELECT i + 1 FROM n WHERE i < 49999)
+            INSERT INTO memberships
+              (guild_id, user_id, joined_at, joined_at_epoch, left_at, left_at_epoch,
+               account_username)
+            SELECT '1', CAST(1000 + i % 10000 AS TEXT), 'x', i * 10,
+                   CASE WHEN i < 40000 THEN 'x' END,
+                   CASE WHEN i < 40000 THEN i * 10 + 5 END,
+                   'user' || (i % 10000)
+            FROM n
+            "#,
+        )
+        .execute(&db.pool)
+        .await
+        .unwrap();
+
+        let repo = MembershipsRepo::new(&db);
+        let timed = |what: &str, started: Instant| {
+            let took = started.elapsed();
+            assert!(took < LIMIT, "{what} took {took:?}");
+        };
+
+        let started = Instant::now();
+        assert_eq!(repo.history_for_user(GUILD, UserId::new(1042)).await.unwrap().len(), 5);
+        timed("history_for_user", started);
+
+        let started = Instant::now();
+        assert_eq!(repo.open_stint_user_ids(GUILD).await.unwrap().len(), 10_000);
+        timed("open_stint_user_ids", started);
+
+        let started = Instant::now();
+        let window = TimeWindow {
+            since: Some(300_000),
+            until: None,
+        };
+        assert_eq!(repo.all_exits(GUILD, window, None, 25).await.unwrap().rows.len(), 25);
+        timed("all_exits", started);
+
+        let started = Instant::now();
+        assert_eq!(repo.rejoiners(GUILD, 2, 25).await.unwrap().len(), 25);
+        timed("rejoiners", started);
+    }
 }
@@ -0,0 +1,113 @@
+//! Once-a-day recap of the last 24h, posted to each guild's mod log.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, NaiveDate, Timelike, Utc};
+use poise::serenity_prelude as serenity;
+use serenity::all::{GuildId, Timestamp};
+use serenity::prelude::Context;
+
+use crate::commands::stats::tally_window;
+use crate::events::post_embed;
+use crate::repos::{LogKind, MembershipStore};
+use crate::state::AppState;
+use crate::strings::t;
+
+/// Rows scanned per guild for the 24h window (newest first).
+const DIGEST_SCAN_CAP: i64 = 5_000;
+
+/// Wake at the top of every hour and post digests for guilds whose configured
+/// local hour has come. Runs until shutdown.
+pub async fn run_digest_loop(ctx: Context, state: Arc<AppState>) {
+    // Guards against double posts if the loop wakes twice within the same hour.
+    let mut last_sent: HashMap<GuildId, NaiveDate> = HashMap::new();
+
+    loop {
+        let now = Utc::now();
+        let next_hour = (now + Duration::hours(1))
+            .with_minute(0)
+            .and_then(|t| t.with_second(0))
+            .unwrap_or(now + Duration::hours(1));
+        let wait = (next_hour - now).to_std().unwrap_or_default();
+        tokio::select! {
+            _ = state.shutdown.cancelled() => break,
+            _ = tokio::time::sleep(wait) => {}
+        }
+
+        for guild_id in ctx.cache.guilds() {
+            if let Err(e) = maybe_post_digest(&ctx, &state, guild_id, &mut last_sent).await {
+                tracing::warn!(%guild_id, error = format!("{e:#}"), "Daily digest failed");
+            }
+        }
+    }
+}
+
+async fn maybe_post_digest(
+    ctx: &Context,
+    state: &AppState,
+    guild_id: GuildId,
+    last_sent: &mut HashMap<GuildId, NaiveDate>,
+) -> anyhow::Result<()> {
+    let settings = state.settings_repo().get_cached(&guild_id).await?;
+    let targets = settings.log_targets(LogKind::Mod);
+    if !settings.digest_enabled || targets.is_empty() {
+        return Ok(());
+    }
+
+    let local_now = Utc::now().with_timezone(&settings.tz());
+    if local_now.hour() != settings.digest_hour() {
+        return Ok(());
+    }
+    let today = local_now.date_naive();
+    if last_sent.get(&guild_id) == Some(&today) {
+        return Ok(());
+    }
+
+    let lang = settings.lang();
+    let body = digest_body(state, guild_id, lang, Utc::now() - Duration::hours(24)).await?;
+    // Webhook, mirrors, retries and stale-channel cleanup as for every other log post.
+    let outcomes = post_embed(
+        state,
+        guild_id,
+        &ctx.http,
+        &targets,
+        settings.embed_colour(),
+        &t("digest.title", lang, &[]),
+        |e| e.description(body).timestamp(Timestamp::now()),
+    )
+    .await;
+    if !outcomes.iter().any(|(_, sent)| sent.is_ok()) {
+        anyhow::bail!("no mod log destination accepted the digest");
+    }
+    last_sent.insert(guild_id, today);
+    Ok(())
+}
+
+async fn digest_body(
+    state: &AppState,
+    guild_id: GuildId,
+    lang: &str,
+    since: DateTime<Utc>,
+) -> anyhow::Result<String> {
+    let mrepo = state.memberships();
+    let raw = mrepo
+        .activity_since(guild_id, since.into(), DIGEST_SCAN_CAP)
+        .await?;
+    let window = tally_window(&raw, since);
+    let current = mrepo.stats_current(guild_id).await?;
+
+    Ok(t(
+        "digest.body",
+        lang,
+        &[
+            ("net", &format!("{:+}", window.net())),
+            ("joins", &window.joins),
+            ("leaves", &window.leaves()),
+            ("left", &window.left),
+            ("kicked", &window.kicked),
+            ("banned", &window.banned),
+            ("members", &current.current_members),
+        ],
+    ))
+}
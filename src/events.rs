@@ -3,11 +3,27 @@ use std::sync::Arc;
 use anyhow::Result;
 use poise::FrameworkContext;
 use poise::serenity_prelude as serenity;
-use serenity::all::{ChannelId, CreateEmbed, CreateMessage, GuildId, Timestamp, User, UserId};
+use serenity::all::{
+    ChannelId, Colour, CreateEmbed, CreateMessage, ExecuteWebhook, GuildId, Member, Message,
+    MessageId, MessageUpdateEvent, RoleId, Timestamp, User, UserId, WebhookId,
+};
+use serenity::http::StatusCode;
 use serenity::prelude::Context;
 
-use crate::repos::{GuildSettingsRepo, MembershipsRepo};
-use crate::state::AppState;
+use crate::commands::{
+    MAX_EMBED_DESCRIPTION_CHARS, MAX_EMBED_FIELD_CHARS, ban_reason_suffix, clip, with_colour,
+};
+use crate::repos::{
+    AccountAgeAction, BAN_WINDOW_RANGE, GuildSettings, InvitesRepo, LeaveKind, LogKind,
+    LogTargets, MembershipRow, MembershipsRepo, NameKind, OptOutRepo, RoleMenusRepo, UnixTime,
+    WatchlistRepo,
+};
+use crate::flows::role_menu;
+use crate::audit::KICK_WINDOW_SECS;
+use crate::{audit, invites, retry};
+use crate::state::{AppState, RAID_ALERT_COOLDOWN, RaidKind, RuntimeCounters};
+use crate::strings::t;
+use crate::templating::{self, TemplateVars};
 
 pub async fn event_handler(
     ctx: &Context,
@@ -15,38 +31,260 @@ pub async fn event_handler(
     _framework: FrameworkContext<'_, Arc<AppState>, anyhow::Error>,
     state: &Arc<AppState>,
 ) -> Result<()> {
+    let res = dispatch(ctx, event, state).await;
+    if let Err(e) = &res {
+        state.counters.record_error(e);
+    }
+    res
+}
+
+async fn dispatch(ctx: &Context, event: &serenity::FullEvent, state: &Arc<AppState>) -> Result<()> {
     use serenity::FullEvent::*;
     match event {
         Ready { data_about_bot, .. } => handle_ready(ctx, state, data_about_bot).await?,
+        GuildCreate { guild, .. } => {
+            invites::refresh_invite_cache(&ctx.http, state, guild.id).await
+        }
+        GuildDelete { incomplete, .. } => on_guild_delete(state, incomplete),
+        InviteCreate { data } => on_invite_create(state, data).await?,
+        InviteDelete { data } => on_invite_delete(state, data).await?,
         GuildMemberAddition { new_member } => on_join(ctx, state, new_member).await?,
         GuildMemberRemoval { guild_id, user, .. } => on_leave(ctx, state, guild_id, user).await?,
+        GuildMemberUpdate {
+            old_if_available,
+            event,
+            ..
+        } => on_member_update(ctx, state, old_if_available.as_ref(), event).await?,
         GuildBanAddition {
             guild_id,
             banned_user,
-        } => on_guild_ban_add(state, *guild_id, banned_user).await?,
+        } => on_guild_ban_add(ctx, state, *guild_id, banned_user).await?,
+        GuildBanRemoval {
+            guild_id,
+            unbanned_user,
+        } => on_guild_ban_remove(ctx, state, *guild_id, unbanned_user).await?,
+        MessageDelete {
+            channel_id,
+            deleted_message_id,
+            guild_id: Some(guild_id),
+        } => on_message_delete(ctx, state, *guild_id, *channel_id, *deleted_message_id).await?,
+        MessageUpdate {
+            old_if_available,
+            event,
+            ..
+        } => on_message_edit(ctx, state, old_if_available.as_ref(), event).await?,
         _ => {}
     }
     Ok(())
 }
 
-async fn post_embed(
+/// Where `post_embed` tried to deliver a log embed.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum LogDestination {
+    Webhook,
+    Channel(ChannelId),
+}
+
+/// Send a log embed through the log's webhook when one is set, or else (or when the
+/// webhook fails) to every configured channel; one failing channel (deleted, missing
+/// perms) doesn't stop the others. Rate limits and Discord server errors are retried
+/// a few times before giving up. A webhook or channel Discord no longer knows is
+/// removed from the guild's settings so it isn't retried forever.
+/// Returns each attempt's outcome (`/settings test` reports them; events ignore them).
+pub(crate) async fn post_embed(
+    state: &AppState,
+    guild_id: GuildId,
     http: &serenity::http::Http,
-    channel: Option<ChannelId>,
+    targets: &LogTargets,
+    colour: Option<Colour>,
     title: &str,
     f: impl FnOnce(CreateEmbed) -> CreateEmbed,
-) {
-    if let Some(ch) = channel {
-        let _ = ch
-            .send_message(
-                http,
-                CreateMessage::new().embed(f(CreateEmbed::new().title(title))),
-            )
-            .await;
+) -> Vec<(LogDestination, serenity::Result<()>)> {
+    let mut outcomes = Vec::with_capacity(targets.channels.len() + 1);
+    if targets.is_empty() {
+        return outcomes;
+    }
+    let embed = f(with_colour(CreateEmbed::new().title(title), colour));
+    if let Some(url) = targets.webhook.as_deref() {
+        let sent = post_webhook(http, url, &embed).await;
+        match &sent {
+            Ok(()) => {
+                RuntimeCounters::incr(&state.counters.log_posts);
+                outcomes.push((LogDestination::Webhook, sent));
+                return outcomes;
+            }
+            Err(e) if is_unknown_webhook(e) => forget_stale_webhook(state, guild_id, url).await,
+            Err(e) => tracing::warn!(
+                %guild_id,
+                error = %e,
+                "Failed to post log embed via webhook; falling back to channels"
+            ),
+        }
+        outcomes.push((LogDestination::Webhook, sent));
+    }
+    for ch in &targets.channels {
+        let sent = retry::with_backoff("Posting log embed", || {
+            ch.send_message(http, CreateMessage::new().embed(embed.clone()))
+        })
+        .await;
+        match &sent {
+            Ok(_) => RuntimeCounters::incr(&state.counters.log_posts),
+            Err(e) if retry::status_of(e) == Some(StatusCode::FORBIDDEN) => {
+                tracing::warn!(
+                    %guild_id,
+                    channel_id = %ch,
+                    error = %e,
+                    "Missing permissions to post log embed; skipping"
+                )
+            }
+            Err(e) if is_unknown_channel(e) => forget_stale_channel(state, guild_id, *ch).await,
+            Err(e) => {
+                tracing::warn!(%guild_id, channel_id = %ch, error = %e, "Failed to post log embed")
+            }
+        }
+        outcomes.push((LogDestination::Channel(*ch), sent.map(|_| ())));
+    }
+    outcomes
+}
+
+/// Execute the log webhook at `url` with `embed`.
+async fn post_webhook(
+    http: &serenity::http::Http,
+    url: &str,
+    embed: &CreateEmbed,
+) -> serenity::Result<()> {
+    let Some((id, token)) = parse_webhook_url(url) else {
+        return Err(serenity::Error::Other("invalid webhook URL"));
+    };
+    let payload = ExecuteWebhook::new().embed(embed.clone());
+    retry::with_backoff("Posting log embed via webhook", || {
+        http.execute_webhook(id, None, &token, false, Vec::new(), &payload)
+    })
+    .await?;
+    Ok(())
+}
+
+/// Id and token of a Discord webhook URL (`https://discord.com/api/webhooks/<id>/<token>`);
+/// `None` for anything else.
+pub(crate) fn parse_webhook_url(url: &str) -> Option<(WebhookId, String)> {
+    let url = url::Url::parse(url.trim()).ok()?;
+    serenity::utils::parse_webhook(&url).map(|(id, token)| (id, token.to_string()))
+}
+
+/// Discord's "Unknown Webhook" (JSON error 10015): the webhook was deleted.
+fn is_unknown_webhook(err: &serenity::Error) -> bool {
+    matches!(
+        err,
+        serenity::Error::Http(serenity::HttpError::UnsuccessfulRequest(r))
+            if r.status_code == StatusCode::NOT_FOUND && r.error.code == 10015
+    )
+}
+
+/// Clear a deleted webhook from every log using it; the channels take over.
+async fn forget_stale_webhook(state: &AppState, guild_id: GuildId, url: &str) {
+    let repo = state.settings_repo();
+    let res = async {
+        let settings = repo.get(&guild_id).await?;
+        for kind in LogKind::ALL {
+            if settings.webhook(kind) == Some(url) {
+                repo.set_text_column(&guild_id, kind.webhook_column(), None).await?;
+            }
+        }
+        Ok::<_, crate::repos::RepoError>(())
+    }
+    .await;
+    match res {
+        Ok(()) => tracing::warn!(
+            %guild_id,
+            "Log webhook no longer exists; removed it from the settings"
+        ),
+        Err(e) => tracing::warn!(
+            %guild_id,
+            error = format!("{e:#}"),
+            "Log webhook no longer exists, but clearing it failed"
+        ),
+    }
+}
+
+/// Discord's "Cannot send messages in a non-text channel" (JSON error 50008), e.g. a
+/// forum (which needs a post) or a voice channel whose chat is unavailable.
+pub(crate) fn is_not_postable(err: &serenity::Error) -> bool {
+    matches!(
+        err,
+        serenity::Error::Http(serenity::HttpError::UnsuccessfulRequest(r))
+            if r.error.code == 50008
+    )
+}
+
+/// Discord's "Unknown Channel" (JSON error 10003): the channel was deleted.
+pub(crate) fn is_unknown_channel(err: &serenity::Error) -> bool {
+    matches!(
+        err,
+        serenity::Error::Http(serenity::HttpError::UnsuccessfulRequest(r))
+            if r.status_code == StatusCode::NOT_FOUND && r.error.code == 10003
+    )
+}
+
+/// Clear a deleted log channel from the guild's settings, so admins see it unset
+/// in `/settings show` instead of logs silently going nowhere.
+async fn forget_stale_channel(state: &AppState, guild_id: GuildId, channel: ChannelId) {
+    match state.settings_repo().forget_channel(&guild_id, channel).await {
+        Ok(kinds) => {
+            let kinds: Vec<_> = kinds.iter().map(|k| k.as_str()).collect();
+            tracing::warn!(
+                %guild_id,
+                channel_id = %channel,
+                removed_from = %kinds.join(", "),
+                "Log channel no longer exists; removed it from the settings"
+            );
+        }
+        Err(e) => tracing::warn!(
+            %guild_id,
+            channel_id = %channel,
+            error = format!("{e:#}"),
+            "Log channel no longer exists, but clearing it failed"
+        ),
+    }
+}
+
+/// Fill a custom join/leave template (placeholders: see `templating`).
+/// The result is capped to what fits in an embed description.
+fn render_template(
+    ctx: &Context,
+    template: &str,
+    guild_id: GuildId,
+    user: &User,
+    joined: Option<UnixTime>,
+    lang: &str,
+) -> String {
+    let (server, count) = ctx
+        .cache
+        .guild(guild_id)
+        .map(|g| (g.name.clone(), Some(g.member_count)))
+        .unwrap_or_else(|| (t("events.this_server", lang, &[]), None));
+
+    let vars = TemplateVars {
+        user: &user.name,
+        mention: format!("<@{}>", user.id.get()),
+        server: &server,
+        count,
+        joined,
+        account_created: user.id.created_at().into(),
+    };
+    clip(templating::render(template, &vars), MAX_EMBED_DESCRIPTION_CHARS)
+}
+
+/// Message content for an embed field; empty text (attachment-only, embeds) is spelled out.
+fn content_field(content: &str, lang: &str) -> String {
+    if content.trim().is_empty() {
+        t("events.no_text_content", lang, &[])
+    } else {
+        clip(content.to_string(), MAX_EMBED_FIELD_CHARS)
     }
 }
 
 pub async fn handle_ready(
-    _ctx: &Context,
+    ctx: &Context,
     state: &Arc<AppState>,
     ready: &serenity::Ready,
 ) -> Result<()> {
@@ -54,26 +292,80 @@ pub async fn handle_ready(
 
     let mrepo = MembershipsRepo::new(&state.db);
     for guild in &ready.guilds {
-        tracing::info!("Connected to guild: {}", guild.id);
+        tracing::info!(guild_id = %guild.id, "Connected to guild");
         mrepo.rebuild_usernames_fts_for_guild(guild.id).await.map_err(
-            |e| tracing::warn!("Failed to rebuild usernames FTS for guild {}: {}", guild.id, e)
+            |e| tracing::warn!(guild_id = %guild.id, error = %e, "Failed to rebuild usernames FTS")
         ).ok();
     }
 
-    // Light maintenance loop for recent_bans
+    if state.claim_background_loops() {
+        spawn_background_loops(ctx, state);
+    }
+
+    // Role menus outlive the process; resume serving their picks. Re-attaching a menu
+    // stops its previous collector, so this is safe on every `Ready`.
+    match RoleMenusRepo::new(&state.db).all().await {
+        Ok(menus) => {
+            for menu in menus {
+                tokio::spawn(role_menu::attach(ctx.clone(), state.clone(), menu));
+            }
+        }
+        Err(e) => tracing::warn!(error = %e, "Failed to load role menus"),
+    }
+
+    Ok(())
+}
+
+/// Loops that run for the life of the process; started from the first `Ready` only.
+fn spawn_background_loops(ctx: &Context, state: &Arc<AppState>) {
+    // Light maintenance loop for recent_bans; keep entries at least as long as the
+    // largest window any guild may configure.
     let state_clone = state.clone();
     tokio::spawn(async move {
         let every_min = std::time::Duration::from_secs(60);
+        let max_age = (*BAN_WINDOW_RANGE.end()).max(60);
         loop {
-            state_clone.prune_recent_bans(60);
-            tokio::time::sleep(every_min).await;
+            state_clone.prune_recent_bans(max_age);
+            tokio::select! {
+                _ = state_clone.shutdown.cancelled() => break,
+                _ = tokio::time::sleep(every_min) => {}
+            }
         }
     });
 
-    Ok(())
+    // Daily member-count snapshots for every guild we're in (for /stats growth)
+    let state_clone = state.clone();
+    let cache = ctx.cache.clone();
+    tokio::spawn(async move {
+        let every_day = std::time::Duration::from_secs(24 * 60 * 60);
+        loop {
+            let mrepo = MembershipsRepo::new(&state_clone.db);
+            for guild_id in cache.guilds() {
+                if let Err(e) = mrepo.record_snapshot(guild_id).await {
+                    tracing::warn!(
+                        %guild_id,
+                        error = format!("{e:#}"),
+                        "Failed to record member snapshot"
+                    );
+                }
+            }
+            tokio::select! {
+                _ = state_clone.shutdown.cancelled() => break,
+                _ = tokio::time::sleep(every_day) => {}
+            }
+        }
+    });
+
+    // Daily digest to each guild's mod log (opt-in per guild)
+    tokio::spawn(crate::digest::run_digest_loop(ctx.clone(), state.clone()));
+
+    // Daily pruning of history past each guild's retention window (opt-in per guild)
+    tokio::spawn(crate::retention::run_retention_loop(state.clone()));
 }
 
-/// Join: just persist basic info; no invites needed.
+/// Join: persist basic info plus the attributed invite (best effort).
+/// Guild and user ids are on the enclosing event span (see `crate::spans`).
+#[tracing::instrument(skip_all)]
 pub async fn on_join(
     ctx: &Context,
     state: &AppState,
@@ -82,61 +374,994 @@ pub async fn on_join(
     let guild_id = member.guild_id;
     let user_id = member.user.id;
 
+    let invite = invites::attribute_join(&ctx.http, state, guild_id).await;
+
+    RuntimeCounters::incr(&state.counters.joins);
+    state.metrics.record_join(guild_id);
+    state.record_join_tick(guild_id);
+
+    let settings = state.settings_repo().get_cached(&guild_id).await?;
+    let untracked = is_untracked(state, &settings, guild_id, user_id).await?;
+
     let mrepo = MembershipsRepo::new(&state.db);
-    mrepo.record_join(guild_id, member).await?;
-    mrepo.upsert_usernames_fts_row(guild_id, &user_id.to_string()).await?;
+    // Counted *before* inserting this stint, so `previous_stays` excludes the current join.
+    let previous_stays = mrepo.stint_count(guild_id, user_id).await?;
+    let (last_stint, prior_ban) = if previous_stays > 0 {
+        (
+            mrepo.latest_stint(guild_id, user_id).await?,
+            mrepo.was_ever_banned(guild_id, user_id).await?,
+        )
+    } else {
+        (None, None)
+    };
+    if !untracked {
+        mrepo
+            .record_join_with_fts(
+                guild_id,
+                member,
+                invite.as_ref().map(|i| i.code.as_str()),
+                invite.as_ref().and_then(|i| i.inviter_id),
+            )
+            .await?;
+    }
+    // A still-open previous stint means we missed their leave; the count doesn't grow.
+    let was_open = last_stint.as_ref().is_some_and(|row| row.left_at.is_none());
+    let added = !untracked && !was_open;
+    let member_count = member_count_after_join(state, &mrepo, guild_id, added).await?;
+    if let Some(i) = invite.as_ref() {
+        InvitesRepo::new(&state.db).record_use(guild_id, &i.code).await?;
+    }
 
-    let grepo = GuildSettingsRepo::new(&state.db);
-    let settings = grepo.get(&guild_id).await?;
+    let lang = settings.lang();
+    check_raid(ctx, state, guild_id, &settings, RaidKind::Joins).await;
 
-    post_embed(&ctx.http, settings.join_log, "Member joined", |e| {
-        e.description(format!("<@{}> joined.", user_id.get()))
-            .timestamp(Timestamp::now())
+    let invited_by = match invite.as_ref() {
+        Some(i) => match i.inviter_id {
+            Some(inviter) => t(
+                "events.join.via_invite_from",
+                lang,
+                &[("code", &i.code), ("inviter", &format!("<@{}>", inviter.get()))],
+            ),
+            None => t("events.join.via_invite", lang, &[("code", &i.code)]),
+        },
+        None => String::new(),
+    };
+
+    let mention = format!("<@{}>", user_id.get());
+    let text = match (settings.join_template(), previous_stays) {
+        (Some(tpl), _) => {
+            let joined = member.joined_at.map_or_else(UnixTime::now, UnixTime::from);
+            render_template(ctx, tpl, guild_id, &member.user, Some(joined), lang)
+        }
+        (None, 0) => t(
+            "events.join.joined",
+            lang,
+            &[("user", &mention), ("invite", &invited_by)],
+        ),
+        (None, n) => {
+            let key = if n == 1 {
+                "events.join.rejoined_one"
+            } else {
+                "events.join.rejoined_many"
+            };
+            t(key, lang, &[("user", &mention), ("invite", &invited_by), ("count", &n)])
+        }
+    };
+    let title = if previous_stays > 0 {
+        t("events.join.title_rejoined", lang, &[])
+    } else {
+        t("events.join.title_joined", lang, &[])
+    };
+    let last_exit = last_stint.as_ref().and_then(|row| describe_last_exit(row, lang));
+    let young_account = check_account_age(ctx, &settings, member).await;
+
+    post_embed(state, guild_id, &ctx.http, &settings.log_targets(LogKind::Join), settings.embed_colour(), &title, |e| {
+        let mut e = e.description(text).timestamp(Timestamp::now());
+        if previous_stays > 0 && settings.join_template().is_some() {
+            e = e.field(
+                t("events.join.previous_stays", lang, &[]),
+                previous_stays.to_string(),
+                true,
+            );
+        }
+        if let Some(exit) = last_exit {
+            e = e.field(t("events.join.last_exit", lang, &[]), exit, true);
+        }
+        e = e.field(t("events.join.member_count", lang, &[]), format!("#{member_count}"), true);
+        if let Some(young) = young_account {
+            e = e.field(t("events.join.young_account", lang, &[]), young, false);
+        }
+        e
     })
     .await;
 
+    if let Some(ban) = prior_ban {
+        alert_banned_rejoin(ctx, state, &settings, guild_id, user_id, &ban).await;
+    }
+    alert_if_watched(ctx, state, &settings, guild_id, user_id).await?;
+
+    Ok(())
+}
+
+/// Colour for alerts that need a moderator's eye, whatever the guild's embed colour.
+const ALERT_COLOUR: Colour = Colour::RED;
+
+/// Warn the mod log (or the join log) that a returning member was banned here before,
+/// with the ban's reason when the audit log gave one.
+async fn alert_banned_rejoin(
+    ctx: &Context,
+    state: &AppState,
+    settings: &GuildSettings,
+    guild_id: GuildId,
+    user_id: UserId,
+    ban: &MembershipRow,
+) {
+    let lang = settings.lang();
+    let banned_at = ban.left_at.unwrap_or(ban.joined_at);
+    let mut text = t(
+        "events.banned_rejoin.body",
+        lang,
+        &[
+            ("user", &format!("<@{}>", user_id.get())),
+            ("id", &user_id.get()),
+            ("when", &banned_at.discord('D')),
+            ("reason", &ban_reason_suffix(ban.ban_reason.as_deref(), lang)),
+        ],
+    );
+    if let Some(unbanned_at) = ban.unbanned_at {
+        let when = unbanned_at.discord('D');
+        text.push('\n');
+        text.push_str(&t("events.banned_rejoin.unbanned", lang, &[("when", &when)]));
+    }
+    post_embed(
+        state,
+        guild_id,
+        &ctx.http,
+        &settings.join_alert_targets(),
+        Some(ALERT_COLOUR),
+        &t("events.banned_rejoin.title", lang, &[]),
+        |e| e.description(text).timestamp(Timestamp::now()),
+    )
+    .await;
+}
+
+/// Post a watchlist alert to the mod log (or the join log) when a watched user joins.
+async fn alert_if_watched(
+    ctx: &Context,
+    state: &AppState,
+    settings: &GuildSettings,
+    guild_id: GuildId,
+    user_id: UserId,
+) -> Result<()> {
+    let Some(entry) = WatchlistRepo::new(&state.db).get(guild_id, user_id).await? else {
+        return Ok(());
+    };
+    let lang = settings.lang();
+    let reason = match entry.reason {
+        Some(r) => clip(r, MAX_EMBED_FIELD_CHARS),
+        None => t("events.watchlist.no_reason", lang, &[]),
+    };
+    let text = t(
+        "events.watchlist.body",
+        lang,
+        &[
+            ("user", &format!("<@{}>", user_id.get())),
+            ("id", &user_id.get()),
+            ("added_by", &format!("<@{}>", entry.added_by)),
+            ("added", &entry.added_at.discord('R')),
+        ],
+    );
+    post_embed(
+        state,
+        guild_id,
+        &ctx.http,
+        &settings.join_alert_targets(),
+        Some(ALERT_COLOUR),
+        &t("events.watchlist.title", lang, &[]),
+        |e| {
+            e.description(text)
+                .field(t("events.watchlist.reason", lang, &[]), reason, false)
+                .timestamp(Timestamp::now())
+        },
+    )
+    .await;
     Ok(())
 }
 
-/// Leave: mark as banned if a recent `GuildBanAdd` was seen; else left.
+/// Whether nothing may be stored about `user_id`: they opted out of tracking and the
+/// guild doesn't require full records. Their events are still posted to the logs.
+async fn is_untracked(
+    state: &AppState,
+    settings: &GuildSettings,
+    guild_id: GuildId,
+    user_id: UserId,
+) -> Result<bool> {
+    if settings.tracking_required {
+        return Ok(false);
+    }
+    Ok(OptOutRepo::new(&state.db).is_opted_out(guild_id, user_id).await?)
+}
+
+/// Member count including a member who just joined: the cached count (bumped when the
+/// join added a member) or, on first use, a fresh count from the DB.
+async fn member_count_after_join(
+    state: &AppState,
+    mrepo: &MembershipsRepo<'_>,
+    guild_id: GuildId,
+    added: bool,
+) -> Result<i64> {
+    if let Some(n) = state.adjust_member_count(guild_id, i64::from(added)) {
+        return Ok(n);
+    }
+    let n = mrepo.current_member_count(guild_id).await?;
+    state.seed_member_count(guild_id, n);
+    Ok(n)
+}
+
+/// "left <t:…:R>" / "banned <t:…:R> — reason: …" for the stint before a rejoin.
+/// `None` when that stint was never closed (its leave was missed).
+fn describe_last_exit(row: &MembershipRow, lang: &str) -> Option<String> {
+    let left_at = row.left_at?;
+    let reason = if row.leave_kind == LeaveKind::Banned {
+        ban_reason_suffix(row.ban_reason.as_deref(), lang)
+    } else {
+        String::new()
+    };
+    Some(format!(
+        "{} {}{reason}",
+        t(&format!("leave_kind.{}", row.leave_kind.verb()), lang, &[]),
+        left_at.discord('R')
+    ))
+}
+
+/// Apply the guild's minimum account age to a new member. Returns the join-log
+/// field text when the account is too young; `None` when the check is off or passed.
+async fn check_account_age(
+    ctx: &Context,
+    settings: &GuildSettings,
+    member: &Member,
+) -> Option<String> {
+    let min_age = settings.min_account_age()?;
+    let created = member.user.id.created_at().unix_timestamp();
+    if Timestamp::now().unix_timestamp() - created >= min_age.num_seconds() {
+        return None;
+    }
+
+    let lang = settings.lang();
+    let days = min_age.num_days();
+    let server = ctx
+        .cache
+        .guild(member.guild_id)
+        .map(|g| g.name.clone())
+        .unwrap_or_else(|| t("events.this_server", lang, &[]));
+    let created_line = t(
+        "events.account_age.created",
+        lang,
+        &[("created", &format!("<t:{created}:R>")), ("days", &days)],
+    );
+
+    let outcome = match settings.account_age_action() {
+        AccountAgeAction::Log => return Some(created_line),
+        AccountAgeAction::Dm => {
+            let notice = t(
+                "events.account_age.dm_notice",
+                lang,
+                &[("server", &server), ("days", &days)],
+            );
+            if dm_member(ctx, member, notice).await {
+                t("events.account_age.dm_sent", lang, &[])
+            } else {
+                t("events.account_age.dm_failed", lang, &[])
+            }
+        }
+        // Checked up front: a failed kick against a higher role would still cost a DM.
+        AccountAgeAction::Kick if !can_kick(ctx, member) => {
+            t("events.account_age.kick_skipped", lang, &[])
+        }
+        AccountAgeAction::Kick => {
+            // DM first: once kicked, the bot shares no server with them anymore.
+            let notice = t(
+                "events.account_age.dm_kicked",
+                lang,
+                &[("server", &server), ("days", &days)],
+            );
+            dm_member(ctx, member, notice).await;
+            let reason = t("events.account_age.kick_reason", lang, &[("days", &days)]);
+            match member.kick_with_reason(&ctx.http, &reason).await {
+                Ok(()) => t("events.account_age.kicked", lang, &[]),
+                Err(e) => {
+                    tracing::warn!(
+                        guild_id = %member.guild_id,
+                        user_id = %member.user.id,
+                        error = %e,
+                        "Failed to kick young account"
+                    );
+                    t("events.account_age.kick_failed", lang, &[("error", &e)])
+                }
+            }
+        }
+    };
+    Some(format!("{created_line}\n{outcome}"))
+}
+
+/// Best-effort DM; members with DMs closed are common, so failures are only debug-logged.
+async fn dm_member(ctx: &Context, member: &Member, text: String) -> bool {
+    match member.user.direct_message(ctx, CreateMessage::new().content(text)).await {
+        Ok(_) => true,
+        Err(e) => {
+            tracing::debug!(user_id = %member.user.id, error = %e, "Couldn't DM member");
+            false
+        }
+    }
+}
+
+/// Whether the bot may kick `member`: it needs Kick Members and a higher top role
+/// (which also rules out the owner). Unknown cache state counts as "no".
+fn can_kick(ctx: &Context, member: &Member) -> bool {
+    let me = ctx.cache.current_user().id;
+    let Some(guild) = ctx.cache.guild(member.guild_id) else {
+        return false;
+    };
+    let Some(bot) = guild.members.get(&me) else {
+        return false;
+    };
+    // Kicking is a guild-wide permission, so channel overwrites don't matter here.
+    let everyone = guild.roles.get(&RoleId::new(guild.id.get()));
+    let may_kick = bot
+        .roles
+        .iter()
+        .filter_map(|r| guild.roles.get(r))
+        .chain(everyone)
+        .any(|r| r.permissions.kick_members() || r.permissions.administrator());
+    may_kick && guild.greater_member_hierarchy(&ctx.cache, me, member.user.id) == Some(me)
+}
+
+/// Alert the moderation log when joins/leaves in the last minute reach the guild's
+/// raid threshold; at most once per `RAID_ALERT_COOLDOWN` and kind.
+async fn check_raid(
+    ctx: &Context,
+    state: &AppState,
+    guild_id: GuildId,
+    settings: &GuildSettings,
+    kind: RaidKind,
+) {
+    let rate = match kind {
+        RaidKind::Joins => state.join_rate(guild_id),
+        RaidKind::Leaves => state.leave_rate(guild_id),
+    };
+    let threshold = settings.raid_threshold();
+    if rate < threshold || !state.claim_raid_alert(guild_id, kind) {
+        return;
+    }
+    tracing::warn!(%guild_id, rate, ?kind, "Raid alert: burst in the last minute");
+
+    let lang = settings.lang();
+    let (title, body) = match kind {
+        RaidKind::Joins => ("events.raid.title_joins", "events.raid.body_joins"),
+        RaidKind::Leaves => ("events.raid.title_leaves", "events.raid.body_leaves"),
+    };
+    let text = format!(
+        "{}\n{}",
+        t(body, lang, &[("rate", &rate), ("threshold", &threshold)]),
+        t("events.raid.cooldown", lang, &[("minutes", &(RAID_ALERT_COOLDOWN.as_secs() / 60))]),
+    );
+    post_embed(
+        state,
+        guild_id,
+        &ctx.http,
+        &settings.moderation_targets(),
+        settings.embed_colour(),
+        &t(title, lang, &[]),
+        |e| e.description(text).timestamp(Timestamp::now()),
+    )
+    .await;
+}
+
+/// Leave: banned if a recent `GuildBanAdd` was seen, kicked if the audit log
+/// shows a fresh `MemberKick` (best effort), else left.
+#[tracing::instrument(skip_all)]
 pub async fn on_leave(
     ctx: &Context,
     state: &AppState,
     guild_id: &GuildId,
     user: &User,
 ) -> Result<()> {
-    let banned = state.was_recently_banned(*guild_id, user.id, 15);
+    let settings = state.settings_repo().get_cached(guild_id).await?;
+    let lang = settings.lang();
 
-    let mrepo = MembershipsRepo::new(&state.db);
-    mrepo.record_leave(*guild_id, user.id, banned).await?;
+    let kind = if state.was_recently_banned(*guild_id, user.id, settings.ban_window_secs()) {
+        LeaveKind::Banned
+    } else if audit::was_recently_kicked(&ctx.http, *guild_id, user.id, KICK_WINDOW_SECS).await {
+        RuntimeCounters::incr(&state.counters.kicks);
+        LeaveKind::Kicked
+    } else {
+        LeaveKind::Left
+    };
+    RuntimeCounters::incr(&state.counters.leaves);
+    state.metrics.record_leave(*guild_id);
+    state.record_leave_tick(*guild_id);
+    check_raid(ctx, state, *guild_id, &settings, RaidKind::Leaves).await;
 
-    let grepo = GuildSettingsRepo::new(&state.db);
-    let settings = grepo.get(guild_id).await?;
-    let target = if banned {
-        settings.mod_log.or(settings.leave_log)
+    let untracked = is_untracked(state, &settings, *guild_id, user.id).await?;
+    let mrepo = MembershipsRepo::new(&state.db);
+    let closed = if untracked {
+        0
     } else {
-        settings.leave_log
+        mrepo.record_leave(*guild_id, user.id, kind, None).await?
     };
+    if closed > 0 {
+        state.adjust_member_count(*guild_id, -1);
+    }
 
-    post_embed(&ctx.http, target, "Member left", |e| {
-        e.description(format!(
-            "<@{}> {}.",
-            user.id.get(),
-            if banned { "was **banned**" } else { "left" }
-        ))
-        .timestamp(Timestamp::now())
+    if closed == 0 {
+        // The ban handler already closed this stint and posted the ban.
+        if kind == LeaveKind::Banned
+            && mrepo
+                .recently_closed_as_ban(*guild_id, user.id, settings.ban_window_secs())
+                .await?
+        {
+            return Ok(());
+        }
+        // We never saw the join (e.g. bot was offline): keep counts consistent.
+        if !untracked {
+            mrepo.record_inferred_stint(*guild_id, user, kind, None).await?;
+        }
+    }
+
+    let targets = match kind {
+        LeaveKind::Left => settings.log_targets(LogKind::Leave),
+        LeaveKind::Kicked | LeaveKind::Banned => settings.moderation_targets(),
+    };
+
+    // Custom text only replaces plain leaves; kicks/bans keep the moderation wording.
+    let text = match (kind, settings.leave_template()) {
+        (LeaveKind::Left, Some(tpl)) => {
+            // The stay that just ended; inferred stints have no real join time.
+            let joined = mrepo
+                .latest_stint(*guild_id, user.id)
+                .await?
+                .filter(|row| !row.inferred)
+                .map(|row| row.joined_at);
+            render_template(ctx, tpl, *guild_id, user, joined, lang)
+        }
+        _ => {
+            // A ban seen here beat its own `GuildBanAdd` handler, which stores the
+            // reason afterwards; so the embed goes out without one.
+            let key = match kind {
+                LeaveKind::Left => "events.leave.left",
+                LeaveKind::Kicked => "events.leave.kicked",
+                LeaveKind::Banned => "events.leave.banned_no_reason",
+            };
+            t(key, lang, &[("user", &format!("<@{}>", user.id.get()))])
+        }
+    };
+
+    let title = t("events.leave.title", lang, &[]);
+    post_embed(state, *guild_id, &ctx.http, &targets, settings.embed_colour(), &title, |e| {
+        e.description(text).timestamp(Timestamp::now())
     })
     .await;
 
     Ok(())
 }
 
-/// Record the ban so we can classify leaves without audit logs.
-async fn on_guild_ban_add(state: &AppState, guild_id: GuildId, banned_user: &User) -> Result<()> {
+/// Member updates: role grants/removals and name changes, each posted to the mod log.
+pub async fn on_member_update(
+    ctx: &Context,
+    state: &AppState,
+    old: Option<&serenity::all::Member>,
+    event: &serenity::all::GuildMemberUpdateEvent,
+) -> Result<()> {
+    // Without the cached old member there's no role list to diff against.
+    if let Some(old) = old {
+        log_role_changes(ctx, state, old, event).await?;
+    }
+    log_name_changes(ctx, state, old, event).await
+}
+
+/// Post roles added/removed since `old`, limited to the guild's watched roles if it set any.
+/// Roles granting Administrator are flagged.
+async fn log_role_changes(
+    ctx: &Context,
+    state: &AppState,
+    old: &serenity::all::Member,
+    event: &serenity::all::GuildMemberUpdateEvent,
+) -> Result<()> {
+    let guild_id = event.guild_id;
+    let added: Vec<RoleId> = event
+        .roles
+        .iter()
+        .filter(|r| !old.roles.contains(r))
+        .copied()
+        .collect();
+    let removed: Vec<RoleId> = old
+        .roles
+        .iter()
+        .filter(|r| !event.roles.contains(r))
+        .copied()
+        .collect();
+    if added.is_empty() && removed.is_empty() {
+        return Ok(());
+    }
+
+    let settings = state.settings_repo().get_cached(&guild_id).await?;
+    let lang = settings.lang();
+    let added: Vec<RoleId> = added.into_iter().filter(|r| settings.watches_role(*r)).collect();
+    let removed: Vec<RoleId> = removed
+        .into_iter()
+        .filter(|r| settings.watches_role(*r))
+        .collect();
+    if added.is_empty() && removed.is_empty() {
+        return Ok(());
+    }
+
+    let admin_roles: Vec<RoleId> = ctx
+        .cache
+        .guild(guild_id)
+        .map(|g| {
+            g.roles
+                .values()
+                .filter(|r| r.permissions.administrator())
+                .map(|r| r.id)
+                .collect()
+        })
+        .unwrap_or_default();
+    let admin_flag = t("events.roles.admin_flag", lang, &[]);
+    let mention = |roles: &[RoleId]| {
+        roles
+            .iter()
+            .map(|r| {
+                let flag = if admin_roles.contains(r) { admin_flag.as_str() } else { "" };
+                format!("<@&{}>{flag}", r.get())
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    let mut lines = vec![format!("<@{}>", event.user.id.get())];
+    if !added.is_empty() {
+        lines.push(t("events.roles.added", lang, &[("roles", &mention(&added))]));
+    }
+    if !removed.is_empty() {
+        lines.push(t("events.roles.removed", lang, &[("roles", &mention(&removed))]));
+    }
+
+    let title = t("events.roles.title", lang, &[]);
+    post_embed(state, guild_id, &ctx.http, &settings.log_targets(LogKind::Mod), settings.embed_colour(), &title, |e| {
+        e.description(lines.join("\n")).timestamp(Timestamp::now())
+    })
+    .await;
+
+    Ok(())
+}
+
+/// Name changes: diff username/nickname, append to the audit trail + mod log and
+/// refresh the stored names (and autocomplete entry) on the latest stint.
+///
+/// If the old member wasn't cached, fall back to the last-known names we stored;
+/// untracked users are skipped since there's nothing to compare against.
+async fn log_name_changes(
+    ctx: &Context,
+    state: &AppState,
+    old: Option<&serenity::all::Member>,
+    event: &serenity::all::GuildMemberUpdateEvent,
+) -> Result<()> {
+    let guild_id = event.guild_id;
+    let user_id = event.user.id;
+    let mrepo = MembershipsRepo::new(&state.db);
+
+    let (old_account, old_nick) = match old {
+        Some(m) => (Some(m.user.name.clone()), m.nick.clone()),
+        None => match mrepo.last_known_names(guild_id, user_id).await? {
+            Some(names) => names,
+            None => return Ok(()),
+        },
+    };
+    let old_nick = old_nick.filter(|n| !n.is_empty());
+    let new_nick = event.nick.clone().filter(|n| !n.is_empty());
+    let new_account = Some(event.user.name.clone());
+
+    let mut changes = Vec::new();
+    if old_account.is_some() && old_account != new_account {
+        changes.push((NameKind::Account, old_account, new_account));
+    }
+    if old_nick != new_nick {
+        changes.push((NameKind::Server, old_nick, new_nick.clone()));
+    }
+    if changes.is_empty() {
+        return Ok(());
+    }
+
+    let settings = state.settings_repo().get_cached(&guild_id).await?;
+    if !is_untracked(state, &settings, guild_id, user_id).await? {
+        for (kind, old_value, new_value) in &changes {
+            mrepo
+                .record_name_change(
+                    guild_id,
+                    user_id,
+                    *kind,
+                    old_value.as_deref(),
+                    new_value.as_deref(),
+                )
+                .await?;
+        }
+        mrepo
+            .update_names_with_fts(guild_id, user_id, &event.user.name, new_nick.as_deref())
+            .await?;
+    }
+
+    let lang = settings.lang();
+
+    let none = t("events.names.none", lang, &[]);
+    let show = |v: &Option<String>| v.clone().unwrap_or_else(|| none.clone());
+    let lines: Vec<String> = changes
+        .iter()
+        .map(|(kind, old_value, new_value)| {
+            let key = match kind {
+                NameKind::Account => "events.names.username",
+                NameKind::Server => "events.names.nickname",
+            };
+            t(key, lang, &[("old", &show(old_value)), ("new", &show(new_value))])
+        })
+        .collect();
+
+    let title = t("events.names.title", lang, &[]);
+    post_embed(state, guild_id, &ctx.http, &settings.log_targets(LogKind::Mod), settings.embed_colour(), &title, |e| {
+        e.description(format!("<@{}>\n{}", user_id.get(), lines.join("\n")))
+            .timestamp(Timestamp::now())
+    })
+    .await;
+
+    Ok(())
+}
+
+/// Record the ban so we can classify leaves without audit logs, close the stint
+/// as banned and post it.
+///
+/// Gateway order isn't guaranteed: if the removal was already recorded as a plain
+/// leave, relabel that stint instead. Either way exactly one ban embed is posted.
+async fn on_guild_ban_add(
+    ctx: &Context,
+    state: &AppState,
+    guild_id: GuildId,
+    banned_user: &User,
+) -> Result<()> {
     state.mark_recent_ban(guild_id, banned_user.id);
+    RuntimeCounters::incr(&state.counters.bans);
+    state.metrics.record_ban(guild_id);
+
+    // Reason lives in the audit log only (best effort; needs View Audit Log)
+    let reason = audit::recent_ban_reason(&ctx.http, guild_id, banned_user.id, 30).await;
+
+    let settings = state.settings_repo().get_cached(&guild_id).await?;
+    if is_untracked(state, &settings, guild_id, banned_user.id).await? {
+        // Nothing stored to close; `on_leave` posts the ban when the removal arrives.
+        return Ok(());
+    }
 
-    // Optional: close open stint immediately as banned (best effort)
     let mrepo = MembershipsRepo::new(&state.db);
-    let _ = mrepo.record_leave(guild_id, banned_user.id, true).await;
+    let closed = mrepo
+        .record_leave(
+            guild_id,
+            banned_user.id,
+            LeaveKind::Banned,
+            reason.as_deref(),
+        )
+        .await?;
+    if closed > 0 {
+        state.adjust_member_count(guild_id, -1);
+    }
+    let recorded = closed > 0
+        || mrepo
+            .upgrade_recent_leave_to_ban(
+                guild_id,
+                banned_user.id,
+                reason.as_deref(),
+                settings.ban_window_secs(),
+            )
+            .await?;
+    if !recorded {
+        // Not tracked, or banned without being a member.
+        return Ok(());
+    }
+
+    let lang = settings.lang();
+    let text = t(
+        "events.leave.banned",
+        lang,
+        &[
+            ("user", &format!("<@{}>", banned_user.id.get())),
+            ("reason", &ban_reason_suffix(reason.as_deref(), lang)),
+        ],
+    );
+    post_embed(
+        state,
+        guild_id,
+        &ctx.http,
+        &settings.moderation_targets(),
+        settings.embed_colour(),
+        &t("events.leave.title", lang, &[]),
+        |e| e.description(text).timestamp(Timestamp::now()),
+    )
+    .await;
+    Ok(())
+}
+
+/// Unban: stamp the banned stint and post it. A ban we never recorded (before tracking,
+/// or of a non-member) is still posted, just without the ban time.
+async fn on_guild_ban_remove(
+    ctx: &Context,
+    state: &AppState,
+    guild_id: GuildId,
+    unbanned_user: &User,
+) -> Result<()> {
+    let banned_at = MembershipsRepo::new(&state.db)
+        .record_unban(guild_id, unbanned_user.id)
+        .await?;
+
+    let settings = state.settings_repo().get_cached(&guild_id).await?;
+    let lang = settings.lang();
+    let text = t(
+        "events.unban.body",
+        lang,
+        &[("user", &format!("<@{}>", unbanned_user.id.get()))],
+    );
+    let banned = match banned_at {
+        Some(at) => format!("{} ({})", at.discord('f'), at.discord('R')),
+        None => t("events.unban.not_recorded", lang, &[]),
+    };
+    post_embed(
+        state,
+        guild_id,
+        &ctx.http,
+        &settings.moderation_targets(),
+        settings.embed_colour(),
+        &t("events.unban.title", lang, &[]),
+        |e| {
+            e.description(text)
+                .field(t("events.unban.banned", lang, &[]), banned, false)
+                .timestamp(Timestamp::now())
+        },
+    )
+    .await;
+    Ok(())
+}
+
+/// Bot removed from a guild: drop its caches. An `unavailable` delete is an outage,
+/// not a removal, so nothing is purged then.
+fn on_guild_delete(state: &AppState, guild: &serenity::all::UnavailableGuild) {
+    if guild.unavailable {
+        tracing::warn!(guild_id = %guild.id, "Guild became unavailable (outage)");
+        return;
+    }
+    tracing::info!(guild_id = %guild.id, "Removed from guild; clearing its cached state");
+    state.forget_guild(guild.id);
+    RuntimeCounters::incr(&state.counters.guilds_left);
+}
+
+/// Track new invites so the next join can be diffed against them, and store them.
+async fn on_invite_create(state: &AppState, data: &serenity::all::InviteCreateEvent) -> Result<()> {
+    let Some(guild_id) = data.guild_id else {
+        return Ok(());
+    };
+    state
+        .invite_cache
+        .entry(guild_id)
+        .or_default()
+        .insert(data.code.clone(), data.uses);
+    InvitesRepo::new(&state.db)
+        .upsert_invite(
+            guild_id,
+            &data.code,
+            data.inviter.as_ref().map(|u| u.id),
+            data.created_at,
+            data.max_age,
+            data.uses,
+        )
+        .await?;
+    Ok(())
+}
+
+async fn on_invite_delete(state: &AppState, data: &serenity::all::InviteDeleteEvent) -> Result<()> {
+    let Some(guild_id) = data.guild_id else {
+        return Ok(());
+    };
+    if let Some(mut codes) = state.invite_cache.get_mut(&guild_id) {
+        codes.remove(&data.code);
+    }
+    InvitesRepo::new(&state.db)
+        .mark_deleted(guild_id, &data.code)
+        .await?;
     Ok(())
 }
+
+/// Deleted message → mod log (opt-in). Content comes from the message cache, so
+/// anything sent before the bot came online is logged by id only.
+async fn on_message_delete(
+    ctx: &Context,
+    state: &AppState,
+    guild_id: GuildId,
+    channel_id: ChannelId,
+    message_id: MessageId,
+) -> Result<()> {
+    let settings = state.settings_repo().get_cached(&guild_id).await?;
+    if !settings.message_log_enabled {
+        return Ok(());
+    }
+
+    let cached: Option<Message> = ctx.cache.message(channel_id, message_id).map(|m| m.clone());
+    if cached.as_ref().is_some_and(|m| m.author.bot) {
+        return Ok(());
+    }
+
+    let lang = settings.lang();
+    let channel = format!("<#{}>", channel_id.get());
+    let targets = settings.log_targets(LogKind::Mod);
+    let title = t("events.message_deleted.title", lang, &[]);
+    post_embed(state, guild_id, &ctx.http, &targets, settings.embed_colour(), &title, |e| {
+        let e = e.timestamp(Timestamp::now());
+        match cached {
+            Some(m) => {
+                let mut e = e
+                    .description(t(
+                        "events.message_deleted.cached",
+                        lang,
+                        &[("author", &format!("<@{}>", m.author.id.get())), ("channel", &channel)],
+                    ))
+                    .field(
+                        t("events.message.content", lang, &[]),
+                        content_field(&m.content, lang),
+                        false,
+                    );
+                if !m.attachments.is_empty() {
+                    e = e.field(
+                        t("events.message.attachments", lang, &[]),
+                        m.attachments.len().to_string(),
+                        true,
+                    );
+                }
+                e
+            }
+            None => e.description(t(
+                "events.message_deleted.uncached",
+                lang,
+                &[("id", &message_id.get()), ("channel", &channel)],
+            )),
+        }
+    })
+    .await;
+    Ok(())
+}
+
+/// Edited message → mod log (opt-in), with the old content when it was cached.
+async fn on_message_edit(
+    ctx: &Context,
+    state: &AppState,
+    old: Option<&Message>,
+    event: &MessageUpdateEvent,
+) -> Result<()> {
+    let Some(guild_id) = event.guild_id else {
+        return Ok(());
+    };
+    // Updates without content are embed unfurls / pins, not edits.
+    let Some(new_content) = event.content.as_deref() else {
+        return Ok(());
+    };
+    if event.author.as_ref().is_some_and(|a| a.bot) {
+        return Ok(());
+    }
+    if old.is_some_and(|m| m.content == new_content) {
+        return Ok(());
+    }
+
+    let settings = state.settings_repo().get_cached(&guild_id).await?;
+    if !settings.message_log_enabled {
+        return Ok(());
+    }
+
+    let lang = settings.lang();
+    let author = event
+        .author
+        .as_ref()
+        .map(|a| format!("<@{}>", a.id.get()))
+        .unwrap_or_else(|| t("events.message_edited.someone", lang, &[]));
+    let link = event.id.link(event.channel_id, Some(guild_id));
+    let before = match old {
+        Some(m) => content_field(&m.content, lang),
+        None => t("events.message_edited.uncached", lang, &[]),
+    };
+
+    let targets = settings.log_targets(LogKind::Mod);
+    let title = t("events.message_edited.title", lang, &[]);
+    post_embed(state, guild_id, &ctx.http, &targets, settings.embed_colour(), &title, |e| {
+        e.description(t(
+            "events.message_edited.body",
+            lang,
+            &[
+                ("author", &author),
+                ("channel", &format!("<#{}>", event.channel_id.get())),
+                ("link", &link),
+            ],
+        ))
+        .field(t("events.message_edited.before", lang, &[]), before, false)
+        .field(
+            t("events.message_edited.after", lang, &[]),
+            content_field(new_content, lang),
+            false,
+        )
+        .timestamp(Timestamp::now())
+    })
+    .await;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use poise::serenity_prelude::HttpBuilder;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    use super::*;
+    use crate::test_support::{GUILD, test_state};
+
+    const DELETED: ChannelId = ChannelId::new(50);
+    const NO_ACCESS: ChannelId = ChannelId::new(60);
+
+    /// Minimal stand-in for the Discord API: posts to `DELETED` get "Unknown Channel",
+    /// everything else "Missing Access". Returns the base URL to use as proxy.
+    async fn fake_discord() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut conn, _)) = listener.accept().await {
+                let mut buf = vec![0; 64 * 1024];
+                let n = conn.read(&mut buf).await.unwrap_or(0);
+                let request = String::from_utf8_lossy(&buf[..n]);
+                let (status, body) = if request.contains(&format!("/channels/{DELETED}/")) {
+                    ("404 Not Found", r#"{"code": 10003, "message": "Unknown Channel"}"#)
+                } else {
+                    ("403 Forbidden", r#"{"code": 50001, "message": "Missing Access"}"#)
+                };
+                let response = format!(
+                    "HTTP/1.1 {status}\r\nContent-Type: application/json\r\n\
+                     Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                let _ = conn.write_all(response.as_bytes()).await;
+            }
+        });
+        format!("http://{addr}")
+    }
+
+    #[tokio::test]
+    async fn unknown_channel_is_cleared_and_other_failures_kept() {
+        let state = test_state().await;
+        let repo = state.settings_repo();
+        repo.set_columns(
+            &GUILD,
+            &[
+                ("mod_log_channel_id", Some(DELETED)),
+                ("join_log_channel_id", Some(DELETED)),
+            ],
+            &[],
+            &[(LogKind::Mod, &[NO_ACCESS])],
+        )
+        .await
+        .unwrap();
+        let http = HttpBuilder::new("Bot test")
+            .proxy(fake_discord().await)
+            .ratelimiter_disabled(true)
+            .build();
+
+        let targets = repo.get_cached(&GUILD).await.unwrap().log_targets(LogKind::Mod);
+        let outcomes = post_embed(&state, GUILD, &http, &targets, None, "test", |e| e).await;
+        assert_eq!(outcomes.len(), 2);
+        assert!(outcomes.iter().all(|(_, sent)| sent.is_err()));
+
+        // The deleted channel is gone from every log; the one we merely can't post to stays.
+        let after = repo.get_cached(&GUILD).await.unwrap();
+        assert_eq!(after.mod_log, None);
+        assert_eq!(after.join_log, None);
+        assert_eq!(after.targets(LogKind::Mod), [NO_ACCESS]);
+    }
+}
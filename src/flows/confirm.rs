@@ -0,0 +1,153 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use poise::serenity_prelude as serenity;
+use serenity::all::{
+    ButtonStyle, ComponentInteraction, CreateActionRow, CreateButton, CreateEmbed,
+};
+
+use crate::flows::{ComponentFlow, FlowIds, FlowTimeout, FlowView, Surface, UiHandle, run};
+use crate::state::Ctx;
+
+const YES_ID: &str = "yes";
+const NO_ID: &str = "no";
+
+/// How long the user has to answer before it counts as "no".
+const CONFIRM_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Yes/No prompt that resolves to a `bool` (timeout = no).
+pub struct Confirm {
+    ids: FlowIds,
+    prompt: String,
+    answer: Option<bool>,
+}
+
+impl Confirm {
+    pub fn new(prompt: impl Into<String>) -> Self {
+        Self {
+            ids: FlowIds::new(),
+            prompt: prompt.into(),
+            answer: None,
+        }
+    }
+
+    /// `true` only if the user explicitly clicked Yes.
+    pub fn confirmed(&self) -> bool {
+        self.answer.unwrap_or(false)
+    }
+
+    /// Take the answer carried by a click on `custom_id`; `None` (still unanswered) for
+    /// ids that aren't this prompt's Yes/No.
+    fn record(&mut self, custom_id: &str) -> Option<bool> {
+        let yes = match self.ids.strip(custom_id) {
+            Some(YES_ID) => true,
+            Some(NO_ID) => false,
+            _ => return None,
+        };
+        self.answer = Some(yes);
+        Some(yes)
+    }
+
+    fn view(&self, status: &str, with_buttons: bool) -> FlowView {
+        let embed = CreateEmbed::new()
+            .title("Are you sure?")
+            .description(format!("{}\n\n{status}", self.prompt));
+
+        let components = if with_buttons {
+            vec![CreateActionRow::Buttons(vec![
+                CreateButton::new(self.ids.id(YES_ID))
+                    .label("Yes")
+                    .style(ButtonStyle::Danger),
+                CreateButton::new(self.ids.id(NO_ID))
+                    .label("No")
+                    .style(ButtonStyle::Secondary),
+            ])]
+        } else {
+            Vec::new()
+        };
+
+        FlowView::new(embed, components)
+    }
+}
+
+#[async_trait]
+impl ComponentFlow for Confirm {
+    fn ids(&self) -> &FlowIds {
+        &self.ids
+    }
+
+    fn render(&self) -> FlowView {
+        self.view("", true)
+    }
+
+    async fn on_component(
+        &mut self,
+        ui: &mut UiHandle<'_>,
+        ci: &ComponentInteraction,
+    ) -> Result<bool> {
+        let Some(yes) = self.record(&ci.data.custom_id) else {
+            return Ok(true);
+        };
+
+        let status = if yes {
+            "✅ Confirmed."
+        } else {
+            "❎ Cancelled — nothing was changed."
+        };
+        ui.finish_with(ci, self.view(status, false)).await?;
+        Ok(false)
+    }
+
+    async fn cleanup(&mut self, ui: &mut UiHandle<'_>) -> Result<()> {
+        // Answered flows were already finalized in `on_component`.
+        if self.answer.is_none() {
+            ui.edit(self.view("⌛ Timed out — nothing was changed.", false))
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+/// Ask the invoking user to confirm `prompt` (ephemeral). Returns `false` on No or timeout.
+pub async fn confirm(ctx: Ctx<'_>, prompt: impl Into<String>) -> Result<bool> {
+    let mut flow = Confirm::new(prompt);
+    run(
+        ctx,
+        &mut flow,
+        Surface::AttachedEphemeral,
+        FlowTimeout::Fixed(CONFIRM_TIMEOUT),
+    )
+    .await?;
+    Ok(flow.confirmed())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_an_explicit_yes_confirms() {
+        let mut flow = Confirm::new("Clear?");
+        let yes = flow.ids().id(YES_ID);
+        assert_eq!(flow.record(&yes), Some(true));
+        assert!(flow.confirmed());
+
+        let mut flow = Confirm::new("Clear?");
+        let no = flow.ids().id(NO_ID);
+        assert_eq!(flow.record(&no), Some(false));
+        assert!(!flow.confirmed());
+
+        // Timeout: `run` ends without a click.
+        assert!(!Confirm::new("Clear?").confirmed());
+    }
+
+    #[test]
+    fn clicks_on_other_prompts_are_ignored() {
+        let mut flow = Confirm::new("Clear?");
+        let other = Confirm::new("Something else?");
+        assert_eq!(flow.record(&other.ids().id(YES_ID)), None);
+        assert_eq!(flow.record("yes"), None);
+        assert!(!flow.confirmed());
+    }
+}
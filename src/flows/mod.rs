@@ -0,0 +1,426 @@
+//! Interactive component flows (buttons/selects on a single message).
+//!
+//! A flow renders a [`FlowView`], then `run` feeds it every matching component
+//! interaction (and any modal it opened) until it asks to stop or the timeout elapses.
+
+use std::hash::{BuildHasher, Hasher, RandomState};
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use poise::serenity_prelude as serenity;
+use serenity::all::{
+    ChannelId, ComponentInteraction, ComponentInteractionCollector, CreateActionRow,
+    CreateEmbed, CreateInteractionResponse, CreateInteractionResponseMessage, CreateMessage,
+    CreateModal, EditMessage, GuildId, Message, MessageId, ModalInteraction,
+    ModalInteractionCollector, UserId,
+};
+use serenity::futures::{StreamExt, stream};
+use serenity::http::StatusCode;
+
+use crate::retry;
+use crate::state::Ctx;
+
+pub mod confirm;
+pub mod paginator;
+pub mod recent_members;
+pub mod role_menu;
+pub mod settings_panel;
+
+/// Where a flow's message lives.
+#[derive(Debug, Clone, Copy)]
+pub enum Surface {
+    /// Ephemeral reply to the invoking command.
+    AttachedEphemeral,
+    /// Regular message in the invoking channel, visible to everyone. The command is
+    /// deferred before posting it (so a slow render can't fail the interaction) and
+    /// then answered with an ephemeral link to the message.
+    DetachedReply,
+}
+
+/// The flow's message as `UiHandle` needs it for out-of-interaction edits.
+enum FlowMessage<'a> {
+    Attached(poise::ReplyHandle<'a>),
+    Detached(ChannelId),
+}
+
+/// How long `run` keeps listening for interactions.
+#[derive(Debug, Clone, Copy)]
+pub enum FlowTimeout {
+    /// End after this long, regardless of activity.
+    Fixed(Duration),
+    /// End after this long without any interaction (resets on every click).
+    Idle(Duration),
+    /// Never time out; the flow must end itself.
+    // No flow in the tree runs unbounded yet; kept as part of the `run` contract.
+    #[allow(dead_code)]
+    None,
+}
+
+impl FlowTimeout {
+    fn deadline_from(self, now: Instant) -> Option<Instant> {
+        match self {
+            FlowTimeout::Fixed(d) | FlowTimeout::Idle(d) => Some(now + d),
+            FlowTimeout::None => None,
+        }
+    }
+}
+
+/// Per-flow `custom_id` namespace: a random prefix on every component and modal id, so
+/// two flows running at once never see each other's clicks (`run` drops foreign ids).
+#[derive(Debug, Clone)]
+pub struct FlowIds {
+    prefix: String,
+}
+
+impl FlowIds {
+    pub fn new() -> Self {
+        // std's hasher keys are randomly seeded per instance; good enough for a nonce.
+        let nonce = RandomState::new().build_hasher().finish();
+        Self {
+            prefix: format!("{nonce:016x}:"),
+        }
+    }
+
+    /// Fixed namespace for messages that outlive the process (e.g. role menus): their
+    /// collectors are re-attached after a restart, so the ids can't be random.
+    pub fn persistent(name: &str) -> Self {
+        Self {
+            prefix: format!("{name}:"),
+        }
+    }
+
+    /// `name` namespaced for this flow; use it for every component and modal id.
+    pub fn id(&self, name: &str) -> String {
+        format!("{}{name}", self.prefix)
+    }
+
+    /// The bare name behind `custom_id`, if it belongs to this flow.
+    pub fn strip<'s>(&self, custom_id: &'s str) -> Option<&'s str> {
+        custom_id.strip_prefix(self.prefix.as_str())
+    }
+
+    fn owns(&self, custom_id: &str) -> bool {
+        custom_id.starts_with(self.prefix.as_str())
+    }
+}
+
+impl Default for FlowIds {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// One rendered state of a flow.
+#[derive(Clone, Default)]
+pub struct FlowView {
+    pub embed: CreateEmbed,
+    pub components: Vec<CreateActionRow>,
+}
+
+impl FlowView {
+    pub fn new(embed: CreateEmbed, components: Vec<CreateActionRow>) -> Self {
+        Self { embed, components }
+    }
+}
+
+/// Post `view` the way `Surface::DetachedReply` does: defer the command, send a regular
+/// message in the invoking channel and answer with an ephemeral link to it.
+pub async fn post_detached(ctx: Ctx<'_>, view: FlowView) -> Result<Message> {
+    ctx.defer_ephemeral().await?;
+    let msg = ctx
+        .channel_id()
+        .send_message(
+            ctx.http(),
+            CreateMessage::new()
+                .embed(view.embed)
+                .components(view.components),
+        )
+        .await?;
+    ctx.send(
+        poise::CreateReply::default()
+            .content(format!("📌 Posted here: {}", msg.link()))
+            .ephemeral(true),
+    )
+    .await?;
+    Ok(msg)
+}
+
+/// Handle to the flow's message, passed to callbacks for responding/editing.
+pub struct UiHandle<'a> {
+    ctx: Ctx<'a>,
+    message: FlowMessage<'a>,
+    /// Fixed for the flow's lifetime: `UiHandle` only ever edits the message, never
+    /// reposts it, so `run` can filter its collectors on this id once.
+    message_id: MessageId,
+    pub author_id: UserId,
+    pub guild_id: Option<GuildId>,
+}
+
+impl<'a> UiHandle<'a> {
+    async fn first_render(ctx: Ctx<'a>, surface: Surface, view: FlowView) -> Result<Self> {
+        let (message, message_id) = match surface {
+            Surface::AttachedEphemeral => {
+                let reply = ctx
+                    .send(
+                        poise::CreateReply::default()
+                            .embed(view.embed)
+                            .components(view.components)
+                            .ephemeral(true),
+                    )
+                    .await?;
+                let message_id = reply.message().await?.id;
+                (FlowMessage::Attached(reply), message_id)
+            }
+            Surface::DetachedReply => {
+                let msg = post_detached(ctx, view).await?;
+                (FlowMessage::Detached(msg.channel_id), msg.id)
+            }
+        };
+
+        Ok(Self {
+            ctx,
+            message,
+            message_id,
+            author_id: ctx.author().id,
+            guild_id: ctx.guild_id(),
+        })
+    }
+
+    /// Acknowledge `ci` by replacing the message with `view`.
+    pub async fn update(&self, ci: &ComponentInteraction, view: FlowView) -> Result<()> {
+        ci.create_response(
+            self.ctx.http(),
+            CreateInteractionResponse::UpdateMessage(
+                CreateInteractionResponseMessage::new()
+                    .embed(view.embed)
+                    .components(view.components),
+            ),
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Acknowledge a modal submission (opened from this message) by replacing the message.
+    pub async fn update_from_modal(&self, mi: &ModalInteraction, view: FlowView) -> Result<()> {
+        mi.create_response(
+            self.ctx.http(),
+            CreateInteractionResponse::UpdateMessage(
+                CreateInteractionResponseMessage::new()
+                    .embed(view.embed)
+                    .components(view.components),
+            ),
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Acknowledge `ci` with an ephemeral note to the clicker, leaving the message as is.
+    pub async fn deny(&self, ci: &ComponentInteraction, text: &str) -> Result<()> {
+        ci.create_response(
+            self.ctx.http(),
+            CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::new()
+                    .content(text)
+                    .ephemeral(true),
+            ),
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Acknowledge `ci` by opening `modal`; the submission arrives via `ComponentFlow::on_modal`.
+    pub async fn open_modal(&self, ci: &ComponentInteraction, modal: CreateModal) -> Result<()> {
+        ci.create_response(self.ctx.http(), CreateInteractionResponse::Modal(modal))
+            .await?;
+        Ok(())
+    }
+
+    pub fn ctx(&self) -> Ctx<'a> {
+        self.ctx
+    }
+
+    /// Pin the flow's message (detached surfaces only; ephemeral replies can't be pinned).
+    /// Failing, e.g. without Manage Messages, isn't fatal: the invoker gets a note and the
+    /// flow runs unpinned. Returns whether the message is now pinned.
+    async fn pin(&self) -> bool {
+        let FlowMessage::Detached(channel_id) = self.message else {
+            return false;
+        };
+        let Err(e) = channel_id.pin(self.ctx.http(), self.message_id).await else {
+            return true;
+        };
+        tracing::warn!(%channel_id, error = %e, "Failed to pin flow message");
+        let note = if retry::status_of(&e) == Some(StatusCode::FORBIDDEN) {
+            "⚠️ Couldn't pin this panel: I need **Manage Messages** in this channel."
+        } else {
+            "⚠️ Couldn't pin this panel."
+        };
+        let reply = poise::CreateReply::default().content(note).ephemeral(true);
+        if let Err(e) = self.ctx.send(reply).await {
+            tracing::warn!(%channel_id, error = %e, "Failed to report pin failure");
+        }
+        false
+    }
+
+    /// Undo `pin` once the flow is over.
+    async fn unpin(&self) {
+        if let FlowMessage::Detached(channel_id) = self.message
+            && let Err(e) = channel_id.unpin(self.ctx.http(), self.message_id).await
+        {
+            tracing::warn!(%channel_id, error = %e, "Failed to unpin flow message");
+        }
+    }
+
+    /// Acknowledge `ci` with a final `view`; components are removed.
+    pub async fn finish_with(&self, ci: &ComponentInteraction, view: FlowView) -> Result<()> {
+        self.update(ci, FlowView::new(view.embed, Vec::new())).await
+    }
+
+    /// Edit the message outside of an interaction (e.g. on timeout).
+    /// Transient Discord errors are retried, since nothing else will redo the edit.
+    pub async fn edit(&mut self, view: FlowView) -> Result<()> {
+        match &self.message {
+            FlowMessage::Attached(handle) => {
+                let reply = poise::CreateReply::default()
+                    .embed(view.embed)
+                    .components(view.components);
+                retry::with_backoff("Editing flow message", || {
+                    handle.edit(self.ctx, reply.clone())
+                })
+                .await?;
+            }
+            FlowMessage::Detached(channel_id) => {
+                let edit = EditMessage::new()
+                    .embed(view.embed)
+                    .components(view.components);
+                retry::with_backoff("Editing flow message", || {
+                    channel_id.edit_message(self.ctx.http(), self.message_id, edit.clone())
+                })
+                .await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+pub trait ComponentFlow: Send {
+    /// Namespace for this flow's component/modal ids; `run` only delivers ids in it.
+    fn ids(&self) -> &FlowIds;
+
+    /// Current state as an embed + components (ids built with `ids().id(..)`).
+    fn render(&self) -> FlowView;
+
+    /// Whether users other than the invoker may interact (for flows on a detached
+    /// surface). Shared flows must check the clicker's permissions themselves.
+    fn shared(&self) -> bool {
+        false
+    }
+
+    /// Whether `run` pins the flow's message while it runs (detached surfaces only)
+    /// and unpins it once the flow ends.
+    fn pinned(&self) -> bool {
+        false
+    }
+
+    /// Handle one component interaction. Return `Ok(true)` to keep running, `Ok(false)` to end.
+    /// Implementations must acknowledge `ci` (e.g. via `UiHandle::update`); match on
+    /// `ids().strip(&ci.data.custom_id)` to get the bare id.
+    async fn on_component(
+        &mut self,
+        ui: &mut UiHandle<'_>,
+        ci: &ComponentInteraction,
+    ) -> Result<bool>;
+
+    /// Handle a modal submitted from a modal opened with `UiHandle::open_modal`.
+    /// Same contract as `on_component`. Default: ignore it and keep running.
+    async fn on_modal(
+        &mut self,
+        _ui: &mut UiHandle<'_>,
+        _mi: &ModalInteraction,
+    ) -> Result<bool> {
+        Ok(true)
+    }
+
+    /// Runs once when the flow ends (finished or timed out). Default: strip the components.
+    async fn cleanup(&mut self, ui: &mut UiHandle<'_>) -> Result<()> {
+        let view = self.render();
+        ui.edit(FlowView::new(view.embed, Vec::new())).await
+    }
+}
+
+/// What `run` feeds into a flow.
+enum FlowEvent {
+    Component(ComponentInteraction),
+    Modal(ModalInteraction),
+}
+
+/// Render `flow` on `surface` and drive it until it finishes or `timeout` elapses.
+///
+/// Only interactions from the invoking user (anyone, for `shared` flows), in the invoking
+/// guild, on the flow's own message (or modals opened from it) and carrying the flow's
+/// id prefix are delivered.
+/// `cleanup` always runs, including on timeout, followed by the unpin of a `pinned` flow.
+pub async fn run<F: ComponentFlow>(
+    ctx: Ctx<'_>,
+    flow: &mut F,
+    surface: Surface,
+    timeout: FlowTimeout,
+) -> Result<()> {
+    let mut ui = UiHandle::first_render(ctx, surface, flow.render()).await?;
+    let pinned = flow.pinned() && ui.pin().await;
+
+    let guild_id = ui.guild_id;
+    let component_ids = flow.ids().clone();
+    let modal_ids = component_ids.clone();
+    let mut components = ComponentInteractionCollector::new(ctx.serenity_context())
+        .message_id(ui.message_id)
+        .filter(move |ci| ci.guild_id == guild_id && component_ids.owns(&ci.data.custom_id));
+    let mut modals = ModalInteractionCollector::new(ctx.serenity_context())
+        .message_id(ui.message_id)
+        .filter(move |mi| mi.guild_id == guild_id && modal_ids.owns(&mi.data.custom_id));
+    if !flow.shared() {
+        components = components.author_id(ui.author_id);
+        modals = modals.author_id(ui.author_id);
+    }
+    let components = components.stream().map(FlowEvent::Component);
+    let modals = modals.stream().map(FlowEvent::Modal);
+    let mut interactions = Box::pin(stream::select(components, modals));
+
+    let mut deadline = timeout.deadline_from(Instant::now());
+    let mut res = Ok(());
+    loop {
+        let next = match deadline {
+            Some(at) => match tokio::time::timeout_at(at.into(), interactions.next()).await {
+                Ok(next) => next,
+                Err(_) => break, // timed out
+            },
+            None => interactions.next().await,
+        };
+        let Some(event) = next else { break };
+
+        let handled = match &event {
+            FlowEvent::Component(ci) => flow.on_component(&mut ui, ci).await,
+            FlowEvent::Modal(mi) => flow.on_modal(&mut ui, mi).await,
+        };
+        match handled {
+            Ok(true) => {}
+            Ok(false) => break,
+            Err(e) => {
+                res = Err(e);
+                break;
+            }
+        }
+
+        if let FlowTimeout::Idle(_) = timeout {
+            deadline = timeout.deadline_from(Instant::now());
+        }
+    }
+
+    let cleaned = flow.cleanup(&mut ui).await;
+    if pinned {
+        ui.unpin().await;
+    }
+    res.and(cleaned)
+}
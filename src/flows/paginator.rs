@@ -0,0 +1,105 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use poise::serenity_prelude as serenity;
+use serenity::all::{
+    ButtonStyle, ComponentInteraction, CreateActionRow, CreateButton, CreateEmbed,
+    CreateEmbedFooter,
+};
+
+use crate::flows::{ComponentFlow, FlowIds, FlowTimeout, FlowView, Surface, UiHandle, run};
+use crate::state::Ctx;
+
+const PREV_ID: &str = "prev";
+const NEXT_ID: &str = "next";
+
+/// How long a paginator stays interactive after the last click.
+const PAGINATOR_IDLE: Duration = Duration::from_secs(300);
+
+/// Shows one embed at a time with ◀/▶ buttons and a page indicator.
+pub struct Paginator {
+    ids: FlowIds,
+    pages: Vec<CreateEmbed>,
+    index: usize,
+}
+
+impl Paginator {
+    pub fn new(pages: Vec<CreateEmbed>) -> Self {
+        Self {
+            ids: FlowIds::new(),
+            pages,
+            index: 0,
+        }
+    }
+}
+
+#[async_trait]
+impl ComponentFlow for Paginator {
+    fn ids(&self) -> &FlowIds {
+        &self.ids
+    }
+
+    fn render(&self) -> FlowView {
+        let total = self.pages.len();
+        let embed = self
+            .pages
+            .get(self.index)
+            .cloned()
+            .unwrap_or_default()
+            .footer(CreateEmbedFooter::new(format!(
+                "Page {}/{}",
+                self.index + 1,
+                total.max(1)
+            )));
+
+        let buttons = vec![
+            CreateButton::new(self.ids.id(PREV_ID))
+                .label("◀")
+                .style(ButtonStyle::Secondary)
+                .disabled(self.index == 0),
+            CreateButton::new(self.ids.id(NEXT_ID))
+                .label("▶")
+                .style(ButtonStyle::Secondary)
+                .disabled(self.index + 1 >= total),
+        ];
+
+        FlowView::new(embed, vec![CreateActionRow::Buttons(buttons)])
+    }
+
+    async fn on_component(
+        &mut self,
+        ui: &mut UiHandle<'_>,
+        ci: &ComponentInteraction,
+    ) -> Result<bool> {
+        match self.ids.strip(&ci.data.custom_id) {
+            Some(PREV_ID) => self.index = self.index.saturating_sub(1),
+            Some(NEXT_ID) => self.index = (self.index + 1).min(self.pages.len().saturating_sub(1)),
+            _ => {}
+        }
+        ui.update(ci, self.render()).await?;
+        Ok(true)
+    }
+}
+
+/// Send `pages` as an ephemeral paginator (or a plain reply when there's only one page).
+pub async fn paginate(ctx: Ctx<'_>, pages: Vec<CreateEmbed>) -> Result<()> {
+    match pages.len() {
+        0 => Ok(()),
+        1 => {
+            let page = pages.into_iter().next().unwrap_or_default();
+            ctx.send(poise::CreateReply::default().embed(page)).await?;
+            Ok(())
+        }
+        _ => {
+            let mut flow = Paginator::new(pages);
+            run(
+                ctx,
+                &mut flow,
+                Surface::AttachedEphemeral,
+                FlowTimeout::Idle(PAGINATOR_IDLE),
+            )
+            .await
+        }
+    }
+}
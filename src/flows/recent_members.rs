@@ -0,0 +1,148 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use poise::serenity_prelude as serenity;
+use serenity::all::{
+    ButtonStyle, ComponentInteraction, Colour, CreateActionRow, CreateButton, CreateEmbed,
+    CreateEmbedFooter, GuildId,
+};
+
+use crate::commands::{guild_colour, with_colour};
+use crate::flows::{ComponentFlow, FlowIds, FlowTimeout, FlowView, Surface, UiHandle, run};
+use crate::repos::{MembershipStore, UserSummary};
+use crate::state::Ctx;
+
+const PREV_ID: &str = "prev";
+const NEXT_ID: &str = "next";
+
+const PAGE_SIZE: i64 = 10;
+
+/// How long the browser stays interactive after the last click.
+const RECENT_IDLE: Duration = Duration::from_secs(300);
+
+/// `/member recent`: newest members first, fetching older pages on demand.
+///
+/// Pages already seen are kept so ◀ doesn't hit the database; ▶ passes the smallest
+/// `last_row_id` of the last loaded page as the cursor.
+pub struct RecentMembers {
+    ids: FlowIds,
+    guild_id: GuildId,
+    colour: Option<Colour>,
+    pages: Vec<Vec<UserSummary>>,
+    index: usize,
+    /// Whether rows older than the last loaded page exist.
+    has_more: bool,
+}
+
+impl RecentMembers {
+    /// Load the first page.
+    pub async fn load(ctx: Ctx<'_>, guild_id: GuildId) -> Result<Self> {
+        let mut flow = Self {
+            ids: FlowIds::new(),
+            guild_id,
+            colour: guild_colour(ctx).await,
+            pages: Vec::new(),
+            index: 0,
+            has_more: true,
+        };
+        flow.fetch_next(ctx).await?;
+        Ok(flow)
+    }
+
+    /// Append the next older page (one extra row tells us whether more remain).
+    async fn fetch_next(&mut self, ctx: Ctx<'_>) -> Result<()> {
+        let cursor = self
+            .pages
+            .last()
+            .and_then(|page| page.iter().map(|r| r.last_row_id).min());
+
+        let mut rows = ctx.data().memberships()
+            .recent_user_summaries_page(self.guild_id, PAGE_SIZE + 1, cursor)
+            .await?;
+
+        self.has_more = rows.len() as i64 > PAGE_SIZE;
+        rows.truncate(PAGE_SIZE as usize);
+        if !rows.is_empty() {
+            self.pages.push(rows);
+        }
+        Ok(())
+    }
+
+    fn on_last_loaded_page(&self) -> bool {
+        self.index + 1 >= self.pages.len()
+    }
+}
+
+#[async_trait]
+impl ComponentFlow for RecentMembers {
+    fn ids(&self) -> &FlowIds {
+        &self.ids
+    }
+
+    fn render(&self) -> FlowView {
+        let description = match self.pages.get(self.index) {
+            Some(page) => page
+                .iter()
+                .map(|r| {
+                    let status = if r.is_present() { "present" } else { "left" };
+                    format!("• {} — <@{}> · {status}", r.label(), r.user_id)
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+            None => "No members recorded yet.".to_string(),
+        };
+
+        let embed = with_colour(
+            CreateEmbed::new()
+                .title("Recently active members")
+                .description(description)
+                .footer(CreateEmbedFooter::new(format!("Page {}", self.index + 1))),
+            self.colour,
+        );
+
+        let buttons = vec![
+            CreateButton::new(self.ids.id(PREV_ID))
+                .label("◀")
+                .style(ButtonStyle::Secondary)
+                .disabled(self.index == 0),
+            CreateButton::new(self.ids.id(NEXT_ID))
+                .label("▶")
+                .style(ButtonStyle::Secondary)
+                .disabled(self.on_last_loaded_page() && !self.has_more),
+        ];
+
+        FlowView::new(embed, vec![CreateActionRow::Buttons(buttons)])
+    }
+
+    async fn on_component(
+        &mut self,
+        ui: &mut UiHandle<'_>,
+        ci: &ComponentInteraction,
+    ) -> Result<bool> {
+        match self.ids.strip(&ci.data.custom_id) {
+            Some(PREV_ID) => self.index = self.index.saturating_sub(1),
+            Some(NEXT_ID) => {
+                if self.on_last_loaded_page() && self.has_more {
+                    self.fetch_next(ui.ctx()).await?;
+                }
+                self.index = (self.index + 1).min(self.pages.len().saturating_sub(1));
+            }
+            _ => {}
+        }
+        ui.update(ci, self.render()).await?;
+        Ok(true)
+    }
+}
+
+/// Show the recent-members browser for `guild_id` (ephemeral, idle timeout).
+pub async fn browse_recent_members(ctx: Ctx<'_>, guild_id: GuildId) -> Result<()> {
+    let mut flow = RecentMembers::load(ctx, guild_id).await?;
+    run(
+        ctx,
+        &mut flow,
+        Surface::AttachedEphemeral,
+        FlowTimeout::Idle(RECENT_IDLE),
+    )
+    .await
+}
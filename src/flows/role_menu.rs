@@ -0,0 +1,190 @@
+//! Self-assign role menus: a public message with a select of roles; picking a role
+//! toggles it on the member who picked it.
+//!
+//! Unlike other flows a menu outlives both the command that posted it and the process,
+//! so `flows::run` (which lives inside a command invocation) can't drive it. Each menu
+//! gets its own collector loop instead: started by `/role-menu create` and re-attached
+//! for every stored menu by `handle_ready`.
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use poise::serenity_prelude as serenity;
+use serenity::all::{
+    Cache, Colour, ComponentInteraction, ComponentInteractionCollector,
+    ComponentInteractionDataKind, CreateActionRow, CreateAllowedMentions, CreateEmbed,
+    CreateSelectMenu, CreateSelectMenuKind, CreateSelectMenuOption, EditInteractionResponse,
+    GuildId, RoleId,
+};
+use serenity::futures::StreamExt;
+use serenity::prelude::Context;
+
+use crate::commands::with_colour;
+use crate::flows::{FlowIds, FlowView};
+use crate::repos::RoleMenu;
+use crate::state::AppState;
+use crate::strings::{DEFAULT_LANG, t};
+
+const PICK_ID: &str = "pick";
+
+fn ids() -> FlowIds {
+    FlowIds::persistent("role-menu")
+}
+
+/// The menu message (rendered before it is posted, so before it has an id): `roles`
+/// listed in the embed and offered in the select. Role names come from the cache;
+/// roles that no longer exist are left out of the select.
+pub fn render(
+    guild_id: GuildId,
+    title: &str,
+    roles: &[RoleId],
+    cache: &Cache,
+    colour: Option<Colour>,
+    lang: &str,
+) -> FlowView {
+    let options: Vec<CreateSelectMenuOption> = {
+        let guild = cache.guild(guild_id);
+        roles
+            .iter()
+            .filter_map(|id| {
+                let role = guild.as_ref()?.roles.get(id)?;
+                Some(CreateSelectMenuOption::new(role.name.clone(), id.to_string()))
+            })
+            .collect()
+    };
+
+    let roles = roles
+        .iter()
+        .map(|id| format!("• <@&{id}>"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let embed = with_colour(
+        CreateEmbed::new()
+            .title(title)
+            .description(t("role_menu.instructions", lang, &[("roles", &roles)])),
+        colour,
+    );
+
+    if options.is_empty() {
+        return FlowView::new(embed, Vec::new());
+    }
+    let max = options.len() as u8;
+    let kind = CreateSelectMenuKind::String { options };
+    let select = CreateSelectMenu::new(ids().id(PICK_ID), kind)
+        .placeholder(t("role_menu.placeholder", lang, &[]))
+        .min_values(1)
+        .max_values(max);
+    FlowView::new(embed, vec![CreateActionRow::SelectMenu(select)])
+}
+
+/// Serve picks on `menu`'s message until shutdown, until the menu is removed, or until
+/// it is attached again (see `AppState::track_role_menu`).
+pub async fn attach(ctx: Context, state: Arc<AppState>, menu: RoleMenu) {
+    let stop = state.track_role_menu(menu.guild_id, menu.message_id);
+    let pick_id = ids().id(PICK_ID);
+    let guild_id = menu.guild_id;
+    let mut picks = ComponentInteractionCollector::new(&ctx)
+        .message_id(menu.message_id)
+        .filter(move |ci| ci.guild_id == Some(guild_id) && ci.data.custom_id == pick_id)
+        .stream();
+
+    loop {
+        let ci = tokio::select! {
+            _ = stop.cancelled() => break,
+            next = picks.next() => match next {
+                Some(ci) => ci,
+                None => break,
+            },
+        };
+        let lang = match state.settings_repo().get_cached(&guild_id).await {
+            Ok(settings) => settings.lang(),
+            Err(_) => DEFAULT_LANG,
+        };
+        if let Err(e) = toggle_roles(&ctx, &menu, &ci, lang).await {
+            tracing::warn!(
+                %guild_id,
+                message_id = %menu.message_id,
+                error = format!("{e:#}"),
+                "Role menu pick failed"
+            );
+        }
+    }
+}
+
+/// Toggle each picked role on the member and tell them (ephemerally) what changed.
+async fn toggle_roles(
+    ctx: &Context,
+    menu: &RoleMenu,
+    ci: &ComponentInteraction,
+    lang: &str,
+) -> Result<()> {
+    // Role edits can take a while; acknowledge first so the interaction doesn't expire.
+    ci.defer_ephemeral(&ctx.http).await?;
+    let Some(member) = ci.member.as_ref() else {
+        return Ok(());
+    };
+    let ComponentInteractionDataKind::StringSelect { values } = &ci.data.kind else {
+        return Ok(());
+    };
+    let picked = values
+        .iter()
+        .filter_map(|v| v.parse::<u64>().ok().filter(|&id| id != 0))
+        .map(RoleId::new)
+        .filter(|role| menu.roles.contains(role));
+
+    // Shown in the guild's audit log for every grant/removal.
+    let audit_reason = t("role_menu.audit_reason", lang, &[]);
+    let mut lines = Vec::new();
+    for role in picked {
+        let (res, done) = if member.roles.contains(&role) {
+            let res = ctx
+                .http
+                .remove_member_role(menu.guild_id, member.user.id, role, Some(&audit_reason))
+                .await;
+            (res, "role_menu.removed")
+        } else {
+            let res = ctx
+                .http
+                .add_member_role(menu.guild_id, member.user.id, role, Some(&audit_reason))
+                .await;
+            (res, "role_menu.added")
+        };
+        let mention = format!("<@&{role}>");
+        lines.push(match res {
+            Ok(()) => t(done, lang, &[("role", &mention)]),
+            Err(e) => {
+                let error = describe_role_error(&e, lang);
+                t("role_menu.failed", lang, &[("role", &mention), ("error", &error)])
+            }
+        });
+    }
+    if lines.is_empty() {
+        lines.push(t("role_menu.nothing_changed", lang, &[]));
+    }
+
+    ci.edit_response(
+        &ctx.http,
+        EditInteractionResponse::new()
+            .content(lines.join("\n"))
+            .allowed_mentions(CreateAllowedMentions::new()),
+    )
+    .await?;
+    Ok(())
+}
+
+/// Member-facing reason a role couldn't be changed.
+fn describe_role_error(err: &serenity::Error, lang: &str) -> String {
+    let code = match err {
+        serenity::Error::Http(serenity::HttpError::UnsuccessfulRequest(r)) => Some(r.error.code),
+        _ => None,
+    };
+    match code {
+        // Missing Permissions: no Manage Roles, or the role sits above the bot's highest role.
+        Some(50013) => t("role_menu.error.missing_perms", lang, &[]),
+        Some(10011) => t("role_menu.error.unknown_role", lang, &[]),
+        _ => {
+            tracing::warn!(error = %err, "Role menu role change failed");
+            t("role_menu.error.rejected", lang, &[])
+        }
+    }
+}
@@ -0,0 +1,517 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use poise::serenity_prelude as serenity;
+use serenity::all::{
+    ActionRowComponent, ButtonStyle, ChannelId, ChannelType, ComponentInteraction,
+    ComponentInteractionDataKind, CreateActionRow, CreateButton, CreateEmbed, CreateInputText,
+    CreateModal, CreateSelectMenu, CreateSelectMenuKind, GuildId, InputTextStyle,
+    ModalInteraction,
+};
+
+use crate::flows::{ComponentFlow, FlowIds, FlowTimeout, FlowView, Surface, UiHandle, run};
+use crate::repos::{GuildSettings, GuildSettingsRepo, LogKind, MAX_LOG_CHANNELS};
+use crate::state::Ctx;
+use crate::strings::t;
+
+const JOIN_ID: &str = "join";
+const LEAVE_ID: &str = "leave";
+const MOD_ID: &str = "mod";
+const JOIN_MSG_ID: &str = "join_msg";
+const LEAVE_MSG_ID: &str = "leave_msg";
+const CLEAR_ALL_ID: &str = "clear_all";
+const SAVE_ID: &str = "save";
+const CANCEL_ID: &str = "cancel";
+
+const JOIN_MSG_MODAL_ID: &str = "join_msg_modal";
+const LEAVE_MSG_MODAL_ID: &str = "leave_msg_modal";
+/// Inside the modal only, so it needs no namespace.
+const TEMPLATE_INPUT_ID: &str = "template";
+
+/// Discord caps text inputs at 4000 chars.
+const TEMPLATE_MAX_CHARS: u16 = 4000;
+
+const PANEL_IDLE_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Channel kinds the log selects offer by default.
+const LOG_CHANNEL_TYPES: [ChannelType; 3] =
+    [ChannelType::Text, ChannelType::News, ChannelType::Forum];
+/// Added with `/settings log-channel-types extended:true`: the chats built into voice
+/// and stage channels. Whether the bot can post there is checked by `/settings test`.
+const EXTENDED_LOG_CHANNEL_TYPES: [ChannelType; 2] = [ChannelType::Voice, ChannelType::Stage];
+
+/// A pending edit to one setting; nothing is written until Save.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum DraftValue<T> {
+    #[default]
+    Keep,
+    Set(T),
+    Clear,
+}
+
+impl<T: Clone> DraftValue<T> {
+    /// The value this draft would leave behind, given the saved one.
+    fn resolve(&self, saved: Option<&T>) -> Option<T> {
+        match self {
+            DraftValue::Keep => saved.cloned(),
+            DraftValue::Set(v) => Some(v.clone()),
+            DraftValue::Clear => None,
+        }
+    }
+
+    fn is_dirty(&self) -> bool {
+        !matches!(self, DraftValue::Keep)
+    }
+}
+
+/// Interactive `/settings panel`: pick log channels and edit message templates, then Save.
+///
+/// Each log type takes up to `MAX_LOG_CHANNELS` channels; the first picked becomes the
+/// primary (`*_log_channel_id`), the rest are stored as mirrors in `log_routes`.
+///
+/// A `shared` panel is posted in the channel so several admins can fill it in together;
+/// anyone with Manage Server may click it. It stays pinned while open (given Manage Messages).
+pub struct SettingsPanel {
+    ids: FlowIds,
+    guild_id: GuildId,
+    shared: bool,
+    saved: GuildSettings,
+    join_log: DraftValue<Vec<ChannelId>>,
+    leave_log: DraftValue<Vec<ChannelId>>,
+    mod_log: DraftValue<Vec<ChannelId>>,
+    join_template: DraftValue<String>,
+    leave_template: DraftValue<String>,
+    finished: bool,
+    lang: &'static str,
+}
+
+impl SettingsPanel {
+    pub fn new(guild_id: GuildId, saved: GuildSettings, shared: bool) -> Self {
+        Self {
+            ids: FlowIds::new(),
+            guild_id,
+            shared,
+            lang: saved.lang(),
+            saved,
+            join_log: DraftValue::Keep,
+            leave_log: DraftValue::Keep,
+            mod_log: DraftValue::Keep,
+            join_template: DraftValue::Keep,
+            leave_template: DraftValue::Keep,
+            finished: false,
+        }
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.join_log.is_dirty()
+            || self.leave_log.is_dirty()
+            || self.mod_log.is_dirty()
+            || self.join_template.is_dirty()
+            || self.leave_template.is_dirty()
+    }
+
+    fn log_draft(&self, kind: LogKind) -> &DraftValue<Vec<ChannelId>> {
+        match kind {
+            LogKind::Join => &self.join_log,
+            LogKind::Leave => &self.leave_log,
+            LogKind::Mod => &self.mod_log,
+        }
+    }
+
+    /// Channels `kind` would post to after Save (primary first).
+    fn resolved_logs(&self, kind: LogKind) -> Vec<ChannelId> {
+        self.log_draft(kind)
+            .resolve(Some(&self.saved.targets(kind)))
+            .unwrap_or_default()
+    }
+
+    fn build_embed(&self, status: &str) -> CreateEmbed {
+        let lang = self.lang;
+        let channel = |kind: LogKind| {
+            let resolved = self.resolved_logs(kind);
+            let shown = if resolved.is_empty() {
+                t("settings.show.not_set", lang, &[])
+            } else {
+                resolved
+                    .iter()
+                    .map(|c| format!("<#{}>", c.get()))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            };
+            mark(shown, self.log_draft(kind).is_dirty(), lang)
+        };
+        let template = |draft: &DraftValue<String>, saved: Option<&String>| {
+            let shown = draft
+                .resolve(saved)
+                .map(|t| format!("`{}`", t.replace('`', "'")))
+                .unwrap_or_else(|| t("settings.panel.default", lang, &[]));
+            mark(shown, draft.is_dirty(), lang)
+        };
+
+        let mut desc = t(
+            "settings.panel.body",
+            lang,
+            &[
+                ("join", &channel(LogKind::Join)),
+                ("leave", &channel(LogKind::Leave)),
+                ("mod", &channel(LogKind::Mod)),
+                (
+                    "join_msg",
+                    &template(&self.join_template, self.saved.join_message_template.as_ref()),
+                ),
+                (
+                    "leave_msg",
+                    &template(&self.leave_template, self.saved.leave_message_template.as_ref()),
+                ),
+            ],
+        );
+        if !status.is_empty() {
+            desc.push_str("\n\n");
+            desc.push_str(status);
+        }
+
+        CreateEmbed::new()
+            .title(t("settings.panel.title", lang, &[]))
+            .description(desc)
+    }
+
+    fn log_channel_types(&self) -> Vec<ChannelType> {
+        let mut types = LOG_CHANNEL_TYPES.to_vec();
+        if self.saved.extended_log_channels {
+            types.extend(EXTENDED_LOG_CHANNEL_TYPES);
+        }
+        types
+    }
+
+    fn build_components(&self) -> Vec<CreateActionRow> {
+        let label = |key: &str| t(key, self.lang, &[]);
+        let select = |id: &str, kind: LogKind| {
+            let current = self.resolved_logs(kind);
+            CreateActionRow::SelectMenu(
+                CreateSelectMenu::new(
+                    self.ids.id(id),
+                    CreateSelectMenuKind::Channel {
+                        channel_types: Some(self.log_channel_types()),
+                        default_channels: (!current.is_empty()).then_some(current),
+                    },
+                )
+                .placeholder(label(&format!("settings.panel.pick.{}", kind.as_str())))
+                .min_values(0)
+                .max_values(MAX_LOG_CHANNELS as u8),
+            )
+        };
+
+        vec![
+            select(JOIN_ID, LogKind::Join),
+            select(LEAVE_ID, LogKind::Leave),
+            select(MOD_ID, LogKind::Mod),
+            CreateActionRow::Buttons(vec![
+                CreateButton::new(self.ids.id(JOIN_MSG_ID))
+                    .label(label("settings.panel.edit_join_msg"))
+                    .style(ButtonStyle::Secondary),
+                CreateButton::new(self.ids.id(LEAVE_MSG_ID))
+                    .label(label("settings.panel.edit_leave_msg"))
+                    .style(ButtonStyle::Secondary),
+            ]),
+            CreateActionRow::Buttons(vec![
+                CreateButton::new(self.ids.id(SAVE_ID))
+                    .label(label("settings.panel.save"))
+                    .style(ButtonStyle::Success)
+                    .disabled(!self.is_dirty()),
+                CreateButton::new(self.ids.id(CLEAR_ALL_ID))
+                    .label(label("settings.panel.clear_all"))
+                    .style(ButtonStyle::Danger),
+                CreateButton::new(self.ids.id(CANCEL_ID))
+                    .label(label("settings.panel.cancel"))
+                    .style(ButtonStyle::Secondary),
+            ]),
+        ]
+    }
+
+    fn view(&self, status: &str, with_components: bool) -> FlowView {
+        let components = if with_components {
+            self.build_components()
+        } else {
+            Vec::new()
+        };
+        FlowView::new(self.build_embed(status), components)
+    }
+
+    fn template_modal(
+        &self,
+        modal_id: &str,
+        title_key: &str,
+        current: Option<String>,
+    ) -> CreateModal {
+        let lang = self.lang;
+        let label = t("settings.panel.template", lang, &[]);
+        let mut input = CreateInputText::new(InputTextStyle::Paragraph, label, TEMPLATE_INPUT_ID)
+            .placeholder(t("settings.panel.template_example", lang, &[]))
+            .max_length(TEMPLATE_MAX_CHARS)
+            .required(false);
+        if let Some(current) = current {
+            input = input.value(current);
+        }
+        CreateModal::new(self.ids.id(modal_id), t(title_key, lang, &[]))
+            .components(vec![CreateActionRow::InputText(input)])
+    }
+
+    /// Write every non-`Keep` draft to `guild_settings` / `log_routes` (one transaction)
+    /// and describe what actually changed, diffing the stored row before and after.
+    async fn apply_changes(&self, repo: &GuildSettingsRepo<'_>) -> Result<String> {
+        let before = repo.get(&self.guild_id).await?;
+        let mut channels: Vec<(&str, Option<ChannelId>)> = Vec::new();
+        let mut routes: Vec<(LogKind, &[ChannelId])> = Vec::new();
+        for kind in LogKind::ALL {
+            let picked: &[ChannelId] = match self.log_draft(kind) {
+                DraftValue::Keep => continue,
+                DraftValue::Set(chs) => chs,
+                DraftValue::Clear => &[],
+            };
+            channels.push((kind.primary_column(), picked.first().copied()));
+            routes.push((kind, picked.get(1..).unwrap_or_default()));
+        }
+        let texts: Vec<(&str, Option<&str>)> = [
+            ("join_message_template", &self.join_template),
+            ("leave_message_template", &self.leave_template),
+        ]
+        .into_iter()
+        .filter_map(|(column, draft)| match draft {
+            DraftValue::Keep => None,
+            DraftValue::Set(t) => Some((column, Some(t.as_str()))),
+            DraftValue::Clear => Some((column, None)),
+        })
+        .collect();
+
+        repo.set_columns(&self.guild_id, &channels, &texts, &routes)
+            .await?;
+        let after = repo.get(&self.guild_id).await?;
+        Ok(save_summary(&before, &after, self.lang))
+    }
+}
+
+/// "Saved" status listing each setting as set / cleared / unchanged.
+fn save_summary(before: &GuildSettings, after: &GuildSettings, lang: &str) -> String {
+    let line = |label: String, state: String| {
+        t("settings.panel.summary_line", lang, &[("label", &label), ("state", &state)])
+    };
+    let mut lines = Vec::new();
+    let mut changed = false;
+    for kind in LogKind::ALL {
+        let (old, new) = (before.targets(kind), after.targets(kind));
+        let state = if old == new {
+            t("settings.panel.unchanged", lang, &[])
+        } else if new.is_empty() {
+            t("settings.panel.cleared", lang, &[])
+        } else {
+            let shown = new
+                .iter()
+                .map(|c| format!("<#{}>", c.get()))
+                .collect::<Vec<_>>()
+                .join(", ");
+            t("settings.panel.set_to", lang, &[("channels", &shown)])
+        };
+        changed |= old != new;
+        let label = t(&format!("settings.log_label.{}", kind.as_str()), lang, &[]);
+        lines.push(line(label, state));
+    }
+    for (label, old, new) in [
+        ("settings.panel.join_msg", &before.join_message_template, &after.join_message_template),
+        ("settings.panel.leave_msg", &before.leave_message_template, &after.leave_message_template),
+    ] {
+        let state = match (old == new, new) {
+            (true, _) => "settings.panel.unchanged",
+            (false, None) => "settings.panel.cleared",
+            (false, Some(_)) => "settings.panel.set",
+        };
+        changed |= old != new;
+        lines.push(line(t(label, lang, &[]), t(state, lang, &[])));
+    }
+
+    if changed {
+        t("settings.panel.saved", lang, &[("changes", &lines.join("\n"))])
+    } else {
+        t("settings.panel.saved_unchanged", lang, &[])
+    }
+}
+
+/// Append an "unsaved" marker to edited rows.
+fn mark(shown: String, dirty: bool, lang: &str) -> String {
+    if dirty {
+        t("settings.panel.unsaved", lang, &[("value", &shown)])
+    } else {
+        shown
+    }
+}
+
+/// Whether the clicker has Manage Server (permissions as resolved by Discord).
+fn can_manage_guild(ci: &ComponentInteraction) -> bool {
+    ci.member
+        .as_ref()
+        .and_then(|m| m.permissions)
+        .is_some_and(|p| p.manage_guild())
+}
+
+/// Draft for a channel select: no selection means "clear".
+fn channel_draft(ci: &ComponentInteraction) -> Option<DraftValue<Vec<ChannelId>>> {
+    match &ci.data.kind {
+        ComponentInteractionDataKind::ChannelSelect { values } => Some(if values.is_empty() {
+            DraftValue::Clear
+        } else {
+            DraftValue::Set(values.clone())
+        }),
+        _ => None,
+    }
+}
+
+/// Draft for a template modal: blank input means "clear".
+fn template_draft(mi: &ModalInteraction) -> DraftValue<String> {
+    let value = mi
+        .data
+        .components
+        .iter()
+        .flat_map(|row| &row.components)
+        .find_map(|c| match c {
+            ActionRowComponent::InputText(input) if input.custom_id == TEMPLATE_INPUT_ID => {
+                input.value.clone()
+            }
+            _ => None,
+        })
+        .unwrap_or_default();
+
+    let trimmed = value.trim();
+    if trimmed.is_empty() {
+        DraftValue::Clear
+    } else {
+        DraftValue::Set(trimmed.to_string())
+    }
+}
+
+#[async_trait]
+impl ComponentFlow for SettingsPanel {
+    fn ids(&self) -> &FlowIds {
+        &self.ids
+    }
+
+    fn render(&self) -> FlowView {
+        self.view("", true)
+    }
+
+    fn shared(&self) -> bool {
+        self.shared
+    }
+
+    /// A shared panel may sit in a busy channel for a while; keep it findable.
+    fn pinned(&self) -> bool {
+        self.shared
+    }
+
+    async fn on_component(
+        &mut self,
+        ui: &mut UiHandle<'_>,
+        ci: &ComponentInteraction,
+    ) -> Result<bool> {
+        let Some(id) = self.ids.strip(&ci.data.custom_id) else {
+            return Ok(true);
+        };
+        if self.shared && !can_manage_guild(ci) {
+            ui.deny(ci, &t("settings.panel.manage_only", self.lang, &[]))
+                .await?;
+            return Ok(true);
+        }
+        match id {
+            JOIN_ID | LEAVE_ID | MOD_ID => {
+                let Some(draft) = channel_draft(ci) else {
+                    return Ok(true);
+                };
+                match id {
+                    JOIN_ID => self.join_log = draft,
+                    LEAVE_ID => self.leave_log = draft,
+                    _ => self.mod_log = draft,
+                }
+            }
+            JOIN_MSG_ID => {
+                let current = self
+                    .join_template
+                    .resolve(self.saved.join_message_template.as_ref());
+                let modal =
+                    self.template_modal(JOIN_MSG_MODAL_ID, "settings.panel.join_msg", current);
+                ui.open_modal(ci, modal).await?;
+                return Ok(true);
+            }
+            LEAVE_MSG_ID => {
+                let current = self
+                    .leave_template
+                    .resolve(self.saved.leave_message_template.as_ref());
+                let modal =
+                    self.template_modal(LEAVE_MSG_MODAL_ID, "settings.panel.leave_msg", current);
+                ui.open_modal(ci, modal).await?;
+                return Ok(true);
+            }
+            CLEAR_ALL_ID => {
+                self.join_log = DraftValue::Clear;
+                self.leave_log = DraftValue::Clear;
+                self.mod_log = DraftValue::Clear;
+                self.join_template = DraftValue::Clear;
+                self.leave_template = DraftValue::Clear;
+                let status = t("settings.panel.clear_pending", self.lang, &[]);
+                ui.update(ci, self.view(&status, true)).await?;
+                return Ok(true);
+            }
+            SAVE_ID => {
+                let summary = self.apply_changes(&ui.ctx().data().settings_repo()).await?;
+                self.finished = true;
+                ui.finish_with(ci, self.view(&summary, false)).await?;
+                return Ok(false);
+            }
+            CANCEL_ID => {
+                self.join_log = DraftValue::Keep;
+                self.leave_log = DraftValue::Keep;
+                self.mod_log = DraftValue::Keep;
+                self.join_template = DraftValue::Keep;
+                self.leave_template = DraftValue::Keep;
+                self.finished = true;
+                let status = t("settings.panel.cancelled", self.lang, &[]);
+                ui.finish_with(ci, self.view(&status, false)).await?;
+                return Ok(false);
+            }
+            _ => return Ok(true),
+        }
+
+        ui.update(ci, self.render()).await?;
+        Ok(true)
+    }
+
+    async fn on_modal(&mut self, ui: &mut UiHandle<'_>, mi: &ModalInteraction) -> Result<bool> {
+        match self.ids.strip(&mi.data.custom_id) {
+            Some(JOIN_MSG_MODAL_ID) => self.join_template = template_draft(mi),
+            Some(LEAVE_MSG_MODAL_ID) => self.leave_template = template_draft(mi),
+            _ => return Ok(true),
+        }
+        ui.update_from_modal(mi, self.render()).await?;
+        Ok(true)
+    }
+
+    async fn cleanup(&mut self, ui: &mut UiHandle<'_>) -> Result<()> {
+        // Saved/cancelled panels were already finalized in `on_component`.
+        if !self.finished {
+            let status = t("settings.panel.timed_out", self.lang, &[]);
+            ui.edit(self.view(&status, false)).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Open the settings panel for `guild_id` (idle timeout): ephemeral, or posted in the
+/// channel for every admin to use when `shared`.
+pub async fn open_settings_panel(ctx: Ctx<'_>, guild_id: GuildId, shared: bool) -> Result<()> {
+    let saved = ctx.data().settings_repo().get(&guild_id).await?;
+    let mut panel = SettingsPanel::new(guild_id, saved, shared);
+    let surface = if shared {
+        Surface::DetachedReply
+    } else {
+        Surface::AttachedEphemeral
+    };
+    run(ctx, &mut panel, surface, FlowTimeout::Idle(PANEL_IDLE_TIMEOUT)).await
+}
@@ -0,0 +1,57 @@
+//! Liveness/readiness endpoint for container orchestration (behind the `health` feature).
+//!
+//! Any `GET` answers 200 when a shard is connected to the gateway and the database
+//! answers `SELECT 1`, 503 otherwise. The body is a small JSON status either way.
+
+use std::sync::Arc;
+
+use hyper::{Body, Method, Request, Response, StatusCode};
+use serenity::all::Cache;
+use serenity::gateway::{ConnectionStage, ShardManager};
+
+use crate::db::Db;
+use crate::http::response;
+
+/// What the endpoint checks; cheap to clone into each connection.
+#[derive(Clone)]
+pub struct HealthProbe {
+    pub db: Db,
+    pub cache: Arc<Cache>,
+    pub shards: Arc<ShardManager>,
+}
+
+impl HealthProbe {
+    async fn check(&self) -> (StatusCode, String) {
+        let gateway = self
+            .shards
+            .runners
+            .lock()
+            .await
+            .values()
+            .any(|r| r.stage == ConnectionStage::Connected);
+        let db = sqlx::query("SELECT 1").execute(&self.db.pool).await.is_ok();
+        let ok = gateway && db;
+
+        let body = serde_json::json!({
+            "ok": ok,
+            "gateway": gateway,
+            "db": db,
+            "guilds": self.cache.guild_count(),
+        });
+        let status = if ok {
+            StatusCode::OK
+        } else {
+            StatusCode::SERVICE_UNAVAILABLE
+        };
+        (status, body.to_string())
+    }
+
+    pub async fn respond(self, req: Request<Body>) -> Response<Body> {
+        let (status, body) = if req.method() == Method::GET {
+            self.check().await
+        } else {
+            (StatusCode::METHOD_NOT_ALLOWED, String::new())
+        };
+        response(status, "application/json", body)
+    }
+}
@@ -0,0 +1,82 @@
+//! Minimal HTTP serving for the operator endpoints (`health`, `metrics` features).
+
+use std::convert::Infallible;
+use std::future::Future;
+use std::net::SocketAddr;
+
+use anyhow::{Context, Result};
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Request, Response, Server};
+use tokio_util::sync::CancellationToken;
+
+/// Answer every request on `addr` with `handler` until `shutdown` is cancelled.
+/// `name` is only used in log lines.
+pub async fn serve<F, Fut>(
+    name: &str,
+    addr: SocketAddr,
+    handler: F,
+    shutdown: CancellationToken,
+) -> Result<()>
+where
+    F: Fn(Request<Body>) -> Fut + Clone + Send + Sync + 'static,
+    Fut: Future<Output = Response<Body>> + Send + 'static,
+{
+    let make_service = make_service_fn(move |_conn| {
+        let handler = handler.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |req| {
+                let fut = handler(req);
+                async move { Ok::<_, Infallible>(fut.await) }
+            }))
+        }
+    });
+
+    let server = Server::try_bind(&addr)
+        .with_context(|| format!("Could not bind {name} endpoint to {addr}"))?
+        .serve(make_service)
+        .with_graceful_shutdown(shutdown.cancelled_owned());
+    tracing::info!("{name} endpoint listening on http://{addr}");
+    server.await?;
+    Ok(())
+}
+
+/// Spawn `serve` for the address in env var `bind_var`, if it's set.
+/// A malformed address is a startup error; a server that dies later is only logged.
+pub fn spawn_from_env<F, Fut>(
+    bind_var: &str,
+    name: &'static str,
+    handler: F,
+    shutdown: CancellationToken,
+) -> Result<()>
+where
+    F: Fn(Request<Body>) -> Fut + Clone + Send + Sync + 'static,
+    Fut: Future<Output = Response<Body>> + Send + 'static,
+{
+    let Ok(raw) = std::env::var(bind_var) else {
+        return Ok(());
+    };
+    let addr: SocketAddr = raw
+        .parse()
+        .with_context(|| format!("{bind_var} must be host:port (got {raw:?})"))?;
+    tokio::spawn(async move {
+        if let Err(e) = serve(name, addr, handler, shutdown).await {
+            tracing::error!("{name} endpoint stopped: {e:#}");
+        }
+    });
+    Ok(())
+}
+
+/// Response with `status`, `content_type` and `body`.
+pub fn response(
+    status: hyper::StatusCode,
+    content_type: &'static str,
+    body: String,
+) -> Response<Body> {
+    let mut res = Response::new(Body::from(body));
+    *res.status_mut() = status;
+    res.headers_mut().insert(
+        hyper::header::CONTENT_TYPE,
+        hyper::header::HeaderValue::from_static(content_type),
+    );
+    res
+}
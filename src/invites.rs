@@ -1,14 +1,93 @@
 use std::collections::HashMap;
 
 use anyhow::Result;
-use serenity::all::{GuildId, RichInvite};
+use serenity::all::{GuildId, RichInvite, UserId};
 use serenity::http::Http;
 
-/// Fetch all invites for a guild (requires Manage Guild) and map code->uses.
-pub async fn fetch_invites_map(http: &Http, guild_id: GuildId) -> Result<HashMap<String, u64>> {
-    let invites: Vec<RichInvite> = guild_id.invites(http).await?;
-    Ok(invites
-        .into_iter()
-        .filter_map(|i| Some((i.code, i.uses)))
-        .collect())
+use crate::repos::InvitesRepo;
+use crate::state::AppState;
+
+/// Which invite a join was attributed to (if any could be determined).
+#[derive(Debug, Clone)]
+pub struct InviteAttribution {
+    pub code: String,
+    pub inviter_id: Option<UserId>,
+}
+
+/// Fetch all invites for a guild (requires Manage Guild).
+pub async fn fetch_invites(http: &Http, guild_id: GuildId) -> Result<Vec<RichInvite>> {
+    Ok(guild_id.invites(http).await?)
+}
+
+fn uses_map(invites: &[RichInvite]) -> HashMap<String, u64> {
+    invites.iter().map(|i| (i.code.clone(), i.uses)).collect()
+}
+
+/// Mirror a fetched invite list into the `invites` table (best effort).
+async fn persist_invites(state: &AppState, guild_id: GuildId, invites: &[RichInvite]) {
+    let repo = InvitesRepo::new(&state.db);
+    for i in invites {
+        let res = repo
+            .upsert_invite(
+                guild_id,
+                &i.code,
+                i.inviter.as_ref().map(|u| u.id),
+                i.created_at,
+                i.max_age,
+                i.uses,
+            )
+            .await;
+        if let Err(e) = res {
+            tracing::warn!(%guild_id, code = %i.code, error = %e, "Could not store invite");
+        }
+    }
+}
+
+/// Seed/refresh the cached invite uses for a guild (best effort).
+pub async fn refresh_invite_cache(http: &Http, state: &AppState, guild_id: GuildId) {
+    match fetch_invites(http, guild_id).await {
+        Ok(invites) => {
+            state.invite_cache.insert(guild_id, uses_map(&invites));
+            persist_invites(state, guild_id, &invites).await;
+        }
+        Err(e) => {
+            tracing::debug!(%guild_id, error = format!("{e:#}"), "Could not fetch invites");
+        }
+    }
+}
+
+/// Diff the cached invite uses against a fresh fetch to find the invite a new member used.
+///
+/// Only attributes the join when exactly one invite's use count went up; vanity URLs,
+/// missing permissions, or concurrent joins yield `None`.
+pub async fn attribute_join(
+    http: &Http,
+    state: &AppState,
+    guild_id: GuildId,
+) -> Option<InviteAttribution> {
+    let fresh = match fetch_invites(http, guild_id).await {
+        Ok(v) => v,
+        Err(e) => {
+            tracing::debug!(%guild_id, error = format!("{e:#}"), "Could not fetch invites");
+            return None;
+        }
+    };
+
+    let before = state
+        .invite_cache
+        .insert(guild_id, uses_map(&fresh))
+        .unwrap_or_default();
+    persist_invites(state, guild_id, &fresh).await;
+
+    let mut used = fresh
+        .iter()
+        .filter(|i| i.uses > before.get(&i.code).copied().unwrap_or(0));
+
+    match (used.next(), used.next()) {
+        (Some(invite), None) => Some(InviteAttribution {
+            code: invite.code.clone(),
+            inviter_id: invite.inviter.as_ref().map(|u| u.id),
+        }),
+        _ => None,
+    }
 }
@@ -1,8 +1,27 @@
 mod app;
+mod audit;
+#[cfg(feature = "charts")]
+mod charts;
+mod command_log;
 mod commands;
+mod digest;
 mod events;
+mod flows;
+#[cfg(feature = "health")]
+mod health;
+#[cfg(any(feature = "health", feature = "metrics"))]
+mod http;
 mod invites;
+mod members;
+mod metrics;
+mod retention;
+mod retry;
+mod spans;
 mod state;
+mod strings;
+#[cfg(test)]
+mod test_support;
+mod templating;
 mod repos;
 mod db;
 
@@ -0,0 +1,24 @@
+use anyhow::Result;
+use serenity::all::{GuildId, Member, UserId};
+use serenity::http::Http;
+
+/// Discord caps a single member-list request at 1000 entries.
+const MEMBERS_PAGE_SIZE: u64 = 1000;
+
+/// Fetch the full live member list for a guild by paging the HTTP endpoint (requires GUILD_MEMBERS).
+pub async fn fetch_all_members(http: &Http, guild_id: GuildId) -> Result<Vec<Member>> {
+    let mut out = Vec::new();
+    let mut after: Option<UserId> = None;
+
+    loop {
+        let page = guild_id.members(http, Some(MEMBERS_PAGE_SIZE), after).await?;
+        let done = (page.len() as u64) < MEMBERS_PAGE_SIZE;
+        after = page.last().map(|m| m.user.id);
+        out.extend(page);
+        if done || after.is_none() {
+            break;
+        }
+    }
+
+    Ok(out)
+}
@@ -0,0 +1,146 @@
+//! Prometheus counters and command latency, rendered in the text exposition format.
+//!
+//! Collection is always on (a few map entries per guild); serving `/metrics` needs the
+//! `metrics` feature and `METRICS_BIND`. Labels are guild ids and command names only,
+//! never user ids, so the series count stays bounded.
+
+#[cfg(feature = "metrics")]
+use std::fmt::Write;
+use std::time::Duration;
+
+use dashmap::DashMap;
+use serenity::all::GuildId;
+
+/// Upper bounds (seconds) of the command latency histogram buckets.
+const LATENCY_BUCKETS: [f64; 9] = [0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0];
+
+#[derive(Default)]
+struct Histogram {
+    /// Non-cumulative counts per bucket; the last slot is `+Inf`.
+    buckets: [u64; LATENCY_BUCKETS.len() + 1],
+    count: u64,
+    sum: f64,
+}
+
+impl Histogram {
+    fn observe(&mut self, secs: f64) {
+        let slot = LATENCY_BUCKETS
+            .iter()
+            .position(|le| secs <= *le)
+            .unwrap_or(LATENCY_BUCKETS.len());
+        self.buckets[slot] += 1;
+        self.count += 1;
+        self.sum += secs;
+    }
+}
+
+/// Process-wide metrics registry (lives in `AppState`).
+#[derive(Default)]
+pub struct Metrics {
+    joins: DashMap<GuildId, u64>,
+    leaves: DashMap<GuildId, u64>,
+    bans: DashMap<GuildId, u64>,
+    commands: DashMap<String, Histogram>,
+}
+
+impl Metrics {
+    pub fn record_join(&self, guild_id: GuildId) {
+        *self.joins.entry(guild_id).or_default() += 1;
+    }
+
+    pub fn record_leave(&self, guild_id: GuildId) {
+        *self.leaves.entry(guild_id).or_default() += 1;
+    }
+
+    pub fn record_ban(&self, guild_id: GuildId) {
+        *self.bans.entry(guild_id).or_default() += 1;
+    }
+
+    /// Time one successful command run, keyed by its qualified name (`stats exits`).
+    pub fn observe_command(&self, command: &str, elapsed: Duration) {
+        self.commands
+            .entry(command.to_string())
+            .or_default()
+            .observe(elapsed.as_secs_f64());
+    }
+
+    /// Drop a guild's series once the bot has left it.
+    pub fn forget_guild(&self, guild_id: GuildId) {
+        self.joins.remove(&guild_id);
+        self.leaves.remove(&guild_id);
+        self.bans.remove(&guild_id);
+    }
+
+    /// Everything in the Prometheus text format, series sorted for stable output.
+    #[cfg(feature = "metrics")]
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        render_counter(&mut out, "joins_total", "Members who joined.", &self.joins);
+        render_counter(
+            &mut out,
+            "leaves_total",
+            "Members who left, were kicked or banned.",
+            &self.leaves,
+        );
+        render_counter(&mut out, "bans_total", "Members banned.", &self.bans);
+
+        let name = "command_duration_seconds";
+        let _ = writeln!(out, "# HELP {name} Slash command execution time.");
+        let _ = writeln!(out, "# TYPE {name} histogram");
+        let mut names: Vec<String> = self.commands.iter().map(|e| e.key().clone()).collect();
+        names.sort();
+        for command in names {
+            let Some(h) = self.commands.get(&command) else {
+                continue;
+            };
+            let label = format!("command=\"{}\"", escape_label(&command));
+            let mut cumulative = 0;
+            for (le, n) in LATENCY_BUCKETS.iter().zip(h.buckets.iter()) {
+                cumulative += n;
+                let _ = writeln!(out, "{name}_bucket{{{label},le=\"{le}\"}} {cumulative}");
+            }
+            let _ = writeln!(out, "{name}_bucket{{{label},le=\"+Inf\"}} {}", h.count);
+            let _ = writeln!(out, "{name}_sum{{{label}}} {}", h.sum);
+            let _ = writeln!(out, "{name}_count{{{label}}} {}", h.count);
+        }
+        out
+    }
+}
+
+#[cfg(feature = "metrics")]
+fn render_counter(out: &mut String, name: &str, help: &str, series: &DashMap<GuildId, u64>) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} counter");
+    let mut rows: Vec<(GuildId, u64)> = series.iter().map(|e| (*e.key(), *e.value())).collect();
+    rows.sort();
+    for (guild, n) in rows {
+        let _ = writeln!(out, "{name}{{guild=\"{guild}\"}} {n}");
+    }
+}
+
+/// Command names are ours, but escape anyway so an odd name can't break the format.
+#[cfg(feature = "metrics")]
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// `GET /metrics` handler for `crate::http::serve`.
+#[cfg(feature = "metrics")]
+pub async fn respond(
+    state: std::sync::Arc<crate::state::AppState>,
+    req: hyper::Request<hyper::Body>,
+) -> hyper::Response<hyper::Body> {
+    use hyper::{Method, StatusCode};
+
+    if req.method() != Method::GET || req.uri().path() != "/metrics" {
+        return crate::http::response(StatusCode::NOT_FOUND, "text/plain", String::new());
+    }
+    crate::http::response(
+        StatusCode::OK,
+        "text/plain; version=0.0.4",
+        state.metrics.render(),
+    )
+}
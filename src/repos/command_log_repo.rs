@@ -0,0 +1,77 @@
+use poise::serenity_prelude as serenity;
+use serenity::all::{GuildId, UserId};
+
+use crate::db::Db;
+use crate::repos::error::Result;
+use crate::repos::timestamp::UnixTime;
+
+#[derive(Clone)]
+pub struct CommandLogRepo<'a> {
+    db: &'a Db,
+}
+
+impl<'a> CommandLogRepo<'a> {
+    pub fn new(db: &'a Db) -> Self {
+        Self { db }
+    }
+
+    /// Append one invocation; `args` should already be redacted and capped by the caller.
+    pub async fn record(
+        &self,
+        guild_id: Option<GuildId>,
+        user_id: UserId,
+        command: &str,
+        args: &str,
+    ) -> Result<()> {
+        let guild_id = guild_id.map(|g| g.to_string());
+        let user_id = user_id.to_string();
+        let now = UnixTime::now();
+        sqlx::query!(
+            r#"
+            INSERT INTO command_log (guild_id, user_id, command, args, invoked_at)
+            VALUES (?, ?, ?, ?, ?)
+            "#,
+            guild_id,
+            user_id,
+            command,
+            args,
+            now
+        )
+        .execute(&self.db.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// The guild's most recent invocations, newest first.
+    pub async fn recent(&self, guild_id: GuildId, limit: i64) -> Result<Vec<CommandLogEntry>> {
+        let guild_id = guild_id.to_string();
+        let rows = sqlx::query_as!(
+            CommandLogEntry,
+            r#"
+            SELECT id         AS "id!: i64",
+                   user_id,
+                   command,
+                   args,
+                   invoked_at AS "invoked_at: UnixTime"
+            FROM command_log
+            WHERE guild_id = ?
+            ORDER BY id DESC
+            LIMIT ?
+            "#,
+            guild_id,
+            limit
+        )
+        .fetch_all(&self.db.pool)
+        .await?;
+        Ok(rows)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CommandLogEntry {
+    pub id: i64,
+    pub user_id: String,
+    pub command: String,
+    pub args: String,
+    pub invoked_at: UnixTime,
+}
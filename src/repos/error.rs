@@ -0,0 +1,59 @@
+use std::fmt;
+
+/// What a repo call can fail with, so callers can tell "nothing there" from "DB down".
+#[derive(Debug)]
+pub enum RepoError {
+    /// The row the caller asked to change/delete doesn't exist.
+    NotFound,
+    /// Any other database failure.
+    Sqlx(sqlx::Error),
+    /// SQLite was built without FTS5, so username search tables can't be used.
+    FtsUnavailable,
+}
+
+pub type Result<T, E = RepoError> = std::result::Result<T, E>;
+
+impl RepoError {
+    /// Reclassify errors from `usernames_fts` queries: a missing module/table means
+    /// FTS5 is unavailable rather than a generic DB failure.
+    pub(crate) fn fts(self) -> Self {
+        match self {
+            RepoError::Sqlx(e) => {
+                let msg = e.to_string();
+                if msg.contains("no such module: fts5")
+                    || msg.contains("no such table: usernames_fts")
+                {
+                    RepoError::FtsUnavailable
+                } else {
+                    RepoError::Sqlx(e)
+                }
+            }
+            other => other,
+        }
+    }
+}
+
+impl fmt::Display for RepoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RepoError::NotFound => write!(f, "not found"),
+            RepoError::Sqlx(e) => write!(f, "database error: {e}"),
+            RepoError::FtsUnavailable => write!(f, "full-text search (FTS5) is unavailable"),
+        }
+    }
+}
+
+impl std::error::Error for RepoError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RepoError::Sqlx(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<sqlx::Error> for RepoError {
+    fn from(e: sqlx::Error) -> Self {
+        RepoError::Sqlx(e)
+    }
+}
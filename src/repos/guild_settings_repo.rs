@@ -1,31 +1,357 @@
-use anyhow::Result;
 use poise::serenity_prelude as serenity;
-use serenity::all::ChannelId;
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use serenity::all::{ChannelId, Colour, GuildId, RoleId};
+use sqlx::{Sqlite, Transaction};
 
 use crate::db::Db;
+use crate::repos::error::Result;
 
-#[derive(Debug, Clone, Copy, Default)]
+/// Ban → removal window when a guild hasn't configured one.
+pub const DEFAULT_BAN_WINDOW_SECS: i64 = 15;
+/// Allowed range for `ban_detection_window_secs`.
+pub const BAN_WINDOW_RANGE: std::ops::RangeInclusive<i64> = 1..=120;
+/// Joins/leaves per minute that raise a raid alert when a guild hasn't configured one.
+pub const DEFAULT_RAID_THRESHOLD: i64 = 10;
+/// Allowed range for `raid_threshold`.
+pub const RAID_THRESHOLD_RANGE: std::ops::RangeInclusive<i64> = 3..=500;
+/// Allowed range for `min_account_age_days`.
+pub const ACCOUNT_AGE_RANGE: std::ops::RangeInclusive<i64> = 1..=365;
+/// Allowed range for `retention_days`.
+pub const RETENTION_RANGE: std::ops::RangeInclusive<i64> = 30..=3650;
+/// Local hour the daily digest goes out when a guild hasn't picked one.
+const DEFAULT_DIGEST_HOUR: u32 = 9;
+/// Most channels one log type can fan out to (primary included).
+pub const MAX_LOG_CHANNELS: usize = 5;
+/// Most roles a guild can put on its role-change watch list.
+pub const MAX_WATCHED_ROLES: usize = 25;
+
+/// Which log a channel route belongs to; stored as `log_routes.log_kind`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum LogKind {
+    #[name = "join"]
+    Join,
+    #[name = "leave"]
+    Leave,
+    #[name = "mod"]
+    Mod,
+}
+
+impl LogKind {
+    pub const ALL: [LogKind; 3] = [LogKind::Join, LogKind::Leave, LogKind::Mod];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LogKind::Join => "join",
+            LogKind::Leave => "leave",
+            LogKind::Mod => "mod",
+        }
+    }
+
+    /// `guild_settings` column holding the primary channel for this log.
+    pub fn primary_column(self) -> &'static str {
+        match self {
+            LogKind::Join => "join_log_channel_id",
+            LogKind::Leave => "leave_log_channel_id",
+            LogKind::Mod => "mod_log_channel_id",
+        }
+    }
+
+    /// `guild_settings` column holding this log's webhook URL.
+    pub fn webhook_column(self) -> &'static str {
+        match self {
+            LogKind::Join => "join_log_webhook_url",
+            LogKind::Leave => "leave_log_webhook_url",
+            LogKind::Mod => "mod_log_webhook_url",
+        }
+    }
+}
+
+/// Where one log's embeds go. With a webhook, the channels are only a fallback
+/// for when executing it fails.
+#[derive(Debug, Clone, Default)]
+pub struct LogTargets {
+    pub webhook: Option<String>,
+    pub channels: Vec<ChannelId>,
+}
+
+impl LogTargets {
+    pub fn is_empty(&self) -> bool {
+        self.webhook.is_none() && self.channels.is_empty()
+    }
+}
+
+/// What `on_join` does with accounts younger than `min_account_age_days`;
+/// stored as `guild_settings.account_age_action`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum AccountAgeAction {
+    /// Only flag the account in the join log.
+    #[default]
+    #[name = "log"]
+    Log,
+    /// Flag it and DM the member a notice.
+    #[name = "dm"]
+    Dm,
+    /// Flag it, DM the member, then kick them (when the bot is allowed to).
+    #[name = "kick"]
+    Kick,
+}
+
+impl AccountAgeAction {
+    pub const ALL: [AccountAgeAction; 3] =
+        [AccountAgeAction::Log, AccountAgeAction::Dm, AccountAgeAction::Kick];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AccountAgeAction::Log => "log",
+            AccountAgeAction::Dm => "dm",
+            AccountAgeAction::Kick => "kick",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|a| a.as_str() == s)
+    }
+}
+
+/// One guild's settings row. Serializes to the `/settings export` JSON format.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct GuildSettings {
     pub join_log: Option<ChannelId>,
     pub leave_log: Option<ChannelId>,
     pub mod_log: Option<ChannelId>,
+    pub join_message_template: Option<String>,
+    pub leave_message_template: Option<String>,
+    pub embed_color: Option<i64>,
+    pub timezone: Option<String>,
+    pub ban_detection_window_secs: Option<i64>,
+    /// Joins or leaves per minute that trigger a raid alert (unset = default).
+    pub raid_threshold: Option<i64>,
+    /// Joining accounts younger than this are flagged (unset = off).
+    pub min_account_age_days: Option<i64>,
+    /// `AccountAgeAction::as_str` of what to do with them (unset = log only).
+    pub account_age_action: Option<String>,
+    pub digest_enabled: bool,
+    pub digest_hour: Option<i64>,
+    /// Post deleted/edited message content to the mod log (off by default for privacy).
+    pub message_log_enabled: bool,
+    /// Ignore member opt-outs and keep full records (for servers that must).
+    pub tracking_required: bool,
+    /// Offer voice/stage channel chats as log targets in the settings panel.
+    pub extended_log_channels: bool,
+    /// Prune history older than this many days (unset = keep forever).
+    pub retention_days: Option<i64>,
+    /// Mirrors beyond the primary channel, from `log_routes`.
+    pub extra_join_logs: Vec<ChannelId>,
+    pub extra_leave_logs: Vec<ChannelId>,
+    pub extra_mod_logs: Vec<ChannelId>,
+    /// Webhook URLs that take precedence over the channels above (unset = channels only).
+    pub join_log_webhook_url: Option<String>,
+    pub leave_log_webhook_url: Option<String>,
+    pub mod_log_webhook_url: Option<String>,
+    /// Only log role changes touching these roles; empty = log every role change.
+    pub watched_roles: Vec<RoleId>,
+    /// Language code for bot messages (`crate::strings::LANGUAGES`); unset = English.
+    pub lang: Option<String>,
+}
+
+impl GuildSettings {
+    /// Every channel a `kind` log goes to: the primary first, then mirrors (deduplicated).
+    pub fn targets(&self, kind: LogKind) -> Vec<ChannelId> {
+        let (primary, extras) = match kind {
+            LogKind::Join => (self.join_log, &self.extra_join_logs),
+            LogKind::Leave => (self.leave_log, &self.extra_leave_logs),
+            LogKind::Mod => (self.mod_log, &self.extra_mod_logs),
+        };
+        let mut out: Vec<ChannelId> = primary.into_iter().collect();
+        for ch in extras {
+            if !out.contains(ch) {
+                out.push(*ch);
+            }
+        }
+        out
+    }
+
+    /// Webhook URL for `kind`, if one is set (blank counts as unset).
+    pub fn webhook(&self, kind: LogKind) -> Option<&str> {
+        let url = match kind {
+            LogKind::Join => &self.join_log_webhook_url,
+            LogKind::Leave => &self.leave_log_webhook_url,
+            LogKind::Mod => &self.mod_log_webhook_url,
+        };
+        url.as_deref().filter(|u| !u.trim().is_empty())
+    }
+
+    /// Webhook and channels a `kind` log is posted to.
+    pub fn log_targets(&self, kind: LogKind) -> LogTargets {
+        LogTargets {
+            webhook: self.webhook(kind).map(str::to_string),
+            channels: self.targets(kind),
+        }
+    }
+
+    /// Kicks/bans go to the mod log, falling back to the leave log when none is set.
+    pub fn moderation_targets(&self) -> LogTargets {
+        let mod_targets = self.log_targets(LogKind::Mod);
+        if mod_targets.is_empty() {
+            self.log_targets(LogKind::Leave)
+        } else {
+            mod_targets
+        }
+    }
+
+    /// Alerts about who just joined (watchlist hits, banned rejoins) go to the mod log,
+    /// falling back to the join log when none is set.
+    pub fn join_alert_targets(&self) -> LogTargets {
+        let mod_targets = self.log_targets(LogKind::Mod);
+        if mod_targets.is_empty() {
+            self.log_targets(LogKind::Join)
+        } else {
+            mod_targets
+        }
+    }
+
+    /// Whether a grant/removal of `role` should be logged.
+    pub fn watches_role(&self, role: RoleId) -> bool {
+        self.watched_roles.is_empty() || self.watched_roles.contains(&role)
+    }
+
+    fn extras_mut(&mut self, kind: LogKind) -> &mut Vec<ChannelId> {
+        match kind {
+            LogKind::Join => &mut self.extra_join_logs,
+            LogKind::Leave => &mut self.extra_leave_logs,
+            LogKind::Mod => &mut self.extra_mod_logs,
+        }
+    }
+
+    /// Custom join text, if one is set (blank counts as unset).
+    pub fn join_template(&self) -> Option<&str> {
+        self.join_message_template
+            .as_deref()
+            .filter(|t| !t.trim().is_empty())
+    }
+
+    /// Custom leave text, if one is set (blank counts as unset).
+    pub fn leave_template(&self) -> Option<&str> {
+        self.leave_message_template
+            .as_deref()
+            .filter(|t| !t.trim().is_empty())
+    }
+
+    /// Configured embed colour, ignoring out-of-range values.
+    pub fn embed_colour(&self) -> Option<Colour> {
+        colour_from_i64(self.embed_color)
+    }
+
+    /// Zone for calendar bucketing; UTC when unset or unparseable.
+    pub fn tz(&self) -> chrono_tz::Tz {
+        self.timezone
+            .as_deref()
+            .and_then(|z| z.parse().ok())
+            .unwrap_or(chrono_tz::UTC)
+    }
+
+    /// Language for bot messages; English when unset or unsupported.
+    pub fn lang(&self) -> &'static str {
+        crate::strings::lang_or_default(self.lang.as_deref())
+    }
+
+    /// How long after a ban a member removal is still classified as that ban.
+    pub fn ban_window_secs(&self) -> i64 {
+        self.ban_detection_window_secs
+            .unwrap_or(DEFAULT_BAN_WINDOW_SECS)
+            .clamp(*BAN_WINDOW_RANGE.start(), *BAN_WINDOW_RANGE.end())
+    }
+
+    /// Joins or leaves within one minute that count as a raid.
+    pub fn raid_threshold(&self) -> usize {
+        self.raid_threshold
+            .unwrap_or(DEFAULT_RAID_THRESHOLD)
+            .clamp(*RAID_THRESHOLD_RANGE.start(), *RAID_THRESHOLD_RANGE.end()) as usize
+    }
+
+    /// Minimum account age for new members, if the check is on.
+    pub fn min_account_age(&self) -> Option<chrono::Duration> {
+        self.min_account_age_days
+            .filter(|d| ACCOUNT_AGE_RANGE.contains(d))
+            .map(chrono::Duration::days)
+    }
+
+    /// What to do with too-young accounts; log-only when unset or unknown.
+    pub fn account_age_action(&self) -> AccountAgeAction {
+        self.account_age_action
+            .as_deref()
+            .and_then(AccountAgeAction::parse)
+            .unwrap_or_default()
+    }
+
+    /// Hour of day (guild timezone, 0–23) at which the daily digest is posted.
+    pub fn digest_hour(&self) -> u32 {
+        self.digest_hour
+            .filter(|h| (0..24).contains(h))
+            .map_or(DEFAULT_DIGEST_HOUR, |h| h as u32)
+    }
+}
+
+fn colour_from_i64(value: Option<i64>) -> Option<Colour> {
+    value
+        .filter(|v| (0..=0xFF_FF_FF).contains(v))
+        .map(|v| Colour::new(v as u32))
 }
 
+/// In-memory read-through cache of settings rows (lives in `AppState`).
+pub type SettingsCache = DashMap<serenity::all::GuildId, GuildSettings>;
+
 #[derive(Clone)]
 pub struct GuildSettingsRepo<'a> {
     db: &'a Db,
+    cache: Option<&'a SettingsCache>,
 }
 
 impl<'a> GuildSettingsRepo<'a> {
     pub fn new(db: &'a Db) -> Self {
-        Self { db }
+        Self { db, cache: None }
+    }
+
+    /// Serve `get_cached` from `cache` and drop entries on every write through this repo.
+    pub fn with_cache(mut self, cache: &'a SettingsCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Like `get`, but answered from the cache when possible (hot paths: events, colours).
+    pub async fn get_cached(&self, guild_id: &serenity::all::GuildId) -> Result<GuildSettings> {
+        if let Some(hit) = self.cache.and_then(|c| c.get(guild_id)) {
+            return Ok(hit.clone());
+        }
+        let fresh = self.get(guild_id).await?;
+        if let Some(cache) = self.cache {
+            cache.insert(*guild_id, fresh.clone());
+        }
+        Ok(fresh)
+    }
+
+    /// Forget the cached row so the next `get_cached` reads the DB again.
+    fn invalidate(&self, guild_id: &serenity::all::GuildId) {
+        if let Some(cache) = self.cache {
+            cache.remove(guild_id);
+        }
     }
 
     pub async fn get(&self, guild_id: &serenity::all::GuildId) -> Result<GuildSettings> {
         let guild = guild_id.to_string();
         let rec = sqlx::query!(
             r#"
-            SELECT join_log_channel_id, leave_log_channel_id, mod_log_channel_id
+            SELECT join_log_channel_id, leave_log_channel_id, mod_log_channel_id,
+                   join_message_template, leave_message_template, embed_color,
+                   timezone, ban_detection_window_secs,
+                   digest_enabled AS "digest_enabled: bool", digest_hour,
+                   message_log_enabled AS "message_log_enabled: bool", lang,
+                   tracking_required AS "tracking_required: bool",
+                   extended_log_channels AS "extended_log_channels: bool", retention_days,
+                   raid_threshold, min_account_age_days, account_age_action,
+                   join_log_webhook_url, leave_log_webhook_url, mod_log_webhook_url
             FROM guild_settings WHERE guild_id = ?
             "#,
             guild
@@ -33,7 +359,7 @@ impl<'a> GuildSettingsRepo<'a> {
         .fetch_optional(&self.db.pool)
         .await?;
 
-        Ok(GuildSettings {
+        let mut settings = GuildSettings {
             join_log: rec
                 .as_ref()
                 .and_then(|r| r.join_log_channel_id.as_deref())
@@ -49,34 +375,154 @@ impl<'a> GuildSettingsRepo<'a> {
                 .and_then(|r| r.mod_log_channel_id.as_deref())
                 .and_then(|s| s.parse::<u64>().ok())
                 .map(serenity::all::ChannelId::new),
-        })
+            join_message_template: rec.as_ref().and_then(|r| r.join_message_template.clone()),
+            leave_message_template: rec.as_ref().and_then(|r| r.leave_message_template.clone()),
+            embed_color: rec.as_ref().and_then(|r| r.embed_color),
+            timezone: rec.as_ref().and_then(|r| r.timezone.clone()),
+            ban_detection_window_secs: rec.as_ref().and_then(|r| r.ban_detection_window_secs),
+            digest_enabled: rec.as_ref().is_some_and(|r| r.digest_enabled),
+            digest_hour: rec.as_ref().and_then(|r| r.digest_hour),
+            message_log_enabled: rec.as_ref().is_some_and(|r| r.message_log_enabled),
+            tracking_required: rec.as_ref().is_some_and(|r| r.tracking_required),
+            extended_log_channels: rec.as_ref().is_some_and(|r| r.extended_log_channels),
+            retention_days: rec.as_ref().and_then(|r| r.retention_days),
+            lang: rec.as_ref().and_then(|r| r.lang.clone()),
+            raid_threshold: rec.as_ref().and_then(|r| r.raid_threshold),
+            min_account_age_days: rec.as_ref().and_then(|r| r.min_account_age_days),
+            account_age_action: rec.as_ref().and_then(|r| r.account_age_action.clone()),
+            join_log_webhook_url: rec.as_ref().and_then(|r| r.join_log_webhook_url.clone()),
+            leave_log_webhook_url: rec.as_ref().and_then(|r| r.leave_log_webhook_url.clone()),
+            mod_log_webhook_url: rec.as_ref().and_then(|r| r.mod_log_webhook_url.clone()),
+            ..Default::default()
+        };
+
+        let routes = sqlx::query!(
+            r#"SELECT log_kind, channel_id FROM log_routes WHERE guild_id = ? ORDER BY rowid"#,
+            guild
+        )
+        .fetch_all(&self.db.pool)
+        .await?;
+        for r in routes {
+            let Some(kind) = LogKind::ALL.into_iter().find(|k| k.as_str() == r.log_kind) else {
+                continue;
+            };
+            if let Ok(id) = r.channel_id.parse::<u64>() {
+                settings.extras_mut(kind).push(ChannelId::new(id));
+            }
+        }
+
+        let roles = sqlx::query_scalar!(
+            r#"SELECT role_id FROM watched_roles WHERE guild_id = ? ORDER BY rowid"#,
+            guild
+        )
+        .fetch_all(&self.db.pool)
+        .await?;
+        settings.watched_roles = roles
+            .iter()
+            .filter_map(|s| s.parse::<u64>().ok())
+            .map(RoleId::new)
+            .collect();
+        Ok(settings)
     }
 
+    /// Write every column (mirror channels and watched roles included) from `settings` in one transaction, replacing whatever was
+    /// stored (used by `/settings import`; running it twice gives the same row).
     pub async fn upsert(
         &self,
         guild_id: &serenity::all::GuildId,
-        join: Option<ChannelId>,
-        leave: Option<ChannelId>,
-        log_channel: Option<ChannelId>,
+        settings: &GuildSettings,
     ) -> Result<()> {
-        let guild_id = guild_id.to_string();
-        let join = join.map(|c| c.to_string());
-        let leave = leave.map(|c| c.to_string());
-        let modu = log_channel.map(|c| c.to_string());
+        let gid = guild_id.to_string();
+        let join = settings.join_log.map(|c| c.to_string());
+        let leave = settings.leave_log.map(|c| c.to_string());
+        let modu = settings.mod_log.map(|c| c.to_string());
+        let mut tx = self.db.transaction().await?;
 
         sqlx::query!(
             r#"
-            INSERT INTO guild_settings (guild_id, join_log_channel_id, leave_log_channel_id, mod_log_channel_id)
-            VALUES (?, ?, ?, ?)
+            INSERT INTO guild_settings (
+                guild_id, join_log_channel_id, leave_log_channel_id, mod_log_channel_id,
+                join_message_template, leave_message_template, embed_color, timezone,
+                ban_detection_window_secs, digest_enabled, digest_hour, message_log_enabled,
+                lang, raid_threshold, min_account_age_days, account_age_action,
+                join_log_webhook_url, leave_log_webhook_url, mod_log_webhook_url,
+                tracking_required, extended_log_channels, retention_days
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             ON CONFLICT(guild_id) DO UPDATE SET
-              join_log_channel_id = COALESCE(excluded.join_log_channel_id, guild_settings.join_log_channel_id),
-              leave_log_channel_id = COALESCE(excluded.leave_log_channel_id, guild_settings.leave_log_channel_id),
-              mod_log_channel_id   = COALESCE(excluded.mod_log_channel_id,   guild_settings.mod_log_channel_id)
+              join_log_channel_id       = excluded.join_log_channel_id,
+              leave_log_channel_id      = excluded.leave_log_channel_id,
+              mod_log_channel_id        = excluded.mod_log_channel_id,
+              join_message_template     = excluded.join_message_template,
+              leave_message_template    = excluded.leave_message_template,
+              embed_color               = excluded.embed_color,
+              timezone                  = excluded.timezone,
+              ban_detection_window_secs = excluded.ban_detection_window_secs,
+              digest_enabled            = excluded.digest_enabled,
+              digest_hour               = excluded.digest_hour,
+              message_log_enabled       = excluded.message_log_enabled,
+              lang                      = excluded.lang,
+              raid_threshold            = excluded.raid_threshold,
+              min_account_age_days      = excluded.min_account_age_days,
+              account_age_action        = excluded.account_age_action,
+              join_log_webhook_url      = excluded.join_log_webhook_url,
+              leave_log_webhook_url     = excluded.leave_log_webhook_url,
+              mod_log_webhook_url       = excluded.mod_log_webhook_url,
+              tracking_required         = excluded.tracking_required,
+              extended_log_channels     = excluded.extended_log_channels,
+              retention_days            = excluded.retention_days
             "#,
-            guild_id, join, leave, modu
+            gid,
+            join,
+            leave,
+            modu,
+            settings.join_message_template,
+            settings.leave_message_template,
+            settings.embed_color,
+            settings.timezone,
+            settings.ban_detection_window_secs,
+            settings.digest_enabled,
+            settings.digest_hour,
+            settings.message_log_enabled,
+            settings.lang,
+            settings.raid_threshold,
+            settings.min_account_age_days,
+            settings.account_age_action,
+            settings.join_log_webhook_url,
+            settings.leave_log_webhook_url,
+            settings.mod_log_webhook_url,
+            settings.tracking_required,
+            settings.extended_log_channels,
+            settings.retention_days
         )
-        .execute(&self.db.pool)
+        .execute(&mut *tx)
         .await?;
+        for kind in LogKind::ALL {
+            let extras = match kind {
+                LogKind::Join => &settings.extra_join_logs,
+                LogKind::Leave => &settings.extra_leave_logs,
+                LogKind::Mod => &settings.extra_mod_logs,
+            };
+            replace_routes(&mut tx, &gid, kind, extras).await?;
+        }
+        replace_watched_roles(&mut tx, &gid, &settings.watched_roles).await?;
+
+        tx.commit().await?;
+        self.invalidate(guild_id);
+        Ok(())
+    }
+
+    /// Replace the role-change watch list (empty = log every role change).
+    pub async fn set_watched_roles(
+        &self,
+        guild_id: &serenity::all::GuildId,
+        roles: &[RoleId],
+    ) -> Result<()> {
+        let gid = guild_id.to_string();
+        let mut tx = self.db.transaction().await?;
+        replace_watched_roles(&mut tx, &gid, roles).await?;
+        tx.commit().await?;
+        self.invalidate(guild_id);
         Ok(())
     }
 
@@ -89,6 +535,7 @@ impl<'a> GuildSettingsRepo<'a> {
         )
         .execute(&self.db.pool)
         .await?;
+        self.invalidate(guild_id);
         Ok(())
     }
 
@@ -110,10 +557,311 @@ impl<'a> GuildSettingsRepo<'a> {
             let q = format!("UPDATE guild_settings SET {column} = NULL WHERE guild_id = ?");
             sqlx::query(&q).bind(gid).execute(&self.db.pool).await?;
         }
+        self.invalidate(guild_id);
+        Ok(())
+    }
+
+    /// Like `set_column`, for free-text columns (message templates).
+    pub async fn set_text_column(
+        &self,
+        guild_id: &serenity::all::GuildId,
+        column: &str,
+        value: Option<&str>,
+    ) -> Result<()> {
+        let gid = guild_id.to_string();
+        let q = format!("UPDATE guild_settings SET {column} = ? WHERE guild_id = ?");
+        sqlx::query(&q)
+            .bind(value)
+            .bind(gid)
+            .execute(&self.db.pool)
+            .await?;
+        self.invalidate(guild_id);
+        Ok(())
+    }
+
+    /// Set (or clear) the embed colour; callers validate the 0xRRGGBB range.
+    pub async fn set_embed_color(
+        &self,
+        guild_id: &serenity::all::GuildId,
+        color: Option<u32>,
+    ) -> Result<()> {
+        let gid = guild_id.to_string();
+        let color = color.map(i64::from);
+        sqlx::query!(
+            r#"UPDATE guild_settings SET embed_color = ? WHERE guild_id = ?"#,
+            color,
+            gid
+        )
+        .execute(&self.db.pool)
+        .await?;
+        self.invalidate(guild_id);
+        Ok(())
+    }
+
+    /// Set (or clear) the ban detection window; callers validate `BAN_WINDOW_RANGE`.
+    pub async fn set_ban_window(
+        &self,
+        guild_id: &serenity::all::GuildId,
+        secs: Option<i64>,
+    ) -> Result<()> {
+        let gid = guild_id.to_string();
+        sqlx::query!(
+            r#"UPDATE guild_settings SET ban_detection_window_secs = ? WHERE guild_id = ?"#,
+            secs,
+            gid
+        )
+        .execute(&self.db.pool)
+        .await?;
+        self.invalidate(guild_id);
+        Ok(())
+    }
+
+    /// Set (or clear) the raid alert threshold; callers validate `RAID_THRESHOLD_RANGE`.
+    pub async fn set_raid_threshold(
+        &self,
+        guild_id: &serenity::all::GuildId,
+        per_minute: Option<i64>,
+    ) -> Result<()> {
+        let gid = guild_id.to_string();
+        sqlx::query!(
+            r#"UPDATE guild_settings SET raid_threshold = ? WHERE guild_id = ?"#,
+            per_minute,
+            gid
+        )
+        .execute(&self.db.pool)
+        .await?;
+        self.invalidate(guild_id);
+        Ok(())
+    }
+
+    /// Keep history for this many days (`None` keeps it forever).
+    pub async fn set_retention_days(
+        &self,
+        guild_id: &serenity::all::GuildId,
+        days: Option<i64>,
+    ) -> Result<()> {
+        let gid = guild_id.to_string();
+        sqlx::query!(
+            r#"UPDATE guild_settings SET retention_days = ? WHERE guild_id = ?"#,
+            days,
+            gid
+        )
+        .execute(&self.db.pool)
+        .await?;
+        self.invalidate(guild_id);
+        Ok(())
+    }
+
+    /// Every guild with retention turned on, with its number of days.
+    pub async fn retention_policies(&self) -> Result<Vec<(GuildId, i64)>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT guild_id AS "guild_id!", retention_days AS "retention_days!: i64"
+            FROM guild_settings
+            WHERE retention_days IS NOT NULL
+            "#
+        )
+        .fetch_all(&self.db.pool)
+        .await?;
+        Ok(rows
+            .into_iter()
+            .filter_map(|r| {
+                let id = r.guild_id.parse::<u64>().ok().filter(|&id| id != 0)?;
+                Some((GuildId::new(id), r.retention_days))
+            })
+            .collect())
+    }
+
+    /// Set the minimum account age check (`None` turns it off) and what it does.
+    pub async fn set_account_age(
+        &self,
+        guild_id: &serenity::all::GuildId,
+        min_days: Option<i64>,
+        action: AccountAgeAction,
+    ) -> Result<()> {
+        let gid = guild_id.to_string();
+        let action = action.as_str();
+        sqlx::query!(
+            r#"
+            UPDATE guild_settings
+               SET min_account_age_days = ?, account_age_action = ?
+             WHERE guild_id = ?
+            "#,
+            min_days,
+            action,
+            gid
+        )
+        .execute(&self.db.pool)
+        .await?;
+        self.invalidate(guild_id);
+        Ok(())
+    }
+
+    /// Turn the daily digest on/off and optionally change its hour (callers validate 0–23).
+    pub async fn set_digest(
+        &self,
+        guild_id: &serenity::all::GuildId,
+        enabled: bool,
+        hour: Option<u32>,
+    ) -> Result<()> {
+        let gid = guild_id.to_string();
+        let hour = hour.map(i64::from);
+        sqlx::query!(
+            r#"
+            UPDATE guild_settings
+               SET digest_enabled = ?, digest_hour = COALESCE(?, digest_hour)
+             WHERE guild_id = ?
+            "#,
+            enabled,
+            hour,
+            gid
+        )
+        .execute(&self.db.pool)
+        .await?;
+        self.invalidate(guild_id);
+        Ok(())
+    }
+
+    /// Turn message delete/edit logging on or off.
+    pub async fn set_message_log(
+        &self,
+        guild_id: &serenity::all::GuildId,
+        enabled: bool,
+    ) -> Result<()> {
+        let gid = guild_id.to_string();
+        sqlx::query!(
+            r#"UPDATE guild_settings SET message_log_enabled = ? WHERE guild_id = ?"#,
+            enabled,
+            gid
+        )
+        .execute(&self.db.pool)
+        .await?;
+        self.invalidate(guild_id);
+        Ok(())
+    }
+
+    /// Honour member opt-outs (`false`) or keep tracking everyone (`true`).
+    pub async fn set_tracking_required(
+        &self,
+        guild_id: &serenity::all::GuildId,
+        required: bool,
+    ) -> Result<()> {
+        let gid = guild_id.to_string();
+        sqlx::query!(
+            r#"UPDATE guild_settings SET tracking_required = ? WHERE guild_id = ?"#,
+            required,
+            gid
+        )
+        .execute(&self.db.pool)
+        .await?;
+        self.invalidate(guild_id);
+        Ok(())
+    }
+
+    /// Offer voice/stage channel chats (`true`) or only text-like channels in the panel.
+    pub async fn set_extended_log_channels(
+        &self,
+        guild_id: &serenity::all::GuildId,
+        extended: bool,
+    ) -> Result<()> {
+        let gid = guild_id.to_string();
+        sqlx::query!(
+            r#"UPDATE guild_settings SET extended_log_channels = ? WHERE guild_id = ?"#,
+            extended,
+            gid
+        )
+        .execute(&self.db.pool)
+        .await?;
+        self.invalidate(guild_id);
+        Ok(())
+    }
+
+    /// Apply several column updates (channel ids and free text) plus replacement sets
+    /// of mirror channels in one transaction, creating the row if needed. Either all of
+    /// them land or none do.
+    pub async fn set_columns(
+        &self,
+        guild_id: &serenity::all::GuildId,
+        channels: &[(&str, Option<ChannelId>)],
+        texts: &[(&str, Option<&str>)],
+        routes: &[(LogKind, &[ChannelId])],
+    ) -> Result<()> {
+        let gid = guild_id.to_string();
+        let mut tx = self.db.transaction().await?;
+
+        sqlx::query!(
+            r#"INSERT INTO guild_settings (guild_id) VALUES (?) ON CONFLICT(guild_id) DO NOTHING"#,
+            gid
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        for (column, value) in channels {
+            let q = format!("UPDATE guild_settings SET {column} = ? WHERE guild_id = ?");
+            sqlx::query(&q)
+                .bind(value.map(|c| c.get().to_string()))
+                .bind(&gid)
+                .execute(&mut *tx)
+                .await?;
+        }
+        for (column, value) in texts {
+            let q = format!("UPDATE guild_settings SET {column} = ? WHERE guild_id = ?");
+            sqlx::query(&q)
+                .bind(*value)
+                .bind(&gid)
+                .execute(&mut *tx)
+                .await?;
+        }
+        for (kind, extras) in routes {
+            replace_routes(&mut tx, &gid, *kind, extras).await?;
+        }
+
+        tx.commit().await?;
+        self.invalidate(guild_id);
         Ok(())
     }
 
+    /// Remove `channel` from every log of this guild, primary column and mirrors alike
+    /// (e.g. once Discord reports it deleted). Returns the logs it was removed from.
+    pub async fn forget_channel(
+        &self,
+        guild_id: &serenity::all::GuildId,
+        channel: ChannelId,
+    ) -> Result<Vec<LogKind>> {
+        let settings = self.get(guild_id).await?;
+        let mut removed = Vec::new();
+        for kind in LogKind::ALL {
+            let primary = match kind {
+                LogKind::Join => settings.join_log,
+                LogKind::Leave => settings.leave_log,
+                LogKind::Mod => settings.mod_log,
+            };
+            if primary == Some(channel) {
+                self.set_column(guild_id, kind.primary_column(), None).await?;
+                removed.push(kind);
+            }
+        }
+
+        let gid = guild_id.to_string();
+        let ch = channel.to_string();
+        let mirrors = sqlx::query_scalar!(
+            r#"DELETE FROM log_routes WHERE guild_id = ? AND channel_id = ? RETURNING log_kind"#,
+            gid,
+            ch
+        )
+        .fetch_all(&self.db.pool)
+        .await?;
+        for kind in LogKind::ALL {
+            if mirrors.iter().any(|k| k == kind.as_str()) && !removed.contains(&kind) {
+                removed.push(kind);
+            }
+        }
+        self.invalidate(guild_id);
+        Ok(removed)
+    }
+
     /// Convenience: get settings for this guild.
+    #[allow(dead_code)] // public repo API; current callers go through `get_cached`
     pub async fn get_for_guild(&self, guild_id: &serenity::all::GuildId) -> Result<GuildSettings> {
         self.get(guild_id).await
     }
@@ -148,3 +896,103 @@ impl<'a> GuildSettingsRepo<'a> {
             .await
     }
 }
+
+/// Swap the mirror channels for one log type inside an open transaction.
+async fn replace_routes(
+    tx: &mut Transaction<'static, Sqlite>,
+    guild_id: &str,
+    kind: LogKind,
+    channels: &[ChannelId],
+) -> Result<()> {
+    let kind = kind.as_str();
+    sqlx::query!(
+        r#"DELETE FROM log_routes WHERE guild_id = ? AND log_kind = ?"#,
+        guild_id,
+        kind
+    )
+    .execute(&mut **tx)
+    .await?;
+    for ch in channels {
+        let ch = ch.to_string();
+        sqlx::query!(
+            r#"INSERT OR IGNORE INTO log_routes (guild_id, log_kind, channel_id) VALUES (?, ?, ?)"#,
+            guild_id,
+            kind,
+            ch
+        )
+        .execute(&mut **tx)
+        .await?;
+    }
+    Ok(())
+}
+
+/// Swap the role-change watch list inside an open transaction.
+async fn replace_watched_roles(
+    tx: &mut Transaction<'static, Sqlite>,
+    guild_id: &str,
+    roles: &[RoleId],
+) -> Result<()> {
+    sqlx::query!(r#"DELETE FROM watched_roles WHERE guild_id = ?"#, guild_id)
+        .execute(&mut **tx)
+        .await?;
+    for role in roles {
+        let role = role.to_string();
+        sqlx::query!(
+            r#"INSERT OR IGNORE INTO watched_roles (guild_id, role_id) VALUES (?, ?)"#,
+            guild_id,
+            role
+        )
+        .execute(&mut **tx)
+        .await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{GUILD, test_db};
+
+    #[tokio::test]
+    async fn writes_show_up_in_get_cached() {
+        let db = test_db().await;
+        let cache = SettingsCache::new();
+        let repo = GuildSettingsRepo::new(&db).with_cache(&cache);
+        repo.ensure_row(&GUILD).await.unwrap();
+        let ch = ChannelId::new(50);
+
+        // Warm the cache before every write so a missed invalidation would show.
+        repo.get_cached(&GUILD).await.unwrap();
+        repo.set_mod_log(&GUILD, Some(ch)).await.unwrap();
+        assert_eq!(repo.get_cached(&GUILD).await.unwrap().mod_log, Some(ch));
+
+        repo.set_embed_color(&GUILD, Some(0x00ff00)).await.unwrap();
+        assert_eq!(repo.get_cached(&GUILD).await.unwrap().embed_color, Some(0x00ff00));
+
+        repo.set_digest(&GUILD, true, Some(7)).await.unwrap();
+        let s = repo.get_cached(&GUILD).await.unwrap();
+        assert!(s.digest_enabled);
+        assert_eq!(s.digest_hour(), 7);
+
+        repo.set_watched_roles(&GUILD, &[RoleId::new(9)]).await.unwrap();
+        assert_eq!(repo.get_cached(&GUILD).await.unwrap().watched_roles, [RoleId::new(9)]);
+
+        repo.forget_channel(&GUILD, ch).await.unwrap();
+        assert_eq!(repo.get_cached(&GUILD).await.unwrap().mod_log, None);
+    }
+
+    #[tokio::test]
+    async fn writes_without_the_cache_leave_it_stale() {
+        let db = test_db().await;
+        let cache = SettingsCache::new();
+        let cached = GuildSettingsRepo::new(&db).with_cache(&cache);
+        cached.ensure_row(&GUILD).await.unwrap();
+        cached.get_cached(&GUILD).await.unwrap();
+
+        // Why callers must use `AppState::settings_repo()` rather than `new`.
+        let bare = GuildSettingsRepo::new(&db);
+        bare.set_mod_log(&GUILD, Some(ChannelId::new(50))).await.unwrap();
+        assert_eq!(cached.get_cached(&GUILD).await.unwrap().mod_log, None);
+        assert_eq!(bare.get(&GUILD).await.unwrap().mod_log, Some(ChannelId::new(50)));
+    }
+}
@@ -0,0 +1,163 @@
+use poise::serenity_prelude as serenity;
+use serenity::all::{GuildId, Timestamp, UserId};
+
+use crate::db::Db;
+use crate::repos::error::Result;
+use crate::repos::timestamp::UnixTime;
+
+#[derive(Clone)]
+pub struct InvitesRepo<'a> {
+    db: &'a Db,
+}
+
+impl<'a> InvitesRepo<'a> {
+    pub fn new(db: &'a Db) -> Self {
+        Self { db }
+    }
+
+    /// Insert or refresh an invite as Discord reports it. `max_age_secs == 0` means it
+    /// never expires. Uses only move forward, so a stale fetch can't undo a newer one.
+    pub async fn upsert_invite(
+        &self,
+        guild_id: GuildId,
+        code: &str,
+        inviter_id: Option<UserId>,
+        created_at: Timestamp,
+        max_age_secs: u32,
+        uses: u64,
+    ) -> Result<()> {
+        let guild_id = guild_id.to_string();
+        let inviter_id = inviter_id.map(|u| u.to_string());
+        let created_at = UnixTime::from(created_at);
+        let expires_at =
+            (max_age_secs > 0).then(|| UnixTime(created_at.secs() + i64::from(max_age_secs)));
+        let uses = i64::try_from(uses).unwrap_or(i64::MAX);
+
+        sqlx::query!(
+            r#"
+            INSERT INTO invites (guild_id, code, inviter_id, created_at, expires_at, uses)
+            VALUES (?, ?, ?, ?, ?, ?)
+            ON CONFLICT(guild_id, code) DO UPDATE SET
+              inviter_id = COALESCE(excluded.inviter_id, invites.inviter_id),
+              expires_at = excluded.expires_at,
+              uses       = MAX(invites.uses, excluded.uses),
+              deleted_at = NULL
+            "#,
+            guild_id,
+            code,
+            inviter_id,
+            created_at,
+            expires_at,
+            uses
+        )
+        .execute(&self.db.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Count a join we attributed to `code`.
+    pub async fn record_use(&self, guild_id: GuildId, code: &str) -> Result<()> {
+        let guild_id = guild_id.to_string();
+        let now = UnixTime::now();
+        sqlx::query!(
+            r#"
+            UPDATE invites
+               SET joins = joins + 1, last_used_at = ?
+             WHERE guild_id = ? AND code = ?
+            "#,
+            now,
+            guild_id,
+            code
+        )
+        .execute(&self.db.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Keep the row (and its history) but note that the invite no longer exists.
+    pub async fn mark_deleted(&self, guild_id: GuildId, code: &str) -> Result<()> {
+        let guild_id = guild_id.to_string();
+        let now = UnixTime::now();
+        sqlx::query!(
+            r#"
+            UPDATE invites SET deleted_at = ?
+             WHERE guild_id = ? AND code = ? AND deleted_at IS NULL
+            "#,
+            now,
+            guild_id,
+            code
+        )
+        .execute(&self.db.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Per-inviter totals across all their invites, most attributed joins first.
+    pub async fn top_inviters(&self, guild_id: GuildId, limit: i64) -> Result<Vec<InviterTotals>> {
+        let guild_id = guild_id.to_string();
+        let rows = sqlx::query_as!(
+            InviterTotals,
+            r#"
+            SELECT inviter_id       AS "inviter_id!",
+                   COUNT(*)         AS "invites!: i64",
+                   SUM(uses)        AS "uses!: i64"
+            FROM invites
+            WHERE guild_id = ? AND inviter_id IS NOT NULL
+            GROUP BY inviter_id
+            ORDER BY SUM(joins) DESC, SUM(uses) DESC, inviter_id ASC
+            LIMIT ?
+            "#,
+            guild_id,
+            limit
+        )
+        .fetch_all(&self.db.pool)
+        .await?;
+        Ok(rows)
+    }
+
+    /// Everything stored about one invite code.
+    pub async fn invite_detail(&self, guild_id: GuildId, code: &str) -> Result<Option<InviteRow>> {
+        let guild_id = guild_id.to_string();
+        let row = sqlx::query_as!(
+            InviteRow,
+            r#"
+            SELECT code,
+                   inviter_id,
+                   created_at   AS "created_at: UnixTime",
+                   expires_at   AS "expires_at: UnixTime",
+                   uses,
+                   joins,
+                   last_used_at AS "last_used_at: UnixTime",
+                   deleted_at   AS "deleted_at: UnixTime"
+            FROM invites
+            WHERE guild_id = ? AND code = ?
+            "#,
+            guild_id,
+            code
+        )
+        .fetch_optional(&self.db.pool)
+        .await?;
+        Ok(row)
+    }
+}
+
+/// One `invites` row.
+#[derive(Debug, Clone)]
+pub struct InviteRow {
+    pub code: String,
+    pub inviter_id: Option<String>,
+    pub created_at: UnixTime,
+    pub expires_at: Option<UnixTime>,
+    pub uses: i64,
+    pub joins: i64,
+    pub last_used_at: Option<UnixTime>,
+    pub deleted_at: Option<UnixTime>,
+}
+
+/// Aggregate for `top_inviters`.
+#[derive(Debug, Clone)]
+pub struct InviterTotals {
+    pub inviter_id: String,
+    pub invites: i64,
+    pub uses: i64,
+}
@@ -0,0 +1,93 @@
+use poise::serenity_prelude as serenity;
+use serde::Serialize;
+use serenity::all::{GuildId, UserId};
+
+use crate::db::Db;
+use crate::repos::error::{RepoError, Result};
+use crate::repos::timestamp::UnixTime;
+
+#[derive(Clone)]
+pub struct MemberNotesRepo<'a> {
+    db: &'a Db,
+}
+
+impl<'a> MemberNotesRepo<'a> {
+    pub fn new(db: &'a Db) -> Self {
+        Self { db }
+    }
+
+    /// Attach a note to a user; returns the new note id.
+    pub async fn add_note(
+        &self,
+        guild_id: GuildId,
+        user_id: UserId,
+        author_id: UserId,
+        body: &str,
+    ) -> Result<i64> {
+        let guild_id = guild_id.to_string();
+        let user_id = user_id.to_string();
+        let author_id = author_id.to_string();
+        let created_at = UnixTime::now();
+
+        let res = sqlx::query!(
+            r#"
+            INSERT INTO member_notes (guild_id, user_id, author_id, body, created_at)
+            VALUES (?, ?, ?, ?, ?)
+            "#,
+            guild_id,
+            user_id,
+            author_id,
+            body,
+            created_at
+        )
+        .execute(&self.db.pool)
+        .await?;
+        Ok(res.last_insert_rowid())
+    }
+
+    /// All notes for a user, oldest first.
+    pub async fn list_notes(&self, guild_id: GuildId, user_id: UserId) -> Result<Vec<MemberNote>> {
+        let guild_id = guild_id.to_string();
+        let user_id = user_id.to_string();
+        let rows = sqlx::query_as!(
+            MemberNote,
+            r#"
+            SELECT id AS "id!", author_id, body, created_at AS "created_at: UnixTime"
+            FROM member_notes
+            WHERE guild_id = ? AND user_id = ?
+            ORDER BY id ASC
+            "#,
+            guild_id,
+            user_id
+        )
+        .fetch_all(&self.db.pool)
+        .await?;
+        Ok(rows)
+    }
+
+    /// Delete one note; `RepoError::NotFound` if no such note exists for this user.
+    pub async fn delete_note(&self, guild_id: GuildId, user_id: UserId, note_id: i64) -> Result<()> {
+        let guild_id = guild_id.to_string();
+        let user_id = user_id.to_string();
+        let res = sqlx::query!(
+            r#"DELETE FROM member_notes WHERE id = ? AND guild_id = ? AND user_id = ?"#,
+            note_id,
+            guild_id,
+            user_id
+        )
+        .execute(&self.db.pool)
+        .await?;
+        if res.rows_affected() == 0 {
+            return Err(RepoError::NotFound);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MemberNote {
+    pub id: i64,
+    pub author_id: String,
+    pub body: String,
+    pub created_at: UnixTime,
+}
@@ -0,0 +1,271 @@
+//! Read side of the membership data, as a trait so commands can run against
+//! something other than SQLite (e.g. an in-memory fake in tests).
+//!
+//! `AppState::memberships` hands out the SQLite-backed implementation. Writes
+//! (joins, leaves, purges, merges) stay on `MembershipsRepo` itself.
+
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use poise::serenity_prelude as serenity;
+use serenity::all::{GuildId, UserId};
+
+use crate::repos::error::Result;
+use crate::repos::memberships_repo::{
+    ChurnStint, ExitPage, ExportRow, FirstJoinRow, FullUserRecord, InviteJoinRow, MembershipRow,
+    MembershipsRepo, NameChangeRow, RejoinTimes, RejoinerRow, StatsCurrent, StintTimes, TenureRow,
+    TimeWindow, UserSummary,
+};
+use crate::repos::timestamp::UnixTime;
+
+/// Membership queries used by `/member`, `/stats`, `/userinfo` and the digest.
+#[async_trait]
+pub trait MembershipStore: Send + Sync {
+    /// A user's stints, oldest first.
+    async fn history_for_user(
+        &self,
+        guild_id: GuildId,
+        user_id: UserId,
+    ) -> Result<Vec<MembershipRow>>;
+
+    /// A user's recorded nickname/username changes, oldest first.
+    async fn name_history_for_user(
+        &self,
+        guild_id: GuildId,
+        user_id: UserId,
+    ) -> Result<Vec<NameChangeRow>>;
+
+    /// Everything stored about one user (for `/member export-user`).
+    async fn full_user_record(&self, guild_id: GuildId, user_id: UserId) -> Result<FullUserRecord>;
+
+    /// Every membership row of a guild (for `/member export`).
+    async fn all_rows_for_guild(&self, guild_id: GuildId) -> Result<Vec<ExportRow>>;
+
+    /// Autocomplete search (FTS, then LIKE, then typo-tolerant).
+    async fn search_user_summaries_prefix(
+        &self,
+        guild_id: GuildId,
+        partial: &str,
+        limit: i64,
+    ) -> Result<Vec<UserSummary>>;
+
+    /// `LIKE` search over last-known names.
+    async fn search_user_summaries(
+        &self,
+        guild_id: GuildId,
+        like: &str,
+        limit: i64,
+    ) -> Result<Vec<UserSummary>>;
+
+    /// One page of users by most recent stint.
+    async fn recent_user_summaries_page(
+        &self,
+        guild_id: GuildId,
+        limit: i64,
+        after_last_row_id: Option<i64>,
+    ) -> Result<Vec<UserSummary>>;
+
+    /// Headline totals for `/stats current`.
+    async fn stats_current(&self, guild_id: GuildId) -> Result<StatsCurrent>;
+
+    async fn rejoiners(
+        &self,
+        guild_id: GuildId,
+        min_rejoins: i64,
+        limit: i64,
+    ) -> Result<Vec<RejoinerRow>>;
+
+    async fn churn_stats(
+        &self,
+        guild_id: GuildId,
+        min_stints: i64,
+        cap: i64,
+    ) -> Result<Vec<ChurnStint>>;
+
+    async fn tenure_by_user(
+        &self,
+        guild_id: GuildId,
+        include_former: bool,
+        limit: i64,
+    ) -> Result<Vec<TenureRow>>;
+
+    async fn earliest_joiners(&self, guild_id: GuildId, limit: i64) -> Result<Vec<FirstJoinRow>>;
+
+    async fn all_exits(
+        &self,
+        guild_id: GuildId,
+        window: TimeWindow,
+        after_id: Option<i64>,
+        limit: i64,
+    ) -> Result<ExitPage>;
+
+    async fn activity_since(
+        &self,
+        guild_id: GuildId,
+        since: UnixTime,
+        cap: i64,
+    ) -> Result<Vec<RejoinTimes>>;
+
+    async fn recent_joins_since(
+        &self,
+        guild_id: GuildId,
+        since: UnixTime,
+        cap: i64,
+    ) -> Result<Vec<UnixTime>>;
+
+    async fn retention_cohorts(&self, guild_id: GuildId) -> Result<Vec<StintTimes>>;
+
+    async fn completed_stint_durations(&self, guild_id: GuildId) -> Result<Vec<chrono::Duration>>;
+
+    async fn open_stint_count(&self, guild_id: GuildId) -> Result<i64>;
+
+    async fn snapshots_in_range(
+        &self,
+        guild_id: GuildId,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<(NaiveDate, i64)>>;
+
+    async fn invite_joins_raw(&self, guild_id: GuildId, cap: i64) -> Result<Vec<InviteJoinRow>>;
+}
+
+#[async_trait]
+impl MembershipStore for MembershipsRepo<'_> {
+    async fn history_for_user(
+        &self,
+        guild_id: GuildId,
+        user_id: UserId,
+    ) -> Result<Vec<MembershipRow>> {
+        MembershipsRepo::history_for_user(self, guild_id, user_id).await
+    }
+
+    async fn name_history_for_user(
+        &self,
+        guild_id: GuildId,
+        user_id: UserId,
+    ) -> Result<Vec<NameChangeRow>> {
+        MembershipsRepo::name_history_for_user(self, guild_id, user_id).await
+    }
+
+    async fn full_user_record(&self, guild_id: GuildId, user_id: UserId) -> Result<FullUserRecord> {
+        MembershipsRepo::full_user_record(self, guild_id, user_id).await
+    }
+
+    async fn all_rows_for_guild(&self, guild_id: GuildId) -> Result<Vec<ExportRow>> {
+        MembershipsRepo::all_rows_for_guild(self, guild_id).await
+    }
+
+    async fn search_user_summaries_prefix(
+        &self,
+        guild_id: GuildId,
+        partial: &str,
+        limit: i64,
+    ) -> Result<Vec<UserSummary>> {
+        MembershipsRepo::search_user_summaries_prefix(self, guild_id, partial, limit).await
+    }
+
+    async fn search_user_summaries(
+        &self,
+        guild_id: GuildId,
+        like: &str,
+        limit: i64,
+    ) -> Result<Vec<UserSummary>> {
+        MembershipsRepo::search_user_summaries(self, guild_id, like, limit).await
+    }
+
+    async fn recent_user_summaries_page(
+        &self,
+        guild_id: GuildId,
+        limit: i64,
+        after_last_row_id: Option<i64>,
+    ) -> Result<Vec<UserSummary>> {
+        MembershipsRepo::recent_user_summaries_page(self, guild_id, limit, after_last_row_id).await
+    }
+
+    async fn stats_current(&self, guild_id: GuildId) -> Result<StatsCurrent> {
+        MembershipsRepo::stats_current(self, guild_id).await
+    }
+
+    async fn rejoiners(
+        &self,
+        guild_id: GuildId,
+        min_rejoins: i64,
+        limit: i64,
+    ) -> Result<Vec<RejoinerRow>> {
+        MembershipsRepo::rejoiners(self, guild_id, min_rejoins, limit).await
+    }
+
+    async fn churn_stats(
+        &self,
+        guild_id: GuildId,
+        min_stints: i64,
+        cap: i64,
+    ) -> Result<Vec<ChurnStint>> {
+        MembershipsRepo::churn_stats(self, guild_id, min_stints, cap).await
+    }
+
+    async fn tenure_by_user(
+        &self,
+        guild_id: GuildId,
+        include_former: bool,
+        limit: i64,
+    ) -> Result<Vec<TenureRow>> {
+        MembershipsRepo::tenure_by_user(self, guild_id, include_former, limit).await
+    }
+
+    async fn earliest_joiners(&self, guild_id: GuildId, limit: i64) -> Result<Vec<FirstJoinRow>> {
+        MembershipsRepo::earliest_joiners(self, guild_id, limit).await
+    }
+
+    async fn all_exits(
+        &self,
+        guild_id: GuildId,
+        window: TimeWindow,
+        after_id: Option<i64>,
+        limit: i64,
+    ) -> Result<ExitPage> {
+        MembershipsRepo::all_exits(self, guild_id, window, after_id, limit).await
+    }
+
+    async fn activity_since(
+        &self,
+        guild_id: GuildId,
+        since: UnixTime,
+        cap: i64,
+    ) -> Result<Vec<RejoinTimes>> {
+        MembershipsRepo::activity_since(self, guild_id, since, cap).await
+    }
+
+    async fn recent_joins_since(
+        &self,
+        guild_id: GuildId,
+        since: UnixTime,
+        cap: i64,
+    ) -> Result<Vec<UnixTime>> {
+        MembershipsRepo::recent_joins_since(self, guild_id, since, cap).await
+    }
+
+    async fn retention_cohorts(&self, guild_id: GuildId) -> Result<Vec<StintTimes>> {
+        MembershipsRepo::retention_cohorts(self, guild_id).await
+    }
+
+    async fn completed_stint_durations(&self, guild_id: GuildId) -> Result<Vec<chrono::Duration>> {
+        MembershipsRepo::completed_stint_durations(self, guild_id).await
+    }
+
+    async fn open_stint_count(&self, guild_id: GuildId) -> Result<i64> {
+        MembershipsRepo::open_stint_count(self, guild_id).await
+    }
+
+    async fn snapshots_in_range(
+        &self,
+        guild_id: GuildId,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<(NaiveDate, i64)>> {
+        MembershipsRepo::snapshots_in_range(self, guild_id, from, to).await
+    }
+
+    async fn invite_joins_raw(&self, guild_id: GuildId, cap: i64) -> Result<Vec<InviteJoinRow>> {
+        MembershipsRepo::invite_joins_raw(self, guild_id, cap).await
+    }
+}
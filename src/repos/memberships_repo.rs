@@ -1,9 +1,25 @@
-use anyhow::Result;
+use std::collections::HashSet;
+
 use poise::serenity_prelude as serenity;
-use serenity::all::{GuildId, Member, Timestamp, UserId};
-use sqlx::FromRow;
+use serenity::all::{GuildId, Member, Timestamp, User, UserId};
+use serde::Serialize;
+use sqlx::{FromRow, QueryBuilder, Sqlite, SqliteConnection, Transaction};
+use unicode_normalization::UnicodeNormalization;
+use unicode_normalization::char::is_combining_mark;
 
 use crate::db::Db;
+use crate::repos::error::{RepoError, Result};
+use crate::repos::member_notes_repo::{MemberNote, MemberNotesRepo};
+use crate::repos::timestamp::UnixTime;
+
+/// Fewer exact (FTS/LIKE) hits than this and autocomplete also tries typo-tolerant matches.
+const FUZZY_MIN_HITS: usize = 5;
+/// Most recent users scanned by the typo-tolerant fallback.
+const FUZZY_CANDIDATE_CAP: i64 = 2_000;
+/// Shorter inputs are too ambiguous to correct.
+const FUZZY_MIN_QUERY_CHARS: usize = 3;
+/// Rows per multi-row insert when rebuilding a guild's FTS index.
+const FTS_REBUILD_BATCH: usize = 500;
 
 #[derive(Clone)]
 pub struct MembershipsRepo<'a> {
@@ -17,64 +33,551 @@ impl<'a> MembershipsRepo<'a> {
 
     // ---------- writes ----------
 
-    /// Start a membership stint for this user (no invite fields anymore).
-    pub async fn record_join(&self, guild_id: GuildId, member: &Member) -> Result<()> {
-        let guild_id = guild_id.to_string();
+    /// Start a membership stint for this user, with the invite it was attributed to (if known),
+    /// and refresh their FTS row in the same transaction so autocomplete never misses a join.
+    pub async fn record_join_with_fts(
+        &self,
+        guild_id: GuildId,
+        member: &Member,
+        invite_code: Option<&str>,
+        inviter_id: Option<UserId>,
+    ) -> Result<()> {
+        let mut tx = self.db.transaction().await?;
+        let now = UnixTime::now();
+        Self::insert_open_stint(&mut tx, guild_id, member, now, invite_code, inviter_id).await?;
+        Self::write_fts_row(&mut tx, guild_id, &member.user.id.to_string()).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Insert an open stint for `member` starting at `joined`.
+    async fn insert_open_stint(
+        conn: &mut SqliteConnection,
+        guild_id: GuildId,
+        member: &Member,
+        joined: UnixTime,
+        invite_code: Option<&str>,
+        inviter_id: Option<UserId>,
+    ) -> Result<()> {
+        let gid = guild_id.to_string();
         let user_id = member.user.id.to_string();
-        let joined_at = Timestamp::now().to_rfc2822();
+        let joined_at = joined.to_rfc3339();
+        let joined_at_epoch = joined.secs();
 
         let account_username = member.user.name.clone();
         let server_username = member.nick.clone();
+        let inviter_id = inviter_id.map(|u| u.to_string());
 
         sqlx::query!(
             r#"
             INSERT INTO memberships (
-                guild_id, user_id, joined_at, left_at, banned,
-                account_username, server_username
+                guild_id, user_id, joined_at, joined_at_epoch, left_at, banned,
+                account_username, server_username, invite_code, inviter_id
             )
-            VALUES (?, ?, ?, NULL, 0, ?, ?)
+            VALUES (?, ?, ?, ?, NULL, 0, ?, ?, ?, ?)
             "#,
-            guild_id,
+            gid,
             user_id,
             joined_at,
+            joined_at_epoch,
             account_username,
-            server_username
+            server_username,
+            invite_code,
+            inviter_id
         )
-        .execute(&self.db.pool)
+        .execute(conn)
         .await?;
         Ok(())
     }
 
-    /// Close the latest open membership stint: set left_at + banned flag.
+    /// Number of stints recorded for a user (open and closed, inferred included).
+    pub async fn stint_count(&self, guild_id: GuildId, user_id: UserId) -> Result<i64> {
+        let guild_id = guild_id.to_string();
+        let user_id = user_id.to_string();
+        let n = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) AS "n!: i64" FROM memberships WHERE guild_id = ? AND user_id = ?"#,
+            guild_id,
+            user_id
+        )
+        .fetch_one(&self.db.pool)
+        .await?;
+        Ok(n)
+    }
+
+    /// The user's most recent stint, if any.
+    pub async fn latest_stint(
+        &self,
+        guild_id: GuildId,
+        user_id: UserId,
+    ) -> Result<Option<MembershipRow>> {
+        let guild_id = guild_id.to_string();
+        let user_id = user_id.to_string();
+        let row = sqlx::query_as!(
+            MembershipRow,
+            r#"
+            SELECT joined_at_epoch AS "joined_at!: UnixTime",
+                   left_at_epoch AS "left_at: UnixTime",
+                   leave_kind    AS "leave_kind: LeaveKind",
+                   ban_reason,
+                   account_username,
+                   server_username,
+                   inferred      AS "inferred: bool",
+                   unbanned_at_epoch AS "unbanned_at: UnixTime"
+            FROM memberships
+            WHERE guild_id = ? AND user_id = ?
+            ORDER BY id DESC
+            LIMIT 1
+            "#,
+            guild_id,
+            user_id
+        )
+        .fetch_optional(&self.db.pool)
+        .await?;
+        Ok(row)
+    }
+
+    /// The user's most recent stint that ended in a ban, if they were ever banned here.
+    pub async fn was_ever_banned(
+        &self,
+        guild_id: GuildId,
+        user_id: UserId,
+    ) -> Result<Option<MembershipRow>> {
+        let guild_id = guild_id.to_string();
+        let user_id = user_id.to_string();
+        let banned = LeaveKind::Banned as i64;
+        let row = sqlx::query_as!(
+            MembershipRow,
+            r#"
+            SELECT joined_at_epoch AS "joined_at!: UnixTime",
+                   left_at_epoch AS "left_at: UnixTime",
+                   leave_kind    AS "leave_kind: LeaveKind",
+                   ban_reason,
+                   account_username,
+                   server_username,
+                   inferred      AS "inferred: bool",
+                   unbanned_at_epoch AS "unbanned_at: UnixTime"
+            FROM memberships
+            WHERE guild_id = ? AND user_id = ? AND leave_kind = ?
+            ORDER BY id DESC
+            LIMIT 1
+            "#,
+            guild_id,
+            user_id,
+            banned
+        )
+        .fetch_optional(&self.db.pool)
+        .await?;
+        Ok(row)
+    }
+
+    /// Open a stint for every member without one (backfill). Uses each member's
+    /// Discord join time when known; returns how many rows were inserted.
+    pub async fn bulk_record_joins(&self, guild_id: GuildId, members: &[Member]) -> Result<u64> {
+        const BATCH: usize = 500;
+        let gid = guild_id.to_string();
+
+        let open: std::collections::HashSet<String> = sqlx::query_scalar!(
+            r#"SELECT user_id FROM memberships WHERE guild_id = ? AND left_at IS NULL"#,
+            gid
+        )
+        .fetch_all(&self.db.pool)
+        .await?
+        .into_iter()
+        .collect();
+
+        let missing: Vec<&Member> = members
+            .iter()
+            .filter(|m| !open.contains(&m.user.id.to_string()))
+            .collect();
+
+        let now = Timestamp::now();
+        let mut tx = self.db.transaction().await?;
+        let mut added = 0;
+        for batch in missing.chunks(BATCH) {
+            let mut qb = QueryBuilder::<Sqlite>::new(
+                "INSERT INTO memberships (guild_id, user_id, joined_at, joined_at_epoch, left_at, banned, account_username, server_username) ",
+            );
+            qb.push_values(batch, |mut row, m| {
+                let joined_at = UnixTime::from(m.joined_at.unwrap_or(now));
+                row.push_bind(&gid)
+                    .push_bind(m.user.id.to_string())
+                    .push_bind(joined_at.to_rfc3339())
+                    .push_bind(joined_at.secs())
+                    .push("NULL")
+                    .push("0")
+                    .push_bind(m.user.name.clone())
+                    .push_bind(m.nick.clone());
+            });
+            added += qb.build().execute(&mut *tx).await?.rows_affected();
+        }
+        tx.commit().await?;
+
+        Ok(added)
+    }
+
+    /// Users with an open stint (tracked as currently in the guild).
+    pub async fn open_stint_user_ids(&self, guild_id: GuildId) -> Result<HashSet<UserId>> {
+        let mut conn = self.db.pool.acquire().await?;
+        Self::open_ids_on(&mut conn, guild_id).await
+    }
+
+    async fn open_ids_on(
+        conn: &mut SqliteConnection,
+        guild_id: GuildId,
+    ) -> Result<HashSet<UserId>> {
+        let gid = guild_id.to_string();
+        let ids = sqlx::query_scalar!(
+            r#"SELECT user_id FROM memberships WHERE guild_id = ? AND left_at IS NULL"#,
+            gid
+        )
+        .fetch_all(conn)
+        .await?;
+        Ok(ids
+            .iter()
+            .filter_map(|id| id.parse::<u64>().ok().filter(|&n| n != 0))
+            .map(UserId::new)
+            .collect())
+    }
+
+    /// Bring open stints in line with the live member list, in one transaction: users
+    /// tracked as present but missing from `live` get their stint closed as a plain
+    /// leave (now); members of `live` without an open stint get one, dated from their
+    /// Discord join time. Usernames FTS is left for the caller to rebuild.
+    pub async fn reconcile_open_stints(
+        &self,
+        guild_id: GuildId,
+        live: &[Member],
+    ) -> Result<ReconcileCounts> {
+        let mut tx = self.db.transaction().await?;
+        let open = Self::open_ids_on(&mut tx, guild_id).await?;
+        let present: HashSet<UserId> = live.iter().map(|m| m.user.id).collect();
+        let now = UnixTime::now();
+
+        let mut counts = ReconcileCounts::default();
+        for user_id in open.difference(&present) {
+            counts.closed +=
+                Self::close_open_stint(&mut tx, guild_id, *user_id, LeaveKind::Left, None, now)
+                    .await?;
+        }
+        for member in live.iter().filter(|m| !open.contains(&m.user.id)) {
+            let joined = member.joined_at.map(UnixTime::from).unwrap_or(now);
+            Self::insert_open_stint(&mut tx, guild_id, member, joined, None, None).await?;
+            counts.opened += 1;
+        }
+        tx.commit().await?;
+        Ok(counts)
+    }
+
+    /// Close the latest open membership stint: set left_at + how the member left
+    /// (and the audit-log ban reason, if known). Returns how many stints were closed;
+    /// 0 means none was open (e.g. the ban handler already closed it).
     pub async fn record_leave(
         &self,
         guild_id: GuildId,
         user_id: UserId,
-        banned: bool,
-    ) -> Result<()> {
+        kind: LeaveKind,
+        ban_reason: Option<&str>,
+    ) -> Result<u64> {
+        let mut conn = self.db.pool.acquire().await?;
+        let now = UnixTime::now();
+        Self::close_open_stint(&mut conn, guild_id, user_id, kind, ban_reason, now).await
+    }
+
+    /// Close `user_id`'s open stint at `left`; returns how many rows were closed.
+    async fn close_open_stint(
+        conn: &mut SqliteConnection,
+        guild_id: GuildId,
+        user_id: UserId,
+        kind: LeaveKind,
+        ban_reason: Option<&str>,
+        left: UnixTime,
+    ) -> Result<u64> {
         let guild_id = guild_id.to_string();
         let user_id = user_id.to_string();
-        let left_at = Timestamp::now().to_rfc2822();
-        let banned_i64 = if banned { 1_i64 } else { 0_i64 };
+        let left_at = left.to_rfc3339();
+        let left_at_epoch = left.secs();
+        let kind_i64 = kind as i64;
+        let banned_i64 = (kind == LeaveKind::Banned) as i64;
 
-        sqlx::query!(
+        // Only the open stint is touched, so a second call for the same removal is a no-op.
+        let res = sqlx::query!(
             r#"
             UPDATE memberships
-               SET left_at = ?, banned = ?
+               SET left_at = ?, left_at_epoch = ?, leave_kind = ?, banned = ?, ban_reason = ?
              WHERE guild_id = ? AND user_id = ? AND left_at IS NULL
             "#,
             left_at,
+            left_at_epoch,
+            kind_i64,
             banned_i64,
+            ban_reason,
             guild_id,
             user_id
         )
+        .execute(conn)
+        .await?;
+        Ok(res.rows_affected())
+    }
+
+    /// Record a leave for a user with no open stint (we missed their join, e.g. while
+    /// offline): insert a closed stint flagged `inferred`, with joined_at = left_at.
+    pub async fn record_inferred_stint(
+        &self,
+        guild_id: GuildId,
+        user: &User,
+        kind: LeaveKind,
+        ban_reason: Option<&str>,
+    ) -> Result<()> {
+        let guild_id = guild_id.to_string();
+        let user_id = user.id.to_string();
+        let ts = UnixTime::now();
+        let now = ts.to_rfc3339();
+        let now_epoch = ts.secs();
+        let kind_i64 = kind as i64;
+        let banned_i64 = (kind == LeaveKind::Banned) as i64;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO memberships (
+                guild_id, user_id, joined_at, joined_at_epoch, left_at, left_at_epoch, banned,
+                leave_kind, ban_reason, account_username, inferred
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, 1)
+            "#,
+            guild_id,
+            user_id,
+            now,
+            now_epoch,
+            now,
+            now_epoch,
+            banned_i64,
+            kind_i64,
+            ban_reason,
+            user.name
+        )
+        .execute(&self.db.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Whether the user's latest stint was closed as a ban within the last `within_secs`
+    /// (i.e. the ban handler already recorded this removal).
+    pub async fn recently_closed_as_ban(
+        &self,
+        guild_id: GuildId,
+        user_id: UserId,
+        within_secs: i64,
+    ) -> Result<bool> {
+        Ok(self
+            .last_closed_stint(guild_id, user_id, within_secs)
+            .await?
+            .is_some_and(|(_, kind)| kind == LeaveKind::Banned))
+    }
+
+    /// The latest stint's id and leave kind, if it was closed within `within_secs`.
+    async fn last_closed_stint(
+        &self,
+        guild_id: GuildId,
+        user_id: UserId,
+        within_secs: i64,
+    ) -> Result<Option<(i64, LeaveKind)>> {
+        let gid = guild_id.to_string();
+        let uid = user_id.to_string();
+
+        let Some(last) = sqlx::query!(
+            r#"
+            SELECT id                AS "id!: i64",
+                   left_at_epoch     AS "left_at: UnixTime",
+                   leave_kind        AS "leave_kind: LeaveKind"
+            FROM memberships
+            WHERE guild_id = ? AND user_id = ?
+            ORDER BY id DESC
+            LIMIT 1
+            "#,
+            gid,
+            uid
+        )
+        .fetch_optional(&self.db.pool)
+        .await?
+        else {
+            return Ok(None);
+        };
+
+        let Some(left_at) = last.left_at else {
+            return Ok(None);
+        };
+        let age = UnixTime::now().secs() - left_at.secs();
+        Ok((age <= within_secs).then_some((last.id, last.leave_kind)))
+    }
+
+    /// A ban that arrives after the removal was already recorded: relabel the latest
+    /// stint as banned if it closed within `within_secs`. Returns whether it was
+    /// relabeled; a stint already closed as banned (by `on_leave`, which has no reason to
+    /// give) only gets `ban_reason` filled in, and returns `false`.
+    pub async fn upgrade_recent_leave_to_ban(
+        &self,
+        guild_id: GuildId,
+        user_id: UserId,
+        ban_reason: Option<&str>,
+        within_secs: i64,
+    ) -> Result<bool> {
+        let Some((id, kind)) = self.last_closed_stint(guild_id, user_id, within_secs).await?
+        else {
+            return Ok(false);
+        };
+        if kind == LeaveKind::Banned {
+            sqlx::query!(
+                r#"UPDATE memberships SET ban_reason = ? WHERE id = ? AND ban_reason IS NULL"#,
+                ban_reason,
+                id
+            )
+            .execute(&self.db.pool)
+            .await?;
+            return Ok(false);
+        }
+
+        let banned = LeaveKind::Banned as i64;
+        sqlx::query!(
+            r#"UPDATE memberships SET leave_kind = ?, banned = 1, ban_reason = ? WHERE id = ?"#,
+            banned,
+            ban_reason,
+            id
+        )
+        .execute(&self.db.pool)
+        .await?;
+        Ok(true)
+    }
+
+    /// Mark the user's latest still-banned stint as unbanned now and return when that
+    /// ban happened. `None` when there is no such stint (the ban predates tracking,
+    /// the user was banned without being a member, or it was already lifted).
+    pub async fn record_unban(
+        &self,
+        guild_id: GuildId,
+        user_id: UserId,
+    ) -> Result<Option<UnixTime>> {
+        let guild_id = guild_id.to_string();
+        let user_id = user_id.to_string();
+        let now = UnixTime::now();
+        let banned = LeaveKind::Banned as i64;
+
+        let banned_at = sqlx::query_scalar!(
+            r#"
+            UPDATE memberships
+               SET unbanned_at_epoch = ?
+             WHERE id = (
+                   SELECT id FROM memberships
+                   WHERE guild_id = ? AND user_id = ? AND leave_kind = ?
+                     AND unbanned_at_epoch IS NULL
+                   ORDER BY id DESC
+                   LIMIT 1
+             )
+            RETURNING left_at_epoch AS "banned_at: UnixTime"
+            "#,
+            now,
+            guild_id,
+            user_id,
+            banned
+        )
+        .fetch_optional(&self.db.pool)
+        .await?;
+        Ok(banned_at.flatten())
+    }
+
+    /// Append a name-change record (account username or server nickname).
+    pub async fn record_name_change(
+        &self,
+        guild_id: GuildId,
+        user_id: UserId,
+        kind: NameKind,
+        old_value: Option<&str>,
+        new_value: Option<&str>,
+    ) -> Result<()> {
+        let guild_id = guild_id.to_string();
+        let user_id = user_id.to_string();
+        let kind = kind.as_str();
+        let changed_at = UnixTime::now();
+
+        sqlx::query!(
+            r#"
+            INSERT INTO name_changes (guild_id, user_id, kind, old_value, new_value, changed_at)
+            VALUES (?, ?, ?, ?, ?, ?)
+            "#,
+            guild_id,
+            user_id,
+            kind,
+            old_value,
+            new_value,
+            changed_at
+        )
         .execute(&self.db.pool)
         .await?;
         Ok(())
     }
 
+    /// Store new last-known names on the user's latest stint and rebuild their FTS row
+    /// (label: nickname → username → `User {id}`) in one transaction, so autocomplete
+    /// finds a renamed member right away.
+    pub async fn update_names_with_fts(
+        &self,
+        guild_id: GuildId,
+        user_id: UserId,
+        account_username: &str,
+        server_username: Option<&str>,
+    ) -> Result<()> {
+        let gid = guild_id.to_string();
+        let uid = user_id.to_string();
+
+        let mut tx = self.db.transaction().await?;
+        sqlx::query!(
+            r#"
+            UPDATE memberships
+               SET account_username = ?, server_username = ?
+             WHERE id = (
+                   SELECT id FROM memberships
+                   WHERE guild_id = ? AND user_id = ?
+                   ORDER BY id DESC
+                   LIMIT 1
+             )
+            "#,
+            account_username,
+            server_username,
+            gid,
+            uid
+        )
+        .execute(&mut *tx)
+        .await?;
+        Self::write_fts_row(&mut tx, guild_id, &uid).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
     // ---------- reads ----------
 
+    /// Last-known (account_username, server_username) from the latest stint, if tracked.
+    pub async fn last_known_names(
+        &self,
+        guild_id: GuildId,
+        user_id: UserId,
+    ) -> Result<Option<(Option<String>, Option<String>)>> {
+        let guild_id = guild_id.to_string();
+        let user_id = user_id.to_string();
+        let row = sqlx::query!(
+            r#"
+            SELECT account_username, server_username
+            FROM memberships
+            WHERE guild_id = ? AND user_id = ?
+            ORDER BY id DESC
+            LIMIT 1
+            "#,
+            guild_id,
+            user_id
+        )
+        .fetch_optional(&self.db.pool)
+        .await?;
+        Ok(row.map(|r| (r.account_username, r.server_username)))
+    }
+
     pub async fn history_for_user(
         &self,
         guild_id: GuildId,
@@ -85,11 +588,14 @@ impl<'a> MembershipsRepo<'a> {
         let rows = sqlx::query_as!(
             MembershipRow,
             r#"
-            SELECT joined_at,
-                   left_at,
-                   banned        AS "banned: bool",
+            SELECT joined_at_epoch AS "joined_at!: UnixTime",
+                   left_at_epoch AS "left_at: UnixTime",
+                   leave_kind    AS "leave_kind: LeaveKind",
+                   ban_reason,
                    account_username,
-                   server_username
+                   server_username,
+                   inferred      AS "inferred: bool",
+                   unbanned_at_epoch AS "unbanned_at: UnixTime"
             FROM memberships
             WHERE guild_id = ? AND user_id = ?
             ORDER BY id ASC
@@ -102,6 +608,33 @@ impl<'a> MembershipsRepo<'a> {
         Ok(rows)
     }
 
+    /// Recorded username/nickname changes for a user, oldest first.
+    pub async fn name_history_for_user(
+        &self,
+        guild_id: GuildId,
+        user_id: UserId,
+    ) -> Result<Vec<NameChangeRow>> {
+        let guild_id = guild_id.to_string();
+        let user_id = user_id.to_string();
+        let rows = sqlx::query_as!(
+            NameChangeRow,
+            r#"
+            SELECT kind,
+                   old_value,
+                   new_value,
+                   changed_at AS "changed_at: UnixTime"
+            FROM name_changes
+            WHERE guild_id = ? AND user_id = ?
+            ORDER BY id ASC
+            "#,
+            guild_id,
+            user_id
+        )
+        .fetch_all(&self.db.pool)
+        .await?;
+        Ok(rows)
+    }
+
     /// Last row per user for this guild, with last-known names.
     pub async fn recent_user_summaries(
         &self,
@@ -120,7 +653,8 @@ impl<'a> MembershipsRepo<'a> {
               m.user_id          AS user_id,
               l.last_row_id      AS last_row_id,
               m.account_username AS account_username,
-              m.server_username  AS server_username
+              m.server_username  AS server_username,
+              m.left_at_epoch    AS left_at
             FROM last l
             JOIN memberships m
               ON m.id = l.last_row_id
@@ -156,7 +690,8 @@ impl<'a> MembershipsRepo<'a> {
           m.user_id          AS user_id,
           l.last_row_id      AS last_row_id,
           m.account_username AS account_username,
-          m.server_username  AS server_username
+          m.server_username  AS server_username,
+          m.left_at_epoch    AS left_at
         FROM last l
         JOIN memberships m
           ON m.id = l.last_row_id
@@ -200,7 +735,8 @@ impl<'a> MembershipsRepo<'a> {
               m.user_id          AS user_id,
               l.last_row_id      AS last_row_id,
               m.account_username AS account_username,
-              m.server_username  AS server_username
+              m.server_username  AS server_username,
+              m.left_at_epoch    AS left_at
             FROM last l
             JOIN memberships m
               ON m.id = l.last_row_id
@@ -219,24 +755,66 @@ impl<'a> MembershipsRepo<'a> {
         Ok(rows)
     }
 
-    /// Users with >= min_stints stints (i.e., joined multiple times).
-    pub async fn rejoiners(
+    /// Every stint of users with at least `min_stints` stints, grouped by user and in
+    /// order within each user, for `/stats churn`. Names come from each user's latest
+    /// stint. At most `cap` rows are read.
+    pub async fn churn_stats(
         &self,
-        guild_id: serenity::all::GuildId,
-        min_rejoins: i64,
-        limit: i64,
-    ) -> anyhow::Result<Vec<RejoinerRow>> {
-        let guild_id = guild_id.to_string();
-        let rows = sqlx::query!(
+        guild_id: GuildId,
+        min_stints: i64,
+        cap: i64,
+    ) -> Result<Vec<ChurnStint>> {
+        let gid = guild_id.to_string();
+        let rows = sqlx::query_as!(
+            ChurnStint,
             r#"
-        WITH last AS (
-          SELECT user_id, MAX(id) AS last_row_id
+        WITH agg AS (
+          SELECT user_id, COUNT(*) AS stints, MAX(id) AS last_row_id
           FROM memberships
-          WHERE guild_id = ?
+          WHERE guild_id = ?1
           GROUP BY user_id
-        ),
-        agg AS (
-          SELECT user_id,
+          HAVING COUNT(*) >= ?2
+        )
+        SELECT s.user_id                 AS "user_id!: String",
+               s.joined_at_epoch         AS "joined_at!: UnixTime",
+               s.left_at_epoch           AS "left_at: UnixTime",
+               s.inferred                AS "inferred: bool",
+               m.account_username,
+               m.server_username
+        FROM agg a
+        JOIN memberships s ON s.guild_id = ?1 AND s.user_id = a.user_id
+        JOIN memberships m ON m.id = a.last_row_id
+        ORDER BY a.stints DESC, s.user_id, s.id
+        LIMIT ?3
+        "#,
+            gid,
+            min_stints,
+            cap
+        )
+        .fetch_all(&self.db.pool)
+        .await?;
+        Ok(rows)
+    }
+
+    /// Users with >= min_stints stints (i.e., joined multiple times).
+    pub async fn rejoiners(
+        &self,
+        guild_id: serenity::all::GuildId,
+        min_rejoins: i64,
+        limit: i64,
+    ) -> Result<Vec<RejoinerRow>> {
+        let guild_id = guild_id.to_string();
+        let rows = sqlx::query_as!(
+            RejoinerQueryRow,
+            r#"
+        WITH last AS (
+          SELECT user_id, MAX(id) AS last_row_id
+          FROM memberships
+          WHERE guild_id = ?
+          GROUP BY user_id
+        ),
+        agg AS (
+          SELECT user_id,
                  COUNT(*) AS stints,
                  SUM(CASE WHEN left_at IS NOT NULL THEN 1 ELSE 0 END) AS times_left
           FROM memberships
@@ -263,26 +841,112 @@ impl<'a> MembershipsRepo<'a> {
         .fetch_all(&self.db.pool)
         .await?;
 
-        let out = rows
-            .into_iter()
-            .map(|r| RejoinerRow {
-                user_id: r.user_id.expect("User id cant be NULL"),
-                rejoin_count: r.stint_count.unwrap_or(0),
-                times_left: r.times_left.unwrap_or(0),
-                account_username: r.account_username.flatten(),
-                server_username: r.server_username.flatten(),
-            })
-            .collect();
+        Ok(rejoiners_from_rows(&guild_id, rows))
+    }
+
+    /// Total time each user has spent in the guild (all stints summed, open stints counted
+    /// up to now), longest first. Former members are skipped unless `include_former`.
+    /// Inferred stints carry no real join time and add nothing.
+    pub async fn tenure_by_user(
+        &self,
+        guild_id: GuildId,
+        include_former: bool,
+        limit: i64,
+    ) -> Result<Vec<TenureRow>> {
+        let guild_id = guild_id.to_string();
+        let now = UnixTime::now();
+        let rows = sqlx::query_as!(
+            TenureRow,
+            r#"
+        WITH agg AS (
+          SELECT user_id,
+                 SUM(CASE WHEN inferred = 0
+                          THEN COALESCE(left_at_epoch, ?) - joined_at_epoch
+                          ELSE 0 END)       AS total_secs,
+                 MAX(left_at IS NULL)       AS present,
+                 MAX(id)                    AS last_row_id
+          FROM memberships
+          WHERE guild_id = ?
+          GROUP BY user_id
+        )
+        SELECT a.user_id                    AS "user_id!: String",
+               a.total_secs                 AS "total_secs!: i64",
+               a.present                    AS "present!: bool",
+               m.account_username,
+               m.server_username
+        FROM agg a
+        JOIN memberships m ON m.id = a.last_row_id
+        WHERE (? OR a.present = 1) AND a.total_secs > 0
+        ORDER BY a.total_secs DESC, a.user_id
+        LIMIT ?
+        "#,
+            now,
+            guild_id,
+            include_former,
+            limit
+        )
+        .fetch_all(&self.db.pool)
+        .await?;
+        Ok(rows)
+    }
 
-        Ok(out)
+    /// The first `limit` users ever recorded here, by their earliest tracked join
+    /// (inferred stints have no real join time and are skipped). Users who joined in
+    /// the same second are ordered by their first membership row id.
+    pub async fn earliest_joiners(
+        &self,
+        guild_id: GuildId,
+        limit: i64,
+    ) -> Result<Vec<FirstJoinRow>> {
+        let guild_id = guild_id.to_string();
+        let rows = sqlx::query_as!(
+            FirstJoinRow,
+            r#"
+        WITH firsts AS (
+          SELECT user_id,
+                 MIN(joined_at_epoch) AS first_joined,
+                 MIN(id)              AS first_row_id
+          FROM memberships
+          WHERE guild_id = ? AND inferred = 0
+          GROUP BY user_id
+        ),
+        last AS (
+          SELECT user_id, MAX(id) AS last_row_id
+          FROM memberships
+          WHERE guild_id = ?
+          GROUP BY user_id
+        )
+        SELECT f.user_id                    AS "user_id!: String",
+               f.first_joined               AS "joined_at!: UnixTime",
+               m.left_at IS NULL            AS "present!: bool",
+               m.account_username,
+               m.server_username
+        FROM firsts f
+        JOIN last l ON l.user_id = f.user_id
+        JOIN memberships m ON m.id = l.last_row_id
+        ORDER BY f.first_joined, f.first_row_id
+        LIMIT ?
+        "#,
+            guild_id,
+            guild_id,
+            limit
+        )
+        .fetch_all(&self.db.pool)
+        .await?;
+        Ok(rows)
     }
 
-    /// Fetch exits (left_at IS NOT NULL) and let caller filter by time window.
+    /// One page of exits (left_at IS NOT NULL) within `window`, newest stint first.
+    ///
+    /// Pass the previous page's `next_cursor` as `after_id` to continue; it is `None`
+    /// once the last page has been returned.
     pub async fn all_exits(
         &self,
         guild_id: serenity::all::GuildId,
-        limit: i64, // cap for safety; set high if you want "all"
-    ) -> anyhow::Result<Vec<ExitRow>> {
+        window: TimeWindow,
+        after_id: Option<i64>,
+        limit: i64,
+    ) -> Result<ExitPage> {
         let guild_id = guild_id.to_string();
         let rows = sqlx::query!(
             r#"
@@ -292,45 +956,87 @@ impl<'a> MembershipsRepo<'a> {
           WHERE guild_id = ?
           GROUP BY user_id
         )
-        SELECT m.user_id                      AS "user_id: String",
-               m.left_at                      AS "left_at: String",
-               m.banned                       AS "banned: bool",
+        SELECT m.id                           AS "id!: i64",
+               m.user_id                      AS "user_id: String",
+               -- Corrupted (non-integer) values come back NULL and are skipped below.
+               CASE WHEN typeof(m.left_at_epoch) = 'integer'
+                    THEN m.left_at_epoch END  AS "left_at: UnixTime",
+               m.leave_kind                   AS "leave_kind: LeaveKind",
+               m.ban_reason                   AS "ban_reason: Option<String>",
                n.account_username             AS "account_username: Option<String>",
                n.server_username              AS "server_username: Option<String>"
         FROM memberships m
         JOIN last l ON l.user_id = m.user_id
         JOIN memberships n ON n.id = l.last_row_id
         WHERE m.guild_id = ?
-          AND m.left_at IS NOT NULL
+          AND m.left_at_epoch IS NOT NULL
+          AND (?3 IS NULL OR m.id < ?3)
+          AND (?4 IS NULL OR m.left_at_epoch >= ?4)
+          AND (?5 IS NULL OR m.left_at_epoch <= ?5)
         ORDER BY m.id DESC
-        LIMIT ?
+        LIMIT ?6
         "#,
             guild_id,
             guild_id,
+            after_id,
+            window.since,
+            window.until,
             limit
         )
         .fetch_all(&self.db.pool)
         .await?;
 
+        let full_page = rows.len() as i64 >= limit;
+        let min_id = rows.iter().map(|r| r.id).min();
         let out = rows
             .into_iter()
-            .map(|r| ExitRow {
-                user_id: r.user_id,
-                left_at: r.left_at.expect("left_at is NOT NULL"),
-                banned: r.banned,
-                account_username: r.account_username.flatten(),
-                server_username: r.server_username.flatten(),
+            .filter_map(|r| {
+                let Some(left_at) = r.left_at else {
+                    tracing::warn!(
+                        "all_exits: skipping row {} with malformed left_at_epoch in guild {}",
+                        r.id,
+                        guild_id
+                    );
+                    return None;
+                };
+                Some(ExitRow {
+                    user_id: r.user_id,
+                    left_at,
+                    leave_kind: r.leave_kind,
+                    ban_reason: r.ban_reason.flatten(),
+                    account_username: r.account_username.flatten(),
+                    server_username: r.server_username.flatten(),
+                })
             })
             .collect();
 
-        Ok(out)
+        Ok(ExitPage {
+            rows: out,
+            next_cursor: min_id.filter(|_| full_page),
+        })
+    }
+
+    /// Members currently in the guild, as tracked (users with an open stint).
+    pub async fn current_member_count(&self, guild_id: GuildId) -> Result<i64> {
+        let gid = guild_id.to_string();
+        let cnt = sqlx::query_scalar!(
+            r#"
+        SELECT COUNT(DISTINCT user_id) AS "cnt!: i64"
+        FROM memberships
+        WHERE guild_id = ? AND left_at IS NULL
+        "#,
+            gid
+        )
+        .fetch_one(&self.db.pool)
+        .await?;
+        Ok(cnt)
     }
 
     /// Current point-in-time + lifetime counters.
     pub async fn stats_current(
         &self,
         guild_id: serenity::all::GuildId,
-    ) -> anyhow::Result<StatsCurrent> {
+    ) -> Result<StatsCurrent> {
         let gid = guild_id.to_string();
 
         // DISTINCT counts must be separate queries for clarity/perf on SQLite.
@@ -358,7 +1064,7 @@ impl<'a> MembershipsRepo<'a> {
         .await?
         .cnt;
 
-        let total_rejoins = sqlx::query!(
+        let total_stints = sqlx::query!(
             r#"
         SELECT COUNT(*) AS "cnt!: i64"
         FROM memberships
@@ -370,6 +1076,24 @@ impl<'a> MembershipsRepo<'a> {
         .await?
         .cnt;
 
+        // A rejoin is every stint after a user's first one.
+        let total_rejoins = sqlx::query!(
+            r#"
+        SELECT COALESCE(SUM(stints - 1), 0) AS "cnt!: i64"
+        FROM (
+          SELECT COUNT(*) AS stints
+          FROM memberships
+          WHERE guild_id = ?
+          GROUP BY user_id
+          HAVING COUNT(*) > 1
+        )
+        "#,
+            gid
+        )
+        .fetch_one(&self.db.pool)
+        .await?
+        .cnt;
+
         let total_exits = sqlx::query!(
             r#"
         SELECT COUNT(*) AS "cnt!: i64"
@@ -386,7 +1110,19 @@ impl<'a> MembershipsRepo<'a> {
             r#"
         SELECT COUNT(*) AS "cnt!: i64"
         FROM memberships
-        WHERE guild_id = ? AND left_at IS NOT NULL AND banned = 1
+        WHERE guild_id = ? AND left_at IS NOT NULL AND leave_kind = 2
+        "#,
+            gid
+        )
+        .fetch_one(&self.db.pool)
+        .await?
+        .cnt;
+
+        let total_kicked = sqlx::query!(
+            r#"
+        SELECT COUNT(*) AS "cnt!: i64"
+        FROM memberships
+        WHERE guild_id = ? AND left_at IS NOT NULL AND leave_kind = 1
         "#,
             gid
         )
@@ -397,183 +1133,592 @@ impl<'a> MembershipsRepo<'a> {
         Ok(StatsCurrent {
             current_members,
             unique_ever,
+            total_stints,
             total_rejoins,
             total_exits,
             total_banned,
+            total_kicked,
         })
     }
 
-    /// Load a capped set of join timestamps for a trend window (filtered in Rust).
-    /// For simplicity, pull up to `cap` rows newest-first.
-    pub async fn recent_joins_raw(
+    /// Join times (skipping inferred stints) at/after `since`, newest first and capped.
+    pub async fn recent_joins_since(
         &self,
         guild_id: serenity::all::GuildId,
+        since: UnixTime,
         cap: i64,
-    ) -> anyhow::Result<Vec<String>> {
+    ) -> Result<Vec<UnixTime>> {
         let gid = guild_id.to_string();
-        let rows = sqlx::query!(
+        let rows = sqlx::query_scalar!(
             r#"
-        SELECT joined_at AS "joined_at: String"
+        SELECT joined_at_epoch AS "joined_at!: UnixTime"
         FROM memberships
-        WHERE guild_id = ?
+        WHERE guild_id = ? AND inferred = 0 AND joined_at_epoch >= ?
         ORDER BY id DESC
         LIMIT ?
         "#,
             gid,
+            since,
             cap
         )
         .fetch_all(&self.db.pool)
         .await?;
-
-        Ok(rows.into_iter().map(|r| r.joined_at).collect())
+        Ok(rows)
     }
 
-    /// Load a capped set of (joined_at,left_at,banned) timestamps for trend deltas.
-    pub async fn recent_rejoins_raw(
+    /// Stints that joined or left at/after `since` (unix seconds), newest first and capped,
+    /// for trend deltas. Uses the epoch columns' indexes; nothing is parsed in Rust.
+    pub async fn activity_since(
         &self,
         guild_id: serenity::all::GuildId,
+        since: UnixTime,
         cap: i64,
-    ) -> anyhow::Result<Vec<RejoinTimes>> {
+    ) -> Result<Vec<RejoinTimes>> {
         let gid = guild_id.to_string();
-        let rows = sqlx::query!(
+        let rows = sqlx::query_as!(
+            RejoinTimes,
             r#"
         SELECT user_id                AS "user_id: String",
-               joined_at              AS "joined_at: String",
-               left_at                AS "left_at: Option<String>",
-               banned                 AS "banned: bool"
+               joined_at_epoch        AS "joined_at!: UnixTime",
+               left_at_epoch          AS "left_at: UnixTime",
+               leave_kind             AS "leave_kind: LeaveKind",
+               inferred               AS "inferred: bool"
         FROM memberships
-        WHERE guild_id = ?
+        WHERE guild_id = ?1
+          AND (joined_at_epoch >= ?2 OR left_at_epoch >= ?2)
         ORDER BY id DESC
-        LIMIT ?
+        LIMIT ?3
         "#,
             gid,
+            since,
             cap
         )
         .fetch_all(&self.db.pool)
         .await?;
+        Ok(rows)
+    }
+
+    /// Each user's first stint (joined_at + matching left_at), for cohort retention.
+    /// Users whose first stint is inferred are skipped (their join time is unknown).
+    pub async fn retention_cohorts(&self, guild_id: GuildId) -> Result<Vec<StintTimes>> {
+        let gid = guild_id.to_string();
+        let rows = sqlx::query_as!(
+            StintTimes,
+            r#"
+            SELECT m.joined_at_epoch AS "joined_at!: UnixTime",
+                   m.left_at_epoch   AS "left_at: UnixTime"
+            FROM memberships m
+            WHERE m.guild_id = ?1
+              AND m.inferred = 0
+              AND m.id = (
+                SELECT MIN(f.id) FROM memberships f
+                WHERE f.guild_id = ?1 AND f.user_id = m.user_id
+              )
+            "#,
+            gid
+        )
+        .fetch_all(&self.db.pool)
+        .await?;
+        Ok(rows)
+    }
+
+    /// Length of every finished stint (left_at set); inferred stints are skipped.
+    pub async fn completed_stint_durations(
+        &self,
+        guild_id: GuildId,
+    ) -> Result<Vec<chrono::Duration>> {
+        let gid = guild_id.to_string();
+        let rows = sqlx::query!(
+            r#"
+            SELECT left_at_epoch - joined_at_epoch AS "secs!: i64"
+            FROM memberships
+            WHERE guild_id = ? AND left_at_epoch IS NOT NULL AND inferred = 0
+            "#,
+            gid
+        )
+        .fetch_all(&self.db.pool)
+        .await?;
 
         Ok(rows
             .into_iter()
-            .map(|r| RejoinTimes {
-                user_id: r.user_id,
-                joined_at: r.joined_at,
-                left_at: r.left_at.flatten(),
-                banned: r.banned,
-            })
+            .map(|r| chrono::Duration::seconds(r.secs))
             .collect())
     }
 
-    /// Rebuild FTS rows for a guild from the latest membership row per user.
-    pub async fn rebuild_usernames_fts_for_guild(
-        &self,
-        guild_id: serenity::all::GuildId,
-    ) -> anyhow::Result<()> {
+    /// Number of stints still open (member currently present).
+    pub async fn open_stint_count(&self, guild_id: GuildId) -> Result<i64> {
         let gid = guild_id.to_string();
+        let cnt = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) AS "cnt!: i64" FROM memberships WHERE guild_id = ? AND left_at IS NULL"#,
+            gid
+        )
+        .fetch_one(&self.db.pool)
+        .await?;
+        Ok(cnt)
+    }
 
-        // Wipe existing rows for this guild
-        sqlx::query!("DELETE FROM usernames_fts WHERE guild_id = ?", gid)
-            .execute(&self.db.pool)
-            .await?;
-
-        // Insert one row per user (latest stint) into FTS
-        // label + label_norm help both display-like and strict prefix matching.
-        sqlx::query!(
-        r#"
-        WITH last AS (
-          SELECT user_id, MAX(id) AS last_row_id
-          FROM memberships
-          WHERE guild_id = ?
-          GROUP BY user_id
+    /// Every membership row for the guild, oldest first (for exports).
+    pub async fn all_rows_for_guild(&self, guild_id: GuildId) -> Result<Vec<ExportRow>> {
+        let gid = guild_id.to_string();
+        let rows = sqlx::query_as!(
+            ExportRow,
+            r#"
+            SELECT guild_id,
+                   user_id,
+                   joined_at_epoch AS "joined_at!: UnixTime",
+                   left_at_epoch   AS "left_at: UnixTime",
+                   banned AS "banned: bool",
+                   leave_kind AS "leave_kind: LeaveKind",
+                   account_username,
+                   server_username,
+                   inferred AS "inferred: bool"
+            FROM memberships
+            WHERE guild_id = ?
+            ORDER BY id ASC
+            "#,
+            gid
         )
-        INSERT INTO usernames_fts (guild_id, user_id, account_username, server_username, label, label_norm)
-        SELECT
-          ?                               AS guild_id,
-          m.user_id                       AS user_id,
-          m.account_username              AS account_username,
-          m.server_username               AS server_username,
-          COALESCE(NULLIF(m.server_username, ''), m.account_username, 'User ' || m.user_id) AS label,
-          LOWER(COALESCE(NULLIF(m.server_username, ''), m.account_username, m.user_id))      AS label_norm
-        FROM last l
-        JOIN memberships m ON m.id = l.last_row_id
-        "#,
-        gid, gid
-    )
-    .execute(&self.db.pool)
-    .await?;
+        .fetch_all(&self.db.pool)
+        .await?;
+        Ok(rows)
+    }
 
-        Ok(())
+    /// Stints, name changes and moderator notes for one user.
+    pub async fn full_user_record(
+        &self,
+        guild_id: GuildId,
+        user_id: UserId,
+    ) -> Result<FullUserRecord> {
+        Ok(FullUserRecord {
+            guild_id: guild_id.to_string(),
+            user_id: user_id.to_string(),
+            memberships: self.history_for_user(guild_id, user_id).await?,
+            name_changes: self.name_history_for_user(guild_id, user_id).await?,
+            notes: MemberNotesRepo::new(self.db)
+                .list_notes(guild_id, user_id)
+                .await?,
+        })
     }
 
-    /// Upsert a single user into FTS (call on join or when you refresh names).
-    pub async fn upsert_usernames_fts_row(
+    /// Delete everything stored about one user in this guild (stints, FTS, notes,
+    /// name changes). Runs inside the caller's transaction so it's all-or-nothing.
+    pub async fn purge_user(
         &self,
-        guild_id: serenity::all::GuildId,
-        user_id: &str,
-    ) -> anyhow::Result<()> {
+        tx: &mut Transaction<'_, Sqlite>,
+        guild_id: GuildId,
+        user_id: UserId,
+    ) -> Result<PurgeCounts> {
         let gid = guild_id.to_string();
         let uid = user_id.to_string();
 
-        // Grab the latest membership row to get last-known names.
-        let row = sqlx::query!(
-            r#"
-        SELECT m.user_id, m.account_username, m.server_username
-        FROM memberships m
-        WHERE m.guild_id = ? AND m.user_id = ?
-        ORDER BY m.id DESC
-        LIMIT 1
-        "#,
+        let memberships = sqlx::query!(
+            "DELETE FROM memberships WHERE guild_id = ? AND user_id = ?",
             gid,
             uid
         )
-        .fetch_optional(&self.db.pool)
-        .await?;
-
-        // Remove old FTS row (if any)
-        sqlx::query!(
+        .execute(&mut **tx)
+        .await?
+        .rows_affected();
+        let fts = sqlx::query!(
             "DELETE FROM usernames_fts WHERE guild_id = ? AND user_id = ?",
             gid,
             uid
         )
-        .execute(&self.db.pool)
-        .await?;
+        .execute(&mut **tx)
+        .await?
+        .rows_affected();
+        let notes = sqlx::query!(
+            "DELETE FROM member_notes WHERE guild_id = ? AND user_id = ?",
+            gid,
+            uid
+        )
+        .execute(&mut **tx)
+        .await?
+        .rows_affected();
+        let name_changes = sqlx::query!(
+            "DELETE FROM name_changes WHERE guild_id = ? AND user_id = ?",
+            gid,
+            uid
+        )
+        .execute(&mut **tx)
+        .await?
+        .rows_affected();
 
-        if let Some(r) = row {
-            let label = r
-                .server_username
-                .as_deref()
-                .filter(|s| !s.is_empty())
-                .map(|s| s.to_string())
-                .or(r.account_username.clone())
-                .unwrap_or_else(|| format!("User {}", r.user_id));
+        Ok(PurgeCounts {
+            memberships,
+            fts,
+            notes,
+            name_changes,
+        })
+    }
 
-            let label_norm = label.to_lowercase();
+    /// Retention: delete closed stints that ended before `cutoff` (except each user's
+    /// latest one, so members and their FTS rows stay known) plus notes and name
+    /// changes written before it, in one transaction.
+    pub async fn prune_older_than(
+        &self,
+        guild_id: GuildId,
+        cutoff: UnixTime,
+    ) -> Result<PruneCounts> {
+        let gid = guild_id.to_string();
+        let mut tx = self.db.transaction().await?;
 
-            sqlx::query!(
+        let memberships = sqlx::query!(
             r#"
-            INSERT INTO usernames_fts (guild_id, user_id, account_username, server_username, label, label_norm)
-            VALUES (?, ?, ?, ?, ?, ?)
+            DELETE FROM memberships
+            WHERE guild_id = ?
+              AND left_at_epoch IS NOT NULL
+              AND left_at_epoch < ?
+              AND id NOT IN (
+                    SELECT MAX(id) FROM memberships WHERE guild_id = ? GROUP BY user_id
+              )
             "#,
             gid,
-            uid,
-            r.account_username,
-            r.server_username,
-            label,
-            label_norm
+            cutoff,
+            gid
         )
-        .execute(&self.db.pool)
-        .await?;
-        }
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
 
-        Ok(())
-    }
+        let notes = sqlx::query!(
+            r#"DELETE FROM member_notes WHERE guild_id = ? AND created_at < ?"#,
+            gid,
+            cutoff
+        )
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
 
-    /// FTS-backed search for autocomplete. Falls back to LIKE if FTS is missing.
+        let name_changes = sqlx::query!(
+            r#"DELETE FROM name_changes WHERE guild_id = ? AND changed_at < ?"#,
+            gid,
+            cutoff
+        )
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        tx.commit().await?;
+        Ok(PruneCounts {
+            memberships,
+            notes,
+            name_changes,
+        })
+    }
+
+    /// Move every stint, note and name change of `from` onto `into` (same guild) in one
+    /// transaction, then rebuild `into`'s search entry and drop `from`'s.
+    /// Callers reject `from == into`.
+    pub async fn merge_users(
+        &self,
+        guild_id: GuildId,
+        from: UserId,
+        into: UserId,
+    ) -> Result<MergeCounts> {
+        let gid = guild_id.to_string();
+        let from_id = from.to_string();
+        let into_id = into.to_string();
+        let mut tx = self.db.transaction().await?;
+
+        let memberships = sqlx::query!(
+            "UPDATE memberships SET user_id = ? WHERE guild_id = ? AND user_id = ?",
+            into_id,
+            gid,
+            from_id
+        )
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+        let notes = sqlx::query!(
+            "UPDATE member_notes SET user_id = ? WHERE guild_id = ? AND user_id = ?",
+            into_id,
+            gid,
+            from_id
+        )
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+        let name_changes = sqlx::query!(
+            "UPDATE name_changes SET user_id = ? WHERE guild_id = ? AND user_id = ?",
+            into_id,
+            gid,
+            from_id
+        )
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+        sqlx::query!(
+            "DELETE FROM usernames_fts WHERE guild_id = ? AND user_id = ?",
+            gid,
+            from_id
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| RepoError::from(e).fts())?;
+
+        tx.commit().await?;
+        self.upsert_usernames_fts_row(guild_id, &into_id).await?;
+
+        Ok(MergeCounts {
+            memberships,
+            notes,
+            name_changes,
+        })
+    }
+
+    /// Store today's (UTC) current-member count; re-running the same day overwrites it.
+    pub async fn record_snapshot(&self, guild_id: GuildId) -> Result<()> {
+        let gid = guild_id.to_string();
+        let day = chrono::Utc::now().date_naive().format("%Y-%m-%d").to_string();
+        let taken_at = UnixTime::now();
+        sqlx::query!(
+            r#"
+            INSERT INTO member_count_snapshots (guild_id, day, member_count, taken_at)
+            SELECT ?1, ?2, COUNT(DISTINCT user_id), ?3
+            FROM memberships
+            WHERE guild_id = ?1 AND left_at IS NULL
+            ON CONFLICT(guild_id, day) DO UPDATE SET
+              member_count = excluded.member_count,
+              taken_at     = excluded.taken_at
+            "#,
+            gid,
+            day,
+            taken_at
+        )
+        .execute(&self.db.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Daily snapshots with `from <= day <= to`, oldest first.
+    pub async fn snapshots_in_range(
+        &self,
+        guild_id: GuildId,
+        from: chrono::NaiveDate,
+        to: chrono::NaiveDate,
+    ) -> Result<Vec<(chrono::NaiveDate, i64)>> {
+        let gid = guild_id.to_string();
+        let from = from.format("%Y-%m-%d").to_string();
+        let to = to.format("%Y-%m-%d").to_string();
+        let rows = sqlx::query!(
+            r#"
+            SELECT day, member_count
+            FROM member_count_snapshots
+            WHERE guild_id = ? AND day >= ? AND day <= ?
+            ORDER BY day ASC
+            "#,
+            gid,
+            from,
+            to
+        )
+        .fetch_all(&self.db.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|r| {
+                let day = chrono::NaiveDate::parse_from_str(&r.day, "%Y-%m-%d").ok()?;
+                Some((day, r.member_count))
+            })
+            .collect())
+    }
+
+    /// Load a capped set of joins with their attributed inviter (newest first) for leaderboards.
+    /// `inviter_id` is NULL for joins without a known invite; names are the inviter's last-known.
+    pub async fn invite_joins_raw(
+        &self,
+        guild_id: serenity::all::GuildId,
+        cap: i64,
+    ) -> Result<Vec<InviteJoinRow>> {
+        let gid = guild_id.to_string();
+        let rows = sqlx::query!(
+            r#"
+        WITH last AS (
+          SELECT user_id, MAX(id) AS last_row_id
+          FROM memberships
+          WHERE guild_id = ?
+          GROUP BY user_id
+        )
+        SELECT m.inviter_id                   AS "inviter_id: Option<String>",
+               m.joined_at_epoch              AS "joined_at!: UnixTime",
+               n.account_username             AS "account_username: Option<String>",
+               n.server_username              AS "server_username: Option<String>"
+        FROM memberships m
+        LEFT JOIN last l ON l.user_id = m.inviter_id
+        LEFT JOIN memberships n ON n.id = l.last_row_id
+        WHERE m.guild_id = ?
+        ORDER BY m.id DESC
+        LIMIT ?
+        "#,
+            gid,
+            gid,
+            cap
+        )
+        .fetch_all(&self.db.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| InviteJoinRow {
+                inviter_id: r.inviter_id.flatten(),
+                joined_at: r.joined_at,
+                inviter_account_username: r.account_username.flatten(),
+                inviter_server_username: r.server_username.flatten(),
+            })
+            .collect())
+    }
+
+    /// Rebuild FTS rows for a guild from the latest membership row per user; returns
+    /// how many rows were indexed. `RepoError::FtsUnavailable` if this SQLite build lacks FTS5.
+    pub async fn rebuild_usernames_fts_for_guild(
+        &self,
+        guild_id: serenity::all::GuildId,
+    ) -> Result<u64> {
+        let gid = guild_id.to_string();
+
+        // Delete + reinsert atomically so a failure never leaves the guild unsearchable
+        let mut tx = self.db.transaction().await?;
+
+        // Wipe existing rows for this guild
+        sqlx::query!("DELETE FROM usernames_fts WHERE guild_id = ?", gid)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| RepoError::from(e).fts())?;
+
+        // One row per user (latest stint). Labels are built here rather than in SQL
+        // because `label_norm` needs Unicode folding that SQLite's LOWER() can't do.
+        let latest = sqlx::query!(
+            r#"
+        WITH last AS (
+          SELECT user_id, MAX(id) AS last_row_id
+          FROM memberships
+          WHERE guild_id = ?
+          GROUP BY user_id
+        )
+        SELECT m.user_id, m.account_username, m.server_username
+        FROM last l
+        JOIN memberships m ON m.id = l.last_row_id
+        "#,
+            gid
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        // label + label_norm help both display-like and strict prefix matching.
+        let mut indexed = 0;
+        for batch in latest.chunks(FTS_REBUILD_BATCH) {
+            let mut qb = QueryBuilder::<Sqlite>::new(
+                "INSERT INTO usernames_fts (guild_id, user_id, account_username, server_username, label, label_norm) ",
+            );
+            qb.push_values(batch, |mut row, r| {
+                let label = fts_label(
+                    &r.user_id,
+                    r.account_username.as_deref(),
+                    r.server_username.as_deref(),
+                );
+                row.push_bind(&gid)
+                    .push_bind(&r.user_id)
+                    .push_bind(&r.account_username)
+                    .push_bind(&r.server_username)
+                    .push_bind(fold_name(&label))
+                    .push_bind(label);
+            });
+            indexed += qb
+                .build()
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| RepoError::from(e).fts())?
+                .rows_affected();
+        }
+
+        tx.commit().await?;
+        Ok(indexed)
+    }
+
+    /// Upsert a single user into FTS (call when you refresh names).
+    pub async fn upsert_usernames_fts_row(
+        &self,
+        guild_id: serenity::all::GuildId,
+        user_id: &str,
+    ) -> Result<()> {
+        let mut tx = self.db.transaction().await?;
+        Self::write_fts_row(&mut tx, guild_id, user_id).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Replace the user's FTS row with one built from their latest membership row
+    /// (or just drop it if they have none), inside the caller's transaction.
+    async fn write_fts_row(
+        tx: &mut Transaction<'_, Sqlite>,
+        guild_id: GuildId,
+        user_id: &str,
+    ) -> Result<()> {
+        let gid = guild_id.to_string();
+        let uid = user_id.to_string();
+
+        // Grab the latest membership row to get last-known names.
+        let row = sqlx::query!(
+            r#"
+        SELECT m.user_id, m.account_username, m.server_username
+        FROM memberships m
+        WHERE m.guild_id = ? AND m.user_id = ?
+        ORDER BY m.id DESC
+        LIMIT 1
+        "#,
+            gid,
+            uid
+        )
+        .fetch_optional(&mut **tx)
+        .await?;
+
+        // Remove old FTS row (if any)
+        sqlx::query!(
+            "DELETE FROM usernames_fts WHERE guild_id = ? AND user_id = ?",
+            gid,
+            uid
+        )
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| RepoError::from(e).fts())?;
+
+        if let Some(r) = row {
+            let label = fts_label(
+                &r.user_id,
+                r.account_username.as_deref(),
+                r.server_username.as_deref(),
+            );
+            let label_norm = fold_name(&label);
+
+            sqlx::query!(
+            r#"
+            INSERT INTO usernames_fts (guild_id, user_id, account_username, server_username, label, label_norm)
+            VALUES (?, ?, ?, ?, ?, ?)
+            "#,
+            gid,
+            uid,
+            r.account_username,
+            r.server_username,
+            label,
+            label_norm
+        )
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| RepoError::from(e).fts())?;
+        }
+
+        Ok(())
+    }
+
+    /// FTS-backed search for autocomplete. Falls back to LIKE if FTS is missing or finds
+    /// nothing, and tops up with typo-tolerant matches when both find only a few users.
     pub async fn search_user_summaries_prefix(
         &self,
         guild_id: serenity::all::GuildId,
         partial: &str,
         limit: i64,
-    ) -> anyhow::Result<Vec<UserSummary>> {
+    ) -> Result<Vec<UserSummary>> {
         let gid = guild_id.to_string();
         let trimmed = partial.trim();
 
@@ -582,17 +1727,11 @@ impl<'a> MembershipsRepo<'a> {
             return self.recent_user_summaries(guild_id, limit).await;
         }
 
-        // Try FTS5 first.
-        // Build a MATCH query that hits normalized label and raw fields with prefix.
-        // Example: label_norm:par* OR account_username:par* OR server_username:par*
-        let match_expr = format!(
-            "label_norm:{q}* OR account_username:{q}* OR server_username:{q}*",
-            q = trimmed.to_lowercase().replace('"', "") // simplistic sanitize
-        );
-
-        // We select through the "last" CTE to return consistent UserSummary (latest names).
-        let fts_rows = sqlx::query_as::<_, UserSummary>(
-            r#"
+        // Try FTS5 first (skipped when nothing searchable is left after sanitizing).
+        if let Some(match_expr) = fts_prefix_query(trimmed) {
+            // We select through the "last" CTE to return consistent UserSummary (latest names).
+            let fts_rows = sqlx::query_as::<_, UserSummary>(
+                r#"
         WITH last AS (
           SELECT user_id, MAX(id) AS last_row_id
           FROM memberships
@@ -609,38 +1748,40 @@ impl<'a> MembershipsRepo<'a> {
           m.user_id          AS user_id,
           l.last_row_id      AS last_row_id,
           m.account_username AS account_username,
-          m.server_username  AS server_username
+          m.server_username  AS server_username,
+          m.left_at_epoch    AS left_at
         FROM hits h
         JOIN last l ON l.user_id = h.user_id
         JOIN memberships m ON m.id = l.last_row_id
         ORDER BY h.rank, l.last_row_id DESC
         LIMIT ?
         "#,
-        )
-        .bind(&gid) // last CTE
-        .bind(&gid) // hits filter
-        .bind(&match_expr) // MATCH string
-        .bind(limit)
-        .fetch_all(&self.db.pool)
-        .await;
-
-        match fts_rows {
-            Ok(rows) => return Ok(rows),
-            Err(e) => {
-                // If FTS is unavailable (e.g., "no such module: fts5") or MATCH failed,
-                // gracefully fall back to LIKE-based search.
-                let msg = e.to_string();
-                let is_fts_missing =
-                    msg.contains("no such module: fts5") || msg.contains("malformed MATCH");
-                if !is_fts_missing {
-                    // unknown SQL error: propagate
-                    return Err(e.into());
+            )
+            .bind(&gid) // last CTE
+            .bind(&gid) // hits filter
+            .bind(&match_expr) // MATCH string
+            .bind(limit)
+            .fetch_all(&self.db.pool)
+            .await;
+
+            match fts_rows {
+                Ok(rows) if !rows.is_empty() => {
+                    return self.with_fuzzy_matches(guild_id, trimmed, rows, limit).await;
+                }
+                // No hits: LIKE may still find substrings FTS prefixes can't.
+                Ok(_) => {}
+                Err(e) => {
+                    // Input is fully quoted, so only a missing FTS5 module is expected here;
+                    // anything else is a real error.
+                    if !e.to_string().contains("no such module: fts5") {
+                        return Err(e.into());
+                    }
                 }
             }
         }
 
-        // Fallback to your known-good LIKE search:
-        let like = format!("%{}%", trimmed);
+        // Fallback to your known-good LIKE search (wildcards in the input are literal):
+        let like = format!("%{}%", escape_like(trimmed));
         let rows = sqlx::query_as::<_, UserSummary>(
             r#"
         WITH last AS (
@@ -653,12 +1794,13 @@ impl<'a> MembershipsRepo<'a> {
           m.user_id          AS user_id,
           l.last_row_id      AS last_row_id,
           m.account_username AS account_username,
-          m.server_username  AS server_username
+          m.server_username  AS server_username,
+          m.left_at_epoch    AS left_at
         FROM last l
         JOIN memberships m
           ON m.id = l.last_row_id
-        WHERE (m.account_username IS NOT NULL AND m.account_username LIKE ?)
-           OR (m.server_username  IS NOT NULL AND m.server_username  LIKE ?)
+        WHERE (m.account_username IS NOT NULL AND m.account_username LIKE ? ESCAPE '\')
+           OR (m.server_username  IS NOT NULL AND m.server_username  LIKE ? ESCAPE '\')
         ORDER BY l.last_row_id DESC
         LIMIT ?
         "#,
@@ -670,19 +1812,265 @@ impl<'a> MembershipsRepo<'a> {
         .fetch_all(&self.db.pool)
         .await?;
 
+        self.with_fuzzy_matches(guild_id, trimmed, rows, limit).await
+    }
+
+    /// Append users whose names are within a few typos of `query` when `rows` (exact hits)
+    /// are fewer than `FUZZY_MIN_HITS`. Only the `FUZZY_CANDIDATE_CAP` most recent users
+    /// are scanned, closest names first.
+    async fn with_fuzzy_matches(
+        &self,
+        guild_id: GuildId,
+        query: &str,
+        mut rows: Vec<UserSummary>,
+        limit: i64,
+    ) -> Result<Vec<UserSummary>> {
+        let query: Vec<char> = fold_name(query).chars().collect();
+        let limit = usize::try_from(limit).unwrap_or(0);
+        if rows.len() >= FUZZY_MIN_HITS.min(limit) || query.len() < FUZZY_MIN_QUERY_CHARS {
+            return Ok(rows);
+        }
+
+        // One typo per four characters typed, at most two.
+        let max_typos = (query.len() / 4).clamp(1, 2);
+        let mut scored: Vec<(usize, UserSummary)> = self
+            .recent_user_summaries(guild_id, FUZZY_CANDIDATE_CAP)
+            .await?
+            .into_iter()
+            .filter(|c| !rows.iter().any(|r| r.user_id == c.user_id))
+            .filter_map(|c| {
+                let best = [c.account_username.as_deref(), c.server_username.as_deref()]
+                    .into_iter()
+                    .flatten()
+                    .map(|name| name_distance(&query, name))
+                    .min()?;
+                (best <= max_typos).then_some((best, c))
+            })
+            .collect();
+        // Stable sort: equally close names keep the most recent users first.
+        scored.sort_by_key(|(d, _)| *d);
+
+        let room = limit.saturating_sub(rows.len());
+        rows.extend(scored.into_iter().take(room).map(|(_, c)| c));
         Ok(rows)
     }
 }
 
+/// Typos between `query` (folded chars) and `name`: the distance to the whole name
+/// or to its start, whichever is closer, since autocomplete input is often a prefix.
+fn name_distance(query: &[char], name: &str) -> usize {
+    let name: Vec<char> = fold_name(name).chars().collect();
+    let whole = edit_distance(query, &name);
+    // One extra char of prefix lets a dropped letter count as a single typo.
+    let prefix_len = (query.len() + 1).min(name.len());
+    let prefix = (query.len()..=prefix_len)
+        .map(|n| edit_distance(query, &name[..n]))
+        .min()
+        .unwrap_or(whole);
+    whole.min(prefix)
+}
+
+/// Edit distance counting insertions, deletions, substitutions and swaps of two
+/// adjacent characters (optimal string alignment), so "jonh" is one typo from "john".
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut prev2 = vec![0; b.len() + 1];
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut cur = vec![0; b.len() + 1];
+    for i in 1..=a.len() {
+        cur[0] = i;
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            cur[j] = (prev[j] + 1).min(cur[j - 1] + 1).min(prev[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                cur[j] = cur[j].min(prev2[j - 2] + 1);
+            }
+        }
+        std::mem::swap(&mut prev2, &mut prev);
+        std::mem::swap(&mut prev, &mut cur);
+    }
+    prev[b.len()]
+}
+
+/// Build an FTS5 prefix query from free-form input: every whitespace-separated token
+/// must prefix-match one of the name columns. Tokens are reduced to letters/digits and
+/// quoted, so operators (`NOT`, `OR`, `-`, `^`, `:`, `*`) are matched literally.
+/// `None` if nothing searchable remains.
+fn fts_prefix_query(input: &str) -> Option<String> {
+    let terms: Vec<String> = input
+        .split_whitespace()
+        .map(|tok| {
+            let tok: String = tok.chars().filter(|c| c.is_alphanumeric()).collect();
+            fold_name(&tok)
+        })
+        .filter(|tok| !tok.is_empty())
+        .map(|tok| format!("{{label_norm account_username server_username}} : \"{tok}\" *"))
+        .collect();
+
+    if terms.is_empty() {
+        None
+    } else {
+        Some(terms.join(" AND "))
+    }
+}
+
+/// Display label for the FTS index: nickname, else username, else `User {id}`.
+fn fts_label(user_id: &str, account: Option<&str>, server: Option<&str>) -> String {
+    server
+        .filter(|s| !s.is_empty())
+        .or(account)
+        .map_or_else(|| format!("User {user_id}"), str::to_string)
+}
+
+/// Search key for a name: lowercased with diacritics stripped (NFD, combining marks
+/// dropped), so "jose" finds "José". Used for `label_norm` and for queries alike.
+fn fold_name(name: &str) -> String {
+    name.nfd()
+        .filter(|c| !is_combining_mark(*c))
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Escape `%`, `_` and `\` for a `LIKE ... ESCAPE '\'` pattern.
+fn escape_like(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    for c in input.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
 // ---------- row types ----------
 
-#[derive(Debug, Clone)]
+/// How a stint ended; stored as `memberships.leave_kind`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, sqlx::Type, Serialize)]
+#[serde(rename_all = "lowercase")]
+#[repr(i64)]
+pub enum LeaveKind {
+    #[default]
+    Left = 0,
+    Kicked = 1,
+    Banned = 2,
+}
+
+impl LeaveKind {
+    /// Past-tense verb for history lines ("left", "kicked", "banned").
+    pub fn verb(self) -> &'static str {
+        match self {
+            LeaveKind::Left => "left",
+            LeaveKind::Kicked => "kicked",
+            LeaveKind::Banned => "banned",
+        }
+    }
+}
+
+/// Which name a `name_changes` row refers to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NameKind {
+    Account,
+    Server,
+}
+
+impl NameKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            NameKind::Account => "account",
+            NameKind::Server => "server",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
 pub struct MembershipRow {
-    pub joined_at: String,
-    pub left_at: Option<String>,
+    pub joined_at: UnixTime,
+    pub left_at: Option<UnixTime>,
+    pub leave_kind: LeaveKind,
+    pub ban_reason: Option<String>,
+    pub account_username: Option<String>,
+    pub server_username: Option<String>,
+    /// Join time unknown: we only saw the leave (joined_at = left_at).
+    pub inferred: bool,
+    /// Set once a ban ending this stint was lifted.
+    pub unbanned_at: Option<UnixTime>,
+}
+
+/// Everything stored about one user in one guild (for `/member export-user`).
+#[derive(Debug, Clone, Serialize)]
+pub struct FullUserRecord {
+    pub guild_id: String,
+    pub user_id: String,
+    pub memberships: Vec<MembershipRow>,
+    pub name_changes: Vec<NameChangeRow>,
+    pub notes: Vec<MemberNote>,
+}
+
+/// Rows removed per table by `purge_user`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PurgeCounts {
+    pub memberships: u64,
+    pub fts: u64,
+    pub notes: u64,
+    pub name_changes: u64,
+}
+
+/// Rows deleted per table by `prune_older_than`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PruneCounts {
+    pub memberships: u64,
+    pub notes: u64,
+    pub name_changes: u64,
+}
+
+impl PruneCounts {
+    pub fn total(&self) -> u64 {
+        self.memberships + self.notes + self.name_changes
+    }
+}
+
+/// Corrections made by `reconcile_open_stints`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ReconcileCounts {
+    /// Stints closed for users who were no longer in the guild.
+    pub closed: u64,
+    /// Stints opened for members who had none.
+    pub opened: u64,
+}
+
+impl ReconcileCounts {
+    pub fn total(&self) -> u64 {
+        self.closed + self.opened
+    }
+}
+
+/// Rows reassigned per table by `merge_users`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MergeCounts {
+    pub memberships: u64,
+    pub notes: u64,
+    pub name_changes: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct ExportRow {
+    pub guild_id: String,
+    pub user_id: String,
+    pub joined_at: UnixTime,
+    pub left_at: Option<UnixTime>,
     pub banned: bool,
+    pub leave_kind: LeaveKind,
     pub account_username: Option<String>,
     pub server_username: Option<String>,
+    pub inferred: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NameChangeRow {
+    pub kind: String, // NameKind::as_str()
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+    pub changed_at: UnixTime,
 }
 
 #[derive(Debug, Clone, FromRow)]
@@ -691,6 +2079,24 @@ pub struct UserSummary {
     pub last_row_id: i64,
     pub account_username: Option<String>,
     pub server_username: Option<String>,
+    pub left_at: Option<UnixTime>, // of the latest stint; None = still here
+}
+
+impl UserSummary {
+    /// Display label from the last-known names: `nick (aka account)`, else whichever exists.
+    pub fn label(&self) -> String {
+        match (self.server_username.as_deref(), self.account_username.as_deref()) {
+            (Some(nick), Some(acc)) if !nick.is_empty() => format!("{nick} (aka {acc})"),
+            (_, Some(acc)) => acc.to_string(),
+            (Some(nick), None) => nick.to_string(),
+            _ => format!("User {}", self.user_id),
+        }
+    }
+
+    /// Whether the latest stint is still open.
+    pub fn is_present(&self) -> bool {
+        self.left_at.is_none()
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -702,11 +2108,93 @@ pub struct RejoinerRow {
     pub server_username: Option<String>,
 }
 
+/// Raw `rejoiners` row, before malformed ones are dropped.
+#[derive(Debug)]
+struct RejoinerQueryRow {
+    user_id: Option<String>,
+    stint_count: Option<i64>,
+    times_left: Option<i64>,
+    account_username: Option<Option<String>>,
+    server_username: Option<Option<String>>,
+}
+
+/// Skip (and log) malformed rows instead of failing the whole command.
+fn rejoiners_from_rows(guild_id: &str, rows: Vec<RejoinerQueryRow>) -> Vec<RejoinerRow> {
+    rows.into_iter()
+        .filter_map(|r| {
+            let Some(user_id) = r.user_id else {
+                tracing::warn!("rejoiners: skipping row with NULL user_id in guild {guild_id}");
+                return None;
+            };
+            Some(RejoinerRow {
+                user_id,
+                rejoin_count: r.stint_count.unwrap_or(0),
+                times_left: r.times_left.unwrap_or(0),
+                account_username: r.account_username.flatten(),
+                server_username: r.server_username.flatten(),
+            })
+        })
+        .collect()
+}
+
+/// One user's accumulated membership time (`tenure_by_user`).
+#[derive(Debug, Clone)]
+pub struct TenureRow {
+    pub user_id: String,
+    pub total_secs: i64,
+    /// Whether the user is currently a member (latest stint open).
+    pub present: bool,
+    pub account_username: Option<String>,
+    pub server_username: Option<String>,
+}
+
+/// A user's earliest tracked join, for `/stats first-joins`.
+#[derive(Debug, Clone)]
+pub struct FirstJoinRow {
+    pub user_id: String,
+    pub joined_at: UnixTime,
+    /// Whether the user is currently a member (latest stint open).
+    pub present: bool,
+    pub account_username: Option<String>,
+    pub server_username: Option<String>,
+}
+
+impl TenureRow {
+    pub fn total(&self) -> chrono::Duration {
+        chrono::Duration::seconds(self.total_secs)
+    }
+}
+
+/// Inclusive unix-second bounds for range queries; `None` leaves that side open.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TimeWindow {
+    pub since: Option<i64>,
+    pub until: Option<i64>,
+}
+
+impl TimeWindow {
+    /// Everything from `since` onwards.
+    pub fn since(since: chrono::DateTime<chrono::Utc>) -> Self {
+        Self {
+            since: Some(since.timestamp()),
+            until: None,
+        }
+    }
+}
+
+/// A page from `all_exits`; `next_cursor` is the smallest id on a full page.
+#[derive(Debug, Clone)]
+pub struct ExitPage {
+    pub rows: Vec<ExitRow>,
+    pub next_cursor: Option<i64>,
+}
+
 #[derive(Debug, Clone)]
 pub struct ExitRow {
     pub user_id: String,
-    pub left_at: String, // RFC2822
-    pub banned: bool,
+    pub left_at: UnixTime,
+    pub leave_kind: LeaveKind,
+    pub ban_reason: Option<String>,
     pub account_username: Option<String>,
     pub server_username: Option<String>,
 }
@@ -715,15 +2203,433 @@ pub struct ExitRow {
 pub struct StatsCurrent {
     pub current_members: i64, // DISTINCT user_id with left_at IS NULL
     pub unique_ever: i64,     // DISTINCT user_id seen ever
-    pub total_rejoins: i64,   // total server stays recorded (rows in memberships)
+    pub total_stints: i64,    // total server stays recorded (rows in memberships)
+    pub total_rejoins: i64,   // stays beyond each user's first
     pub total_exits: i64,     // rows with left_at NOT NULL
-    pub total_banned: i64,    // rows with left_at NOT NULL AND banned=1
+    pub total_banned: i64,    // rows with left_at NOT NULL AND leave_kind=Banned
+    pub total_kicked: i64,    // rows with left_at NOT NULL AND leave_kind=Kicked
 }
 
 #[derive(Debug, Clone)]
 pub struct RejoinTimes {
     pub user_id: String,
-    pub joined_at: String,       // RFC2822
-    pub left_at: Option<String>, // RFC2822
-    pub banned: bool,
+    pub joined_at: UnixTime,
+    pub left_at: Option<UnixTime>,
+    pub leave_kind: LeaveKind,
+    pub inferred: bool, // joined_at is a placeholder
+}
+
+/// One stint of a repeat member, for `/stats churn`.
+#[derive(Debug, Clone)]
+pub struct ChurnStint {
+    pub user_id: String,
+    pub joined_at: UnixTime,
+    pub left_at: Option<UnixTime>,
+    pub inferred: bool, // joined_at is a placeholder
+    /// Last-known names (from the user's latest stint).
+    pub account_username: Option<String>,
+    pub server_username: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct StintTimes {
+    pub joined_at: UnixTime,
+    pub left_at: Option<UnixTime>,
+}
+
+#[derive(Debug, Clone)]
+pub struct InviteJoinRow {
+    pub inviter_id: Option<String>,
+    pub joined_at: UnixTime,
+    pub inviter_account_username: Option<String>,
+    pub inviter_server_username: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{GUILD, seed_ban, seed_join, seed_leave, test_db};
+
+    const OTHER_GUILD: GuildId = GuildId::new(2);
+
+    #[tokio::test]
+    async fn stats_current_counts_stints_exits_and_bans() {
+        let db = test_db().await;
+        // 10: still here. 20: left and came back. 30: banned. 40: other guild only.
+        seed_join(&db, GUILD, 10, "alice", 100).await;
+        seed_join(&db, GUILD, 20, "bob", 110).await;
+        seed_leave(&db, GUILD, 20, 200).await;
+        seed_join(&db, GUILD, 20, "bob", 300).await;
+        seed_join(&db, GUILD, 30, "carol", 120).await;
+        seed_ban(&db, GUILD, 30, 400, Some("spam")).await;
+        seed_join(&db, OTHER_GUILD, 40, "dave", 100).await;
+
+        let s = MembershipsRepo::new(&db).stats_current(GUILD).await.unwrap();
+        assert_eq!(s.current_members, 2);
+        assert_eq!(s.unique_ever, 3);
+        assert_eq!(s.total_stints, 4);
+        assert_eq!(s.total_rejoins, 1);
+        assert_eq!(s.total_exits, 2);
+        assert_eq!(s.total_banned, 1);
+        assert_eq!(s.total_kicked, 0);
+    }
+
+    #[tokio::test]
+    async fn stats_current_is_zero_for_empty_guild() {
+        let db = test_db().await;
+        let s = MembershipsRepo::new(&db).stats_current(GUILD).await.unwrap();
+        assert_eq!(s.unique_ever, 0);
+        assert_eq!(s.total_rejoins, 0);
+    }
+
+    #[tokio::test]
+    async fn rejoiners_orders_by_stints_and_uses_latest_names() {
+        let db = test_db().await;
+        seed_join(&db, GUILD, 10, "once", 100).await;
+        for (at, name) in [(100, "twice_old"), (300, "twice")] {
+            seed_join(&db, GUILD, 20, name, at).await;
+            seed_leave(&db, GUILD, 20, at + 50).await;
+        }
+        for at in [100, 300] {
+            seed_join(&db, GUILD, 30, "thrice", at).await;
+            seed_leave(&db, GUILD, 30, at + 50).await;
+        }
+        seed_join(&db, GUILD, 30, "thrice", 500).await;
+
+        let rows = MembershipsRepo::new(&db).rejoiners(GUILD, 2, 10).await.unwrap();
+        let got: Vec<_> = rows
+            .iter()
+            .map(|r| (r.user_id.as_str(), r.rejoin_count, r.times_left))
+            .collect();
+        assert_eq!(got, [("30", 3, 2), ("20", 2, 2)]);
+        assert_eq!(rows[1].account_username.as_deref(), Some("twice"));
+
+        let limited = MembershipsRepo::new(&db).rejoiners(GUILD, 2, 1).await.unwrap();
+        assert_eq!(limited.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn recent_user_summaries_page_walks_users_newest_first() {
+        let db = test_db().await;
+        seed_join(&db, GUILD, 10, "a", 100).await;
+        seed_join(&db, GUILD, 20, "b", 110).await;
+        seed_join(&db, GUILD, 30, "c", 120).await;
+        seed_leave(&db, GUILD, 10, 130).await;
+        // 10's rejoin makes them the most recent user again.
+        seed_join(&db, GUILD, 10, "a2", 140).await;
+        seed_leave(&db, GUILD, 20, 150).await;
+        seed_join(&db, OTHER_GUILD, 40, "d", 100).await;
+
+        let repo = MembershipsRepo::new(&db);
+        let first = repo.recent_user_summaries_page(GUILD, 2, None).await.unwrap();
+        let ids: Vec<_> = first.iter().map(|s| s.user_id.as_str()).collect();
+        assert_eq!(ids, ["10", "30"]);
+        assert_eq!(first[0].account_username.as_deref(), Some("a2"));
+        assert_eq!(first[0].left_at, None);
+
+        let cursor = first.last().map(|s| s.last_row_id);
+        let second = repo.recent_user_summaries_page(GUILD, 2, cursor).await.unwrap();
+        let ids: Vec<_> = second.iter().map(|s| s.user_id.as_str()).collect();
+        assert_eq!(ids, ["20"]);
+        assert_eq!(second[0].left_at, Some(UnixTime(150)));
+
+        let cursor = second.last().map(|s| s.last_row_id);
+        let rest = repo.recent_user_summaries_page(GUILD, 2, cursor).await.unwrap();
+        assert!(rest.is_empty());
+    }
+
+    // The ban/leave tests replay the repo calls `on_guild_ban_add` and `on_leave` make.
+    const WINDOW: i64 = 60;
+
+    async fn latest(repo: &MembershipsRepo<'_>, user: u64) -> MembershipRow {
+        repo.latest_stint(GUILD, UserId::new(user)).await.unwrap().unwrap()
+    }
+
+    #[tokio::test]
+    async fn ban_then_leave_keeps_the_ban_and_its_reason() {
+        let db = test_db().await;
+        let repo = MembershipsRepo::new(&db);
+        let user = UserId::new(10);
+        seed_join(&db, GUILD, 10, "a", 100).await;
+
+        let closed = repo.record_leave(GUILD, user, LeaveKind::Banned, Some("spam")).await;
+        assert_eq!(closed.unwrap(), 1);
+        // The removal arrives second: nothing open, and it's recognised as the same ban.
+        assert_eq!(repo.record_leave(GUILD, user, LeaveKind::Banned, None).await.unwrap(), 0);
+        assert!(repo.recently_closed_as_ban(GUILD, user, WINDOW).await.unwrap());
+
+        let row = latest(&repo, 10).await;
+        assert_eq!(row.leave_kind, LeaveKind::Banned);
+        assert_eq!(row.ban_reason.as_deref(), Some("spam"));
+        assert_eq!(repo.stint_count(GUILD, user).await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn leave_then_ban_relabels_the_stint_once() {
+        let db = test_db().await;
+        let repo = MembershipsRepo::new(&db);
+        let user = UserId::new(10);
+        seed_join(&db, GUILD, 10, "a", 100).await;
+
+        assert_eq!(repo.record_leave(GUILD, user, LeaveKind::Left, None).await.unwrap(), 1);
+        let closed = repo.record_leave(GUILD, user, LeaveKind::Banned, Some("spam")).await;
+        assert_eq!(closed.unwrap(), 0);
+        let upgrade = repo.upgrade_recent_leave_to_ban(GUILD, user, Some("spam"), WINDOW);
+        assert!(upgrade.await.unwrap());
+        // A repeated ban event neither relabels again nor replaces the reason.
+        let again = repo.upgrade_recent_leave_to_ban(GUILD, user, Some("other"), WINDOW);
+        assert!(!again.await.unwrap());
+
+        let row = latest(&repo, 10).await;
+        assert_eq!(row.leave_kind, LeaveKind::Banned);
+        assert_eq!(row.ban_reason.as_deref(), Some("spam"));
+    }
+
+    #[tokio::test]
+    async fn leave_already_banned_then_ban_fills_in_the_reason() {
+        let db = test_db().await;
+        let repo = MembershipsRepo::new(&db);
+        let user = UserId::new(10);
+        seed_join(&db, GUILD, 10, "a", 100).await;
+
+        // `on_leave` saw the ban marker before the ban handler stored anything.
+        assert_eq!(repo.record_leave(GUILD, user, LeaveKind::Banned, None).await.unwrap(), 1);
+        let closed = repo.record_leave(GUILD, user, LeaveKind::Banned, Some("spam")).await;
+        assert_eq!(closed.unwrap(), 0);
+        let upgrade = repo.upgrade_recent_leave_to_ban(GUILD, user, Some("spam"), WINDOW);
+        assert!(!upgrade.await.unwrap(), "the leave handler already posted this ban");
+
+        let row = latest(&repo, 10).await;
+        assert_eq!(row.leave_kind, LeaveKind::Banned);
+        assert_eq!(row.ban_reason.as_deref(), Some("spam"));
+    }
+
+    #[tokio::test]
+    async fn malformed_exit_times_are_skipped() {
+        let db = test_db().await;
+        for user in [10, 20] {
+            seed_join(&db, GUILD, user, "x", 100).await;
+            seed_leave(&db, GUILD, user, 150).await;
+            seed_join(&db, GUILD, user, "x", 200).await;
+        }
+        seed_leave(&db, GUILD, 10, 250).await;
+        // A row written by something other than this code: text in the epoch column.
+        sqlx::query("UPDATE memberships SET left_at_epoch = 'yesterday' WHERE id = 1")
+            .execute(&db.pool)
+            .await
+            .unwrap();
+
+        let repo = MembershipsRepo::new(&db);
+        let exits = repo.all_exits(GUILD, TimeWindow::default(), None, 10).await.unwrap();
+        let got: Vec<_> = exits.rows.iter().map(|r| (r.user_id.as_str(), r.left_at)).collect();
+        assert_eq!(got, [("20", UnixTime(150)), ("10", UnixTime(250))]);
+    }
+
+    #[test]
+    fn rejoiners_without_a_user_id_are_skipped() {
+        // `memberships.user_id` is NOT NULL, so this row can't be seeded; build it directly.
+        let row = |user_id: Option<&str>, stints| RejoinerQueryRow {
+            user_id: user_id.map(str::to_string),
+            stint_count: Some(stints),
+            times_left: Some(stints - 1),
+            account_username: Some(Some("x".to_string())),
+            server_username: None,
+        };
+        let rows = vec![row(Some("10"), 3), row(None, 2), row(Some("20"), 2)];
+
+        let got = rejoiners_from_rows("1", rows);
+        let got: Vec<_> = got.iter().map(|r| (r.user_id.as_str(), r.rejoin_count)).collect();
+        assert_eq!(got, [("10", 3), ("20", 2)]);
+    }
+
+    fn live_member(user: u64, name: &str) -> Member {
+        let mut m = Member::default();
+        m.user.id = UserId::new(user);
+        m.user.name = name.to_string();
+        m.joined_at = Some(Timestamp::from_unix_timestamp(500).unwrap());
+        m
+    }
+
+    #[tokio::test]
+    async fn reconcile_closes_departed_and_opens_missing() {
+        let db = test_db().await;
+        // 10 left while we were offline; 20 is still here; 30 joined unseen.
+        seed_join(&db, GUILD, 10, "gone", 100).await;
+        seed_join(&db, GUILD, 20, "stays", 110).await;
+        seed_join(&db, OTHER_GUILD, 10, "elsewhere", 100).await;
+        let live = [live_member(20, "stays"), live_member(30, "new")];
+
+        let repo = MembershipsRepo::new(&db);
+        let counts = repo.reconcile_open_stints(GUILD, &live).await.unwrap();
+        assert_eq!((counts.closed, counts.opened), (1, 1));
+
+        let open = repo.open_stint_user_ids(GUILD).await.unwrap();
+        assert_eq!(open, HashSet::from([UserId::new(20), UserId::new(30)]));
+        let new = repo.history_for_user(GUILD, UserId::new(30)).await.unwrap();
+        assert_eq!(new[0].joined_at, UnixTime(500));
+        assert_eq!(repo.open_stint_user_ids(OTHER_GUILD).await.unwrap().len(), 1);
+
+        let again = repo.reconcile_open_stints(GUILD, &live).await.unwrap();
+        assert_eq!(again.total(), 0);
+    }
+
+    #[test]
+    fn fts_prefix_query_quotes_operators() {
+        let cols = "{label_norm account_username server_username} :";
+        assert_eq!(
+            fts_prefix_query(r#"a"b* -c OR"#).unwrap(),
+            format!(r#"{cols} "ab" * AND {cols} "c" * AND {cols} "or" *"#)
+        );
+        assert_eq!(fts_prefix_query(r#"" * - ^ : ()"#), None);
+    }
+
+    fn names(rows: &[UserSummary]) -> Vec<&str> {
+        rows.iter().filter_map(|r| r.account_username.as_deref()).collect()
+    }
+
+    #[tokio::test]
+    async fn search_falls_back_to_like_only_without_fts_hits() {
+        let db = test_db().await;
+        seed_join(&db, GUILD, 10, "alice", 100).await;
+        seed_join(&db, GUILD, 20, "pascal", 110).await;
+        let repo = MembershipsRepo::new(&db);
+        repo.rebuild_usernames_fts_for_guild(GUILD).await.unwrap();
+
+        // Two-char queries skip the typo-tolerant top-up, so only FTS/LIKE hits show.
+        // FTS matches "al" as a prefix of "alice" only; LIKE would also find "pascal".
+        let hits = repo.search_user_summaries_prefix(GUILD, "al", 10).await.unwrap();
+        assert_eq!(names(&hits), ["alice"]);
+        // Operator characters are matched literally instead of breaking the query.
+        for input in [r#""al"#, "al*", "-al"] {
+            let hits = repo.search_user_summaries_prefix(GUILD, input, 10).await.unwrap();
+            assert_eq!(names(&hits), ["alice"], "{input}");
+        }
+        // No name starts with "sc", so the substring search answers.
+        let hits = repo.search_user_summaries_prefix(GUILD, "sc", 10).await.unwrap();
+        assert_eq!(names(&hits), ["pascal"]);
+        // ...and treats LIKE wildcards literally.
+        let hits = repo.search_user_summaries_prefix(GUILD, "s%l", 10).await.unwrap();
+        assert!(hits.is_empty());
+    }
+
+    fn typos(a: &str, b: &str) -> usize {
+        let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+        edit_distance(&a, &b)
+    }
+
+    #[test]
+    fn edit_distance_counts_each_kind_of_typo_once() {
+        assert_eq!(typos("john", "john"), 0);
+        assert_eq!(typos("jon", "john"), 1); // insertion
+        assert_eq!(typos("johnn", "john"), 1); // deletion
+        assert_eq!(typos("jahn", "john"), 1); // substitution
+        assert_eq!(typos("jonh", "john"), 1); // adjacent swap
+        assert_eq!(typos("", "abc"), 3);
+        assert_eq!(typos("kitten", "sitting"), 3);
+    }
+
+    #[tokio::test]
+    async fn search_tops_up_with_close_names() {
+        let db = test_db().await;
+        seed_join(&db, GUILD, 10, "jonathan", 100).await;
+        seed_join(&db, GUILD, 20, "bob", 110).await;
+        let repo = MembershipsRepo::new(&db);
+        repo.rebuild_usernames_fts_for_guild(GUILD).await.unwrap();
+
+        // "jonh" matches nothing exactly, but is one typo away from the start of "jonathan".
+        let hits = repo.search_user_summaries_prefix(GUILD, "jonh", 10).await.unwrap();
+        assert_eq!(names(&hits), ["jonathan"]);
+        // Too many typos for a four-letter query.
+        let hits = repo.search_user_summaries_prefix(GUILD, "jxnx", 10).await.unwrap();
+        assert!(hits.is_empty());
+    }
+
+    /// (membership rows, FTS rows) stored for `user` in `GUILD`.
+    async fn stored_rows(db: &Db, user: u64) -> (i64, i64) {
+        let count = |sql: &'static str| async move {
+            sqlx::query_scalar::<_, i64>(sql)
+                .bind(GUILD.to_string())
+                .bind(user.to_string())
+                .fetch_one(&db.pool)
+                .await
+                .unwrap()
+        };
+        (
+            count("SELECT COUNT(*) FROM memberships WHERE guild_id = ? AND user_id = ?").await,
+            count("SELECT COUNT(*) FROM usernames_fts WHERE guild_id = ? AND user_id = ?").await,
+        )
+    }
+
+    #[tokio::test]
+    async fn join_writes_membership_and_fts_row_together() {
+        let db = test_db().await;
+        let repo = MembershipsRepo::new(&db);
+        repo.record_join_with_fts(GUILD, &live_member(10, "alice"), None, None)
+            .await
+            .unwrap();
+        assert_eq!(stored_rows(&db, 10).await, (1, 1));
+
+        // Make the FTS write fail: the stint inserted before it must not survive either.
+        sqlx::query("DROP TABLE usernames_fts").execute(&db.pool).await.unwrap();
+        let res = repo.record_join_with_fts(GUILD, &live_member(20, "bob"), None, None).await;
+        assert!(res.is_err());
+        let stints: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM memberships WHERE user_id = '20'")
+                .fetch_one(&db.pool)
+                .await
+                .unwrap();
+        assert_eq!(stints, 0);
+    }
+
+    #[tokio::test]
+    async fn renamed_members_are_found_by_their_new_name_only() {
+        let db = test_db().await;
+        seed_join(&db, GUILD, 10, "alice", 100).await;
+        let repo = MembershipsRepo::new(&db);
+        repo.rebuild_usernames_fts_for_guild(GUILD).await.unwrap();
+
+        repo.update_names_with_fts(GUILD, UserId::new(10), "zelda", Some("Zed"))
+            .await
+            .unwrap();
+        let stored = repo.last_known_names(GUILD, UserId::new(10)).await.unwrap();
+        assert_eq!(stored, Some((Some("zelda".into()), Some("Zed".into()))));
+        let label: String =
+            sqlx::query_scalar("SELECT label FROM usernames_fts WHERE user_id = '10'")
+                .fetch_one(&db.pool)
+                .await
+                .unwrap();
+        assert_eq!(label, "Zed");
+
+        for new in ["zel", "ze"] {
+            let hits = repo.search_user_summaries_prefix(GUILD, new, 10).await.unwrap();
+            assert_eq!(names(&hits), ["zelda"], "{new}");
+        }
+        for old in ["alice", "al"] {
+            let hits = repo.search_user_summaries_prefix(GUILD, old, 10).await.unwrap();
+            assert!(hits.is_empty(), "{old}");
+        }
+    }
+
+    #[test]
+    fn fold_name_lowercases_and_strips_diacritics() {
+        assert_eq!(fold_name("Zoë"), "zoe");
+        assert_eq!(fold_name("ÄRGER"), "arger");
+        assert_eq!(fold_name("José Müller"), "jose muller");
+        // Precomposed and combining forms fold the same.
+        assert_eq!(fold_name("e\u{0301}"), fold_name("\u{00e9}"));
+    }
+
+    #[tokio::test]
+    async fn search_matches_folded_names_both_ways() {
+        let db = test_db().await;
+        seed_join(&db, GUILD, 10, "Zoë", 100).await;
+        seed_join(&db, GUILD, 20, "arger", 110).await;
+        let repo = MembershipsRepo::new(&db);
+        repo.rebuild_usernames_fts_for_guild(GUILD).await.unwrap();
+
+        let hits = repo.search_user_summaries_prefix(GUILD, "zo", 10).await.unwrap();
+        assert_eq!(names(&hits), ["Zoë"]);
+        let hits = repo.search_user_summaries_prefix(GUILD, "ÄR", 10).await.unwrap();
+        assert_eq!(names(&hits), ["arger"]);
+    }
 }
@@ -1,6 +1,31 @@
+pub mod command_log_repo;
+pub mod error;
 pub mod guild_settings_repo;
+pub mod invites_repo;
+pub mod membership_store;
+pub mod member_notes_repo;
 pub mod memberships_repo;
-// add more later: invites_repo, moderation_repo, etc.
+pub mod optout_repo;
+pub mod role_menus_repo;
+pub mod timestamp;
+pub mod watchlist_repo;
+// add more later: moderation_repo, etc.
 
-pub use guild_settings_repo::{GuildSettings, GuildSettingsRepo};
-pub use memberships_repo::{MembershipRow, MembershipsRepo, UserSummary};
+pub use command_log_repo::CommandLogRepo;
+pub use error::RepoError;
+pub use guild_settings_repo::{
+    ACCOUNT_AGE_RANGE, AccountAgeAction, BAN_WINDOW_RANGE, DEFAULT_BAN_WINDOW_SECS,
+    DEFAULT_RAID_THRESHOLD, GuildSettings, GuildSettingsRepo, LogKind, LogTargets,
+    MAX_LOG_CHANNELS, MAX_WATCHED_ROLES, RAID_THRESHOLD_RANGE, RETENTION_RANGE, SettingsCache,
+};
+pub use invites_repo::{InviteRow, InvitesRepo};
+pub use member_notes_repo::MemberNotesRepo;
+pub use membership_store::MembershipStore;
+pub use memberships_repo::{
+    ChurnStint, LeaveKind, MembershipRow, MembershipsRepo, NameKind, RejoinTimes, TimeWindow,
+    UserSummary,
+};
+pub use optout_repo::OptOutRepo;
+pub use role_menus_repo::{RoleMenu, RoleMenusRepo};
+pub use timestamp::UnixTime;
+pub use watchlist_repo::WatchlistRepo;
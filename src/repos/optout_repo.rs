@@ -0,0 +1,88 @@
+use poise::serenity_prelude as serenity;
+use serenity::all::{GuildId, UserId};
+use sqlx::{Sqlite, Transaction};
+
+use crate::db::Db;
+use crate::repos::UnixTime;
+use crate::repos::error::Result;
+
+/// Members who opted out of tracking (`/member optout`). Event handlers skip storing
+/// anything about them unless the guild set `tracking_required`.
+#[derive(Clone)]
+pub struct OptOutRepo<'a> {
+    db: &'a Db,
+}
+
+impl<'a> OptOutRepo<'a> {
+    pub fn new(db: &'a Db) -> Self {
+        Self { db }
+    }
+
+    pub async fn is_opted_out(&self, guild_id: GuildId, user_id: UserId) -> Result<bool> {
+        let gid = guild_id.to_string();
+        let uid = user_id.to_string();
+        let found = sqlx::query_scalar!(
+            r#"SELECT 1 AS "x!: i64" FROM tracking_optout WHERE guild_id = ? AND user_id = ?"#,
+            gid,
+            uid
+        )
+        .fetch_optional(&self.db.pool)
+        .await?;
+        Ok(found.is_some())
+    }
+
+    /// Every opted-out user of a guild (for bulk imports like `/settings backfill`).
+    pub async fn opted_out_users(&self, guild_id: GuildId) -> Result<Vec<UserId>> {
+        let gid = guild_id.to_string();
+        let rows = sqlx::query_scalar!(
+            r#"SELECT user_id FROM tracking_optout WHERE guild_id = ?"#,
+            gid
+        )
+        .fetch_all(&self.db.pool)
+        .await?;
+        Ok(rows
+            .iter()
+            .filter_map(|s| s.parse::<u64>().ok())
+            .map(UserId::new)
+            .collect())
+    }
+
+    /// Record the opt-out inside the caller's transaction (alongside the purge).
+    pub async fn opt_out(
+        &self,
+        tx: &mut Transaction<'_, Sqlite>,
+        guild_id: GuildId,
+        user_id: UserId,
+    ) -> Result<()> {
+        let gid = guild_id.to_string();
+        let uid = user_id.to_string();
+        let now = UnixTime::now().secs();
+        sqlx::query!(
+            r#"
+            INSERT INTO tracking_optout (guild_id, user_id, opted_out_at)
+            VALUES (?, ?, ?)
+            ON CONFLICT(guild_id, user_id) DO NOTHING
+            "#,
+            gid,
+            uid,
+            now
+        )
+        .execute(&mut **tx)
+        .await?;
+        Ok(())
+    }
+
+    /// Resume tracking; returns whether the user had opted out.
+    pub async fn opt_in(&self, guild_id: GuildId, user_id: UserId) -> Result<bool> {
+        let gid = guild_id.to_string();
+        let uid = user_id.to_string();
+        let res = sqlx::query!(
+            r#"DELETE FROM tracking_optout WHERE guild_id = ? AND user_id = ?"#,
+            gid,
+            uid
+        )
+        .execute(&self.db.pool)
+        .await?;
+        Ok(res.rows_affected() > 0)
+    }
+}
@@ -0,0 +1,149 @@
+use std::collections::HashMap;
+
+use poise::serenity_prelude as serenity;
+use serenity::all::{ChannelId, GuildId, MessageId, RoleId, UserId};
+
+use crate::db::Db;
+use crate::repos::UnixTime;
+use crate::repos::error::Result;
+
+/// Self-assign role menus (`/role-menu`), keyed by the message they were posted as.
+#[derive(Clone)]
+pub struct RoleMenusRepo<'a> {
+    db: &'a Db,
+}
+
+impl<'a> RoleMenusRepo<'a> {
+    pub fn new(db: &'a Db) -> Self {
+        Self { db }
+    }
+
+    /// Store a freshly posted menu and its roles.
+    pub async fn create(&self, menu: &RoleMenu, created_by: UserId) -> Result<()> {
+        let mid = menu.message_id.to_string();
+        let gid = menu.guild_id.to_string();
+        let cid = menu.channel_id.to_string();
+        let created_by = created_by.to_string();
+        let now = UnixTime::now().secs();
+        let mut tx = self.db.transaction().await?;
+        sqlx::query!(
+            r#"
+            INSERT INTO role_menus (message_id, guild_id, channel_id, title, created_by, created_at)
+            VALUES (?, ?, ?, ?, ?, ?)
+            "#,
+            mid,
+            gid,
+            cid,
+            menu.title,
+            created_by,
+            now
+        )
+        .execute(&mut *tx)
+        .await?;
+        for role in &menu.roles {
+            let rid = role.to_string();
+            sqlx::query!(
+                r#"INSERT OR IGNORE INTO role_menu_roles (message_id, role_id) VALUES (?, ?)"#,
+                mid,
+                rid
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Forget a menu; returns whether it existed in this guild.
+    pub async fn remove(&self, guild_id: GuildId, message_id: MessageId) -> Result<bool> {
+        let gid = guild_id.to_string();
+        let mid = message_id.to_string();
+        let mut tx = self.db.transaction().await?;
+        let res = sqlx::query!(
+            r#"DELETE FROM role_menus WHERE guild_id = ? AND message_id = ?"#,
+            gid,
+            mid
+        )
+        .execute(&mut *tx)
+        .await?;
+        if res.rows_affected() == 0 {
+            return Ok(false);
+        }
+        sqlx::query!(r#"DELETE FROM role_menu_roles WHERE message_id = ?"#, mid)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    /// A guild's menus, oldest first.
+    pub async fn list(&self, guild_id: GuildId) -> Result<Vec<RoleMenu>> {
+        self.load(Some(guild_id.to_string())).await
+    }
+
+    /// Every stored menu (for re-attaching collectors on startup).
+    pub async fn all(&self) -> Result<Vec<RoleMenu>> {
+        self.load(None).await
+    }
+
+    async fn load(&self, guild_id: Option<String>) -> Result<Vec<RoleMenu>> {
+        let menus = sqlx::query!(
+            r#"
+            SELECT message_id AS "message_id!", guild_id, channel_id, title
+            FROM role_menus
+            WHERE ?1 IS NULL OR guild_id = ?1
+            ORDER BY created_at, message_id
+            "#,
+            guild_id
+        )
+        .fetch_all(&self.db.pool)
+        .await?;
+        let roles = sqlx::query!(
+            r#"
+            SELECT r.message_id, r.role_id
+            FROM role_menu_roles r
+            JOIN role_menus m ON m.message_id = r.message_id
+            WHERE ?1 IS NULL OR m.guild_id = ?1
+            ORDER BY r.rowid
+            "#,
+            guild_id
+        )
+        .fetch_all(&self.db.pool)
+        .await?;
+
+        let mut roles_by_menu: HashMap<String, Vec<RoleId>> = HashMap::new();
+        for r in roles {
+            if let Some(role) = parse_id(&r.role_id) {
+                roles_by_menu.entry(r.message_id).or_default().push(RoleId::new(role));
+            }
+        }
+        // Rows with unparseable ids are skipped rather than failing every menu.
+        Ok(menus
+            .into_iter()
+            .filter_map(|m| {
+                Some(RoleMenu {
+                    guild_id: GuildId::new(parse_id(&m.guild_id)?),
+                    channel_id: ChannelId::new(parse_id(&m.channel_id)?),
+                    message_id: MessageId::new(parse_id(&m.message_id)?),
+                    title: m.title,
+                    roles: roles_by_menu.remove(&m.message_id).unwrap_or_default(),
+                })
+            })
+            .collect())
+    }
+}
+
+/// Discord ids are non-zero; `*Id::new(0)` panics.
+fn parse_id(raw: &str) -> Option<u64> {
+    raw.parse().ok().filter(|&id| id != 0)
+}
+
+#[derive(Debug, Clone)]
+pub struct RoleMenu {
+    pub guild_id: GuildId,
+    pub channel_id: ChannelId,
+    pub message_id: MessageId,
+    pub title: String,
+    /// Roles offered, in menu order.
+    pub roles: Vec<RoleId>,
+}
@@ -0,0 +1,53 @@
+use chrono::{DateTime, SecondsFormat, Utc};
+use poise::serenity_prelude::Timestamp;
+use serde::{Serialize, Serializer};
+
+/// A point in time as unix seconds (UTC), the way every timestamp column stores it.
+///
+/// Decodes straight from the INTEGER columns, so readers never re-parse text.
+/// Serializes as ISO-8601 so JSON exports stay human-readable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, sqlx::Type)]
+#[sqlx(transparent)]
+pub struct UnixTime(pub i64);
+
+impl UnixTime {
+    pub fn now() -> Self {
+        Self(Utc::now().timestamp())
+    }
+
+    pub fn secs(self) -> i64 {
+        self.0
+    }
+
+    pub fn to_datetime(self) -> DateTime<Utc> {
+        DateTime::from_timestamp(self.0, 0).unwrap_or_default()
+    }
+
+    /// ISO-8601 / RFC 3339 in UTC, e.g. `2024-05-01T12:00:00Z`.
+    pub fn to_rfc3339(self) -> String {
+        self.to_datetime().to_rfc3339_opts(SecondsFormat::Secs, true)
+    }
+
+    /// Discord timestamp markup: `<t:…:f>`, `<t:…:R>`, ….
+    pub fn discord(self, style: char) -> String {
+        format!("<t:{}:{style}>", self.0)
+    }
+}
+
+impl From<Timestamp> for UnixTime {
+    fn from(ts: Timestamp) -> Self {
+        Self(ts.unix_timestamp())
+    }
+}
+
+impl From<DateTime<Utc>> for UnixTime {
+    fn from(dt: DateTime<Utc>) -> Self {
+        Self(dt.timestamp())
+    }
+}
+
+impl Serialize for UnixTime {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_rfc3339())
+    }
+}
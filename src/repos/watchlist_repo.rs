@@ -0,0 +1,120 @@
+use poise::serenity_prelude as serenity;
+use serenity::all::{GuildId, UserId};
+
+use crate::db::Db;
+use crate::repos::UnixTime;
+use crate::repos::error::Result;
+
+/// Users moderators flagged with `/member watch add`. Keyed by user id alone, so an
+/// entry survives leaves, rejoins and purged membership rows.
+#[derive(Clone)]
+pub struct WatchlistRepo<'a> {
+    db: &'a Db,
+}
+
+impl<'a> WatchlistRepo<'a> {
+    pub fn new(db: &'a Db) -> Self {
+        Self { db }
+    }
+
+    /// Watch a user, or update the reason if they already are; returns whether the
+    /// entry is new.
+    pub async fn add(
+        &self,
+        guild_id: GuildId,
+        user_id: UserId,
+        reason: Option<&str>,
+        added_by: UserId,
+    ) -> Result<bool> {
+        let gid = guild_id.to_string();
+        let uid = user_id.to_string();
+        let added_by = added_by.to_string();
+        let now = UnixTime::now().secs();
+        let mut tx = self.db.transaction().await?;
+        let existed = sqlx::query_scalar!(
+            r#"SELECT 1 AS "x!: i64" FROM watchlist WHERE guild_id = ? AND user_id = ?"#,
+            gid,
+            uid
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .is_some();
+        sqlx::query!(
+            r#"
+            INSERT INTO watchlist (guild_id, user_id, reason, added_by, added_at)
+            VALUES (?, ?, ?, ?, ?)
+            ON CONFLICT(guild_id, user_id) DO UPDATE SET
+              reason   = excluded.reason,
+              added_by = excluded.added_by,
+              added_at = excluded.added_at
+            "#,
+            gid,
+            uid,
+            reason,
+            added_by,
+            now
+        )
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(!existed)
+    }
+
+    /// Stop watching a user; returns whether they were on the list.
+    pub async fn remove(&self, guild_id: GuildId, user_id: UserId) -> Result<bool> {
+        let gid = guild_id.to_string();
+        let uid = user_id.to_string();
+        let res = sqlx::query!(
+            r#"DELETE FROM watchlist WHERE guild_id = ? AND user_id = ?"#,
+            gid,
+            uid
+        )
+        .execute(&self.db.pool)
+        .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    pub async fn get(&self, guild_id: GuildId, user_id: UserId) -> Result<Option<WatchEntry>> {
+        let gid = guild_id.to_string();
+        let uid = user_id.to_string();
+        let row = sqlx::query_as!(
+            WatchEntry,
+            r#"
+            SELECT user_id, reason, added_by, added_at AS "added_at: UnixTime"
+            FROM watchlist
+            WHERE guild_id = ? AND user_id = ?
+            "#,
+            gid,
+            uid
+        )
+        .fetch_optional(&self.db.pool)
+        .await?;
+        Ok(row)
+    }
+
+    /// Every watched user of a guild, most recently added first.
+    pub async fn list(&self, guild_id: GuildId) -> Result<Vec<WatchEntry>> {
+        let gid = guild_id.to_string();
+        let rows = sqlx::query_as!(
+            WatchEntry,
+            r#"
+            SELECT user_id, reason, added_by, added_at AS "added_at: UnixTime"
+            FROM watchlist
+            WHERE guild_id = ?
+            ORDER BY added_at DESC, user_id
+            "#,
+            gid
+        )
+        .fetch_all(&self.db.pool)
+        .await?;
+        Ok(rows)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WatchEntry {
+    pub user_id: String,
+    pub reason: Option<String>,
+    pub added_by: String,
+    pub added_at: UnixTime,
+}
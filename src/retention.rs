@@ -0,0 +1,46 @@
+//! Opt-in data retention: once a day, delete history older than each guild's
+//! `retention_days` (see `MembershipsRepo::prune_older_than`).
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::repos::{MembershipsRepo, UnixTime};
+use crate::state::AppState;
+
+const PRUNE_EVERY: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Prune every guild that turned retention on, then sleep a day. Runs until shutdown.
+pub async fn run_retention_loop(state: Arc<AppState>) {
+    loop {
+        if let Err(e) = prune_all(&state).await {
+            tracing::warn!(error = format!("{e:#}"), "Retention pass failed");
+        }
+        tokio::select! {
+            _ = state.shutdown.cancelled() => break,
+            _ = tokio::time::sleep(PRUNE_EVERY) => {}
+        }
+    }
+}
+
+async fn prune_all(state: &AppState) -> anyhow::Result<()> {
+    let policies = state.settings_repo().retention_policies().await?;
+    let mrepo = MembershipsRepo::new(&state.db);
+    for (guild_id, days) in policies {
+        let cutoff = UnixTime(UnixTime::now().secs() - days * 24 * 60 * 60);
+        match mrepo.prune_older_than(guild_id, cutoff).await {
+            Ok(pruned) => tracing::info!(
+                %guild_id,
+                days,
+                memberships = pruned.memberships,
+                notes = pruned.notes,
+                name_changes = pruned.name_changes,
+                total = pruned.total(),
+                "Pruned data past the retention window"
+            ),
+            Err(e) => {
+                tracing::warn!(%guild_id, error = format!("{e:#}"), "Retention prune failed");
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,50 @@
+//! Bounded retry for Discord API calls that fail transiently (429 / 5xx).
+
+use std::future::Future;
+use std::time::Duration;
+
+use poise::serenity_prelude as serenity;
+use serenity::http::StatusCode;
+
+/// Total tries per call, including the first one.
+const MAX_ATTEMPTS: u32 = 3;
+/// Delay before the first retry; doubled for each one after.
+const BASE_DELAY: Duration = Duration::from_millis(500);
+
+/// HTTP status of a failed Discord request, if the error came from one.
+pub fn status_of(err: &serenity::Error) -> Option<StatusCode> {
+    match err {
+        serenity::Error::Http(e) => e.status_code(),
+        _ => None,
+    }
+}
+
+/// Rate limits and server-side errors are worth another try; anything else
+/// (403 missing permissions, 404 deleted channel, bad payloads) won't fix itself.
+fn is_transient(err: &serenity::Error) -> bool {
+    status_of(err).is_some_and(|s| s == StatusCode::TOO_MANY_REQUESTS || s.is_server_error())
+}
+
+/// Run `op`, retrying with exponential backoff while it fails transiently.
+/// The last error is returned as-is; logging it is up to the caller.
+pub async fn with_backoff<T, F, Fut>(what: &str, mut op: F) -> serenity::Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = serenity::Result<T>>,
+{
+    let mut delay = BASE_DELAY;
+    let mut attempt = 1;
+    loop {
+        match op().await {
+            Err(e) if attempt < MAX_ATTEMPTS && is_transient(&e) => {
+                tracing::debug!(
+                    "{what} failed (attempt {attempt}/{MAX_ATTEMPTS}), retrying in {delay:?}: {e}"
+                );
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+                attempt += 1;
+            }
+            res => return res,
+        }
+    }
+}
@@ -0,0 +1,88 @@
+//! One tracing span per gateway event, so every line logged while handling it
+//! (DB writes, embed posts, command runs) can be correlated.
+//!
+//! `Traced` wraps the poise framework; the span carries the event kind, a request id
+//! (the interaction id for interactions, a process-local counter otherwise) and the
+//! guild/user involved when the event has them. Commands add their name in `pre_command`.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use async_trait::async_trait;
+use poise::serenity_prelude as serenity;
+use serenity::all::{Client, Context, FullEvent, GuildId, Interaction, UserId};
+use tracing::Instrument;
+use tracing::field::Empty;
+
+/// Request ids for non-interaction events (interactions reuse their own id).
+static NEXT_REQUEST_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Framework wrapper that runs each `dispatch` inside an event span.
+pub struct Traced<F>(pub F);
+
+#[async_trait]
+impl<F: serenity::Framework> serenity::Framework for Traced<F> {
+    async fn init(&mut self, client: &Client) {
+        self.0.init(client).await;
+    }
+
+    async fn dispatch(&self, ctx: Context, event: FullEvent) {
+        let span = event_span(&event);
+        self.0.dispatch(ctx, event).instrument(span).await;
+    }
+}
+
+/// Record the running command's name on the enclosing event span.
+pub fn record_command(name: &str) {
+    tracing::Span::current().record("command", name);
+}
+
+fn event_span(event: &FullEvent) -> tracing::Span {
+    let request_id = match event {
+        FullEvent::InteractionCreate { interaction } => interaction.id().get(),
+        _ => NEXT_REQUEST_ID.fetch_add(1, Ordering::Relaxed),
+    };
+    let span = tracing::info_span!(
+        "event",
+        kind = event.snake_case_name(),
+        request_id,
+        guild_id = Empty,
+        user_id = Empty,
+        command = Empty,
+    );
+    let (guild_id, user_id) = subjects(event);
+    if let Some(g) = guild_id {
+        span.record("guild_id", g.get());
+    }
+    if let Some(u) = user_id {
+        span.record("user_id", u.get());
+    }
+    span
+}
+
+/// Guild and user an event is about, for the events the bot acts on.
+fn subjects(event: &FullEvent) -> (Option<GuildId>, Option<UserId>) {
+    use FullEvent::*;
+    match event {
+        GuildMemberAddition { new_member } => (Some(new_member.guild_id), Some(new_member.user.id)),
+        GuildMemberRemoval { guild_id, user, .. } => (Some(*guild_id), Some(user.id)),
+        GuildMemberUpdate { event, .. } => (Some(event.guild_id), Some(event.user.id)),
+        GuildBanAddition { guild_id, banned_user } => (Some(*guild_id), Some(banned_user.id)),
+        GuildBanRemoval { guild_id, unbanned_user } => (Some(*guild_id), Some(unbanned_user.id)),
+        GuildCreate { guild, .. } => (Some(guild.id), None),
+        InviteCreate { data } => (data.guild_id, None),
+        InviteDelete { data } => (data.guild_id, None),
+        MessageDelete { guild_id, .. } => (*guild_id, None),
+        MessageUpdate { event, .. } => (event.guild_id, event.author.as_ref().map(|a| a.id)),
+        InteractionCreate { interaction } => {
+            match interaction {
+                Interaction::Command(c) | Interaction::Autocomplete(c) => {
+                    (c.guild_id, Some(c.user.id))
+                }
+                Interaction::Component(c) => (c.guild_id, Some(c.user.id)),
+                Interaction::Modal(m) => (m.guild_id, Some(m.user.id)),
+                _ => (None, None),
+            }
+        }
+        _ => (None, None),
+    }
+}
@@ -1,14 +1,21 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
 use std::sync::Arc;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use anyhow::Result;
 use dashmap::DashMap;
+use dashmap::mapref::entry::Entry;
 use poise::serenity_prelude as serenity;
-use serenity::all::{GuildId, UserId};
+use serenity::all::{GuildId, MessageId, UserId};
+use tokio_util::sync::CancellationToken;
 
 use crate::db::Db;
+use crate::metrics::Metrics;
+use crate::repos::{GuildSettingsRepo, MembershipStore, MembershipsRepo, SettingsCache};
 
+#[allow(unused_imports)] // kept so `state::GuildSettings` paths keep resolving
 pub use crate::repos::GuildSettings;
 pub type Ctx<'a> = poise::Context<'a, std::sync::Arc<AppState>, anyhow::Error>;
 
@@ -22,16 +29,272 @@ pub struct AppState {
 
     /// Recent bans for leave classification
     pub recent_bans: DashMap<GuildId, DashMap<UserId, i64>>,
+
+    /// Join/leave times within the last `RAID_WINDOW`, for raid detection.
+    pub join_ticks: DashMap<GuildId, VecDeque<Instant>>,
+    pub leave_ticks: DashMap<GuildId, VecDeque<Instant>>,
+
+    /// When each guild last got a raid alert of each kind (for the cooldown).
+    pub raid_alerts: DashMap<(GuildId, RaidKind), Instant>,
+
+    /// Current member count per guild (open stints), adjusted on join/leave so the join
+    /// log needn't count rows per event. Seeded from the DB on first use.
+    pub member_counts: DashMap<GuildId, i64>,
+
+    /// Lock-free runtime counters for operators (see `/admin stats-runtime`).
+    pub counters: RuntimeCounters,
+
+    /// Per-guild counters and command latency for Prometheus (`/metrics`).
+    pub metrics: Metrics,
+
+    /// Cancelled on shutdown; background loops stop when it fires.
+    pub shutdown: CancellationToken,
+
+    /// Set once the background loops are running. Gateway reconnects fire `Ready`
+    /// again; see `claim_background_loops`.
+    background_started: AtomicBool,
+
+    /// Guild settings read by event handlers; writes through `settings_repo()` evict entries.
+    pub settings_cache: SettingsCache,
+
+    /// Last member-autocomplete results per (guild, user), to throttle DB hits while typing.
+    pub autocomplete_cache: DashMap<(GuildId, UserId), CachedAutocomplete>,
+
+    /// Guild and stop token of each running role-menu collector, by menu message.
+    pub role_menus: DashMap<MessageId, (GuildId, CancellationToken)>,
+}
+
+/// One user's most recent autocomplete answer: `(label, value)` pairs.
+pub struct CachedAutocomplete {
+    pub at: Instant,
+    pub choices: Vec<(String, String)>,
+}
+
+/// Sliding window over which joins/leaves are counted for raid detection.
+pub const RAID_WINDOW: Duration = Duration::from_secs(60);
+/// Minimum gap between two raid alerts of the same kind in one guild.
+pub const RAID_ALERT_COOLDOWN: Duration = Duration::from_secs(10 * 60);
+
+/// Which burst a raid alert is about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RaidKind {
+    Joins,
+    /// Leaves, kicks and bans alike (a bulk ban shows up as a burst of removals).
+    Leaves,
+}
+
+/// Keystrokes closer together than this reuse the previous results.
+pub const AUTOCOMPLETE_MIN_INTERVAL: Duration = Duration::from_millis(300);
+/// Max (guild, user) entries kept; the oldest is evicted beyond this.
+const AUTOCOMPLETE_CACHE_CAP: usize = 1_000;
+
+/// Event/throughput counters, bumped from the handlers.
+pub struct RuntimeCounters {
+    pub started_at: Instant,
+    pub joins: AtomicU64,
+    pub leaves: AtomicU64,
+    pub bans: AtomicU64,
+    pub kicks: AtomicU64,
+    pub log_posts: AtomicU64,
+    pub db_errors: AtomicU64,
+    /// Guilds the bot was removed from (kicked, or the guild was deleted).
+    pub guilds_left: AtomicU64,
+}
+
+/// Point-in-time copy of `RuntimeCounters`.
+#[derive(Debug, Clone, Copy)]
+pub struct CounterSnapshot {
+    pub uptime_secs: u64,
+    pub joins: u64,
+    pub leaves: u64,
+    pub bans: u64,
+    pub kicks: u64,
+    pub log_posts: u64,
+    pub db_errors: u64,
+    pub guilds_left: u64,
+}
+
+impl RuntimeCounters {
+    fn new() -> Self {
+        Self {
+            started_at: Instant::now(),
+            joins: AtomicU64::new(0),
+            leaves: AtomicU64::new(0),
+            bans: AtomicU64::new(0),
+            kicks: AtomicU64::new(0),
+            log_posts: AtomicU64::new(0),
+            db_errors: AtomicU64::new(0),
+            guilds_left: AtomicU64::new(0),
+        }
+    }
+
+    pub fn incr(counter: &AtomicU64) {
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Count an error if it originated in the database layer.
+    pub fn record_error(&self, err: &anyhow::Error) {
+        let from_repo = matches!(
+            err.downcast_ref::<crate::repos::RepoError>(),
+            Some(crate::repos::RepoError::Sqlx(_))
+        );
+        if from_repo || err.downcast_ref::<sqlx::Error>().is_some() {
+            Self::incr(&self.db_errors);
+        }
+    }
+
+    pub fn snapshot(&self) -> CounterSnapshot {
+        self.read(|c| c.load(Ordering::Relaxed))
+    }
+
+    /// Snapshot and zero all counters in one pass (uptime is not reset).
+    pub fn snapshot_and_reset(&self) -> CounterSnapshot {
+        self.read(|c| c.swap(0, Ordering::Relaxed))
+    }
+
+    fn read(&self, f: impl Fn(&AtomicU64) -> u64) -> CounterSnapshot {
+        CounterSnapshot {
+            uptime_secs: self.started_at.elapsed().as_secs(),
+            joins: f(&self.joins),
+            leaves: f(&self.leaves),
+            bans: f(&self.bans),
+            kicks: f(&self.kicks),
+            log_posts: f(&self.log_posts),
+            db_errors: f(&self.db_errors),
+            guilds_left: f(&self.guilds_left),
+        }
+    }
 }
 
 impl AppState {
-    pub async fn new(db_url: &str) -> Result<Arc<Self>, anyhow::Error> {
+    pub async fn new(db_url: &str, shutdown: CancellationToken) -> Result<Arc<Self>, anyhow::Error> {
         let db = crate::db::Db::connect(db_url).await?;
-        Ok(Arc::new(Self {
+        Ok(Self::with_db(db, shutdown))
+    }
+
+    /// State around an already-connected database, with every cache empty.
+    pub fn with_db(db: Db, shutdown: CancellationToken) -> Arc<Self> {
+        Arc::new(Self {
             db,
             invite_cache: DashMap::new(),
             recent_bans: DashMap::new(),
-        }))
+            join_ticks: DashMap::new(),
+            leave_ticks: DashMap::new(),
+            raid_alerts: DashMap::new(),
+            member_counts: DashMap::new(),
+            counters: RuntimeCounters::new(),
+            metrics: Metrics::default(),
+            shutdown,
+            background_started: AtomicBool::new(false),
+            autocomplete_cache: DashMap::new(),
+            role_menus: DashMap::new(),
+            settings_cache: DashMap::new(),
+        })
+    }
+
+    /// Settings repo wired to the shared cache; use this (not `GuildSettingsRepo::new`)
+    /// so writes invalidate what event handlers read.
+    pub fn settings_repo(&self) -> GuildSettingsRepo<'_> {
+        GuildSettingsRepo::new(&self.db).with_cache(&self.settings_cache)
+    }
+
+    /// Read-only membership queries for commands. Writes still go through
+    /// `MembershipsRepo::new(&state.db)`.
+    pub fn memberships(&self) -> impl MembershipStore + '_ {
+        MembershipsRepo::new(&self.db)
+    }
+
+    /// `true` for the first caller only: whoever gets it spawns the process-wide
+    /// background loops, so a reconnect's `Ready` doesn't start a second copy.
+    pub fn claim_background_loops(&self) -> bool {
+        !self.background_started.swap(true, Ordering::SeqCst)
+    }
+
+    /// Autocomplete choices for this user: the previous answer if it is younger than
+    /// `min_interval`, else whatever `fetch` returns (remembered for the next keystroke).
+    pub async fn autocomplete_with<F, Fut>(
+        &self,
+        guild_id: GuildId,
+        user_id: UserId,
+        min_interval: Duration,
+        fetch: F,
+    ) -> Result<Vec<(String, String)>>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<Vec<(String, String)>>>,
+    {
+        if let Some(cached) = self.recent_autocomplete(guild_id, user_id, min_interval) {
+            return Ok(cached);
+        }
+        let fresh = fetch().await?;
+        self.store_autocomplete(guild_id, user_id, fresh.clone());
+        Ok(fresh)
+    }
+
+    /// Previous autocomplete results for this user if they were produced less than
+    /// `min_interval` ago (caller should skip the query and reuse them).
+    pub fn recent_autocomplete(
+        &self,
+        guild_id: GuildId,
+        user_id: UserId,
+        min_interval: Duration,
+    ) -> Option<Vec<(String, String)>> {
+        self.autocomplete_cache
+            .get(&(guild_id, user_id))
+            .filter(|e| e.at.elapsed() < min_interval)
+            .map(|e| e.choices.clone())
+    }
+
+    /// Remember fresh autocomplete results; evicts the stalest entry when over the cap.
+    pub fn store_autocomplete(
+        &self,
+        guild_id: GuildId,
+        user_id: UserId,
+        choices: Vec<(String, String)>,
+    ) {
+        let key = (guild_id, user_id);
+        if self.autocomplete_cache.len() >= AUTOCOMPLETE_CACHE_CAP
+            && !self.autocomplete_cache.contains_key(&key)
+        {
+            let oldest = self
+                .autocomplete_cache
+                .iter()
+                .min_by_key(|e| e.at)
+                .map(|e| *e.key());
+            if let Some(oldest) = oldest {
+                self.autocomplete_cache.remove(&oldest);
+            }
+        }
+        self.autocomplete_cache.insert(
+            key,
+            CachedAutocomplete {
+                at: Instant::now(),
+                choices,
+            },
+        );
+    }
+
+    /// Drop every in-memory entry for a guild the bot is no longer in and stop its
+    /// role-menu collectors. Stored history is kept; only caches that would go stale
+    /// are cleared.
+    pub fn forget_guild(&self, guild_id: GuildId) {
+        self.invite_cache.remove(&guild_id);
+        self.recent_bans.remove(&guild_id);
+        self.settings_cache.remove(&guild_id);
+        self.member_counts.remove(&guild_id);
+        self.autocomplete_cache.retain(|(gid, _), _| *gid != guild_id);
+        self.join_ticks.remove(&guild_id);
+        self.leave_ticks.remove(&guild_id);
+        self.raid_alerts.retain(|(gid, _), _| *gid != guild_id);
+        self.metrics.forget_guild(guild_id);
+        // Nobody can click the guild's role menus any more; stop their collectors.
+        self.role_menus.retain(|_, (gid, stop)| {
+            let ours = *gid == guild_id;
+            if ours {
+                stop.cancel();
+            }
+            !ours
+        });
     }
 
     pub fn mark_recent_ban(&self, guild_id: GuildId, user_id: UserId) {
@@ -49,14 +312,84 @@ impl AppState {
         user_id: UserId,
         window_secs: i64,
     ) -> bool {
-        if let Some(map) = self.recent_bans.get(&guild_id) {
-            if let Some(ts) = map.get(&user_id) {
-                return unix_now() - *ts <= window_secs;
-            }
+        if let Some(map) = self.recent_bans.get(&guild_id)
+            && let Some(ts) = map.get(&user_id)
+        {
+            return unix_now() - *ts <= window_secs;
         }
         false
     }
 
+    /// Count a join towards `join_rate`.
+    pub fn record_join_tick(&self, guild_id: GuildId) {
+        record_tick(&self.join_ticks, guild_id)
+    }
+
+    /// Joins seen in the last `RAID_WINDOW`.
+    pub fn join_rate(&self, guild_id: GuildId) -> usize {
+        rate_in_window(&self.join_ticks, guild_id)
+    }
+
+    /// Count a leave/kick/ban towards `leave_rate`.
+    pub fn record_leave_tick(&self, guild_id: GuildId) {
+        record_tick(&self.leave_ticks, guild_id)
+    }
+
+    /// Removals seen in the last `RAID_WINDOW`.
+    pub fn leave_rate(&self, guild_id: GuildId) -> usize {
+        rate_in_window(&self.leave_ticks, guild_id)
+    }
+
+    /// Apply `delta` to the cached member count; `None` if it isn't cached yet.
+    pub fn adjust_member_count(&self, guild_id: GuildId, delta: i64) -> Option<i64> {
+        let mut count = self.member_counts.get_mut(&guild_id)?;
+        *count += delta;
+        Some(*count)
+    }
+
+    pub fn seed_member_count(&self, guild_id: GuildId, count: i64) {
+        self.member_counts.insert(guild_id, count);
+    }
+
+    /// Drop the cached count after bulk changes (backfill, merges); it is re-read on next use.
+    pub fn forget_member_count(&self, guild_id: GuildId) {
+        self.member_counts.remove(&guild_id);
+    }
+
+    /// Fresh stop token for a role menu's collector; any collector already serving that
+    /// menu is stopped, so re-attaching never handles a click twice.
+    pub fn track_role_menu(&self, guild_id: GuildId, message_id: MessageId) -> CancellationToken {
+        let token = self.shutdown.child_token();
+        if let Some((_, old)) = self.role_menus.insert(message_id, (guild_id, token.clone())) {
+            old.cancel();
+        }
+        token
+    }
+
+    /// Stop the collector of a removed role menu.
+    pub fn untrack_role_menu(&self, message_id: MessageId) {
+        if let Some((_, (_, token))) = self.role_menus.remove(&message_id) {
+            token.cancel();
+        }
+    }
+
+    /// Whether a raid alert of `kind` may go out now; claiming it starts the cooldown.
+    pub fn claim_raid_alert(&self, guild_id: GuildId, kind: RaidKind) -> bool {
+        let now = Instant::now();
+        match self.raid_alerts.entry((guild_id, kind)) {
+            Entry::Occupied(mut last) => {
+                if now.duration_since(*last.get()) < RAID_ALERT_COOLDOWN {
+                    return false;
+                }
+                last.insert(now);
+            }
+            Entry::Vacant(slot) => {
+                slot.insert(now);
+            }
+        }
+        true
+    }
+
     pub fn prune_recent_bans(&self, max_age_secs: i64) {
         let now = unix_now();
         for gmap in self.recent_bans.iter_mut() {
@@ -78,9 +411,101 @@ impl AppState {
     }
 }
 
+fn record_tick(ticks: &DashMap<GuildId, VecDeque<Instant>>, guild_id: GuildId) {
+    let now = Instant::now();
+    let mut q = ticks.entry(guild_id).or_default();
+    drop_expired(&mut q, now);
+    q.push_back(now);
+}
+
+fn rate_in_window(ticks: &DashMap<GuildId, VecDeque<Instant>>, guild_id: GuildId) -> usize {
+    let now = Instant::now();
+    ticks.get_mut(&guild_id).map_or(0, |mut q| {
+        drop_expired(&mut q, now);
+        q.len()
+    })
+}
+
+fn drop_expired(q: &mut VecDeque<Instant>, now: Instant) {
+    while q.front().is_some_and(|t| now.duration_since(*t) > RAID_WINDOW) {
+        q.pop_front();
+    }
+}
+
 fn unix_now() -> i64 {
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .unwrap()
         .as_secs() as i64
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+
+    use super::*;
+    use crate::test_support::{GUILD, test_state};
+
+    #[tokio::test]
+    async fn autocomplete_inside_the_window_reuses_the_last_answer() {
+        let state = test_state().await;
+        let fetches = AtomicUsize::new(0);
+        let user = UserId::new(10);
+        let fetch = |label: &'static str| {
+            let fetches = &fetches;
+            move || async move {
+                fetches.fetch_add(1, Ordering::SeqCst);
+                Ok(vec![(label.to_string(), "10".to_string())])
+            }
+        };
+        let window = Duration::from_secs(3600);
+
+        let first = state.autocomplete_with(GUILD, user, window, fetch("al")).await.unwrap();
+        let second = state.autocomplete_with(GUILD, user, window, fetch("ali")).await.unwrap();
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+        assert_eq!(second, first);
+
+        // Outside the window (or for another user) the query runs again.
+        let expired = state
+            .autocomplete_with(GUILD, user, Duration::ZERO, fetch("alic"))
+            .await
+            .unwrap();
+        assert_eq!(expired[0].0, "alic");
+        let other = UserId::new(20);
+        state.autocomplete_with(GUILD, other, window, fetch("b")).await.unwrap();
+        assert_eq!(fetches.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn forget_guild_clears_its_caches_and_stops_its_role_menus() {
+        let state = test_state().await;
+        let other = GuildId::new(2);
+        for gid in [GUILD, other] {
+            state.record_join_tick(gid);
+            state.record_leave_tick(gid);
+            assert!(state.claim_raid_alert(gid, RaidKind::Joins));
+            state.mark_recent_ban(gid, UserId::new(10));
+            state.seed_member_count(gid, 5);
+            state.store_autocomplete(gid, UserId::new(10), Vec::new());
+        }
+        let ours = state.track_role_menu(GUILD, MessageId::new(100));
+        let theirs = state.track_role_menu(other, MessageId::new(200));
+
+        state.forget_guild(GUILD);
+
+        assert_eq!((state.join_rate(GUILD), state.leave_rate(GUILD)), (0, 0));
+        assert!(state.claim_raid_alert(GUILD, RaidKind::Joins));
+        assert!(!state.was_recently_banned(GUILD, UserId::new(10), 60));
+        assert!(state.adjust_member_count(GUILD, 0).is_none());
+        assert!(!state.autocomplete_cache.contains_key(&(GUILD, UserId::new(10))));
+        assert!(ours.is_cancelled());
+        assert!(!state.role_menus.contains_key(&MessageId::new(100)));
+
+        // The other guild is untouched.
+        assert_eq!((state.join_rate(other), state.leave_rate(other)), (1, 1));
+        assert!(!state.claim_raid_alert(other, RaidKind::Joins));
+        assert!(state.was_recently_banned(other, UserId::new(10), 60));
+        assert_eq!(state.adjust_member_count(other, 0), Some(5));
+        assert!(!theirs.is_cancelled());
+    }
+}
@@ -0,0 +1,77 @@
+//! User-facing text by key, in the guild's chosen language.
+//!
+//! Locales are flat `key → text` JSON files under `locales/`, embedded at build time.
+//! Placeholders are written `{name}` and filled from `args`. A key missing from a
+//! locale falls back to English; a key missing from English renders as the key itself
+//! so the gap is visible instead of blank.
+
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::LazyLock;
+
+/// Language used when a guild hasn't picked one (and for missing keys).
+pub const DEFAULT_LANG: &str = "en";
+
+/// Supported languages as `(code, native name)`, in the order `/settings language` offers them.
+pub const LANGUAGES: [(&str, &str); 2] = [("en", "English"), ("de", "Deutsch")];
+
+static LOCALES: LazyLock<HashMap<&'static str, HashMap<String, String>>> = LazyLock::new(|| {
+    [
+        ("en", include_str!("../locales/en.json")),
+        ("de", include_str!("../locales/de.json")),
+    ]
+    .into_iter()
+    .map(|(code, raw)| {
+        let strings = serde_json::from_str(raw)
+            .unwrap_or_else(|e| panic!("locales/{code}.json is not a flat string map: {e}"));
+        (code, strings)
+    })
+    .collect()
+});
+
+/// The supported language code matching `code`, or `DEFAULT_LANG`.
+pub fn lang_or_default(code: Option<&str>) -> &'static str {
+    code.and_then(supported).unwrap_or(DEFAULT_LANG)
+}
+
+/// `code` as its `'static` entry in `LANGUAGES`, if supported.
+pub fn supported(code: &str) -> Option<&'static str> {
+    LANGUAGES
+        .iter()
+        .map(|(c, _)| *c)
+        .find(|c| c.eq_ignore_ascii_case(code.trim()))
+}
+
+/// Look up `key` in `lang` (falling back to English) and fill its `{name}` placeholders.
+pub fn t(key: &str, lang: &str, args: &[(&str, &(dyn Display + Sync))]) -> String {
+    let lookup = |code: &str| LOCALES.get(code).and_then(|m| m.get(key));
+    let Some(template) = lookup(lang).or_else(|| lookup(DEFAULT_LANG)) else {
+        tracing::warn!("Missing string key `{key}`");
+        return key.to_string();
+    };
+
+    // One pass over the template, so argument values (user input included) are never
+    // themselves scanned for placeholders.
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template.as_str();
+    while let Some(open) = rest.find('{') {
+        out.push_str(&rest[..open]);
+        let after = &rest[open + 1..];
+        let arg = after.find('}').and_then(|close| {
+            let name = &after[..close];
+            args.iter().find(|(n, _)| *n == name).map(|(_, v)| (close, *v))
+        });
+        match arg {
+            Some((close, value)) => {
+                out.push_str(&value.to_string());
+                rest = &after[close + 1..];
+            }
+            None => {
+                out.push('{');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
@@ -0,0 +1,116 @@
+//! `{placeholder}` substitution for custom join/leave messages.
+//!
+//! Placeholders:
+//! - `{user}`: account name, `{mention}`: a ping of the member
+//! - `{server}`: server name, `{count}`: current member count
+//! - `{joined_relative}` / `{joined_full}`: when the member joined, as a Discord
+//!   timestamp (`<t:…:R>` "3 days ago" / `<t:…:F>` full date and time)
+//! - `{account_created}`: when the Discord account was made (`<t:…:R>`, from the user id)
+//!
+//! Unknown placeholders are left as written, so a typo shows up in the posted
+//! message instead of silently vanishing. Values that aren't known render as `?`.
+
+use crate::repos::UnixTime;
+
+/// Values available to a template.
+pub struct TemplateVars<'a> {
+    pub user: &'a str,
+    pub mention: String,
+    pub server: &'a str,
+    pub count: Option<u64>,
+    /// Start of the member's (current or just-ended) stay, when known.
+    pub joined: Option<UnixTime>,
+    pub account_created: UnixTime,
+}
+
+impl TemplateVars<'_> {
+    fn value(&self, name: &str) -> Option<String> {
+        let time = |t: Option<UnixTime>, style| {
+            t.map_or_else(|| "?".to_string(), |t| t.discord(style))
+        };
+        Some(match name {
+            "user" => self.user.to_string(),
+            "mention" => self.mention.clone(),
+            "server" => self.server.to_string(),
+            "count" => self.count.map_or_else(|| "?".to_string(), |c| c.to_string()),
+            "joined_relative" => time(self.joined, 'R'),
+            "joined_full" => time(self.joined, 'F'),
+            "account_created" => self.account_created.discord('R'),
+            _ => return None,
+        })
+    }
+}
+
+/// Fill every `{name}` in `template` from `vars`. Single pass, so substituted values
+/// (e.g. a user named `{server}`) are never expanded again.
+pub fn render(template: &str, vars: &TemplateVars) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        out.push_str(&rest[..open]);
+        let after = &rest[open + 1..];
+        let Some(close) = after.find(['{', '}']).filter(|&i| after.as_bytes()[i] == b'}') else {
+            // No placeholder starts here: keep the brace and carry on after it.
+            out.push('{');
+            rest = after;
+            continue;
+        };
+        let name = &after[..close];
+        match vars.value(name) {
+            Some(value) => out.push_str(&value),
+            None => {
+                out.push('{');
+                out.push_str(name);
+                out.push('}');
+            }
+        }
+        rest = &after[close + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars(joined: Option<UnixTime>) -> TemplateVars<'static> {
+        TemplateVars {
+            user: "{server}",
+            mention: "<@10>".to_string(),
+            server: "Shomu",
+            count: Some(42),
+            joined,
+            account_created: UnixTime(1_000),
+        }
+    }
+
+    #[test]
+    fn join_time_renders_as_discord_timestamps() {
+        let v = vars(Some(UnixTime(2_000)));
+        assert_eq!(
+            render("{joined_relative} / {joined_full}", &v),
+            "<t:2000:R> / <t:2000:F>"
+        );
+        assert_eq!(render("{joined_relative} / {joined_full}", &vars(None)), "? / ?");
+    }
+
+    #[test]
+    fn account_age_renders_relative() {
+        assert_eq!(render("made {account_created}", &vars(None)), "made <t:1000:R>");
+    }
+
+    #[test]
+    fn unknown_placeholders_are_kept() {
+        assert_eq!(render("hi {usr} in {server}", &vars(None)), "hi {usr} in Shomu");
+    }
+
+    #[test]
+    fn values_and_stray_braces_are_not_expanded() {
+        let v = vars(None);
+        // The user is literally named "{server}"; it must not turn into "Shomu".
+        assert_eq!(render("{user} joined {server}", &v), "{server} joined Shomu");
+        assert_eq!(render("{{count}} {", &v), "{42} {");
+        assert_eq!(render("} {count", &v), "} {count");
+    }
+}
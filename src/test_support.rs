@@ -0,0 +1,90 @@
+//! Test-only helpers: a migrated in-memory database and seeders for membership
+//! scenarios. Seeders take explicit epoch seconds so results don't depend on the clock.
+
+use std::sync::Arc;
+
+use poise::serenity_prelude as serenity;
+use serenity::all::{GuildId, UserId};
+use tokio_util::sync::CancellationToken;
+
+use crate::db::{Db, DbConfig};
+use crate::repos::{LeaveKind, UnixTime};
+use crate::state::AppState;
+
+pub const GUILD: GuildId = GuildId::new(1);
+
+/// Fresh `sqlite::memory:` database with all migrations applied. One connection,
+/// since every in-memory connection would otherwise get its own empty database.
+pub async fn test_db() -> Db {
+    let cfg = DbConfig {
+        max_connections: 1,
+        wal: false,
+        ..DbConfig::default()
+    };
+    Db::connect_with("sqlite::memory:", cfg)
+        .await
+        .expect("in-memory test database")
+}
+
+/// `AppState` over a fresh `test_db()`, all caches empty.
+pub async fn test_state() -> Arc<AppState> {
+    AppState::with_db(test_db().await, CancellationToken::new())
+}
+
+/// Open a stint for `user` at `at` under account name `name`; returns the row id.
+pub async fn seed_join(db: &Db, guild_id: GuildId, user: u64, name: &str, at: i64) -> i64 {
+    let joined_at = UnixTime(at).to_rfc3339();
+    sqlx::query(
+        r#"
+        INSERT INTO memberships (guild_id, user_id, joined_at, joined_at_epoch, account_username)
+        VALUES (?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(guild_id.to_string())
+    .bind(UserId::new(user).to_string())
+    .bind(joined_at)
+    .bind(at)
+    .bind(name)
+    .execute(&db.pool)
+    .await
+    .expect("seed join")
+    .last_insert_rowid()
+}
+
+/// Close `user`'s open stint at `at` as a plain leave.
+pub async fn seed_leave(db: &Db, guild_id: GuildId, user: u64, at: i64) {
+    seed_exit(db, guild_id, user, at, LeaveKind::Left, None).await;
+}
+
+/// Close `user`'s open stint at `at` as a ban.
+pub async fn seed_ban(db: &Db, guild_id: GuildId, user: u64, at: i64, reason: Option<&str>) {
+    seed_exit(db, guild_id, user, at, LeaveKind::Banned, reason).await;
+}
+
+async fn seed_exit(
+    db: &Db,
+    guild_id: GuildId,
+    user: u64,
+    at: i64,
+    kind: LeaveKind,
+    reason: Option<&str>,
+) {
+    let res = sqlx::query(
+        r#"
+        UPDATE memberships
+           SET left_at = ?, left_at_epoch = ?, leave_kind = ?, banned = ?, ban_reason = ?
+         WHERE guild_id = ? AND user_id = ? AND left_at IS NULL
+        "#,
+    )
+    .bind(UnixTime(at).to_rfc3339())
+    .bind(at)
+    .bind(kind as i64)
+    .bind(kind == LeaveKind::Banned)
+    .bind(reason)
+    .bind(guild_id.to_string())
+    .bind(UserId::new(user).to_string())
+    .execute(&db.pool)
+    .await
+    .expect("seed exit");
+    assert_eq!(res.rows_affected(), 1, "user {user} has no open stint to close");
+}